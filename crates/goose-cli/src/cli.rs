@@ -1,26 +1,31 @@
 use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 
-use goose::config::{Config, ExtensionConfig};
+use goose::config::{Config, ExtensionConfig, ProfileManager};
 
 use crate::commands::bench::agent_generator;
-use crate::commands::configure::handle_configure;
+use crate::commands::configure::{
+    handle_configure, handle_configure_check, handle_configure_trust_project,
+};
 use crate::commands::info::handle_info;
 use crate::commands::mcp::run_server;
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
-use crate::commands::recipe::{handle_deeplink, handle_validate};
+use crate::commands::recipe::{handle_deeplink, handle_test, handle_validate};
 // Import the new handlers from commands::schedule
 use crate::commands::schedule::{
     handle_schedule_add, handle_schedule_cron_help, handle_schedule_list, handle_schedule_remove,
     handle_schedule_run_now, handle_schedule_services_status, handle_schedule_services_stop,
     handle_schedule_sessions,
 };
-use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::session::{
+    handle_session_list, handle_session_remove, ExportFormat, TranscriptFormat,
+};
+use crate::commands::web::WebOptions;
 use crate::logging::setup_logging;
 use crate::recipes::extract_from_cli::extract_recipe_info_from_cli;
 use crate::recipes::recipe::{explain_recipe_with_parameters, load_recipe_content_as_template};
 use crate::session;
-use crate::session::{build_session, SessionBuilderConfig, SessionSettings};
+use crate::session::{build_session, OutputFormat, SessionBuilderConfig, SessionSettings};
 use goose_bench::bench_config::BenchRunConfig;
 use goose_bench::runners::bench_runner::BenchRunner;
 use goose_bench::runners::eval_runner::EvalRunner;
@@ -59,6 +64,14 @@ struct Identifier {
     path: Option<PathBuf>,
 }
 
+/// Activate the profile given with --profile, or check the one selected by GOOSE_PROFILE exists
+fn select_profile(profile: Option<String>) -> Result<()> {
+    match profile.or_else(|| Config::global().active_profile()) {
+        Some(name) => ProfileManager::activate(&name),
+        None => Ok(()),
+    }
+}
+
 fn extract_identifier(identifier: Identifier) -> session::Identifier {
     if let Some(name) = identifier.name {
         session::Identifier::Name(name)
@@ -98,6 +111,45 @@ enum SessionCommand {
         )]
         ascending: bool,
     },
+    #[command(about = "Search the messages, tool calls and working directories of past sessions")]
+    Search {
+        #[arg(help = "Words that all have to appear in a session, case-insensitive")]
+        query: String,
+
+        #[arg(
+            long,
+            help = "Only sessions modified on or after this date (YYYY-MM-DD)"
+        )]
+        since: Option<String>,
+
+        #[arg(
+            long,
+            help = "Only sessions modified on or before this date (YYYY-MM-DD)"
+        )]
+        until: Option<String>,
+
+        #[arg(
+            short,
+            long = "working-dir",
+            value_name = "PATH",
+            help = "Only sessions run in this directory or below it"
+        )]
+        working_dir: Option<PathBuf>,
+
+        #[arg(long = "schedule-id", help = "Only sessions started by this schedule")]
+        schedule_id: Option<String>,
+
+        #[arg(short, long, help = "Maximum number of sessions to show")]
+        limit: Option<usize>,
+
+        #[arg(
+            short,
+            long,
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
     #[command(about = "Remove sessions. Runs interactively if no ID or regex is provided.")]
     Remove {
         #[arg(short, long, help = "Session ID to be removed (optional)")]
@@ -105,7 +157,7 @@ enum SessionCommand {
         #[arg(short, long, help = "Regex for removing matched sessions (optional)")]
         regex: Option<String>,
     },
-    #[command(about = "Export a session to Markdown format")]
+    #[command(about = "Export a session to Markdown, HTML, JSON or a recipe")]
     Export {
         #[command(flatten)]
         identifier: Option<Identifier>,
@@ -114,9 +166,60 @@ enum SessionCommand {
             short,
             long,
             help = "Output file path (default: stdout)",
-            long_help = "Path to save the exported Markdown. If not provided, output will be sent to stdout"
+            long_help = "Path to save the exported session. If not provided, output will be sent to stdout"
         )]
         output: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value_t = ExportFormat::Markdown,
+            help = "Export format",
+            long_help = "Export format: a Markdown transcript, a self-contained HTML page, normalized JSON, or a recipe that repeats the session (uses the configured model)"
+        )]
+        format: ExportFormat,
+    },
+    #[command(about = "Import a conversation from an OpenAI or Anthropic JSON transcript")]
+    Import {
+        #[arg(
+            help = "JSON file with the conversation",
+            long_help = "JSON file with the conversation: a list of messages, or a request body or log with a `messages` list"
+        )]
+        file: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_enum,
+            default_value_t = TranscriptFormat::Auto,
+            help = "Format of the transcript"
+        )]
+        format: TranscriptFormat,
+
+        #[arg(
+            short,
+            long,
+            help = "Name of the new session (default: a generated ID)"
+        )]
+        name: Option<String>,
+
+        #[arg(
+            short,
+            long = "working-dir",
+            value_name = "PATH",
+            help = "Working directory of the session (default: the current directory)"
+        )]
+        working_dir: Option<PathBuf>,
+    },
+    #[command(about = "Import JSONL session files into the SQLite session store")]
+    Migrate {
+        #[arg(
+            long,
+            help = "Replace sessions the database already has",
+            long_help = "Import sessions even if the SQLite store already has a session with the same ID, replacing it"
+        )]
+        force: bool,
     },
 }
 
@@ -246,13 +349,45 @@ enum RecipeCommand {
         )]
         recipe_name: String,
     },
+
+    /// Run a recipe against a scripted mock provider and check its assertions
+    #[command(about = "Test a recipe with scripted model responses")]
+    Test {
+        /// Recipe name to get recipe file to test
+        #[arg(help = "recipe name to get recipe file or full path to the recipe file to test")]
+        recipe_name: String,
+
+        /// Test fixture with the scripted responses, stubs and assertions
+        #[arg(
+            short,
+            long,
+            value_name = "FILE",
+            help = "Test fixture file, defaults to <recipe>.test.yaml next to the recipe"
+        )]
+        fixture: Option<PathBuf>,
+    },
 }
 
 #[derive(Subcommand)]
 enum Command {
     /// Configure Goose settings
     #[command(about = "Configure Goose settings")]
-    Configure {},
+    Configure {
+        /// Report unknown or invalid settings instead of configuring
+        #[arg(
+            long,
+            help = "Check the configuration for unknown or invalid settings and exit"
+        )]
+        check: bool,
+
+        /// Trust the project config of the current directory
+        #[arg(
+            long,
+            help = "Load the .goose/config.yaml of the current project from now on",
+            conflicts_with = "check"
+        )]
+        trust_project: bool,
+    },
 
     /// Display Goose configuration information
     #[command(about = "Display Goose information")]
@@ -260,6 +395,13 @@ enum Command {
         /// Show verbose information including current configuration
         #[arg(short, long, help = "Show verbose information including config.yaml")]
         verbose: bool,
+
+        /// Show the effective configuration and where each value comes from
+        #[arg(
+            long,
+            help = "Show the effective configuration and which layer each value comes from"
+        )]
+        config: bool,
     },
 
     /// Manage system prompts and behaviors
@@ -287,6 +429,15 @@ enum Command {
         )]
         resume: bool,
 
+        /// Configuration profile to use
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Use a named configuration profile",
+            long_help = "Use the provider, model, extensions, mode and tool permissions of a profile defined in the config. Overrides GOOSE_PROFILE."
+        )]
+        profile: Option<String>,
+
         /// Show message history when resuming
         #[arg(
             long,
@@ -481,6 +632,15 @@ enum Command {
         )]
         resume: bool,
 
+        /// Configuration profile to use
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Use a named configuration profile",
+            long_help = "Use the provider, model, extensions, mode and tool permissions of a profile defined in the config. Overrides GOOSE_PROFILE."
+        )]
+        profile: Option<String>,
+
         /// Enable debug output mode
         #[arg(
             long,
@@ -527,6 +687,17 @@ enum Command {
         )]
         quiet: bool,
 
+        /// Output format for headless runs
+        #[arg(
+            long = "output-format",
+            value_name = "FORMAT",
+            value_enum,
+            default_value_t = OutputFormat::Text,
+            help = "Output format: text, json or stream-json",
+            long_help = "How to print a headless run. 'json' prints a single result object when the run ends, 'stream-json' prints one JSON object per line for every agent event followed by the result. Both imply --quiet and exit with a distinct code for each exit reason."
+        )]
+        output_format: OutputFormat,
+
         /// Scheduled job ID (used internally for scheduled executions)
         #[arg(
             long = "scheduled-job-id",
@@ -609,6 +780,45 @@ enum Command {
         /// Open browser automatically
         #[arg(long, help = "Open browser automatically when server starts")]
         open: bool,
+
+        /// Token to log in with instead of a generated one
+        #[arg(
+            long,
+            value_name = "TOKEN",
+            conflicts_with = "no_auth",
+            help = "Token to log in with instead of a generated one"
+        )]
+        auth_token: Option<String>,
+
+        /// Serve without requiring a login
+        #[arg(long, help = "Serve without requiring a login")]
+        no_auth: bool,
+
+        /// Certificate to serve HTTPS with
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "tls_key",
+            help = "PEM certificate chain to serve HTTPS with"
+        )]
+        tls_cert: Option<PathBuf>,
+
+        /// Private key for the certificate
+        #[arg(
+            long,
+            value_name = "FILE",
+            requires = "tls_cert",
+            help = "PEM private key for the certificate"
+        )]
+        tls_key: Option<PathBuf>,
+
+        /// Other origins allowed to open the chat WebSocket
+        #[arg(
+            long = "allowed-origin",
+            value_name = "ORIGIN",
+            help = "Other origin allowed to open the chat WebSocket, e.g. https://goose.example.com (can be repeated)"
+        )]
+        allowed_origins: Vec<String>,
     },
 }
 
@@ -635,12 +845,23 @@ pub async fn cli() -> Result<()> {
     }
 
     match cli.command {
-        Some(Command::Configure {}) => {
+        Some(Command::Configure { check: true, .. }) => {
+            handle_configure_check()?;
+            return Ok(());
+        }
+        Some(Command::Configure {
+            trust_project: true,
+            ..
+        }) => {
+            handle_configure_trust_project()?;
+            return Ok(());
+        }
+        Some(Command::Configure { .. }) => {
             let _ = handle_configure().await;
             return Ok(());
         }
-        Some(Command::Info { verbose }) => {
-            handle_info(verbose)?;
+        Some(Command::Info { verbose, config }) => {
+            handle_info(verbose, config)?;
             return Ok(());
         }
         Some(Command::Mcp { name }) => {
@@ -650,6 +871,7 @@ pub async fn cli() -> Result<()> {
             command,
             identifier,
             resume,
+            profile,
             history,
             debug,
             max_tool_repetitions,
@@ -667,11 +889,35 @@ pub async fn cli() -> Result<()> {
                     handle_session_list(verbose, format, ascending)?;
                     Ok(())
                 }
+                Some(SessionCommand::Search {
+                    query,
+                    since,
+                    until,
+                    working_dir,
+                    schedule_id,
+                    limit,
+                    format,
+                }) => {
+                    crate::commands::session::handle_session_search(
+                        query,
+                        since,
+                        until,
+                        working_dir,
+                        schedule_id,
+                        limit,
+                        format,
+                    )?;
+                    Ok(())
+                }
                 Some(SessionCommand::Remove { id, regex }) => {
                     handle_session_remove(id, regex)?;
                     return Ok(());
                 }
-                Some(SessionCommand::Export { identifier, output }) => {
+                Some(SessionCommand::Export {
+                    identifier,
+                    output,
+                    format,
+                }) => {
                     let session_identifier = if let Some(id) = identifier {
                         extract_identifier(id)
                     } else {
@@ -685,10 +931,34 @@ pub async fn cli() -> Result<()> {
                         }
                     };
 
-                    crate::commands::session::handle_session_export(session_identifier, output)?;
+                    crate::commands::session::handle_session_export(
+                        session_identifier,
+                        output,
+                        format,
+                    )
+                    .await?;
+                    Ok(())
+                }
+                Some(SessionCommand::Import {
+                    file,
+                    format,
+                    name,
+                    working_dir,
+                }) => {
+                    crate::commands::session::handle_session_import(
+                        file,
+                        format,
+                        name,
+                        working_dir,
+                    )?;
+                    Ok(())
+                }
+                Some(SessionCommand::Migrate { force }) => {
+                    crate::commands::session::handle_session_migrate(force)?;
                     Ok(())
                 }
                 None => {
+                    select_profile(profile)?;
                     // Run session command by default
                     let mut session: crate::Session = build_session(SessionBuilderConfig {
                         identifier: identifier.map(extract_identifier),
@@ -748,6 +1018,7 @@ pub async fn cli() -> Result<()> {
             interactive,
             identifier,
             resume,
+            profile,
             no_session,
             debug,
             max_tool_repetitions,
@@ -760,8 +1031,10 @@ pub async fn cli() -> Result<()> {
             render_recipe,
             scheduled_job_id,
             quiet,
+            output_format,
             additional_sub_recipes,
         }) => {
+            select_profile(profile)?;
             let (input_config, session_settings, sub_recipes, final_output_response) = match (
                 instructions,
                 input_text,
@@ -850,7 +1123,7 @@ pub async fn cli() -> Result<()> {
                 max_turns,
                 scheduled_job_id,
                 interactive, // Use the interactive flag from the Run command
-                quiet,
+                quiet: quiet || output_format != OutputFormat::Text,
                 sub_recipes,
                 final_output_response,
             })
@@ -868,7 +1141,11 @@ pub async fn cli() -> Result<()> {
             if interactive {
                 let _ = session.interactive(input_config.contents).await;
             } else if let Some(contents) = input_config.contents {
+                session.set_output_format(output_format);
                 let _ = session.headless(contents).await;
+                if let Some(exit_reason) = session.exit_reason() {
+                    std::process::exit(exit_reason.code());
+                }
             } else {
                 eprintln!("Error: no text provided for prompt in headless mode");
                 std::process::exit(1);
@@ -947,11 +1224,36 @@ pub async fn cli() -> Result<()> {
                 RecipeCommand::Deeplink { recipe_name } => {
                     handle_deeplink(&recipe_name)?;
                 }
+                RecipeCommand::Test {
+                    recipe_name,
+                    fixture,
+                } => {
+                    handle_test(&recipe_name, fixture).await?;
+                }
             }
             return Ok(());
         }
-        Some(Command::Web { port, host, open }) => {
-            crate::commands::web::handle_web(port, host, open).await?;
+        Some(Command::Web {
+            port,
+            host,
+            open,
+            auth_token,
+            no_auth,
+            tls_cert,
+            tls_key,
+            allowed_origins,
+        }) => {
+            crate::commands::web::handle_web(WebOptions {
+                port,
+                host,
+                open,
+                auth_token,
+                no_auth,
+                tls_cert,
+                tls_key,
+                allowed_origins,
+            })
+            .await?;
             return Ok(());
         }
         None => {
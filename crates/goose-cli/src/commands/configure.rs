@@ -9,9 +9,10 @@ use goose::agents::Agent;
 use goose::agents::{extension::Envs, ExtensionConfig};
 use goose::config::extensions::name_to_key;
 use goose::config::permission::PermissionLevel;
+use goose::config::profiles::PROFILE_CONFIG_KEY;
 use goose::config::{
-    Config, ConfigError, ExperimentManager, ExtensionConfigManager, ExtensionEntry,
-    PermissionManager,
+    Config, ConfigError, ConfigSource, ExperimentManager, ExtensionConfigManager, ExtensionEntry,
+    IssueKind, PermissionManager, Profile, ProfileManager,
 };
 use goose::message::Message;
 use goose::providers::{create, providers};
@@ -48,6 +49,67 @@ fn get_display_name(extension_id: &str) -> String {
     }
 }
 
+/// Report unknown, invalid and deprecated settings, failing when any value is invalid
+pub fn handle_configure_check() -> anyhow::Result<()> {
+    let issues = Config::global().check()?;
+    if issues.is_empty() {
+        println!("{}", style("Configuration is valid").green());
+        return Ok(());
+    }
+
+    for issue in &issues {
+        let label = match issue.kind {
+            IssueKind::Invalid => style("invalid").red().bold(),
+            IssueKind::Unknown => style("unknown").yellow().bold(),
+            IssueKind::Deprecated => style("deprecated").yellow().bold(),
+        };
+        println!("  {:<10} {}", label, issue);
+    }
+
+    let invalid = issues
+        .iter()
+        .filter(|issue| issue.kind == IssueKind::Invalid)
+        .count();
+    if invalid > 0 {
+        anyhow::bail!("{} invalid configuration value(s)", invalid);
+    }
+    Ok(())
+}
+
+/// Trust the project whose config the current directory would load, showing what it sets
+pub fn handle_configure_trust_project() -> anyhow::Result<()> {
+    let config = Config::global();
+    let Some(path) = config.untrusted_project() else {
+        match config
+            .layers()
+            .iter()
+            .find(|layer| layer.source == ConfigSource::Project)
+        {
+            Some(layer) => println!("{} is already trusted", layer.path.display()),
+            None => println!("No project config found in this directory or its parents"),
+        }
+        return Ok(());
+    };
+
+    println!("{}", style(path.display()).cyan());
+    println!("{}", std::fs::read_to_string(path)?);
+    let project_dir = path
+        .parent()
+        .and_then(|dir| dir.parent())
+        .ok_or_else(|| anyhow::anyhow!("Invalid project config path {}", path.display()))?;
+    let trust = cliclack::confirm(format!(
+        "Load this config, including its extensions and hooks, whenever goose runs in {}?",
+        project_dir.display()
+    ))
+    .initial_value(false)
+    .interact()?;
+    if trust {
+        config.trust_project(project_dir)?;
+        println!("{}", style("Project trusted").green());
+    }
+    Ok(())
+}
+
 pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
     let config = Config::global();
 
@@ -152,6 +214,14 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
                             style("goose configure").cyan()
                         );
                     }
+                    Some(ConfigError::SecretStoreError(msg)) => {
+                        println!(
+                            "\n  {} Failed to access secret store: {} \n  Please check your GOOSE_SECRET_STORE setting and run '{}' again",
+                            style("Error").red().italic(),
+                            msg,
+                            style("goose configure").cyan()
+                        );
+                    }
                     // handle all other nonspecific errors
                     _ => {
                         println!(
@@ -192,6 +262,11 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
                 "Enable or disable connected extensions",
             )
             .item("remove", "Remove Extension", "Remove an extension")
+            .item(
+                "profiles",
+                "Profiles",
+                "Save and switch between named provider, model and extension setups",
+            )
             .item(
                 "settings",
                 "Goose Settings",
@@ -203,6 +278,7 @@ pub async fn handle_configure() -> Result<(), Box<dyn Error>> {
             "toggle" => toggle_extensions_dialog(),
             "add" => configure_extensions_dialog(),
             "remove" => remove_extension_dialog(),
+            "profiles" => configure_profiles_dialog(),
             "settings" => configure_settings_dialog().await.and(Ok(())),
             "providers" => configure_provider_dialog().await.and(Ok(())),
             _ => unreachable!(),
@@ -828,6 +904,173 @@ pub fn remove_extension_dialog() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+pub fn configure_profiles_dialog() -> Result<(), Box<dyn Error>> {
+    let config = Config::global();
+    let profiles = ProfileManager::get_all()?;
+    let default_profile: Option<String> = config.get_param(PROFILE_CONFIG_KEY).ok();
+
+    let mut names: Vec<&String> = profiles.keys().collect();
+    names.sort();
+    for name in &names {
+        let marker = if default_profile.as_ref() == Some(*name) {
+            " (default)"
+        } else {
+            ""
+        };
+        cliclack::log::info(format!("{}{}", name, marker))?;
+    }
+
+    let mut select = cliclack::select("What would you like to do?").item(
+        "save",
+        "Create or Update Profile",
+        "Save a provider, model, mode and extensions under a name",
+    );
+    if !profiles.is_empty() {
+        select = select
+            .item(
+                "default",
+                "Set Default Profile",
+                "Use a profile unless --profile or GOOSE_PROFILE selects another",
+            )
+            .item("remove", "Remove Profile", "Delete a saved profile");
+    }
+
+    match select.interact()? {
+        "save" => save_profile_dialog(&profiles),
+        "default" => {
+            let mut select = cliclack::select("Which profile should be the default?").item(
+                "",
+                "None",
+                "Use the configuration without a profile",
+            );
+            for name in &names {
+                select = select.item(name.as_str(), name.as_str(), "");
+            }
+            let name = select.interact()?;
+            if name.is_empty() {
+                config.delete(PROFILE_CONFIG_KEY)?;
+                cliclack::outro("No default profile")?;
+            } else {
+                config.set_param(PROFILE_CONFIG_KEY, Value::from(name))?;
+                cliclack::outro(format!("Default profile set to {}", name))?;
+            }
+            Ok(())
+        }
+        "remove" => {
+            let mut select = cliclack::select("Which profile should be removed?");
+            for name in &names {
+                select = select.item(name.as_str(), name.as_str(), "");
+            }
+            let name = select.interact()?;
+            if ProfileManager::remove(name)? {
+                cliclack::outro(format!("Removed profile {}", name))?;
+            } else {
+                cliclack::outro(format!(
+                    "Profile {} is defined in a system or project config file and was not removed",
+                    name
+                ))?;
+            }
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn save_profile_dialog(profiles: &HashMap<String, Profile>) -> Result<(), Box<dyn Error>> {
+    let name: String = cliclack::input("What should the profile be called?")
+        .placeholder("local-ollama")
+        .validate(|input: &String| {
+            if input.trim().is_empty() || input.contains(char::is_whitespace) {
+                Err("Please enter a name without spaces")
+            } else {
+                Ok(())
+            }
+        })
+        .interact()?;
+    let existing = profiles.get(&name).cloned().unwrap_or_default();
+
+    let available_providers = providers();
+    let mut provider_select = cliclack::select("Which provider should the profile use?").item(
+        String::new(),
+        "Keep configured",
+        "Use GOOSE_PROVIDER from the config",
+    );
+    for provider in &available_providers {
+        provider_select = provider_select.item(
+            provider.name.clone(),
+            provider.display_name.as_str(),
+            provider.description.as_str(),
+        );
+    }
+    let provider = provider_select
+        .initial_value(existing.provider.clone().unwrap_or_default())
+        .interact()?;
+
+    let model: String =
+        cliclack::input("Which model should the profile use? (empty keeps configured)")
+            .default_input(existing.model.as_deref().unwrap_or(""))
+            .required(false)
+            .interact()?;
+
+    let mode = cliclack::select("Which Goose mode should the profile use?")
+        .item("", "Keep configured", "Use GOOSE_MODE from the config")
+        .item("auto", "Auto Mode", "")
+        .item("approve", "Approve Mode", "")
+        .item("smart_approve", "Smart Approve Mode", "")
+        .item("chat", "Chat Mode", "")
+        .initial_value(existing.mode.as_deref().unwrap_or(""))
+        .interact()?;
+
+    let mut extension_keys: Vec<String> = ExtensionConfigManager::get_all()?
+        .iter()
+        .map(|entry| entry.config.key())
+        .collect();
+    extension_keys.sort();
+    let extensions = if extension_keys.is_empty() {
+        None
+    } else {
+        let initial: Vec<&String> = match &existing.extensions {
+            Some(enabled) => extension_keys
+                .iter()
+                .filter(|key| enabled.contains(key))
+                .collect(),
+            None => Vec::new(),
+        };
+        let selected = cliclack::multiselect(
+            "Which extensions should the profile enable? (none keeps configured)",
+        )
+        .required(false)
+        .items(
+            &extension_keys
+                .iter()
+                .map(|key| (key, key.as_str(), MULTISELECT_VISIBILITY_HINT))
+                .collect::<Vec<_>>(),
+        )
+        .initial_values(initial)
+        .interact()?;
+        (!selected.is_empty()).then(|| selected.into_iter().cloned().collect())
+    };
+
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+    ProfileManager::set(
+        &name,
+        Profile {
+            provider: non_empty(&provider),
+            model: non_empty(model.trim()),
+            mode: non_empty(mode),
+            extensions,
+            // Tool permissions are edited in the config file
+            permissions: existing.permissions,
+        },
+    )?;
+
+    cliclack::outro(format!(
+        "Saved profile {}, use it with --profile {} or GOOSE_PROFILE={}",
+        name, name, name
+    ))?;
+    Ok(())
+}
+
 pub async fn configure_settings_dialog() -> Result<(), Box<dyn Error>> {
     let setting_type = cliclack::select("What setting would you like to configure?")
         .item("goose_mode", "Goose Mode", "Configure Goose mode")
@@ -1,14 +1,14 @@
 use anyhow::Result;
 use console::style;
 use etcetera::{choose_app_strategy, AppStrategy};
-use goose::config::Config;
+use goose::config::{Config, ConfigSource};
 use serde_yaml;
 
 fn print_aligned(label: &str, value: &str, width: usize) {
     println!("  {:<width$} {}", label, value, width = width);
 }
 
-pub fn handle_info(verbose: bool) -> Result<()> {
+pub fn handle_info(verbose: bool, show_config: bool) -> Result<()> {
     let data_dir = choose_app_strategy(crate::APP_STRATEGY.clone())?;
     let logs_dir = data_dir
         .in_state_dir("logs")
@@ -39,6 +39,24 @@ pub fn handle_info(verbose: bool) -> Result<()> {
     for (label, path) in &paths {
         print_aligned(label, path, basic_padding);
     }
+    for layer in config.layers() {
+        let label = match layer.source {
+            ConfigSource::System => "System config:",
+            ConfigSource::Project => "Project config:",
+            _ => continue,
+        };
+        print_aligned(label, &layer.path.display().to_string(), basic_padding);
+    }
+    if let Some(path) = config.untrusted_project() {
+        print_aligned(
+            "Project config:",
+            &format!(
+                "{} (not trusted, run 'goose configure --trust-project' to load it)",
+                path.display()
+            ),
+            basic_padding,
+        );
+    }
 
     // Print verbose info if requested
     if verbose {
@@ -61,5 +79,29 @@ pub fn handle_info(verbose: bool) -> Result<()> {
         }
     }
 
+    if show_config {
+        println!("\n{}", style("Effective Configuration:").cyan().bold());
+        match config.effective_values() {
+            Ok(entries) if entries.is_empty() => println!("  No configuration values set"),
+            Ok(entries) => {
+                let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0) + 2;
+                for entry in entries {
+                    let origin = match &entry.path {
+                        Some(path) => format!("{} ({})", entry.source, path.display()),
+                        None => entry.source.to_string(),
+                    };
+                    println!(
+                        "  {:<width$} {}  {}",
+                        entry.key,
+                        entry.value,
+                        style(origin).dim(),
+                        width = width
+                    );
+                }
+            }
+            Err(e) => println!("  Error loading configuration: {}", e),
+        }
+    }
+
     Ok(())
 }
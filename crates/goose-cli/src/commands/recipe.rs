@@ -1,10 +1,13 @@
 use anyhow::Result;
 use base64::Engine;
 use console::style;
+use std::path::PathBuf;
 
-use crate::recipes::recipe::load_recipe;
+use crate::recipes::recipe::{load_recipe, load_recipe_without_prompting};
+use crate::recipes::search_recipe::retrieve_recipe_file;
+use crate::recipes::test_recipe::{check_expectations, run_recipe_test, RecipeTestFixture};
 
-/// Validates a recipe file
+/// Validates a recipe file and prints it with the base recipes it extends merged in
 ///
 /// # Arguments
 ///
@@ -16,8 +19,10 @@ use crate::recipes::recipe::load_recipe;
 pub fn handle_validate(recipe_name: &str) -> Result<()> {
     // Load and validate the recipe file
     match load_recipe(recipe_name) {
-        Ok(_) => {
+        Ok(recipe) => {
             println!("{} recipe file is valid", style("✓").green().bold());
+            println!();
+            print!("{}", serde_yaml::to_string(&recipe)?);
             Ok(())
         }
         Err(err) => {
@@ -27,6 +32,63 @@ pub fn handle_validate(recipe_name: &str) -> Result<()> {
     }
 }
 
+/// Runs a recipe against the scripted responses of a test fixture and checks its assertions
+///
+/// # Arguments
+///
+/// * `recipe_name` - Name or path of the recipe to test
+/// * `fixture` - Fixture file, `<recipe>.test.yaml` next to the recipe when not given
+///
+/// # Returns
+///
+/// Result indicating whether every assertion passed
+pub async fn handle_test(recipe_name: &str, fixture: Option<PathBuf>) -> Result<()> {
+    let fixture_path = match fixture {
+        Some(path) => path,
+        None => retrieve_recipe_file(recipe_name)?
+            .file_path
+            .with_extension("test.yaml"),
+    };
+    let fixture = RecipeTestFixture::from_file(&fixture_path)?;
+    let recipe = load_recipe_without_prompting(recipe_name, fixture.param_values())?;
+
+    println!(
+        "{} {} with {}",
+        style("Testing recipe:").green().bold(),
+        style(&recipe.title).green(),
+        fixture_path.display()
+    );
+    let run = run_recipe_test(&recipe, &fixture).await?;
+    let results = check_expectations(&recipe, &fixture, &run, &std::env::current_dir()?);
+
+    for result in &results {
+        if result.passed {
+            println!("  {} {}", style("✓").green().bold(), result.description);
+        } else {
+            println!("  {} {}", style("✗").red().bold(), result.description);
+            if let Some(detail) = &result.detail {
+                println!("      {}", style(detail).dim());
+            }
+        }
+    }
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    if failed == 0 {
+        println!(
+            "{} all {} assertions passed",
+            style("✓").green().bold(),
+            results.len()
+        );
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "{} of {} assertions failed",
+            failed,
+            results.len()
+        ))
+    }
+}
+
 /// Generates a deeplink for a recipe file
 ///
 /// # Arguments
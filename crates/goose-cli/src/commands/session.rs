@@ -1,8 +1,11 @@
-use crate::session::message_to_markdown;
+use crate::session::{export_session, message_to_markdown, session_to_html};
 use crate::utils::safe_truncate;
 use anyhow::{Context, Result};
 use cliclack::{confirm, multiselect, select};
+use goose::session::import::{import_session, ImportFormat};
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
+use goose::session::search::{parse_date_filter, search_sessions, MatchField, SearchQuery};
+use goose::session::store::{import_jsonl_sessions, SqliteStore, SESSION_DB_FILE};
 use goose::session::{self, Identifier};
 use regex::Regex;
 use std::fs;
@@ -22,8 +25,8 @@ pub fn remove_sessions(sessions: Vec<SessionInfo>) -> Result<()> {
 
     if should_delete {
         for session in sessions {
-            fs::remove_file(session.path.clone())
-                .with_context(|| format!("Failed to remove session file '{}'", session.path))?;
+            goose::session::delete_session(Path::new(&session.path))
+                .with_context(|| format!("Failed to remove session '{}'", session.id))?;
             println!("Session `{}` removed.", session.id);
         }
     } else {
@@ -71,6 +74,77 @@ fn prompt_interactive_session_removal(sessions: &[SessionInfo]) -> Result<Vec<Se
     Ok(selected_sessions)
 }
 
+/// Copy the JSONL session files into the SQLite store
+pub fn handle_session_migrate(force: bool) -> Result<()> {
+    let target = SqliteStore::open_default().context("Failed to open the session database")?;
+    let summary = import_jsonl_sessions(&target, force)?;
+
+    println!(
+        "Imported {} session(s) into {}",
+        summary.imported.len(),
+        SESSION_DB_FILE
+    );
+    if !summary.skipped.is_empty() {
+        println!(
+            "Skipped {} session(s) already in the database, use --force to replace them",
+            summary.skipped.len()
+        );
+    }
+    for (id, error) in &summary.failed {
+        eprintln!("Failed to import session `{}`: {}", id, error);
+    }
+    if !summary.failed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "{} session(s) could not be imported",
+            summary.failed.len()
+        ));
+    }
+    println!("Set GOOSE_SESSION_STORE to sqlite to use the imported sessions");
+    Ok(())
+}
+
+/// Formats `goose session import` reads
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TranscriptFormat {
+    /// Detect the format from the transcript
+    #[default]
+    Auto,
+    /// OpenAI chat completions messages
+    Openai,
+    /// Anthropic Messages API messages
+    Anthropic,
+}
+
+pub fn handle_session_import(
+    file: PathBuf,
+    format: TranscriptFormat,
+    name: Option<String>,
+    working_dir: Option<PathBuf>,
+) -> Result<()> {
+    let format = match format {
+        TranscriptFormat::Auto => None,
+        TranscriptFormat::Openai => Some(ImportFormat::OpenAi),
+        TranscriptFormat::Anthropic => Some(ImportFormat::Anthropic),
+    };
+    let cwd = std::env::current_dir()?;
+    let working_dir = working_dir.map(|dir| cwd.join(dir)).unwrap_or(cwd);
+    let session_id = name.unwrap_or_else(session::generate_session_id);
+
+    let (session_file, message_count) = import_session(&file, format, &session_id, working_dir)
+        .with_context(|| format!("Failed to import {}", file.display()))?;
+    println!(
+        "Imported {} messages into session '{}' ({})",
+        message_count,
+        session_id,
+        session_file.display()
+    );
+    println!(
+        "Continue it with: goose session --resume --name {}",
+        session_id
+    );
+    Ok(())
+}
+
 pub fn handle_session_remove(id: Option<String>, regex_string: Option<String>) -> Result<()> {
     let all_sessions = match get_valid_sorted_sessions(SortOrder::Descending) {
         Ok(sessions) => sessions,
@@ -166,11 +240,84 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
     Ok(())
 }
 
-/// Export a session to Markdown without creating a full Session object
+pub fn handle_session_search(
+    text: String,
+    since: Option<String>,
+    until: Option<String>,
+    working_dir: Option<PathBuf>,
+    schedule_id: Option<String>,
+    limit: Option<usize>,
+    format: String,
+) -> Result<()> {
+    let working_dir = match working_dir {
+        Some(dir) if dir.is_relative() => Some(std::env::current_dir()?.join(dir)),
+        dir => dir,
+    };
+    let query = SearchQuery {
+        text,
+        since: since.map(|s| parse_date_filter(&s, false)).transpose()?,
+        until: until.map(|s| parse_date_filter(&s, true)).transpose()?,
+        working_dir,
+        schedule_id,
+        limit,
+    };
+    let results = search_sessions(&query)?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string(&results)?);
+        return Ok(());
+    }
+    if results.is_empty() {
+        println!("No sessions found");
+        return Ok(());
+    }
+    for result in results {
+        let description = if result.metadata.description.is_empty() {
+            "(none)"
+        } else {
+            &result.metadata.description
+        };
+        println!("{} - {} - {}", result.id, description, result.modified);
+        println!(
+            "    Working directory: {}",
+            result.metadata.working_dir.display()
+        );
+        for found in result.matches {
+            let location = match (found.field, found.message_index) {
+                (MatchField::ToolName, Some(index)) => format!("#{} tool", index),
+                (_, Some(index)) => format!("#{}", index),
+                (MatchField::Description, None) => "description".to_string(),
+                (_, None) => "working dir".to_string(),
+            };
+            println!("    [{}] {}", location, found.snippet);
+        }
+    }
+    Ok(())
+}
+
+/// Formats `goose session export` can write
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// Markdown transcript
+    #[default]
+    Markdown,
+    /// Self-contained HTML page with collapsible tool calls
+    Html,
+    /// Normalized JSON for analysis
+    Json,
+    /// Recipe YAML that repeats the session, written by the model
+    Recipe,
+}
+
+/// Export a session without creating a full Session object
 ///
-/// This function directly reads messages from the session file and converts them to Markdown
-/// without creating an Agent or prompting about working directories.
-pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf>) -> Result<()> {
+/// This function directly reads messages from the session file and converts them
+/// without prompting about working directories. Only the recipe format needs a provider.
+pub async fn handle_session_export(
+    identifier: Identifier,
+    output_path: Option<PathBuf>,
+    format: ExportFormat,
+) -> Result<()> {
     // Get the session file path
     let session_file_path = match goose::session::get_path(identifier.clone()) {
         Ok(path) => path,
@@ -179,7 +326,7 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
         }
     };
 
-    if !session_file_path.exists() {
+    if !goose::session::session_exists(&session_file_path) {
         return Err(anyhow::anyhow!(
             "Session file not found (expected path: {})",
             session_file_path.display()
@@ -193,22 +340,77 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
             return Err(anyhow::anyhow!("Failed to read session messages: {}", e));
         }
     };
+    let metadata = goose::session::read_metadata(&session_file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read session metadata: {}", e))?;
+    let session_name = session_file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unnamed Session");
+
+    let content = match format {
+        ExportFormat::Markdown => export_session_to_markdown(messages, &session_file_path, None),
+        ExportFormat::Html => session_to_html(session_name, &metadata, &messages),
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&export_session(session_name, &metadata, &messages))?
+        }
+        ExportFormat::Recipe => export_session_to_recipe(messages, &metadata).await?,
+    };
 
-    // Generate the markdown content using the export functionality
-    let markdown = export_session_to_markdown(messages, &session_file_path, None);
-
-    // Output the markdown
+    // Output the exported session
     if let Some(output) = output_path {
-        fs::write(&output, markdown)
+        fs::write(&output, content)
             .with_context(|| format!("Failed to write to output file: {}", output.display()))?;
         println!("Session exported to {}", output.display());
     } else {
-        println!("{}", markdown);
+        println!("{}", content);
     }
 
     Ok(())
 }
 
+/// Have the model turn the session into a recipe, with the session's first request as its prompt
+async fn export_session_to_recipe(
+    messages: Vec<goose::message::Message>,
+    metadata: &session::SessionMetadata,
+) -> Result<String> {
+    if messages.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Cannot create a recipe from an empty session"
+        ));
+    }
+
+    // The model the session ended with, or the configured one
+    let config = goose::config::Config::global();
+    let (provider_name, model_name) = match metadata.model_switches.last() {
+        Some(switch) => (switch.provider.clone(), switch.model.clone()),
+        None => (
+            config
+                .get_param::<String>("GOOSE_PROVIDER")
+                .context("No provider configured. Run 'goose configure' first")?,
+            config
+                .get_param::<String>("GOOSE_MODEL")
+                .context("No model configured. Run 'goose configure' first")?,
+        ),
+    };
+    let provider =
+        goose::providers::create(&provider_name, goose::model::ModelConfig::new(model_name))?;
+    let agent = goose::agents::Agent::new();
+    agent.update_provider(provider).await?;
+
+    let first_request = messages
+        .iter()
+        .find(|message| {
+            message.role == mcp_core::role::Role::User
+                && !message.as_concat_text().trim().is_empty()
+        })
+        .map(|message| message.as_concat_text());
+    let mut recipe = agent.create_recipe(messages).await?;
+    if recipe.prompt.is_none() {
+        recipe.prompt = first_request;
+    }
+    serde_yaml::to_string(&recipe).context("Failed to serialize recipe")
+}
+
 /// Convert a list of messages to markdown format for session export
 ///
 /// This function handles the formatting of a complete session including headers,
@@ -2,30 +2,152 @@ use anyhow::Result;
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, Request, State,
     },
-    response::{Html, IntoResponse, Response},
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Redirect, Response},
     routing::get,
-    Json, Router,
+    Form, Json, Router,
 };
+use axum_server::tls_rustls::RustlsConfig;
 use futures::{sink::SinkExt, stream::StreamExt};
 use goose::agents::{Agent, AgentEvent};
 use goose::message::Message as GooseMessage;
 use goose::session;
+use rand::{distributions::Alphanumeric, Rng};
 use serde::{Deserialize, Serialize};
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
 use tokio::sync::{Mutex, RwLock};
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
 use tracing::error;
 
 type SessionStore = Arc<RwLock<std::collections::HashMap<String, Arc<Mutex<Vec<GooseMessage>>>>>>;
 type CancellationStore = Arc<RwLock<std::collections::HashMap<String, tokio::task::AbortHandle>>>;
 
+/// Cookie that keeps a browser logged in after it presented the token once
+const AUTH_COOKIE: &str = "goose_web_token";
+
+/// Options for `goose web`
+pub struct WebOptions {
+    pub port: u16,
+    pub host: String,
+    pub open: bool,
+    /// Token to log in with, generated when not given
+    pub auth_token: Option<String>,
+    /// Serve without requiring a login
+    pub no_auth: bool,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    /// Origins besides the server's own that may open the WebSocket
+    pub allowed_origins: Vec<String>,
+}
+
+/// Who may use the web interface
+struct WebAuth {
+    /// Token to log in with, None when authentication is disabled
+    token: Option<String>,
+    allowed_origins: Vec<String>,
+    /// Whether the server is behind TLS, so the cookie can be marked secure
+    secure: bool,
+}
+
+impl WebAuth {
+    fn accepts(&self, token: &str) -> bool {
+        match &self.token {
+            Some(expected) => constant_time_eq(token.as_bytes(), expected.as_bytes()),
+            None => true,
+        }
+    }
+
+    /// Whether a request carries the token as a cookie or bearer header
+    fn is_authenticated(&self, headers: &HeaderMap) -> bool {
+        if self.token.is_none() {
+            return true;
+        }
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        bearer
+            .into_iter()
+            .chain(cookie_values(headers, AUTH_COOKIE))
+            .any(|token| self.accepts(token))
+    }
+
+    fn cookie(&self, token: &str) -> String {
+        format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict{}",
+            AUTH_COOKIE,
+            token,
+            if self.secure { "; Secure" } else { "" }
+        )
+    }
+
+    /// Browsers send an Origin with WebSocket handshakes, which must be this server or allowed
+    fn origin_allowed(&self, headers: &HeaderMap) -> bool {
+        let Some(origin) = headers
+            .get(header::ORIGIN)
+            .and_then(|value| value.to_str().ok())
+        else {
+            // Not a browser, so cross-site requests are not a concern
+            return true;
+        };
+        if self
+            .allowed_origins
+            .iter()
+            .any(|allowed| allowed.trim_end_matches('/') == origin)
+        {
+            return true;
+        }
+
+        let origin_host = origin.split_once("://").map(|(_, host)| host);
+        let host = headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok());
+        matches!((origin_host, host), (Some(origin_host), Some(host)) if origin_host == host)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn cookie_values<'a>(headers: &'a HeaderMap, name: &'a str) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(move |cookie| {
+            let (key, value) = cookie.trim().split_once('=')?;
+            (key == name).then_some(value)
+        })
+}
+
+fn query_token(uri: &Uri) -> Option<String> {
+    uri.query()?
+        .split('&')
+        .filter_map(|pair| pair.strip_prefix("token="))
+        .find_map(|token| urlencoding::decode(token).ok())
+        .map(|token| token.into_owned())
+}
+
+/// A random login token, printed on start like Jupyter does
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+
 #[derive(Clone)]
 struct AppState {
     agent: Arc<Agent>,
     sessions: SessionStore,
     cancellations: CancellationStore,
+    auth: Arc<WebAuth>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -76,7 +198,7 @@ enum WebSocketMessage {
     Complete { message: String },
 }
 
-pub async fn handle_web(port: u16, host: String, open: bool) -> Result<()> {
+pub async fn handle_web(options: WebOptions) -> Result<()> {
     // Setup logging
     crate::logging::setup_logging(Some("goose-web"), None)?;
 
@@ -120,10 +242,22 @@ pub async fn handle_web(port: u16, host: String, open: bool) -> Result<()> {
         }
     }
 
+    let token = if options.no_auth {
+        None
+    } else {
+        Some(options.auth_token.unwrap_or_else(generate_token))
+    };
+    let tls = options.tls_cert.zip(options.tls_key);
+
     let state = AppState {
         agent: Arc::new(agent),
         sessions: Arc::new(RwLock::new(std::collections::HashMap::new())),
         cancellations: Arc::new(RwLock::new(std::collections::HashMap::new())),
+        auth: Arc::new(WebAuth {
+            token: token.clone(),
+            allowed_origins: options.allowed_origins,
+            secure: tls.is_some(),
+        }),
     };
 
     // Build router
@@ -135,15 +269,25 @@ pub async fn handle_web(port: u16, host: String, open: bool) -> Result<()> {
         .route("/api/sessions", get(list_sessions))
         .route("/api/sessions/{session_id}", get(get_session))
         .route("/static/{*path}", get(serve_static))
+        .route("/login", get(serve_login).post(login))
+        .layer(middleware::from_fn_with_state(state.clone(), require_auth))
         .layer(
             CorsLayer::new()
-                .allow_origin(Any)
+                .allow_origin(AllowOrigin::predicate({
+                    let auth = state.auth.clone();
+                    move |_, request| auth.origin_allowed(&request.headers)
+                }))
                 .allow_methods(Any)
                 .allow_headers(Any),
         )
         .with_state(state);
 
-    let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
+    let addr: SocketAddr = format!("{}:{}", options.host, options.port).parse()?;
+    let scheme = if tls.is_some() { "https" } else { "http" };
+    let url = match &token {
+        Some(token) => format!("{}://{}/?token={}", scheme, addr, token),
+        None => format!("{}://{}", scheme, addr),
+    };
 
     println!("\n🪿 Starting Goose web server");
     println!("   Provider: {} | Model: {}", provider_name, model);
@@ -151,23 +295,131 @@ pub async fn handle_web(port: u16, host: String, open: bool) -> Result<()> {
         "   Working directory: {}",
         std::env::current_dir()?.display()
     );
-    println!("   Server: http://{}", addr);
+    println!("   Server: {}", url);
+    if token.is_none() && !addr.ip().is_loopback() {
+        eprintln!("   Warning: anyone who can reach this address can run tools on this machine");
+    }
     println!("   Press Ctrl+C to stop\n");
 
-    if open {
-        // Open browser
-        let url = format!("http://{}", addr);
+    if options.open {
         if let Err(e) = webbrowser::open(&url) {
             eprintln!("Failed to open browser: {}", e);
         }
     }
 
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-    axum::serve(listener, app).await?;
+    match tls {
+        Some((cert, key)) => {
+            // Only the ring provider is compiled in, installing it fails if it already is
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let config = RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to load TLS certificate or key: {}", e))?;
+            axum_server::bind_rustls(addr, config)
+                .serve(app.into_make_service())
+                .await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            axum::serve(listener, app).await?;
+        }
+    }
 
     Ok(())
 }
 
+/// Let requests through once they present the login token
+///
+/// A `?token=` query parameter logs the browser in with a cookie, as the URL printed on start
+/// does. Pages without a valid login go to the login form, API calls get 401.
+async fn require_auth(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let path = request.uri().path();
+    if path == "/login" || path == "/api/health" || path.starts_with("/static/") {
+        return next.run(request).await;
+    }
+
+    if let Some(token) = query_token(request.uri()) {
+        if state.auth.accepts(&token) {
+            let mut response = next.run(request).await;
+            if let Ok(cookie) = HeaderValue::from_str(&state.auth.cookie(&token)) {
+                response.headers_mut().append(header::SET_COOKIE, cookie);
+            }
+            return response;
+        }
+    }
+    if state.auth.is_authenticated(request.headers()) {
+        return next.run(request).await;
+    }
+
+    if path == "/ws" || path.starts_with("/api/") {
+        StatusCode::UNAUTHORIZED.into_response()
+    } else {
+        let next_path = urlencoding::encode(path);
+        Redirect::to(&format!("/login?next={}", next_path)).into_response()
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginForm {
+    token: String,
+    #[serde(default)]
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoginQuery {
+    next: Option<String>,
+}
+
+/// Only redirect to paths on this server after logging in. Browsers read `\` as `/`, so
+/// `/\evil.com` would be a protocol relative URL to another host.
+fn login_redirect(next: Option<&str>) -> &str {
+    match next {
+        Some(next)
+            if next.starts_with('/') && !next.contains('\\') && !next[1..].starts_with('/') =>
+        {
+            next
+        }
+        _ => "/",
+    }
+}
+
+fn login_page(next: &str, error: bool) -> Html<String> {
+    let error = if error {
+        "<p class=\"login-error\">Invalid token</p>"
+    } else {
+        ""
+    };
+    Html(
+        include_str!("../../static/login.html")
+            .replace("{{next}}", &html_escape(next))
+            .replace("{{error}}", error),
+    )
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+async fn serve_login(Query(query): Query<LoginQuery>) -> Html<String> {
+    login_page(login_redirect(query.next.as_deref()), false)
+}
+
+async fn login(State(state): State<AppState>, Form(form): Form<LoginForm>) -> Response {
+    let next = login_redirect(form.next.as_deref());
+    if !state.auth.accepts(&form.token) {
+        return (StatusCode::UNAUTHORIZED, login_page(next, true)).into_response();
+    }
+
+    let mut response = Redirect::to(next).into_response();
+    if let Ok(cookie) = HeaderValue::from_str(&state.auth.cookie(&form.token)) {
+        response.headers_mut().append(header::SET_COOKIE, cookie);
+    }
+    response
+}
+
 async fn serve_index() -> Html<&'static str> {
     Html(include_str!("../../static/index.html"))
 }
@@ -279,8 +531,12 @@ async fn get_session(
 
 async fn websocket_handler(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     State(state): State<AppState>,
-) -> impl IntoResponse {
+) -> Response {
+    if !state.auth.origin_allowed(&headers) {
+        return (StatusCode::FORBIDDEN, "Origin not allowed").into_response();
+    }
     ws.on_upgrade(|socket| handle_socket(socket, state))
 }
 
@@ -484,6 +740,7 @@ async fn process_message_streaming(
         schedule_id: None,
         execution_mode: None,
         max_turns: None,
+        profile: goose::config::Config::global().active_profile(),
     };
 
     // Get response from agent
@@ -623,6 +880,15 @@ async fn process_message_streaming(
                         // Log model change
                         tracing::info!("Model changed to {} in {} mode", model, mode);
                     }
+                    Ok(AgentEvent::LoopDetected(detection)) => {
+                        tracing::warn!("Loop detected: {}", detection.message);
+                    }
+                    Ok(AgentEvent::ToolCallTiming { .. }) => {
+                        // Tool timings are not shown in the web interface yet
+                    }
+                    Ok(AgentEvent::Stopped(reason)) => {
+                        tracing::info!("Reply stopped early: {:?}", reason);
+                    }
 
                     Err(e) => {
                         error!("Error in message stream: {}", e);
@@ -673,3 +939,75 @@ async fn process_message_streaming(
 
 // Add webbrowser dependency for opening browser
 use webbrowser;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth(token: Option<&str>) -> WebAuth {
+        WebAuth {
+            token: token.map(str::to_string),
+            allowed_origins: vec!["https://goose.example.com/".to_string()],
+            secure: false,
+        }
+    }
+
+    fn headers(pairs: &[(header::HeaderName, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name.clone(), value.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_token_from_cookie_bearer_or_query() {
+        let auth = auth(Some("secret"));
+        assert!(!auth.is_authenticated(&HeaderMap::new()));
+        assert!(auth.is_authenticated(&headers(&[(
+            header::COOKIE,
+            "theme=dark; goose_web_token=secret"
+        )])));
+        assert!(auth.is_authenticated(&headers(&[(header::AUTHORIZATION, "Bearer secret")])));
+        assert!(!auth.is_authenticated(&headers(&[(header::COOKIE, "goose_web_token=wrong")])));
+
+        let uri: Uri = "/session/abc?foo=1&token=secret".parse().unwrap();
+        assert_eq!(query_token(&uri).as_deref(), Some("secret"));
+
+        // Without a token everything is allowed
+        assert!(self::auth(None).is_authenticated(&HeaderMap::new()));
+    }
+
+    #[test]
+    fn test_websocket_origin() {
+        let auth = auth(None);
+        let same = headers(&[
+            (header::HOST, "10.0.0.5:3000"),
+            (header::ORIGIN, "http://10.0.0.5:3000"),
+        ]);
+        assert!(auth.origin_allowed(&same));
+
+        let other = headers(&[
+            (header::HOST, "10.0.0.5:3000"),
+            (header::ORIGIN, "https://evil.example.com"),
+        ]);
+        assert!(!auth.origin_allowed(&other));
+
+        let allowed = headers(&[
+            (header::HOST, "10.0.0.5:3000"),
+            (header::ORIGIN, "https://goose.example.com"),
+        ]);
+        assert!(auth.origin_allowed(&allowed));
+        assert!(auth.origin_allowed(&headers(&[(header::HOST, "10.0.0.5:3000")])));
+    }
+
+    #[test]
+    fn test_login_redirect_stays_on_server() {
+        assert_eq!(login_redirect(Some("/session/abc")), "/session/abc");
+        assert_eq!(login_redirect(Some("//evil.example.com")), "/");
+        assert_eq!(login_redirect(Some("/\\evil.example.com")), "/");
+        assert_eq!(login_redirect(Some("/session\\abc")), "/");
+        assert_eq!(login_redirect(Some("https://evil.example.com")), "/");
+        assert_eq!(login_redirect(None), "/");
+    }
+}
@@ -105,7 +105,9 @@ fn setup_logging_internal(
                 .json();
 
             // Create console logging layer for development - INFO and above only
+            // Written to stderr so stdout only carries the run output
             let console_layer = fmt::layer()
+                .with_writer(std::io::stderr)
                 .with_target(true)
                 .with_level(true)
                 .with_ansi(true)
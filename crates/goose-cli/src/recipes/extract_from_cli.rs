@@ -55,6 +55,7 @@ pub fn extract_recipe_info_from_cli(
             goose_provider: s.goose_provider,
             goose_model: s.goose_model,
             temperature: s.temperature,
+            tool_timeouts: s.tool_timeouts,
         }),
         Some(all_sub_recipes),
         recipe.response,
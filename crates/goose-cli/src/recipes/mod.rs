@@ -4,3 +4,4 @@ pub mod print_recipe;
 pub mod recipe;
 pub mod search_recipe;
 pub mod template_recipe;
+pub mod test_recipe;
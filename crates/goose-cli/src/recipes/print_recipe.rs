@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use console::style;
-use goose::recipe::Recipe;
+use goose::recipe::{Recipe, RecipeParameter};
 
 use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
 
@@ -30,11 +30,32 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
                     default_display,
                     param.description
                 );
+                if let Some(constraints) = parameter_constraints(param) {
+                    println!("     {}", style(constraints).dim());
+                }
             }
         }
     }
 }
 
+/// The allowed values of a parameter in words, None when any value of its type is allowed
+fn parameter_constraints(param: &RecipeParameter) -> Option<String> {
+    let mut constraints = Vec::new();
+    if let Some(options) = &param.options {
+        constraints.push(format!("one of: {}", options.join(", ")));
+    }
+    if let Some(pattern) = &param.pattern {
+        constraints.push(format!("matching: {}", pattern));
+    }
+    match (param.min, param.max) {
+        (Some(min), Some(max)) => constraints.push(format!("between {} and {}", min, max)),
+        (Some(min), None) => constraints.push(format!("at least {}", min)),
+        (None, Some(max)) => constraints.push(format!("at most {}", max)),
+        (None, None) => {}
+    }
+    (!constraints.is_empty()).then(|| constraints.join(", "))
+}
+
 pub fn print_parameters_with_values(params: HashMap<String, String>) {
     for (key, value) in params {
         let label = if key == BUILT_IN_RECIPE_DIR_PARAM {
@@ -2,13 +2,16 @@ use crate::recipes::print_recipe::{
     missing_parameters_command_line, print_parameters_with_values, print_recipe_explanation,
     print_required_parameters_for_template,
 };
-use crate::recipes::search_recipe::{retrieve_recipe_file, RecipeFile};
+use crate::recipes::search_recipe::{retrieve_base_recipe_file, retrieve_recipe_file, RecipeFile};
 use crate::recipes::template_recipe::{
     parse_recipe_content, render_recipe_content_with_params, render_recipe_for_preview,
 };
 use anyhow::Result;
 use console::style;
-use goose::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement};
+use goose::recipe::{
+    extends_chain, validate_parameter_values, Recipe, RecipeParameter, RecipeParameterInputType,
+    RecipeParameterRequirement,
+};
 use std::collections::{HashMap, HashSet};
 
 pub const BUILT_IN_RECIPE_DIR_PARAM: &str = "recipe_dir";
@@ -18,18 +21,33 @@ pub fn load_recipe_content_as_template(
     recipe_name: &str,
     params: Vec<(String, String)>,
 ) -> Result<String> {
-    let RecipeFile {
-        content: recipe_file_content,
-        parent_dir: recipe_parent_dir,
-        ..
-    } = retrieve_recipe_file(recipe_name)?;
-    let recipe_dir_str = recipe_parent_dir
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
-    let recipe_parameters = validate_recipe_parameters(&recipe_file_content, recipe_dir_str)?;
+    render_recipe_with_params(recipe_name, params, true)
+}
 
-    let (params_for_template, missing_params) =
-        apply_values_to_parameters(&params, recipe_parameters, recipe_dir_str, true)?;
+/// Load a recipe with the given parameter values, failing instead of prompting for missing ones
+pub fn load_recipe_without_prompting(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+) -> Result<Recipe> {
+    let rendered_content = render_recipe_with_params(recipe_name, params, false)?;
+    Recipe::from_content(&rendered_content)
+}
+
+fn render_recipe_with_params(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+    enable_user_prompt: bool,
+) -> Result<String> {
+    let chain = retrieve_recipe_chain(recipe_name)?;
+    let recipe_dir_str = recipe_dir(&chain[0])?;
+    let recipe_parameters = validate_recipe_parameters(&chain)?;
+
+    let (params_for_template, missing_params) = apply_values_to_parameters(
+        &params,
+        recipe_parameters,
+        recipe_dir_str,
+        enable_user_prompt,
+    )?;
 
     if !missing_params.is_empty() {
         return Err(anyhow::anyhow!(
@@ -38,17 +56,91 @@ pub fn load_recipe_content_as_template(
         ));
     }
 
-    render_recipe_content_with_params(&recipe_file_content, &params_for_template)
+    if let [recipe_file] = chain.as_slice() {
+        return render_recipe_content_with_params(&recipe_file.content, &params_for_template);
+    }
+    let recipes = chain
+        .iter()
+        .map(|recipe_file| {
+            let values = values_for_recipe_file(recipe_file, &params_for_template)?;
+            let rendered_content =
+                render_recipe_content_with_params(&recipe_file.content, &values)?;
+            Recipe::from_content(&rendered_content)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(serde_yaml::to_string(&Recipe::merge_chain(recipes))?)
+}
+
+fn recipe_dir(recipe_file: &RecipeFile) -> Result<&str> {
+    recipe_file
+        .parent_dir
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))
+}
+
+/// The recipe followed by the base recipes it extends, each base after the recipe extending it
+///
+/// Unlike [`Recipe::from_file`] the base names are read from templated recipes, and bases are
+/// also searched for like recipes given on the command line.
+fn retrieve_recipe_chain(recipe_name: &str) -> Result<Vec<RecipeFile>> {
+    extends_chain(
+        retrieve_recipe_file(recipe_name)?,
+        |file| {
+            Ok(
+                parse_recipe_content(&file.content, recipe_dir(file)?.to_string())?
+                    .0
+                    .extends,
+            )
+        },
+        retrieve_base_recipe_file,
+    )
+}
+
+/// Template values for one recipe of a chain, where `recipe_dir` is the recipe's own directory
+fn values_for_recipe_file(
+    recipe_file: &RecipeFile,
+    params: &HashMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut values = params.clone();
+    if values.contains_key(BUILT_IN_RECIPE_DIR_PARAM) {
+        values.insert(
+            BUILT_IN_RECIPE_DIR_PARAM.to_string(),
+            recipe_dir(recipe_file)?.to_string(),
+        );
+    }
+    Ok(values)
+}
+
+/// Render a recipe chain for preview and merge it into the resolved recipe
+fn render_recipe_chain_for_preview(
+    chain: &[RecipeFile],
+    params: &HashMap<String, String>,
+) -> Result<Recipe> {
+    let recipes = chain
+        .iter()
+        .map(|recipe_file| {
+            render_recipe_for_preview(
+                &recipe_file.content,
+                recipe_dir(recipe_file)?.to_string(),
+                &values_for_recipe_file(recipe_file, params)?,
+            )
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Recipe::merge_chain(recipes))
 }
 
-fn validate_recipe_parameters(
-    recipe_file_content: &str,
-    recipe_dir_str: &str,
-) -> Result<Option<Vec<RecipeParameter>>> {
-    let (raw_recipe, template_variables) =
-        parse_recipe_content(recipe_file_content, recipe_dir_str.to_string())?;
-    let recipe_parameters = raw_recipe.parameters;
+fn validate_recipe_parameters(chain: &[RecipeFile]) -> Result<Option<Vec<RecipeParameter>>> {
+    let mut raw_recipes = Vec::new();
+    let mut template_variables = HashSet::new();
+    for recipe_file in chain {
+        let (raw_recipe, variables) =
+            parse_recipe_content(&recipe_file.content, recipe_dir(recipe_file)?.to_string())?;
+        raw_recipes.push(raw_recipe);
+        template_variables.extend(variables);
+    }
+    let recipe_parameters = Recipe::merge_chain(raw_recipes).parameters;
     validate_optional_parameters(&recipe_parameters)?;
+    validate_parameter_definitions(&recipe_parameters)?;
     validate_parameters_in_template(&recipe_parameters, &template_variables)?;
     Ok(recipe_parameters)
 }
@@ -73,21 +165,11 @@ pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>)
     Ok(recipe)
 }
 
+/// Load a recipe for validation and preview, with the base recipes it extends merged in
 pub fn load_recipe(recipe_name: &str) -> Result<Recipe> {
-    let RecipeFile {
-        content: recipe_file_content,
-        parent_dir: recipe_parent_dir,
-        ..
-    } = retrieve_recipe_file(recipe_name)?;
-    let recipe_dir_str = recipe_parent_dir
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
-    validate_recipe_parameters(&recipe_file_content, recipe_dir_str)?;
-    let recipe = render_recipe_for_preview(
-        &recipe_file_content,
-        recipe_dir_str.to_string(),
-        &HashMap::new(),
-    )?;
+    let chain = retrieve_recipe_chain(recipe_name)?;
+    validate_recipe_parameters(&chain)?;
+    let recipe = render_recipe_chain_for_preview(&chain, &HashMap::new())?;
 
     if let Some(response) = &recipe.response {
         if let Some(json_schema) = &response.json_schema {
@@ -102,23 +184,13 @@ pub fn explain_recipe_with_parameters(
     recipe_name: &str,
     params: Vec<(String, String)>,
 ) -> Result<()> {
-    let RecipeFile {
-        content: recipe_file_content,
-        parent_dir: recipe_parent_dir,
-        ..
-    } = retrieve_recipe_file(recipe_name)?;
-    let recipe_dir_str = recipe_parent_dir
-        .to_str()
-        .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
-    let recipe_parameters = validate_recipe_parameters(&recipe_file_content, recipe_dir_str)?;
+    let chain = retrieve_recipe_chain(recipe_name)?;
+    let recipe_dir_str = recipe_dir(&chain[0])?;
+    let recipe_parameters = validate_recipe_parameters(&chain)?;
 
     let (params_for_template, missing_params) =
         apply_values_to_parameters(&params, recipe_parameters, recipe_dir_str, false)?;
-    let recipe = render_recipe_for_preview(
-        &recipe_file_content,
-        recipe_dir_str.to_string(),
-        &params_for_template,
-    )?;
+    let recipe = render_recipe_chain_for_preview(&chain, &params_for_template)?;
     print_recipe_explanation(&recipe);
     print_required_parameters_for_template(params_for_template, missing_params);
 
@@ -195,6 +267,68 @@ fn validate_optional_parameters(parameters: &Option<Vec<RecipeParameter>>) -> Re
     }
 }
 
+fn validate_parameter_definitions(parameters: &Option<Vec<RecipeParameter>>) -> Result<()> {
+    let invalid_definitions: Vec<String> = parameters
+        .as_ref()
+        .unwrap_or(&vec![])
+        .iter()
+        .filter_map(|p| {
+            p.validate_definition()
+                .err()
+                .map(|e| format!("{}: {}", p.key, e))
+        })
+        .collect();
+
+    if invalid_definitions.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Invalid parameter definitions in the recipe: {}",
+            invalid_definitions.join("; ")
+        ))
+    }
+}
+
+/// Ask for a parameter value, offering the choices of select, list and boolean parameters
+fn prompt_for_parameter(param: &RecipeParameter) -> Result<String> {
+    let value = match (&param.input_type, &param.options) {
+        (RecipeParameterInputType::Boolean, _) => {
+            cliclack::confirm(format!("{} ({})", param.key, param.description))
+                .interact()?
+                .to_string()
+        }
+        (RecipeParameterInputType::Select, Some(options)) => {
+            let items: Vec<_> = options.iter().map(|o| (o.clone(), o, "")).collect();
+            cliclack::select(format!(
+                "Please select {} ({})",
+                param.key, param.description
+            ))
+            .items(&items)
+            .interact()?
+        }
+        (RecipeParameterInputType::List, Some(options)) => {
+            let items: Vec<_> = options.iter().map(|o| (o.clone(), o, "")).collect();
+            cliclack::multiselect(format!(
+                "Please select {} ({})",
+                param.key, param.description
+            ))
+            .items(&items)
+            .interact()?
+            .join(", ")
+        }
+        _ => {
+            let param_for_validation = param.clone();
+            cliclack::input(format!(
+                "Please enter {} ({})",
+                param.key, param.description
+            ))
+            .validate(move |input: &String| param_for_validation.validate_value(input))
+            .interact()?
+        }
+    };
+    Ok(value)
+}
+
 fn apply_values_to_parameters(
     user_params: &[(String, String)],
     recipe_parameters: Option<Vec<RecipeParameter>>,
@@ -207,16 +341,13 @@ fn apply_values_to_parameters(
         recipe_parent_dir.to_string(),
     );
     let mut missing_params: Vec<String> = Vec::new();
-    for param in recipe_parameters.unwrap_or_default() {
+    let recipe_parameters = recipe_parameters.unwrap_or_default();
+    for param in &recipe_parameters {
         if !param_map.contains_key(&param.key) {
             match (&param.default, &param.requirement) {
                 (Some(default), _) => param_map.insert(param.key.clone(), default.clone()),
                 (None, RecipeParameterRequirement::UserPrompt) if enable_user_prompt => {
-                    let input_value = cliclack::input(format!(
-                        "Please enter {} ({})",
-                        param.key, param.description
-                    ))
-                    .interact()?;
+                    let input_value = prompt_for_parameter(param)?;
                     param_map.insert(param.key.clone(), input_value)
                 }
                 _ => {
@@ -226,6 +357,18 @@ fn apply_values_to_parameters(
             };
         }
     }
+
+    let invalid_values = validate_parameter_values(&recipe_parameters, &param_map);
+    if !invalid_values.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Invalid parameter values: {}",
+            invalid_values
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("; ")
+        ));
+    }
     Ok((param_map, missing_params))
 }
 
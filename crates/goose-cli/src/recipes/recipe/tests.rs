@@ -222,6 +222,45 @@ mod tests {
             assert!(err_msg.contains("unknown variant `some_invalid_type`"));
         }
 
+        #[test]
+        fn test_load_recipe_as_template_invalid_parameter_value() {
+            let instructions_and_parameters = r#"
+                "instructions": "Deploy to {{ env }} with {{ replicas }} replicas",
+                "parameters": [
+                    {
+                        "key": "env",
+                        "input_type": "select",
+                        "requirement": "required",
+                        "description": "Target environment",
+                        "options": ["staging", "production"]
+                    },
+                    {
+                        "key": "replicas",
+                        "input_type": "number",
+                        "requirement": "optional",
+                        "description": "Replica count",
+                        "default": "2",
+                        "min": 1,
+                        "max": 5
+                    }
+                ]"#;
+            let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+
+            let params = vec![("env".to_string(), "dev".to_string())];
+            let err = load_recipe_as_template(recipe_path.to_str().unwrap(), params).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Invalid parameter values: env: 'dev' is not one of: staging, production"
+            );
+
+            let params = vec![("env".to_string(), "staging".to_string())];
+            let recipe = load_recipe_as_template(recipe_path.to_str().unwrap(), params).unwrap();
+            assert_eq!(
+                recipe.instructions.unwrap(),
+                "Deploy to staging with 2 replicas"
+            );
+        }
+
         #[test]
         fn test_load_recipe_as_template_success_without_parameters() {
             let instructions_and_parameters = r#"
@@ -311,5 +350,69 @@ mod tests {
                 "is_enabled"
             );
         }
+
+        #[test]
+        fn test_load_recipe_as_template_with_extends() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            std::fs::create_dir(temp_dir.path().join("shared")).unwrap();
+            let base_content = r#"
+                title: Base
+                description: Shared team setup
+                instructions: Work on the {{ repo }} repository.
+                extensions:
+                    - type: builtin
+                      name: developer
+                parameters:
+                    - key: repo
+                      input_type: string
+                      requirement: required
+                      description: repository to work on
+            "#;
+            std::fs::write(temp_dir.path().join("shared/base.yaml"), base_content).unwrap();
+            let child_content = r#"
+                title: Review
+                description: Review open pull requests
+                extends: shared/base.yaml
+                instructions: Review the open pull requests of {{ repo }}.
+            "#;
+            let child_path = temp_dir.path().join("review.yaml");
+            std::fs::write(&child_path, child_content).unwrap();
+
+            let params = vec![("repo".to_string(), "goose".to_string())];
+            let recipe = load_recipe_as_template(child_path.to_str().unwrap(), params).unwrap();
+            assert_eq!(recipe.title, "Review");
+            assert!(recipe.extends.is_none());
+            assert_eq!(
+                recipe.instructions.unwrap(),
+                "Work on the goose repository.\n\nReview the open pull requests of goose."
+            );
+            assert_eq!(recipe.extensions.unwrap()[0].name(), "developer");
+            assert_eq!(recipe.parameters.unwrap()[0].key, "repo");
+        }
+
+        #[test]
+        fn test_load_recipe_as_template_extends_cycle() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let recipe_content = |name: &str, extends: &str| {
+                format!(
+                    "title: {}\ndescription: cycle\ninstructions: none\nextends: {}\n",
+                    name, extends
+                )
+            };
+            std::fs::write(temp_dir.path().join("a.yaml"), recipe_content("A", "b")).unwrap();
+            std::fs::write(
+                temp_dir.path().join("b.yaml"),
+                recipe_content("B", "a.yaml"),
+            )
+            .unwrap();
+
+            let err = load_recipe_as_template(
+                temp_dir.path().join("a.yaml").to_str().unwrap(),
+                Vec::new(),
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("Recipe extends itself"));
+            assert!(err.to_string().contains("b.yaml -> "));
+        }
     }
 }
@@ -1,7 +1,9 @@
 use anyhow::{anyhow, Result};
 use goose::config::Config;
+use goose::recipe::find_base_recipe_file;
+pub use goose::recipe::RecipeFile;
+use std::env;
 use std::path::{Path, PathBuf};
-use std::{env, fs};
 
 use crate::recipes::recipe::RECIPE_FILE_EXTENSIONS;
 
@@ -9,12 +11,6 @@ use super::github_recipe::{retrieve_recipe_from_github, GOOSE_RECIPE_GITHUB_REPO
 
 const GOOSE_RECIPE_PATH_ENV_VAR: &str = "GOOSE_RECIPE_PATH";
 
-pub struct RecipeFile {
-    pub content: String,
-    pub parent_dir: PathBuf,
-    pub file_path: PathBuf,
-}
-
 pub fn retrieve_recipe_file(recipe_name: &str) -> Result<RecipeFile> {
     if RECIPE_FILE_EXTENSIONS
         .iter()
@@ -38,6 +34,18 @@ pub fn retrieve_recipe_file(recipe_name: &str) -> Result<RecipeFile> {
     })
 }
 
+/// Find the base recipe named by `extends` next to the extending recipe, see
+/// [`find_base_recipe_file`], and then names in the usual recipe locations
+pub fn retrieve_base_recipe_file(base_name: &str, extending: &RecipeFile) -> Result<RecipeFile> {
+    find_base_recipe_file(base_name, extending).or_else(|e| {
+        if is_file_path(base_name) || is_file_name(base_name) {
+            Err(e)
+        } else {
+            retrieve_recipe_file(base_name)
+        }
+    })
+}
+
 fn is_file_path(recipe_name: &str) -> bool {
     recipe_name.contains('/')
         || recipe_name.contains('\\')
@@ -112,28 +120,5 @@ fn convert_path_with_tilde_expansion(path: &Path) -> PathBuf {
 }
 
 fn read_recipe_file<P: AsRef<Path>>(recipe_path: P) -> Result<RecipeFile> {
-    let raw_path = recipe_path.as_ref();
-    let path = convert_path_with_tilde_expansion(raw_path);
-
-    let content = fs::read_to_string(&path)
-        .map_err(|e| anyhow!("Failed to read recipe file {}: {}", path.display(), e))?;
-
-    let canonical = path.canonicalize().map_err(|e| {
-        anyhow!(
-            "Failed to resolve absolute path for {}: {}",
-            path.display(),
-            e
-        )
-    })?;
-
-    let parent_dir = canonical
-        .parent()
-        .ok_or_else(|| anyhow!("Resolved path has no parent: {}", canonical.display()))?
-        .to_path_buf();
-
-    Ok(RecipeFile {
-        content,
-        parent_dir,
-        file_path: canonical,
-    })
+    RecipeFile::read(&convert_path_with_tilde_expansion(recipe_path.as_ref()))
 }
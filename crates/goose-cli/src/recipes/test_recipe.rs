@@ -0,0 +1,578 @@
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::StreamExt;
+use goose::agents::extension::ExtensionConfig;
+use goose::agents::final_output_tool::FINAL_OUTPUT_TOOL_NAME;
+use goose::agents::{Agent, AgentEvent, StopReason};
+use goose::message::{Message, MessageContent};
+use goose::model::ModelConfig;
+use goose::permission::permission_confirmation::PrincipalType;
+use goose::permission::{Permission, PermissionConfirmation};
+use goose::providers::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
+use goose::providers::errors::ProviderError;
+use goose::recipe::Recipe;
+use goose_bench::eval_suites::used_tool;
+use mcp_core::role::Role;
+use mcp_core::tool::Tool;
+use mcp_core::{Content, ToolCall, ToolError};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Name of the frontend extension that answers stubbed tool calls
+const STUB_EXTENSION_NAME: &str = "recipe_test_stubs";
+
+/// A scripted run of a recipe and what it is expected to do
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeTestFixture {
+    /// Values for the recipe parameters
+    #[serde(default)]
+    pub params: HashMap<String, Value>,
+    /// Message the run starts with, the recipe prompt when not set
+    pub prompt: Option<String>,
+    /// Replies of the mock provider, one for each model call
+    pub responses: Vec<ScriptedResponse>,
+    /// Canned results of tools, which are not run when stubbed
+    #[serde(default)]
+    pub stubs: HashMap<String, StubResult>,
+    #[serde(default)]
+    pub expect: Expectations,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptedResponse {
+    pub text: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<ScriptedToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScriptedToolCall {
+    pub name: String,
+    #[serde(default = "empty_arguments")]
+    pub arguments: Value,
+}
+
+fn empty_arguments() -> Value {
+    json!({})
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum StubResult {
+    Output(String),
+    Error { error: String },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expectations {
+    /// Tools that have to be called, matched like goose-bench's `used_tool`
+    #[serde(default)]
+    pub tools_called: Vec<String>,
+    #[serde(default)]
+    pub tools_not_called: Vec<String>,
+    /// Files that have to exist after the run, relative to the current directory
+    #[serde(default)]
+    pub files_exist: Vec<PathBuf>,
+    /// Text the assistant's messages have to contain
+    #[serde(default)]
+    pub output_contains: Vec<String>,
+}
+
+impl RecipeTestFixture {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read test fixture {}: {}", path.display(), e))?;
+        let fixture: Self = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid test fixture {}: {}", path.display(), e))?;
+        if let Some(index) = fixture
+            .responses
+            .iter()
+            .position(|r| r.text.is_none() && r.tool_calls.is_empty())
+        {
+            return Err(anyhow!(
+                "Scripted response {} needs a text or tool calls",
+                index + 1
+            ));
+        }
+        Ok(fixture)
+    }
+
+    /// Parameter values as the recipe template expects them
+    pub fn param_values(&self) -> Vec<(String, String)> {
+        self.params
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (key.clone(), value)
+            })
+            .collect()
+    }
+
+    fn scripted_messages(&self) -> Vec<Message> {
+        self.responses
+            .iter()
+            .enumerate()
+            .map(|(index, response)| {
+                let mut message = Message::assistant();
+                if let Some(text) = &response.text {
+                    message = message.with_text(text);
+                }
+                for (call_index, call) in response.tool_calls.iter().enumerate() {
+                    message = message.with_tool_request(
+                        format!("scripted_{}_{}", index, call_index),
+                        Ok(ToolCall::new(&call.name, call.arguments.clone())),
+                    );
+                }
+                message
+            })
+            .collect()
+    }
+}
+
+/// A provider that answers with scripted messages in order and keeps the conversation
+struct ScriptedProvider {
+    model_config: ModelConfig,
+    responses: Mutex<VecDeque<Message>>,
+    transcript: Mutex<Vec<Message>>,
+}
+
+impl ScriptedProvider {
+    fn new(responses: Vec<Message>) -> Self {
+        Self {
+            model_config: ModelConfig::new("scripted".to_string()),
+            responses: Mutex::new(responses.into()),
+            transcript: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for ScriptedProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::empty()
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model_config.clone()
+    }
+
+    async fn complete(
+        &self,
+        _system: &str,
+        messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let mut transcript = self.transcript.lock().unwrap();
+        *transcript = messages.to_vec();
+        let response = self.responses.lock().unwrap().pop_front().ok_or_else(|| {
+            ProviderError::ExecutionError(
+                "The agent asked for another response but the script has none left".to_string(),
+            )
+        })?;
+        transcript.push(response.clone());
+        Ok((
+            response,
+            ProviderUsage::new("scripted".to_string(), Usage::default()),
+        ))
+    }
+}
+
+/// What happened in a scripted run of a recipe
+pub struct RecipeTestRun {
+    /// The conversation as the provider last saw it, with its final response
+    pub messages: Vec<Message>,
+    pub unused_responses: usize,
+    pub stop_reason: Option<StopReason>,
+}
+
+impl RecipeTestRun {
+    /// Arguments of the final output call the agent accepted
+    fn final_output(&self) -> Option<&Value> {
+        self.messages.iter().rev().find_map(|message| {
+            message.content.iter().find_map(|content| match content {
+                MessageContent::ToolRequest(request) => request
+                    .tool_call
+                    .as_ref()
+                    .ok()
+                    .filter(|call| call.name == FINAL_OUTPUT_TOOL_NAME)
+                    .filter(|_| self.succeeded(&request.id))
+                    .map(|call| &call.arguments),
+                _ => None,
+            })
+        })
+    }
+
+    fn succeeded(&self, request_id: &str) -> bool {
+        self.messages
+            .iter()
+            .flat_map(|message| message.content.iter())
+            .any(|content| {
+                matches!(content, MessageContent::ToolResponse(response)
+                    if response.id == request_id && response.tool_result.is_ok())
+            })
+    }
+
+    fn assistant_text(&self) -> String {
+        self.messages
+            .iter()
+            .filter(|message| message.role == Role::Assistant)
+            .map(|message| message.as_concat_text())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Run a recipe against the scripted responses of a fixture, with its stubbed tools answered
+/// from the fixture and every other tool run by the recipe's extensions
+pub async fn run_recipe_test(
+    recipe: &Recipe,
+    fixture: &RecipeTestFixture,
+) -> Result<RecipeTestRun> {
+    let prompt = fixture
+        .prompt
+        .clone()
+        .or_else(|| recipe.prompt.clone())
+        .ok_or_else(|| anyhow!("The recipe has no prompt, set one in the test fixture"))?;
+
+    let agent = Agent::new();
+    let provider = Arc::new(ScriptedProvider::new(fixture.scripted_messages()));
+    agent.update_provider(provider.clone()).await?;
+
+    for extension in recipe.extensions.clone().unwrap_or_default() {
+        let name = extension.name();
+        agent
+            .add_extension(extension)
+            .await
+            .map_err(|e| anyhow!("Failed to start extension {}: {}", name, e))?;
+    }
+    if !fixture.stubs.is_empty() {
+        let tools = fixture
+            .stubs
+            .keys()
+            .map(|name| {
+                Tool::new(
+                    name.clone(),
+                    "Stubbed by the recipe test".to_string(),
+                    json!({"type": "object"}),
+                    None,
+                )
+            })
+            .collect();
+        agent
+            .add_extension(ExtensionConfig::Frontend {
+                name: STUB_EXTENSION_NAME.to_string(),
+                tools,
+                instructions: None,
+                bundled: None,
+            })
+            .await?;
+    }
+    if let Some(instructions) = &recipe.instructions {
+        agent.extend_system_prompt(instructions.clone()).await;
+    }
+    if let Some(response) = recipe.response.as_ref().filter(|r| r.json_schema.is_some()) {
+        agent.add_final_output_tool(response.clone()).await;
+    }
+    if let Some(sub_recipes) = recipe.sub_recipes.clone() {
+        agent.add_sub_recipes(sub_recipes).await;
+    }
+
+    let mut stop_reason = None;
+    let mut stream = agent
+        .reply(&[Message::user().with_text(&prompt)], None)
+        .await?;
+    while let Some(event) = stream.next().await {
+        match event? {
+            AgentEvent::Message(message) => {
+                for content in &message.content {
+                    match content {
+                        MessageContent::FrontendToolRequest(request) => {
+                            let result = match &request.tool_call {
+                                Ok(call) => stub_result(&fixture.stubs, call),
+                                Err(e) => Err(e.clone()),
+                            };
+                            agent.handle_tool_result(request.id.clone(), result).await;
+                        }
+                        // Nobody is there to answer, so the run behaves as in auto mode
+                        // whatever GOOSE_MODE is configured
+                        MessageContent::ToolConfirmationRequest(request) => {
+                            agent
+                                .handle_confirmation(
+                                    request.id.clone(),
+                                    PermissionConfirmation {
+                                        principal_type: PrincipalType::Tool,
+                                        permission: Permission::AllowOnce,
+                                    },
+                                )
+                                .await;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            AgentEvent::Stopped(reason) => stop_reason = Some(reason),
+            _ => {}
+        }
+    }
+
+    let messages = provider.transcript.lock().unwrap().clone();
+    let unused_responses = provider.responses.lock().unwrap().len();
+    Ok(RecipeTestRun {
+        messages,
+        unused_responses,
+        stop_reason,
+    })
+}
+
+fn stub_result(
+    stubs: &HashMap<String, StubResult>,
+    call: &ToolCall,
+) -> Result<Vec<Content>, ToolError> {
+    match stubs.get(&call.name) {
+        Some(StubResult::Output(output)) => Ok(vec![Content::text(output.clone())]),
+        Some(StubResult::Error { error }) => Err(ToolError::ExecutionError(error.clone())),
+        None => Err(ToolError::NotFound(format!("No stub for {}", call.name))),
+    }
+}
+
+pub struct AssertionResult {
+    pub description: String,
+    pub passed: bool,
+    /// Why the assertion failed
+    pub detail: Option<String>,
+}
+
+impl AssertionResult {
+    fn new(description: String, passed: bool, detail: impl FnOnce() -> String) -> Self {
+        Self {
+            description,
+            passed,
+            detail: (!passed).then(detail),
+        }
+    }
+}
+
+fn describe_stop(reason: &StopReason) -> String {
+    match reason {
+        StopReason::MaxTurns(turns) => format!("stopped after the maximum of {} turns", turns),
+        StopReason::ToolLoop(message) => format!("stopped in a tool loop: {}", message),
+        StopReason::ProviderError(message) => message.clone(),
+    }
+}
+
+/// Check a run against the fixture's expectations and, when the recipe has a response
+/// schema, check that the final output matches it
+pub fn check_expectations(
+    recipe: &Recipe,
+    fixture: &RecipeTestFixture,
+    run: &RecipeTestRun,
+    working_dir: &Path,
+) -> Vec<AssertionResult> {
+    let mut results = vec![
+        AssertionResult::new(
+            "the run finished".to_string(),
+            run.stop_reason.is_none(),
+            || {
+                run.stop_reason
+                    .as_ref()
+                    .map(describe_stop)
+                    .unwrap_or_default()
+            },
+        ),
+        AssertionResult::new(
+            "every scripted response was used".to_string(),
+            run.unused_responses == 0,
+            || format!("{} responses were left", run.unused_responses),
+        ),
+    ];
+
+    if let Some(schema) = recipe
+        .response
+        .as_ref()
+        .and_then(|r| r.json_schema.as_ref())
+    {
+        let errors = match (run.final_output(), jsonschema::validator_for(schema)) {
+            (None, _) => vec!["no final output was accepted".to_string()],
+            (_, Err(e)) => vec![format!("invalid schema: {}", e)],
+            (Some(output), Ok(validator)) => validator
+                .iter_errors(output)
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect(),
+        };
+        results.push(AssertionResult::new(
+            "the final output matches the response schema".to_string(),
+            errors.is_empty(),
+            || errors.join("; "),
+        ));
+    }
+
+    for tool in &fixture.expect.tools_called {
+        results.push(AssertionResult::new(
+            format!("called {}", tool),
+            used_tool(&run.messages, tool),
+            || "the tool was not called".to_string(),
+        ));
+    }
+    for tool in &fixture.expect.tools_not_called {
+        results.push(AssertionResult::new(
+            format!("did not call {}", tool),
+            !used_tool(&run.messages, tool),
+            || "the tool was called".to_string(),
+        ));
+    }
+    for file in &fixture.expect.files_exist {
+        let path = working_dir.join(file);
+        results.push(AssertionResult::new(
+            format!("{} exists", file.display()),
+            path.exists(),
+            || format!("{} was not found", path.display()),
+        ));
+    }
+    let text = run.assistant_text();
+    for expected in &fixture.expect.output_contains {
+        results.push(AssertionResult::new(
+            format!("the output contains '{}'", expected),
+            text.contains(expected.as_str()),
+            || "the assistant never said it".to_string(),
+        ));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPE: &str = r#"
+title: Release notes
+description: Summarize the changes since the last tag
+prompt: Write the release notes
+instructions: Use the git history.
+response:
+  json_schema:
+    type: object
+    properties:
+      summary:
+        type: string
+    required: [summary]
+"#;
+
+    const FIXTURE: &str = r#"
+responses:
+  - text: Let me read the history.
+    tool_calls:
+      - name: developer__shell
+        arguments:
+          command: git log --oneline v1.0..
+  - tool_calls:
+      - name: recipe__final_output
+        arguments:
+          summary: Adds recipe tests
+  - text: The release notes are ready.
+stubs:
+  developer__shell: "abc123 Add recipe tests"
+expect:
+  tools_called: [shell]
+  tools_not_called: [text_editor]
+  output_contains: [release notes are ready]
+"#;
+
+    #[tokio::test]
+    async fn test_scripted_run_passes() {
+        let recipe = Recipe::from_content(RECIPE).unwrap();
+        let fixture: RecipeTestFixture = serde_yaml::from_str(FIXTURE).unwrap();
+
+        let run = run_recipe_test(&recipe, &fixture).await.unwrap();
+        assert_eq!(
+            run.final_output(),
+            Some(&json!({"summary": "Adds recipe tests"}))
+        );
+        let stub_output =
+            run.messages
+                .iter()
+                .flat_map(|m| m.content.iter())
+                .find_map(|c| match c {
+                    MessageContent::ToolResponse(r) => {
+                        r.tool_result.as_ref().ok()?.first()?.as_text()
+                    }
+                    _ => None,
+                });
+        assert_eq!(stub_output, Some("abc123 Add recipe tests"));
+
+        let results = check_expectations(&recipe, &fixture, &run, Path::new("."));
+        let failed: Vec<_> = results
+            .iter()
+            .filter(|r| !r.passed)
+            .map(|r| (&r.description, &r.detail))
+            .collect();
+        assert!(failed.is_empty(), "{:?}", failed);
+        assert_eq!(results.len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_run_answers_tool_confirmations() {
+        let recipe = Recipe::from_content(RECIPE).unwrap();
+        let fixture: RecipeTestFixture = serde_yaml::from_str(FIXTURE).unwrap();
+
+        let run = temp_env::async_with_vars([("GOOSE_MODE", Some("approve"))], async {
+            tokio::time::timeout(
+                std::time::Duration::from_secs(30),
+                run_recipe_test(&recipe, &fixture),
+            )
+            .await
+        })
+        .await
+        .expect("the run waited for a tool confirmation")
+        .unwrap();
+        assert_eq!(
+            run.final_output(),
+            Some(&json!({"summary": "Adds recipe tests"}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_final_output_fails() {
+        let recipe = Recipe::from_content(RECIPE).unwrap();
+        let fixture: RecipeTestFixture = serde_yaml::from_str(
+            r#"
+responses:
+  - tool_calls:
+      - name: recipe__final_output
+        arguments:
+          title: Missing the summary
+  - text: Done.
+expect:
+  tools_called: [shell]
+"#,
+        )
+        .unwrap();
+
+        let run = run_recipe_test(&recipe, &fixture).await.unwrap();
+        let results = check_expectations(&recipe, &fixture, &run, Path::new("."));
+        let failed: Vec<_> = results
+            .iter()
+            .filter(|r| !r.passed)
+            .map(|r| r.description.as_str())
+            .collect();
+        // Without an accepted final output the agent asks for more responses than the script has
+        assert_eq!(
+            failed,
+            vec![
+                "the run finished",
+                "the final output matches the response schema",
+                "called shell"
+            ]
+        );
+    }
+}
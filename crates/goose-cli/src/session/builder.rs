@@ -7,6 +7,7 @@ use goose::recipe::{Response, SubRecipe};
 use goose::session;
 use goose::session::Identifier;
 use mcp_client::transport::Error as McpClientError;
+use std::collections::HashMap;
 use std::process;
 use std::sync::Arc;
 
@@ -160,20 +161,31 @@ pub struct SessionSettings {
     pub goose_model: Option<String>,
     pub goose_provider: Option<String>,
     pub temperature: Option<f32>,
+    pub tool_timeouts: Option<HashMap<String, u64>>,
 }
 
 pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     // Load config and get provider/model
     let config = Config::global();
+    if let Some(path) = config.untrusted_project() {
+        eprintln!(
+            "{}",
+            style(format!(
+                "Ignoring untrusted project config {}, run 'goose configure --trust-project' to load it",
+                path.display()
+            ))
+            .dim()
+        );
+    }
 
-    let provider_name = session_config
+    let mut provider_name = session_config
         .settings
         .as_ref()
         .and_then(|s| s.goose_provider.clone())
         .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
         .expect("No provider configured. Run 'goose configure' first");
 
-    let model_name = session_config
+    let mut model_name = session_config
         .settings
         .as_ref()
         .and_then(|s| s.goose_model.clone())
@@ -209,7 +221,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         }
     };
     // Keep a reference to the provider for display_session_info
-    let provider_for_display = Arc::clone(&new_provider);
+    let mut provider_for_display = Arc::clone(&new_provider);
 
     // Log model information at startup
     if let Some(lead_worker) = new_provider.as_lead_worker() {
@@ -236,6 +248,15 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         agent.configure_tool_monitor(Some(max_repetitions)).await;
     }
 
+    // Apply recipe tool timeouts before any extension is started
+    if let Some(tool_timeouts) = session_config
+        .settings
+        .as_ref()
+        .and_then(|s| s.tool_timeouts.clone())
+    {
+        agent.set_tool_timeouts(tool_timeouts).await;
+    }
+
     // Handle session file resolution and resuming
     let session_file: Option<std::path::PathBuf> = if session_config.no_session {
         None
@@ -248,7 +269,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
                 }
                 Ok(path) => path,
             };
-            if !session_file.exists() {
+            if !session::session_exists(&session_file) {
                 output::render_error(&format!(
                     "Cannot resume session {} - no such session exists",
                     style(session_file.display()).cyan()
@@ -284,6 +305,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         }
     };
 
+    let mut last_model_switch = None;
     if session_config.resume {
         if let Some(session_file) = session_file.as_ref() {
             // Read the session metadata
@@ -291,6 +313,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
                 output::render_error(&format!("Failed to read session metadata: {}", e));
                 process::exit(1);
             });
+            last_model_switch = metadata.model_switches.last().cloned();
 
             let current_workdir =
                 std::env::current_dir().expect("Failed to get current working directory");
@@ -377,6 +400,26 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         session_config.scheduled_job_id.clone(),
         session_config.max_turns,
     );
+    session.provider_name = Some(provider_name.clone());
+
+    // A resumed session keeps the model it was switched to
+    if let Some(switch) = last_model_switch {
+        match session.switch_model(&switch.provider, &switch.model).await {
+            Ok(provider) => {
+                provider_name = switch.provider;
+                model_name = switch.model;
+                provider_for_display = provider;
+            }
+            Err(e) => eprintln!(
+                "{}",
+                style(format!(
+                    "Warning: Failed to switch back to {}/{}: {}",
+                    switch.provider, switch.model, e
+                ))
+                .yellow()
+            ),
+        }
+    }
 
     // Add extensions if provided
     for extension_str in session_config.extensions {
@@ -6,7 +6,7 @@ use rustyline::{Helper, Result};
 use std::borrow::Cow;
 use std::sync::Arc;
 
-use super::CompletionCache;
+use super::{mentions, CompletionCache};
 
 /// Completer for Goose CLI commands
 pub struct GooseCompleter {
@@ -129,12 +129,22 @@ impl GooseCompleter {
             "/prompts",
             "/prompt",
             "/mode",
+            "/model",
             "/recipe",
         ];
 
+        // Commands defined by command files come after the built-in ones
+        let cache = self.completion_cache.read().unwrap();
+        let custom_commands = cache
+            .commands
+            .iter()
+            .map(|command| format!("/{}", command.name));
+
         // Find commands that match the prefix
         let matching_commands: Vec<Pair> = commands
             .iter()
+            .map(|cmd| cmd.to_string())
+            .chain(custom_commands)
             .filter(|cmd| cmd.starts_with(line))
             .map(|cmd| Pair {
                 display: cmd.to_string(),
@@ -150,6 +160,51 @@ impl GooseCompleter {
         Ok((line.len(), vec![]))
     }
 
+    /// Complete the path of an `@` mention relative to the working directory
+    fn complete_mention(partial: &str) -> Vec<Pair> {
+        let Ok(cwd) = std::env::current_dir() else {
+            return vec![];
+        };
+        mentions::complete_path(partial, &cwd)
+            .into_iter()
+            .map(|path| Pair {
+                display: path.clone(),
+                replacement: path,
+            })
+            .collect()
+    }
+
+    /// Complete argument names of a command defined by a command file
+    fn complete_custom_command_arguments(&self, line: &str) -> Option<(usize, Vec<Pair>)> {
+        let (name, rest) = line[1..].split_once(' ')?;
+        let cache = self.completion_cache.read().unwrap();
+        let command = cache.commands.iter().find(|command| command.name == name)?;
+
+        let given: Vec<&str> = rest
+            .split_whitespace()
+            .filter_map(|part| part.split_once('=').map(|(key, _)| key))
+            .collect();
+        let partial = if line.ends_with(' ') {
+            ""
+        } else {
+            rest.split_whitespace().last().unwrap_or("")
+        };
+        if partial.contains('=') {
+            return Some((line.len(), vec![]));
+        }
+
+        let candidates = command
+            .arguments
+            .iter()
+            .filter(|arg| arg.name.starts_with(partial) && !given.contains(&arg.name.as_str()))
+            .map(|arg| Pair {
+                display: format!("{}=", arg.name),
+                replacement: format!("{}=", arg.name),
+            })
+            .collect();
+        Some((line.len() - partial.len(), candidates))
+    }
+
     /// Complete argument keys for a specific prompt
     fn complete_argument_keys(&self, line: &str) -> Result<(usize, Vec<Pair>)> {
         let parts: Vec<&str> = line[8..].split_whitespace().collect();
@@ -270,6 +325,12 @@ impl Completer for GooseCompleter {
             return Ok((pos, vec![]));
         }
 
+        // A word starting with '@' mentions a file or directory
+        let word_start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        if let Some(partial) = line[word_start..].strip_prefix('@') {
+            return Ok((word_start + 1, Self::complete_mention(partial)));
+        }
+
         // If the line starts with '/', it might be a slash command
         if line.starts_with('/') {
             // If it's just a partial slash command (no space yet)
@@ -335,9 +396,13 @@ impl Completer for GooseCompleter {
                 }
             }
 
-            if line.starts_with("/mode") {
+            if line == "/mode" || line.starts_with("/mode ") {
                 return self.complete_mode_flags(line);
             }
+
+            if let Some(completion) = self.complete_custom_command_arguments(line) {
+                return Ok(completion);
+            }
         }
 
         // Default: no completions
@@ -398,6 +463,7 @@ impl Validator for GooseCompleter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::custom_commands::{CommandArgument, CustomCommand};
     use crate::session::output;
     use mcp_core::prompt::PromptArgument;
     use std::sync::{Arc, RwLock};
@@ -464,6 +530,25 @@ mod tests {
             .prompt_info
             .insert("other_prompt".to_string(), other_prompt_info);
 
+        cache.commands.push(CustomCommand {
+            name: "review".to_string(),
+            description: None,
+            arguments: vec![
+                CommandArgument {
+                    name: "file".to_string(),
+                    required: true,
+                    ..Default::default()
+                },
+                CommandArgument {
+                    name: "focus".to_string(),
+                    ..Default::default()
+                },
+            ],
+            extensions: None,
+            prompt: "Review {{ file }}".to_string(),
+            path: "review.md".into(),
+        });
+
         Arc::new(RwLock::new(cache))
     }
 
@@ -493,6 +578,34 @@ mod tests {
         // Test no match
         let (_pos, candidates) = completer.complete_slash_commands("/nonexistent").unwrap();
         assert_eq!(candidates.len(), 0);
+
+        // Test commands from command files
+        let (_pos, candidates) = completer.complete_slash_commands("/rev").unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].replacement, "/review ");
+    }
+
+    #[test]
+    fn test_complete_custom_command_arguments() {
+        let cache = create_test_cache();
+        let completer = GooseCompleter::new(cache);
+
+        let (pos, candidates) = completer
+            .complete_custom_command_arguments("/review ")
+            .unwrap();
+        assert_eq!(pos, 8);
+        assert_eq!(candidates.len(), 2);
+
+        let (pos, candidates) = completer
+            .complete_custom_command_arguments("/review file=main.rs fo")
+            .unwrap();
+        assert_eq!(pos, 21);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].display, "focus=");
+
+        assert!(completer
+            .complete_custom_command_arguments("/unknown ")
+            .is_none());
     }
 
     #[test]
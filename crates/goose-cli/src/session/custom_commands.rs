@@ -0,0 +1,300 @@
+use anyhow::{anyhow, Context, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
+use goose::config::layers::{find_project_config_dir, is_trusted_project, system_config_dir};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
+use crate::recipes::template_recipe::render_recipe_content_with_params;
+
+/// Directory name, under the config directory or a project's `.goose`, holding command files
+const COMMANDS_DIR: &str = "commands";
+
+/// Template variable holding the words given to a command that are not `key=value` pairs
+pub const ARGS_VARIABLE: &str = "args";
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CommandArgument {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CommandHeader {
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    arguments: Vec<CommandArgument>,
+    #[serde(default)]
+    extensions: Option<Vec<String>>,
+    /// The prompt of YAML command files, Markdown files use their body
+    #[serde(default)]
+    prompt: Option<String>,
+}
+
+/// A slash command defined by a Markdown or YAML prompt file
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCommand {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<CommandArgument>,
+    /// Extensions the model may use while answering the command, all of them when None
+    pub extensions: Option<Vec<String>>,
+    /// minijinja template of the message sent to the model
+    pub prompt: String,
+    pub path: PathBuf,
+}
+
+impl CustomCommand {
+    /// Read a command from a `.md` file with optional YAML front matter, or a `.yaml` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .ok_or_else(|| anyhow!("Invalid command file {}", path.display()))?;
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read command file {}", path.display()))?;
+
+        let (header, body) = if is_markdown(path) {
+            split_front_matter(&content)?
+        } else {
+            let header: CommandHeader = serde_yaml::from_str(&content)
+                .with_context(|| format!("Invalid command file {}", path.display()))?;
+            (header, None)
+        };
+        let prompt = body
+            .or(header.prompt)
+            .filter(|prompt| !prompt.trim().is_empty())
+            .ok_or_else(|| anyhow!("Command file {} has no prompt", path.display()))?;
+
+        Ok(Self {
+            name,
+            description: header.description,
+            arguments: header.arguments,
+            extensions: header.extensions,
+            prompt,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Render the prompt with the arguments given to the command
+    pub fn render(&self, given: &HashMap<String, String>) -> Result<String> {
+        let mut params = given.clone();
+        for argument in &self.arguments {
+            if params.contains_key(&argument.name) {
+                continue;
+            }
+            match &argument.default {
+                Some(default) => {
+                    params.insert(argument.name.clone(), default.clone());
+                }
+                None if argument.required => {
+                    return Err(anyhow!(
+                        "Missing argument '{}' for /{}",
+                        argument.name,
+                        self.name
+                    ));
+                }
+                None => {
+                    params.insert(argument.name.clone(), String::new());
+                }
+            }
+        }
+        params.entry(ARGS_VARIABLE.to_string()).or_default();
+
+        // Includes resolve relative to the command file
+        let dir = self
+            .path
+            .parent()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        params.insert(BUILT_IN_RECIPE_DIR_PARAM.to_string(), dir);
+        render_recipe_content_with_params(&self.prompt, &params)
+    }
+}
+
+fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
+}
+
+fn is_command_file(path: &Path) -> bool {
+    path.is_file()
+        && path.extension().is_some_and(|ext| {
+            ["md", "yaml", "yml"]
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Split a Markdown file into its YAML front matter and body
+fn split_front_matter(content: &str) -> Result<(CommandHeader, Option<String>)> {
+    let Some(rest) = content.strip_prefix("---") else {
+        return Ok((CommandHeader::default(), Some(content.to_string())));
+    };
+    let Some((front_matter, body)) = rest.split_once("\n---") else {
+        return Err(anyhow!("Front matter is not closed with ---"));
+    };
+    let header = if front_matter.trim().is_empty() {
+        CommandHeader::default()
+    } else {
+        serde_yaml::from_str(front_matter).context("Invalid front matter")?
+    };
+    // Drop the rest of the closing --- line
+    let body = body.split_once('\n').map(|(_, body)| body).unwrap_or("");
+    Ok((header, Some(body.trim_start().to_string())))
+}
+
+/// Directories searched for commands, later ones take precedence
+fn command_dirs() -> Vec<PathBuf> {
+    let Ok(strategy) = choose_app_strategy(crate::APP_STRATEGY.clone()) else {
+        return Vec::new();
+    };
+    let cwd = std::env::current_dir().ok();
+    command_dirs_in(&strategy.config_dir(), &system_config_dir(), cwd.as_deref())
+}
+
+/// The project's commands, only once the project is trusted like its config file, then the
+/// user's commands so a cloned repository cannot replace them
+fn command_dirs_in(config_dir: &Path, system_dir: &Path, cwd: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(project_config_dir) =
+        cwd.and_then(|cwd| find_project_config_dir(cwd, Some(config_dir)))
+    {
+        let project_dir = project_config_dir.parent().unwrap_or(&project_config_dir);
+        let system_path = system_dir.join("config.yaml");
+        let config_path = config_dir.join("config.yaml");
+        if is_trusted_project(project_dir, &[&system_path, &config_path]) {
+            dirs.push(project_config_dir.join(COMMANDS_DIR));
+        }
+    }
+    dirs.push(config_dir.join(COMMANDS_DIR));
+    dirs
+}
+
+fn load_from(dirs: &[PathBuf]) -> Vec<CustomCommand> {
+    let mut commands: HashMap<String, CustomCommand> = HashMap::new();
+    for dir in dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| is_command_file(path))
+            .collect();
+        paths.sort();
+        for path in paths {
+            match CustomCommand::from_file(&path) {
+                Ok(command) => {
+                    if let Some(replaced) = commands.insert(command.name.clone(), command) {
+                        tracing::warn!(
+                            "{} replaces the /{} command from {}",
+                            path.display(),
+                            replaced.name,
+                            replaced.path.display()
+                        );
+                    }
+                }
+                Err(e) => tracing::warn!("Skipping command file {}: {:#}", path.display(), e),
+            }
+        }
+    }
+    let mut commands: Vec<CustomCommand> = commands.into_values().collect();
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+/// All user and trusted project commands, a user command replaces a project command of the same name
+pub fn load_commands() -> Vec<CustomCommand> {
+    load_from(&command_dirs())
+}
+
+/// The command invoked as `/name`, if a command file defines it
+pub fn find_command(name: &str) -> Option<CustomCommand> {
+    load_commands()
+        .into_iter()
+        .find(|command| command.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_markdown_command_with_front_matter() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("review.md");
+        std::fs::write(
+            &path,
+            "---\ndescription: Review a file\narguments:\n  - name: file\n    required: true\n  - name: focus\n    default: correctness\nextensions: [developer]\n---\nReview {{ file }} for {{ focus }}. {{ args }}\n",
+        )
+        .unwrap();
+
+        let command = CustomCommand::from_file(&path).unwrap();
+        assert_eq!(command.name, "review");
+        assert_eq!(command.description.as_deref(), Some("Review a file"));
+        assert_eq!(command.extensions, Some(vec!["developer".to_string()]));
+
+        let given = HashMap::from([
+            ("file".to_string(), "main.rs".to_string()),
+            (ARGS_VARIABLE.to_string(), "Be brief.".to_string()),
+        ]);
+        assert_eq!(
+            command.render(&given).unwrap(),
+            "Review main.rs for correctness. Be brief."
+        );
+        assert!(command.render(&HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_user_commands_replace_project_commands() {
+        let user = TempDir::new().unwrap();
+        let project = TempDir::new().unwrap();
+        std::fs::write(user.path().join("hello.md"), "Hello from the user").unwrap();
+        std::fs::write(user.path().join("notes.txt"), "not a command").unwrap();
+        std::fs::write(
+            project.path().join("hello.yaml"),
+            "description: Greet\nprompt: Hello from the project\n",
+        )
+        .unwrap();
+        std::fs::write(project.path().join("empty.md"), "---\n---\n").unwrap();
+
+        let commands = load_from(&[project.path().to_path_buf(), user.path().to_path_buf()]);
+        assert_eq!(commands.len(), 1);
+        assert_eq!(commands[0].prompt, "Hello from the user");
+    }
+
+    #[test]
+    fn test_project_commands_need_a_trusted_project() {
+        let root = TempDir::new().unwrap();
+        let config_dir = root.path().join("config");
+        let system_dir = root.path().join("system");
+        let project = root.path().join("project");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::create_dir_all(project.join(".goose").join(COMMANDS_DIR)).unwrap();
+
+        let dirs = command_dirs_in(&config_dir, &system_dir, Some(&project));
+        assert_eq!(dirs, vec![config_dir.join(COMMANDS_DIR)]);
+
+        std::fs::write(
+            config_dir.join("config.yaml"),
+            format!("GOOSE_TRUSTED_PROJECTS:\n  - {}\n", project.display()),
+        )
+        .unwrap();
+        let dirs = command_dirs_in(&config_dir, &system_dir, Some(&project));
+        assert_eq!(
+            dirs,
+            vec![
+                project.join(".goose").join(COMMANDS_DIR),
+                config_dir.join(COMMANDS_DIR)
+            ]
+        );
+    }
+}
@@ -0,0 +1,402 @@
+use goose::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::session::SessionMetadata;
+use mcp_core::content::Content as McpContent;
+use mcp_core::resource::ResourceContents;
+use mcp_core::role::Role;
+use once_cell::sync::Lazy;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag};
+use std::collections::HashMap;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::SyntaxSet;
+
+static SYNTAXES: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
+static THEME: Lazy<Theme> = Lazy::new(|| {
+    ThemeSet::load_defaults()
+        .themes
+        .remove("InspiredGitHub")
+        .unwrap_or_default()
+});
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2328; line-height: 1.5; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; }
+header dl { display: grid; grid-template-columns: max-content auto; gap: 0.25rem 1rem; color: #59636e; }
+header dd { margin: 0; }
+.message { border: 1px solid #d0d7de; border-radius: 6px; padding: 0.5rem 1rem; margin-bottom: 1rem; }
+.message.user { background: #f6f8fa; }
+.role { font-weight: 600; font-size: 0.85rem; text-transform: uppercase; color: #59636e; }
+.role a { color: inherit; text-decoration: none; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.5rem 0; padding: 0.25rem 0.75rem; background: #fff; }
+details summary { cursor: pointer; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.9rem; }
+details.error summary { color: #cf222e; }
+details.thinking { color: #59636e; }
+pre { overflow-x: auto; padding: 0.75rem; border-radius: 6px; font-size: 0.85rem; }
+pre.plain { background: #f6f8fa; white-space: pre-wrap; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+img { max-width: 100%; border-radius: 6px; }
+.note { color: #59636e; font-style: italic; }
+"#;
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Code with inline highlighting styles, plain when the language is unknown
+fn highlight(code: &str, language: &str) -> String {
+    SYNTAXES
+        .find_syntax_by_token(language)
+        .and_then(|syntax| highlighted_html_for_string(code, &SYNTAXES, syntax, &THEME).ok())
+        .unwrap_or_else(|| format!("<pre class=\"plain\"><code>{}</code></pre>", escape(code)))
+}
+
+/// Whether a link or image destination uses one of `schemes`, ignoring case
+fn has_scheme(destination: &str, schemes: &[&str]) -> bool {
+    let destination = destination.trim_start().to_ascii_lowercase();
+    schemes.iter().any(|scheme| destination.starts_with(scheme))
+}
+
+/// What a link or image in the text is rendered as, None to keep only its text.
+/// Only web and mail links and inline images are kept, remote images become links so the
+/// page stays self-contained.
+fn safe_link(tag: Tag) -> Option<Tag> {
+    match tag {
+        Tag::Link(kind, destination, title) => has_scheme(
+            &destination,
+            &["http://", "https://", "mailto:"],
+        )
+        .then_some(Tag::Link(kind, destination, title)),
+        Tag::Image(kind, destination, title) => {
+            if has_scheme(&destination, &["data:image/"]) {
+                Some(Tag::Image(kind, destination, title))
+            } else if has_scheme(&destination, &["http://", "https://"]) {
+                Some(Tag::Link(kind, destination, title))
+            } else {
+                None
+            }
+        }
+        tag => Some(tag),
+    }
+}
+
+/// Render model or user text as Markdown, with highlighted code blocks.
+/// Raw HTML in the text is shown as text and links that could run scripts are dropped, a shared
+/// transcript should not run what a model wrote.
+fn markdown(text: &str) -> String {
+    let mut events = Vec::new();
+    let mut code_block: Option<(String, String)> = None;
+    // What each open link or image is rendered as, images can sit inside links
+    let mut links: Vec<Option<Tag>> = Vec::new();
+    for event in Parser::new_ext(text, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH) {
+        match (event, &mut code_block) {
+            (Event::Start(Tag::CodeBlock(kind)), _) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            (Event::End(Tag::CodeBlock(_)), block) => {
+                if let Some((language, code)) = block.take() {
+                    events.push(Event::Html(highlight(&code, &language).into()));
+                }
+            }
+            (Event::Text(text), Some((_, code))) => code.push_str(&text),
+            (Event::Html(html), _) => events.push(Event::Text(html)),
+            (Event::Start(tag @ (Tag::Link(..) | Tag::Image(..))), _) => {
+                let tag = safe_link(tag);
+                if let Some(tag) = &tag {
+                    events.push(Event::Start(tag.clone()));
+                }
+                links.push(tag);
+            }
+            (Event::End(Tag::Link(..) | Tag::Image(..)), _) => {
+                if let Some(tag) = links.pop().flatten() {
+                    events.push(Event::End(tag));
+                }
+            }
+            (event, _) => events.push(event),
+        }
+    }
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, events.into_iter());
+    html
+}
+
+fn image(data: &str, mime_type: &str) -> String {
+    format!(
+        "<img src=\"data:{};base64,{}\" alt=\"{}\">",
+        escape(mime_type),
+        escape(data),
+        escape(mime_type)
+    )
+}
+
+/// Language to highlight a file or tool output in
+fn language_for(uri: &str, text: &str) -> &'static str {
+    let extension = uri.rsplit('.').next().unwrap_or("");
+    match extension {
+        "rs" => "rs",
+        "js" | "jsx" => "js",
+        "ts" | "tsx" => "ts",
+        "py" => "py",
+        "json" => "json",
+        "yaml" | "yml" => "yaml",
+        "md" => "md",
+        "html" => "html",
+        "css" => "css",
+        "sh" => "sh",
+        "go" => "go",
+        "java" => "java",
+        _ => {
+            let trimmed = text.trim();
+            if (trimmed.starts_with('{') && trimmed.ends_with('}'))
+                || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+            {
+                "json"
+            } else {
+                ""
+            }
+        }
+    }
+}
+
+fn tool_output(contents: &[McpContent]) -> String {
+    let mut html = String::new();
+    for content in contents {
+        // Same as the Markdown export, only what the model was shown
+        if let Some(audience) = content.audience() {
+            if !audience.contains(&Role::Assistant) {
+                continue;
+            }
+        }
+        match content {
+            McpContent::Text(text) => {
+                html.push_str(&highlight(&text.text, language_for("", &text.text)))
+            }
+            McpContent::Image(content) => html.push_str(&image(&content.data, &content.mime_type)),
+            McpContent::Resource(resource) => match &resource.resource {
+                ResourceContents::TextResourceContents { uri, text, .. } => {
+                    html.push_str(&format!("<p><code>{}</code></p>", escape(uri)));
+                    html.push_str(&highlight(text, language_for(uri, text)));
+                }
+                ResourceContents::BlobResourceContents {
+                    uri,
+                    mime_type,
+                    blob,
+                } => match mime_type {
+                    Some(mime_type) if mime_type.starts_with("image/") => {
+                        html.push_str(&image(blob, mime_type))
+                    }
+                    _ => html.push_str(&format!(
+                        "<p class=\"note\">Binary file <code>{}</code> ({} bytes)</p>",
+                        escape(uri),
+                        blob.len()
+                    )),
+                },
+            },
+        }
+    }
+    if html.is_empty() {
+        html.push_str("<p class=\"note\">No output</p>");
+    }
+    html
+}
+
+/// A collapsed tool call, with its result when the session has one
+fn tool_call(request: &ToolRequest, response: Option<&ToolResponse>) -> String {
+    let (name, arguments) = match &request.tool_call {
+        Ok(call) => (
+            call.name.clone(),
+            highlight(
+                &serde_json::to_string_pretty(&call.arguments).unwrap_or_default(),
+                "json",
+            ),
+        ),
+        Err(e) => (
+            "invalid tool call".to_string(),
+            format!("<pre class=\"plain\">{}</pre>", escape(&e.to_string())),
+        ),
+    };
+    let (class, result) = match response.map(|r| &r.tool_result) {
+        Some(Ok(contents)) => ("tool", tool_output(contents)),
+        Some(Err(e)) => (
+            "tool error",
+            format!("<pre class=\"plain\">{}</pre>", escape(&e.to_string())),
+        ),
+        None => ("tool", "<p class=\"note\">No result</p>".to_string()),
+    };
+    format!(
+        "<details class=\"{}\"><summary>{}</summary>{}{}</details>\n",
+        class,
+        escape(&name),
+        arguments,
+        result
+    )
+}
+
+fn message_body(message: &Message, responses: &HashMap<&str, &ToolResponse>) -> String {
+    let mut html = String::new();
+    for content in &message.content {
+        match content {
+            MessageContent::Text(text) => html.push_str(&markdown(&text.text)),
+            MessageContent::Image(content) => {
+                html.push_str(&image(&content.data, &content.mime_type))
+            }
+            MessageContent::ToolRequest(request) => html.push_str(&tool_call(
+                request,
+                responses.get(request.id.as_str()).copied(),
+            )),
+            // Answers to requests of this session are shown with the request
+            MessageContent::ToolResponse(response)
+                if responses.contains_key(response.id.as_str()) => {}
+            MessageContent::ToolResponse(response) => html.push_str(&format!(
+                "<details class=\"tool\"><summary>result of {}</summary>{}</details>\n",
+                escape(&response.id),
+                match &response.tool_result {
+                    Ok(contents) => tool_output(contents),
+                    Err(e) => format!("<pre class=\"plain\">{}</pre>", escape(&e.to_string())),
+                }
+            )),
+            MessageContent::Thinking(thinking) => html.push_str(&format!(
+                "<details class=\"thinking\"><summary>Thinking</summary>{}</details>\n",
+                markdown(&thinking.thinking)
+            )),
+            MessageContent::RedactedThinking(_) => {
+                html.push_str("<p class=\"note\">Thinking was redacted</p>\n")
+            }
+            _ => {}
+        }
+    }
+    html
+}
+
+/// A self-contained HTML page of the session, to share without goose
+pub fn session_to_html(
+    session_name: &str,
+    metadata: &SessionMetadata,
+    messages: &[Message],
+) -> String {
+    let requested: Vec<&str> = messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|content| match content {
+            MessageContent::ToolRequest(request) => Some(request.id.as_str()),
+            _ => None,
+        })
+        .collect();
+    let responses: HashMap<&str, &ToolResponse> = messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(response) if requested.contains(&response.id.as_str()) => {
+                Some((response.id.as_str(), response))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let title = if metadata.description.is_empty() {
+        session_name.to_string()
+    } else {
+        metadata.description.clone()
+    };
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<header>\n<h1>{}</h1>\n<dl>\n\
+         <dt>Session</dt><dd>{}</dd>\n<dt>Working directory</dt><dd><code>{}</code></dd>\n\
+         <dt>Messages</dt><dd>{}</dd>\n",
+        escape(&title),
+        STYLE,
+        escape(&title),
+        escape(session_name),
+        escape(&metadata.working_dir.to_string_lossy()),
+        messages.len()
+    );
+    if let Some(tokens) = metadata.accumulated_total_tokens {
+        html.push_str(&format!("<dt>Tokens</dt><dd>{}</dd>\n", tokens));
+    }
+    html.push_str("</dl>\n</header>\n<main>\n");
+
+    for (index, message) in messages.iter().enumerate() {
+        let body = message_body(message, &responses);
+        if body.is_empty() {
+            continue;
+        }
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        html.push_str(&format!(
+            "<section class=\"message {role}\" id=\"message-{index}\">\n\
+             <div class=\"role\"><a href=\"#message-{index}\">{role} #{index}</a></div>\n{body}</section>\n"
+        ));
+    }
+    if messages.is_empty() {
+        html.push_str("<p class=\"note\">This session has no messages</p>\n");
+    }
+    html.push_str("</main>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::ToolCall;
+    use serde_json::json;
+
+    #[test]
+    fn test_session_to_html() {
+        let messages = vec![
+            Message::user()
+                .with_text("Run <script>alert(1)</script> and show\n\n```rust\nfn main() {}\n```"),
+            Message::assistant().with_tool_request(
+                "call_1",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            ),
+            Message::user().with_tool_response("call_1", Ok(vec![McpContent::text("Cargo.toml")])),
+            Message::assistant().with_image("aGVsbG8=", "image/png"),
+        ];
+        let html = session_to_html("20250101_1", &SessionMetadata::default(), &messages);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;"));
+        // The code block is highlighted with inline styles
+        assert!(html.contains("<pre style="));
+        // The tool result sits inside its call, the response message adds nothing
+        assert!(html.contains("<details class=\"tool\"><summary>developer__shell</summary>"));
+        assert!(html.contains("Cargo.toml"));
+        assert!(!html.contains("id=\"message-2\""));
+        assert!(html.contains("<img src=\"data:image/png;base64,aGVsbG8=\""));
+    }
+
+    #[test]
+    fn test_markdown_drops_unsafe_links() {
+        let html =
+            markdown("[x](javascript:alert(1)) [y]( JavaScript:alert(2)) [z](https://example.com)");
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(html.contains("x "));
+        assert!(html.contains("<a href=\"https://example.com\">z</a>"));
+
+        let html = markdown("![chart](https://example.com/chart.png) ![p](data:image/png;base64,aGk=) ![f](file:///etc/passwd)");
+        assert!(html.contains("<a href=\"https://example.com/chart.png\">chart</a>"));
+        assert!(html.contains("<img src=\"data:image/png;base64,aGk=\""));
+        assert!(!html.contains("file:"));
+        assert_eq!(html.matches("<img").count(), 1);
+    }
+}
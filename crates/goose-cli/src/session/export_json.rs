@@ -0,0 +1,226 @@
+use chrono::{DateTime, Utc};
+use goose::message::{Message, MessageContent, ToolResponse};
+use goose::session::SessionMetadata;
+use mcp_core::content::Content as McpContent;
+use mcp_core::resource::ResourceContents;
+use mcp_core::role::Role;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Version of the exported JSON layout, raised when fields change meaning
+const EXPORT_VERSION: u32 = 1;
+
+/// A session flattened for analysis: tool calls carry their results, so every
+/// message stands on its own without the protocol's request and response messages
+#[derive(Debug, Serialize)]
+pub struct ExportedSession {
+    pub version: u32,
+    pub session_id: String,
+    pub description: String,
+    pub working_dir: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule_id: Option<String>,
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+    pub messages: Vec<ExportedMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedMessage {
+    /// Position of the message in the session file
+    pub index: usize,
+    pub role: Role,
+    pub created: Option<DateTime<Utc>>,
+    /// Text parts of the message joined by newlines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ExportedToolCall>,
+    /// MIME types of the images attached to the message
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportedToolCall {
+    pub id: String,
+    pub name: Option<String>,
+    pub arguments: Option<Value>,
+    /// Text the tool returned, None if it failed or never answered
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The text of a tool result, images and binary resources are only named
+fn tool_output(contents: &[McpContent]) -> String {
+    contents
+        .iter()
+        .map(|content| match content {
+            McpContent::Text(text) => text.text.clone(),
+            McpContent::Image(image) => format!("[image: {}]", image.mime_type),
+            McpContent::Resource(resource) => match &resource.resource {
+                ResourceContents::TextResourceContents { text, .. } => text.clone(),
+                ResourceContents::BlobResourceContents { uri, .. } => {
+                    format!("[binary resource: {}]", uri)
+                }
+            },
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn export_tool_call(
+    id: &str,
+    call: Result<(&str, &Value), String>,
+    responses: &HashMap<&str, &ToolResponse>,
+) -> ExportedToolCall {
+    let (name, arguments, request_error) = match call {
+        Ok((name, arguments)) => (Some(name.to_string()), Some(arguments.clone()), None),
+        Err(e) => (None, None, Some(e)),
+    };
+    let (output, response_error) = match responses.get(id).map(|r| &r.tool_result) {
+        Some(Ok(contents)) => (Some(tool_output(contents)), None),
+        Some(Err(e)) => (None, Some(e.to_string())),
+        None => (None, None),
+    };
+    ExportedToolCall {
+        id: id.to_string(),
+        name,
+        arguments,
+        output,
+        error: request_error.or(response_error),
+    }
+}
+
+fn export_message(
+    index: usize,
+    message: &Message,
+    responses: &HashMap<&str, &ToolResponse>,
+) -> ExportedMessage {
+    let mut texts = Vec::new();
+    let mut thinking = Vec::new();
+    let mut tool_calls = Vec::new();
+    let mut images = Vec::new();
+
+    for content in &message.content {
+        match content {
+            MessageContent::Text(text) => texts.push(text.text.clone()),
+            MessageContent::Thinking(content) => thinking.push(content.thinking.clone()),
+            MessageContent::Image(image) => images.push(image.mime_type.clone()),
+            MessageContent::ToolRequest(request) => tool_calls.push(export_tool_call(
+                &request.id,
+                request
+                    .tool_call
+                    .as_ref()
+                    .map(|call| (call.name.as_str(), &call.arguments))
+                    .map_err(|e| e.to_string()),
+                responses,
+            )),
+            MessageContent::FrontendToolRequest(request) => tool_calls.push(export_tool_call(
+                &request.id,
+                request
+                    .tool_call
+                    .as_ref()
+                    .map(|call| (call.name.as_str(), &call.arguments))
+                    .map_err(|e| e.to_string()),
+                responses,
+            )),
+            _ => {}
+        }
+    }
+
+    let join = |parts: Vec<String>| (!parts.is_empty()).then(|| parts.join("\n"));
+    ExportedMessage {
+        index,
+        role: message.role.clone(),
+        created: DateTime::from_timestamp(message.created, 0),
+        text: join(texts),
+        thinking: join(thinking),
+        tool_calls,
+        images,
+    }
+}
+
+/// Whether a message only answers tool calls, which the export folds into the calls
+fn only_tool_responses(message: &Message) -> bool {
+    !message.content.is_empty()
+        && message
+            .content
+            .iter()
+            .all(|content| matches!(content, MessageContent::ToolResponse(_)))
+}
+
+pub fn export_session(
+    session_id: &str,
+    metadata: &SessionMetadata,
+    messages: &[Message],
+) -> ExportedSession {
+    let responses: HashMap<&str, &ToolResponse> = messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(response) => Some((response.id.as_str(), response)),
+            _ => None,
+        })
+        .collect();
+
+    ExportedSession {
+        version: EXPORT_VERSION,
+        session_id: session_id.to_string(),
+        description: metadata.description.clone(),
+        working_dir: metadata.working_dir.clone(),
+        schedule_id: metadata.schedule_id.clone(),
+        input_tokens: metadata.accumulated_input_tokens,
+        output_tokens: metadata.accumulated_output_tokens,
+        total_tokens: metadata.accumulated_total_tokens,
+        messages: messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| !only_tool_responses(message))
+            .map(|(index, message)| export_message(index, message, &responses))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::ToolCall;
+    use serde_json::json;
+
+    #[test]
+    fn test_tool_results_are_folded_into_calls() {
+        let messages = vec![
+            Message::user().with_text("List the files"),
+            Message::assistant().with_text("Listing").with_tool_request(
+                "call_1",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            ),
+            Message::user().with_tool_response("call_1", Ok(vec![McpContent::text("Cargo.toml")])),
+            Message::assistant().with_text("There is one file."),
+        ];
+        let exported = export_session("20250101_1", &SessionMetadata::default(), &messages);
+        let value = serde_json::to_value(&exported).unwrap();
+
+        assert_eq!(value["version"], 1);
+        let indices: Vec<_> = exported.messages.iter().map(|m| m.index).collect();
+        assert_eq!(indices, vec![0, 1, 3]);
+        assert_eq!(
+            value["messages"][1]["tool_calls"][0],
+            json!({
+                "id": "call_1",
+                "name": "developer__shell",
+                "arguments": {"command": "ls"},
+                "output": "Cargo.toml"
+            })
+        );
+        assert_eq!(value["messages"][2]["text"], "There is one file.");
+        assert!(value["messages"][2].get("tool_calls").is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use super::completion::GooseCompleter;
+use super::custom_commands::{self, ARGS_VARIABLE};
 use anyhow::Result;
 use rustyline::Editor;
 use shlex;
@@ -21,6 +22,10 @@ pub enum InputResult {
     Clear,
     Recipe(Option<String>),
     Summarize,
+    Rewind(RewindCommandOptions),
+    Branch(Option<String>),
+    SwitchModel(Option<String>),
+    CustomCommand(CustomCommandOptions),
 }
 
 #[derive(Debug)]
@@ -30,6 +35,20 @@ pub struct PromptCommandOptions {
     pub arguments: HashMap<String, String>,
 }
 
+#[derive(Debug)]
+pub struct CustomCommandOptions {
+    pub name: String,
+    /// `key=value` arguments, plus the remaining words under `args`
+    pub arguments: HashMap<String, String>,
+}
+
+#[derive(Debug)]
+pub struct RewindCommandOptions {
+    pub message_index: Option<usize>,
+    pub discard: bool,
+    pub restore_files: bool,
+}
+
 #[derive(Debug)]
 pub struct PlanCommandOptions {
     pub message_text: String,
@@ -96,6 +115,9 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
     const CMD_CLEAR: &str = "/clear";
     const CMD_RECIPE: &str = "/recipe";
     const CMD_SUMMARIZE: &str = "/summarize";
+    const CMD_REWIND: &str = "/rewind";
+    const CMD_BRANCH: &str = "/branch";
+    const CMD_MODEL: &str = "/model";
 
     match input {
         "/exit" | "/quit" => Some(InputResult::Exit),
@@ -156,8 +178,57 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s == CMD_CLEAR => Some(InputResult::Clear),
         s if s.starts_with(CMD_RECIPE) => parse_recipe_command(s),
         s if s == CMD_SUMMARIZE => Some(InputResult::Summarize),
-        _ => None,
+        s if s == CMD_REWIND || s.starts_with("/rewind ") => {
+            parse_rewind_command(s[CMD_REWIND.len()..].trim())
+        }
+        s if s == CMD_BRANCH || s.starts_with("/branch ") => {
+            let name = s[CMD_BRANCH.len()..].trim();
+            Some(InputResult::Branch(
+                (!name.is_empty()).then(|| name.to_string()),
+            ))
+        }
+        s if s == CMD_MODEL || s.starts_with("/model ") => {
+            let spec = s[CMD_MODEL.len()..].trim();
+            Some(InputResult::SwitchModel(
+                (!spec.is_empty()).then(|| spec.to_string()),
+            ))
+        }
+        s => parse_custom_command(s),
+    }
+}
+
+/// A `/name` command defined by a command file, built-in commands take precedence
+fn parse_custom_command(s: &str) -> Option<InputResult> {
+    let (name, args) = s[1..]
+        .split_once(char::is_whitespace)
+        .unwrap_or((&s[1..], ""));
+    custom_commands::find_command(name)?;
+    Some(InputResult::CustomCommand(CustomCommandOptions {
+        name: name.to_string(),
+        arguments: parse_command_arguments(args),
+    }))
+}
+
+fn parse_command_arguments(args: &str) -> HashMap<String, String> {
+    let parts = shlex::split(args).unwrap_or_else(|| {
+        args.split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    });
+    let mut arguments = HashMap::new();
+    let mut words = Vec::new();
+    for part in parts {
+        match part.split_once('=') {
+            Some((key, value))
+                if !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_') =>
+            {
+                arguments.insert(key.to_string(), value.to_string());
+            }
+            _ => words.push(part),
+        }
     }
+    arguments.insert(ARGS_VARIABLE.to_string(), words.join(" "));
+    arguments
 }
 
 fn parse_recipe_command(s: &str) -> Option<InputResult> {
@@ -238,6 +309,33 @@ fn parse_prompt_command(args: &str) -> Option<InputResult> {
     Some(InputResult::PromptCommand(options))
 }
 
+fn parse_rewind_command(args: &str) -> Option<InputResult> {
+    let mut options = RewindCommandOptions {
+        message_index: None,
+        discard: false,
+        restore_files: false,
+    };
+
+    for part in args.split_whitespace() {
+        match part {
+            "--discard" => options.discard = true,
+            "--restore-files" => options.restore_files = true,
+            _ => match part.parse::<usize>() {
+                Ok(index) => options.message_index = Some(index),
+                Err(_) => {
+                    println!(
+                        "{}",
+                        console::style(format!("Invalid /rewind argument: {}", part)).red()
+                    );
+                    return Some(InputResult::Retry);
+                }
+            },
+        }
+    }
+
+    Some(InputResult::Rewind(options))
+}
+
 fn parse_plan_command(input: String) -> Option<InputResult> {
     let options = PlanCommandOptions {
         message_text: input.trim().to_string(),
@@ -266,8 +364,15 @@ fn print_help() {
 /recipe [filepath] - Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
                        If no filepath is provided, it will be saved to ./recipe.yaml.
 /summarize - Summarize the current conversation to reduce context length while preserving key information.
+/rewind [n] [--discard] [--restore-files] - List the messages you can rewind to, or go back to before message n.
+                       Later messages are kept as a new branch unless --discard is given.
+                       --restore-files undoes the text_editor edits made after that point.
+/branch [name] - List the branches of this session, or switch to the named branch.
+/model [provider/model] - Show the current model, or switch provider and model for this session only.
+                       Without a provider the model is switched within the current provider.
 /? or /help - Display this help message
 /clear - Clears the current chat history
+/<command> [key=value...] [text] - Run a command defined in ~/.config/goose/commands or .goose/commands
 
 Navigation:
 Ctrl+C - Interrupt goose (resets the interaction to before the interrupted user request)
@@ -511,4 +616,72 @@ mod tests {
         let result = handle_slash_command("  /summarize  ");
         assert!(matches!(result, Some(InputResult::Summarize)));
     }
+
+    #[test]
+    fn test_rewind_command() {
+        if let Some(InputResult::Rewind(options)) = handle_slash_command("/rewind") {
+            assert!(options.message_index.is_none());
+            assert!(!options.discard);
+        } else {
+            panic!("Expected Rewind");
+        }
+
+        if let Some(InputResult::Rewind(options)) =
+            handle_slash_command("/rewind 4 --discard --restore-files")
+        {
+            assert_eq!(options.message_index, Some(4));
+            assert!(options.discard);
+            assert!(options.restore_files);
+        } else {
+            panic!("Expected Rewind with options");
+        }
+
+        let result = handle_slash_command("/rewind last");
+        assert!(matches!(result, Some(InputResult::Retry)));
+    }
+
+    #[test]
+    fn test_branch_command() {
+        assert!(matches!(
+            handle_slash_command("/branch"),
+            Some(InputResult::Branch(None))
+        ));
+        if let Some(InputResult::Branch(Some(name))) = handle_slash_command("/branch branch-1") {
+            assert_eq!(name, "branch-1");
+        } else {
+            panic!("Expected Branch with a name");
+        }
+    }
+
+    #[test]
+    fn test_model_command() {
+        assert!(matches!(
+            handle_slash_command("/model"),
+            Some(InputResult::SwitchModel(None))
+        ));
+        if let Some(InputResult::SwitchModel(Some(spec))) =
+            handle_slash_command("/model  anthropic/claude-sonnet-4 ")
+        {
+            assert_eq!(spec, "anthropic/claude-sonnet-4");
+        } else {
+            panic!("Expected SwitchModel with a spec");
+        }
+        // /mode is a different command
+        assert!(matches!(
+            handle_slash_command("/mode auto"),
+            Some(InputResult::GooseMode(_))
+        ));
+    }
+
+    #[test]
+    fn test_custom_command_arguments() {
+        let arguments =
+            parse_command_arguments(r#"file=src/main.rs focus="error handling" be brief"#);
+        assert_eq!(arguments["file"], "src/main.rs");
+        assert_eq!(arguments["focus"], "error handling");
+        assert_eq!(arguments[ARGS_VARIABLE], "be brief");
+
+        // Unknown commands are sent to the model as they are
+        assert!(handle_slash_command("/no-such-command-here").is_none());
+    }
 }
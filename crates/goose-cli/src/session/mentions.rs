@@ -0,0 +1,264 @@
+use goose::token_counter::TokenCounter;
+use ignore::gitignore::Gitignore;
+use std::path::{Path, PathBuf};
+
+/// Most tokens the attachments of one message may add together
+const MAX_ATTACHMENT_TOKENS: usize = 50_000;
+/// Largest file read for a mention, bigger files would not fit the token budget anyway
+const MAX_ATTACHMENT_BYTES: u64 = 1_000_000;
+/// Deepest level listed for a mentioned directory
+const MAX_TREE_DEPTH: usize = 3;
+/// Most entries listed for a mentioned directory
+const MAX_TREE_ENTRIES: usize = 200;
+
+/// File and directory contents attached to a message for its `@path` mentions
+#[derive(Debug, Default)]
+pub struct Attachments {
+    /// One text block per attached path
+    pub contents: Vec<String>,
+    /// Why mentioned paths were left out, to show the user
+    pub skipped: Vec<String>,
+}
+
+/// The paths mentioned as `@path` at the start of a word, in order and without repeats
+pub fn find_mentions(text: &str) -> Vec<String> {
+    let mut mentions: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let Some(path) = word.strip_prefix('@') else {
+            continue;
+        };
+        let path = path.trim_end_matches([',', '.', ';', ':', '!', '?', ')', '"', '\'']);
+        if !path.is_empty() && !mentions.iter().any(|m| m == path) {
+            mentions.push(path.to_string());
+        }
+    }
+    mentions
+}
+
+/// Resolve a mention against the working directory, expanding a leading `~`
+pub fn resolve_mention(mention: &str, cwd: &Path) -> PathBuf {
+    if let Some(rest) = mention.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    cwd.join(mention)
+}
+
+fn is_ignored(ignore: &Gitignore, path: &Path) -> bool {
+    ignore.matched(path, path.is_dir()).is_ignore()
+}
+
+/// Read the files and list the directories mentioned in a message.
+/// Mentions of paths that do not exist are left alone, they may not be meant as paths.
+pub fn attach_mentions(
+    text: &str,
+    cwd: &Path,
+    ignore: &Gitignore,
+    counter: &TokenCounter,
+) -> Attachments {
+    let mut attachments = Attachments::default();
+    let mut budget = MAX_ATTACHMENT_TOKENS;
+
+    for mention in find_mentions(text) {
+        let path = resolve_mention(&mention, cwd);
+        if !path.exists() {
+            continue;
+        }
+        if is_ignored(ignore, &path) {
+            attachments
+                .skipped
+                .push(format!("@{} is restricted by .gooseignore", mention));
+            continue;
+        }
+
+        let content = if path.is_dir() {
+            let mut lines = vec![format!("{}/", mention.trim_end_matches('/'))];
+            list_tree(&path, 1, ignore, &mut lines);
+            if lines.len() > MAX_TREE_ENTRIES {
+                lines.truncate(MAX_TREE_ENTRIES);
+                lines.push("  ...".to_string());
+            }
+            format!(
+                "<directory path=\"{}\">\n{}\n</directory>",
+                mention,
+                lines.join("\n")
+            )
+        } else {
+            let metadata = match std::fs::metadata(&path) {
+                Ok(metadata) if metadata.is_file() => metadata,
+                _ => {
+                    attachments
+                        .skipped
+                        .push(format!("@{} is not a regular file", mention));
+                    continue;
+                }
+            };
+            if metadata.len() > MAX_ATTACHMENT_BYTES {
+                attachments.skipped.push(format!(
+                    "@{} is too large to attach ({} bytes)",
+                    mention,
+                    metadata.len()
+                ));
+                continue;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(text) if !text.contains('\0') => {
+                    format!("<file path=\"{}\">\n{}\n</file>", mention, text)
+                }
+                _ => {
+                    attachments
+                        .skipped
+                        .push(format!("@{} is not a text file", mention));
+                    continue;
+                }
+            }
+        };
+
+        let tokens = counter.count_tokens(&content);
+        if tokens > budget {
+            attachments.skipped.push(format!(
+                "@{} is too large to attach ({} tokens, {} left)",
+                mention, tokens, budget
+            ));
+            continue;
+        }
+        budget -= tokens;
+        attachments.contents.push(content);
+    }
+    attachments
+}
+
+fn list_tree(dir: &Path, depth: usize, ignore: &Gitignore, lines: &mut Vec<String>) {
+    if depth > MAX_TREE_DEPTH || lines.len() > MAX_TREE_ENTRIES {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            !hidden && !is_ignored(ignore, path)
+        })
+        .collect();
+    paths.sort();
+
+    let indent = "  ".repeat(depth);
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if path.is_dir() {
+            lines.push(format!("{}{}/", indent, name));
+            list_tree(&path, depth + 1, ignore, lines);
+        } else {
+            lines.push(format!("{}{}", indent, name));
+        }
+    }
+}
+
+/// Completions for a partially typed mention, directories end with `/`
+pub fn complete_path(partial: &str, cwd: &Path) -> Vec<String> {
+    let (dir_part, name_prefix) = match partial.rfind('/') {
+        Some(i) => (&partial[..=i], &partial[i + 1..]),
+        None => ("", partial),
+    };
+    let dir = resolve_mention(dir_part, cwd);
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Hidden entries only when asked for
+            if !name.starts_with(name_prefix)
+                || (name.starts_with('.') && !name_prefix.starts_with('.'))
+            {
+                return None;
+            }
+            let suffix = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir_part, name, suffix))
+        })
+        .collect();
+    candidates.sort();
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ignore::gitignore::GitignoreBuilder;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_mentions() {
+        assert_eq!(
+            find_mentions(
+                "Compare @src/main.rs with @lib.rs, and @src/main.rs again. Mail me@example.com"
+            ),
+            vec!["src/main.rs", "lib.rs"]
+        );
+        assert!(find_mentions("no mentions @ here").is_empty());
+    }
+
+    #[test]
+    fn test_attach_mentions() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(dir.path().join("secrets.txt"), "hunter2").unwrap();
+
+        let mut builder = GitignoreBuilder::new(dir.path());
+        builder.add_line(None, "secrets.*").unwrap();
+        let ignore = builder.build().unwrap();
+
+        let attachments = attach_mentions(
+            "Look at @src/main.rs and @src and @secrets.txt and @missing.rs",
+            dir.path(),
+            &ignore,
+            &TokenCounter::new(),
+        );
+        assert_eq!(
+            attachments.contents,
+            vec![
+                "<file path=\"src/main.rs\">\nfn main() {}\n</file>".to_string(),
+                "<directory path=\"src\">\nsrc/\n  main.rs\n</directory>".to_string(),
+            ]
+        );
+        assert_eq!(attachments.skipped.len(), 1);
+        assert!(attachments.skipped[0].contains(".gooseignore"));
+
+        let large = vec![b'a'; MAX_ATTACHMENT_BYTES as usize + 1];
+        std::fs::write(dir.path().join("large.txt"), large).unwrap();
+        let attachments = attach_mentions(
+            "Look at @large.txt",
+            dir.path(),
+            &ignore,
+            &TokenCounter::new(),
+        );
+        assert!(attachments.contents.is_empty());
+        assert!(attachments.skipped[0].contains("bytes"));
+    }
+
+    #[test]
+    fn test_complete_path() {
+        let dir = TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(dir.path().join(".hidden"), "").unwrap();
+
+        assert_eq!(complete_path("s", dir.path()), vec!["src/"]);
+        assert_eq!(complete_path("src/m", dir.path()), vec!["src/main.rs"]);
+        assert_eq!(complete_path(".h", dir.path()), vec![".hidden"]);
+        assert!(complete_path("", dir.path())
+            .iter()
+            .all(|c| !c.starts_with('.')));
+    }
+}
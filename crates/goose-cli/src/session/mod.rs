@@ -1,12 +1,19 @@
 mod builder;
 mod completion;
+mod custom_commands;
 mod export;
+mod export_html;
+mod export_json;
 mod input;
+mod mentions;
 mod output;
 mod prompt;
+mod structured;
 mod thinking;
 
 pub use self::export::message_to_markdown;
+pub use self::export_html::session_to_html;
+pub use self::export_json::export_session;
 pub use builder::{build_session, SessionBuilderConfig, SessionSettings};
 use console::Color;
 use goose::agents::AgentEvent;
@@ -15,20 +22,24 @@ use goose::permission::Permission;
 use goose::permission::PermissionConfirmation;
 use goose::providers::base::Provider;
 pub use goose::session::Identifier;
+pub use structured::{ExitReason, OutputFormat};
 
 use anyhow::{Context, Result};
 use completion::GooseCompleter;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::extension::{Envs, ExtensionConfig};
 use goose::agents::{Agent, SessionConfig};
+use goose::config::registry::MODES;
 use goose::config::Config;
 use goose::message::{Message, MessageContent};
 use goose::session;
+use goose::token_counter::TokenCounter;
 use input::InputResult;
 use mcp_core::handler::ToolError;
 use mcp_core::prompt::PromptMessage;
 use mcp_core::protocol::JsonRpcMessage;
 use mcp_core::protocol::JsonRpcNotification;
+use structured::{OutputEvent, StructuredOutput};
 
 use rand::{distributions::Alphanumeric, Rng};
 use serde_json::Value;
@@ -53,12 +64,19 @@ pub struct Session {
     run_mode: RunMode,
     scheduled_job_id: Option<String>, // ID of the scheduled job that triggered this session
     max_turns: Option<u32>,
+    /// Machine-readable output replacing rendered text, None for text output
+    structured: Option<StructuredOutput>,
+    /// Name of the provider the agent uses, to show and to switch models within
+    provider_name: Option<String>,
+    /// Goose mode chosen with /mode for this session only, GOOSE_MODE when None
+    goose_mode: Option<String>,
 }
 
 // Cache structure for completion data
 struct CompletionCache {
     prompts: HashMap<String, Vec<String>>,
     prompt_info: HashMap<String, output::PromptInfo>,
+    commands: Vec<custom_commands::CustomCommand>,
     last_updated: Instant,
 }
 
@@ -67,6 +85,7 @@ impl CompletionCache {
         Self {
             prompts: HashMap::new(),
             prompt_info: HashMap::new(),
+            commands: Vec::new(),
             last_updated: Instant::now(),
         }
     }
@@ -138,22 +157,54 @@ impl Session {
             run_mode: RunMode::Normal,
             scheduled_job_id,
             max_turns,
+            structured: None,
+            provider_name: None,
+            goose_mode: None,
         }
     }
 
-    /// Helper function to summarize context messages
-    async fn summarize_context_messages(
-        messages: &mut Vec<Message>,
-        agent: &Agent,
-        message_suffix: &str,
-    ) -> Result<()> {
-        // Summarize messages to fit within context length
-        let (summarized_messages, _) = agent.summarize_context(messages).await?;
-        let msg = format!("Context maxed out\n{}\n{}", "-".repeat(50), message_suffix);
-        output::render_text(&msg, Some(Color::Yellow), true);
-        *messages = summarized_messages;
+    /// Print headless runs in the given format instead of rendered text
+    pub fn set_output_format(&mut self, format: OutputFormat) {
+        self.structured = StructuredOutput::new(format);
+    }
 
-        Ok(())
+    /// How the last headless run ended, None unless a structured output format is set
+    pub fn exit_reason(&self) -> Option<ExitReason> {
+        self.structured.as_ref().map(StructuredOutput::exit_reason)
+    }
+
+    /// A user message with the files and directories it mentions as `@path` attached
+    fn user_message(&self, text: &str) -> Message {
+        let mut message = Message::user().with_text(text);
+        if mentions::find_mentions(text).is_empty() {
+            return message;
+        }
+        let Ok(cwd) = std::env::current_dir() else {
+            return message;
+        };
+
+        let ignore_patterns = goose_mcp::load_ignore_patterns(&cwd);
+        let attachments =
+            mentions::attach_mentions(text, &cwd, &ignore_patterns, &TokenCounter::new());
+        for skipped in &attachments.skipped {
+            println!(
+                "{}",
+                console::style(format!("Not attached: {}", skipped)).yellow()
+            );
+        }
+        for content in attachments.contents {
+            message = message.with_text(content);
+        }
+        message
+    }
+
+    /// Show a status note, on stderr when stdout carries structured output
+    fn render_notice(&self, text: &str) {
+        if self.structured.is_some() {
+            eprintln!("{}", text);
+        } else {
+            output::render_text(text, Some(Color::Yellow), true);
+        }
     }
 
     /// Add a stdio extension to the session
@@ -417,7 +468,8 @@ impl Session {
                         RunMode::Normal => {
                             save_history(&mut editor);
 
-                            self.messages.push(Message::user().with_text(&content));
+                            let message = self.user_message(&content);
+                            self.messages.push(message);
 
                             // Track the current directory and last instruction in projects.json
                             let session_id = self
@@ -533,22 +585,21 @@ impl Session {
                 input::InputResult::GooseMode(mode) => {
                     save_history(&mut editor);
 
-                    let config = Config::global();
                     let mode = mode.to_lowercase();
 
                     // Check if mode is valid
-                    if !["auto", "approve", "chat", "smart_approve"].contains(&mode.as_str()) {
+                    if !MODES.contains(&mode.as_str()) {
                         output::render_error(&format!(
-                            "Invalid mode '{}'. Mode must be one of: auto, approve, chat",
-                            mode
+                            "Invalid mode '{}'. Mode must be one of: {}",
+                            mode,
+                            MODES.join(", ")
                         ));
                         continue;
                     }
 
-                    config
-                        .set_param("GOOSE_MODE", Value::String(mode.to_string()))
-                        .unwrap();
+                    // Only this session changes mode, like a model switch
                     output::goose_mode_message(&format!("Goose mode set to '{}'", mode));
+                    self.goose_mode = Some(mode);
                     continue;
                 }
                 input::InputResult::Plan(options) => {
@@ -586,6 +637,10 @@ impl Session {
                     save_history(&mut editor);
                     self.handle_prompt_command(opts).await?;
                 }
+                input::InputResult::CustomCommand(opts) => {
+                    save_history(&mut editor);
+                    self.handle_custom_command(opts).await?;
+                }
                 InputResult::Recipe(filepath_opt) => {
                     println!("{}", console::style("Generating Recipe").green());
 
@@ -678,9 +733,26 @@ impl Session {
 
                     continue;
                 }
+                InputResult::Rewind(opts) => {
+                    save_history(&mut editor);
+                    self.handle_rewind(opts).await;
+                    continue;
+                }
+                InputResult::Branch(branch) => {
+                    save_history(&mut editor);
+                    self.handle_branch(branch);
+                    continue;
+                }
+                InputResult::SwitchModel(spec) => {
+                    save_history(&mut editor);
+                    self.handle_switch_model(spec).await;
+                    continue;
+                }
             }
         }
 
+        self.run_session_end_hooks().await;
+
         println!(
             "\nClosing session.{}",
             self.session_file
@@ -691,6 +763,180 @@ impl Session {
         Ok(())
     }
 
+    async fn handle_rewind(&mut self, opts: input::RewindCommandOptions) {
+        let Some(message_index) = opts.message_index else {
+            output::render_rewind_points(&self.messages);
+            return;
+        };
+        let Some(session_file) = &self.session_file else {
+            output::render_error("Rewinding needs a saved session");
+            return;
+        };
+
+        let rewind = match session::branches::rewind(session_file, message_index, !opts.discard) {
+            Ok(rewind) => rewind,
+            Err(e) => {
+                output::render_error(&e.to_string());
+                return;
+            }
+        };
+        self.messages = rewind.messages;
+
+        let saved_as = rewind
+            .branch
+            .map(|branch| format!(" Later messages were saved as branch '{}'.", branch))
+            .unwrap_or_default();
+        println!(
+            "{}",
+            console::style(format!(
+                "Rewound to before message {}.{}",
+                message_index, saved_as
+            ))
+            .green()
+        );
+
+        if opts.restore_files {
+            let restored = self.agent.undo_file_edits(&rewind.discarded).await;
+            if restored.is_empty() {
+                println!("{}", console::style("No file edits to restore.").yellow());
+            } else {
+                println!(
+                    "{}",
+                    console::style(format!(
+                        "Restored {} file edit(s): {}",
+                        restored.len(),
+                        restored.join(", ")
+                    ))
+                    .green()
+                );
+            }
+        }
+    }
+
+    fn handle_branch(&mut self, branch: Option<String>) {
+        let Some(session_file) = &self.session_file else {
+            output::render_error("Branches need a saved session");
+            return;
+        };
+
+        match branch {
+            None => match session::branches::list_branches(session_file) {
+                Ok(branches) => output::render_branches(&branches),
+                Err(e) => output::render_error(&e.to_string()),
+            },
+            Some(branch) => match session::branches::switch_branch(session_file, &branch) {
+                Ok(messages) => {
+                    self.messages = messages;
+                    println!(
+                        "{}",
+                        console::style(format!(
+                            "Switched to branch '{}' ({} messages).",
+                            branch,
+                            self.messages.len()
+                        ))
+                        .green()
+                    );
+                }
+                Err(e) => output::render_error(&e.to_string()),
+            },
+        }
+    }
+
+    async fn handle_switch_model(&mut self, spec: Option<String>) {
+        let current = self.agent.provider().await.ok();
+        let Some(spec) = spec else {
+            match current {
+                Some(provider) => println!(
+                    "Current model: {}/{}",
+                    self.provider_name.as_deref().unwrap_or("unknown"),
+                    provider.get_model_config().model_name
+                ),
+                None => output::render_error("No provider is set"),
+            }
+            return;
+        };
+
+        // Without a provider the model is looked up in the current one
+        let (provider_name, model_name) = match spec.split_once('/') {
+            Some((provider, model)) => (provider.to_string(), model.to_string()),
+            None => match &self.provider_name {
+                Some(provider) => (provider.clone(), spec.clone()),
+                None => {
+                    output::render_error("Use /model <provider>/<model>");
+                    return;
+                }
+            },
+        };
+        if provider_name.is_empty() || model_name.is_empty() {
+            output::render_error("Use /model <provider>/<model>");
+            return;
+        }
+
+        if let Err(e) = self.switch_model(&provider_name, &model_name).await {
+            output::render_error(&format!(
+                "Failed to switch to {}/{}: {}",
+                provider_name, model_name, e
+            ));
+            return;
+        }
+        if let Err(e) = self.record_model_switch(&provider_name, &model_name).await {
+            output::render_error(&format!("Failed to record the model switch: {}", e));
+        }
+        println!(
+            "{}",
+            console::style(format!(
+                "Switched to {}/{} for this session.",
+                provider_name, model_name
+            ))
+            .green()
+        );
+    }
+
+    /// Point the agent at another provider and model, keeping the conversation.
+    /// Only this session changes, the configured provider and model stay as they are.
+    async fn switch_model(
+        &mut self,
+        provider_name: &str,
+        model_name: &str,
+    ) -> Result<Arc<dyn Provider>> {
+        let temperature = self
+            .agent
+            .provider()
+            .await
+            .ok()
+            .and_then(|provider| provider.get_model_config().temperature);
+        let model_config =
+            goose::model::ModelConfig::new(model_name.to_string()).with_temperature(temperature);
+        let provider = goose::providers::create(provider_name, model_config)?;
+        self.agent.update_provider(Arc::clone(&provider)).await?;
+        self.provider_name = Some(provider_name.to_string());
+        Ok(provider)
+    }
+
+    /// Note the switch in the session file, so resuming continues with the same model
+    async fn record_model_switch(&self, provider_name: &str, model_name: &str) -> Result<()> {
+        let Some(session_file) = &self.session_file else {
+            return Ok(());
+        };
+        let switch = session::ModelSwitch {
+            provider: provider_name.to_string(),
+            model: model_name.to_string(),
+            message_count: self.messages.len(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+
+        if session::session_exists(session_file) {
+            let mut metadata = session::read_metadata(session_file)?;
+            metadata.model_switches.push(switch);
+            session::update_metadata(session_file, &metadata).await
+        } else {
+            let mut metadata = session::SessionMetadata::new(std::env::current_dir()?);
+            metadata.profile = Config::global().active_profile();
+            metadata.model_switches.push(switch);
+            session::storage::save_messages_with_metadata(session_file, &metadata, &self.messages)
+        }
+    }
+
     async fn plan_with_reasoner_model(
         &mut self,
         plan_messages: Vec<Message>,
@@ -726,15 +972,8 @@ impl Session {
                 if should_act {
                     output::render_act_on_plan();
                     self.run_mode = RunMode::Normal;
-                    // set goose mode: auto if that isn't already the case
-                    let config = Config::global();
-                    let curr_goose_mode =
-                        config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
-                    if curr_goose_mode != "auto" {
-                        config
-                            .set_param("GOOSE_MODE", Value::String("auto".to_string()))
-                            .unwrap();
-                    }
+                    // act on the plan in auto mode
+                    let curr_goose_mode = self.goose_mode.replace("auto".to_string());
 
                     // clear the messages before acting on the plan
                     self.messages.clear();
@@ -743,15 +982,12 @@ impl Session {
                     self.messages.push(plan_message);
                     // act on the plan
                     output::show_thinking();
-                    self.process_agent_response(true).await?;
+                    let result = self.process_agent_response(true).await;
                     output::hide_thinking();
 
                     // Reset run & goose mode
-                    if curr_goose_mode != "auto" {
-                        config
-                            .set_param("GOOSE_MODE", Value::String(curr_goose_mode.to_string()))
-                            .unwrap();
-                    }
+                    self.goose_mode = curr_goose_mode;
+                    result?;
                 } else {
                     // add the plan response (assistant message) & carry the conversation forward
                     // in the next round, the user might wanna slightly modify the plan
@@ -770,21 +1006,60 @@ impl Session {
 
     /// Process a single message and exit
     pub async fn headless(&mut self, message: String) -> Result<()> {
-        self.process_message(message).await
+        let session_id = self
+            .session_file
+            .as_ref()
+            .and_then(|p| p.file_stem())
+            .map(|s| s.to_string_lossy().into_owned());
+        if let Some(structured) = &self.structured {
+            structured.emit(OutputEvent::Start {
+                session_id: session_id.clone(),
+            });
+        }
+
+        let result = self.process_message(message).await;
+        self.run_session_end_hooks().await;
+
+        if let Some(structured) = &mut self.structured {
+            if let Err(e) = &result {
+                structured.error(e.to_string());
+            }
+            let metadata = self
+                .session_file
+                .as_ref()
+                .and_then(|path| session::read_metadata(path).ok());
+            structured.finish(session_id, metadata.as_ref());
+        }
+        result
     }
 
-    async fn process_agent_response(&mut self, interactive: bool) -> Result<()> {
-        let session_config = self.session_file.as_ref().map(|s| {
+    fn session_config(&self) -> Option<SessionConfig> {
+        self.session_file.as_ref().map(|s| {
             let session_id = session::Identifier::Path(s.clone());
             SessionConfig {
                 id: session_id.clone(),
                 working_dir: std::env::current_dir()
                     .expect("failed to get current session working directory"),
                 schedule_id: self.scheduled_job_id.clone(),
-                execution_mode: None,
+                execution_mode: self.goose_mode.clone(),
                 max_turns: self.max_turns,
+                profile: Config::global().active_profile(),
             }
-        });
+        })
+    }
+
+    async fn run_session_end_hooks(&self) {
+        if let Err(e) = self
+            .agent
+            .run_session_end_hooks(self.session_config())
+            .await
+        {
+            eprintln!("Warning: Failed to run session end hooks: {}", e);
+        }
+    }
+
+    async fn process_agent_response(&mut self, interactive: bool) -> Result<()> {
+        let session_config = self.session_config();
         let mut stream = self
             .agent
             .reply(&self.messages, session_config.clone())
@@ -888,7 +1163,7 @@ impl Session {
                                         } else {
                                             format!("Session cleared.\n{}", "-".repeat(50))
                                         };
-                                        output::render_text(&msg, Some(Color::Yellow), true);
+                                        self.render_notice(&msg);
                                         break;  // exit the loop to hand back control to the user
                                     }
                                     "truncate" => {
@@ -899,12 +1174,14 @@ impl Session {
                                         } else {
                                             format!("Context maxed out\n{}\nGoose tried its best to truncate messages for you.", "-".repeat(50))
                                         };
-                                        output::render_text("", Some(Color::Yellow), true);
-                                        output::render_text(&msg, Some(Color::Yellow), true);
+                                        if self.structured.is_none() {
+                                            output::render_text("", Some(Color::Yellow), true);
+                                        }
+                                        self.render_notice(&msg);
                                         self.messages = truncated_messages;
                                     }
                                     "summarize" => {
-                                        // Use the helper function to summarize context
+                                        // Summarize messages to fit within context length
                                         let message_suffix = if context_strategy == "summarize" {
                                             "Goose automatically summarized messages for you."
                                         } else if interactive {
@@ -912,7 +1189,9 @@ impl Session {
                                         } else {
                                             "Goose automatically summarized messages to continue processing."
                                         };
-                                        Self::summarize_context_messages(&mut self.messages, &self.agent, message_suffix).await?;
+                                        let (summarized_messages, _) = self.agent.summarize_context(&self.messages).await?;
+                                        self.render_notice(&format!("Context maxed out\n{}\n{}", "-".repeat(50), message_suffix));
+                                        self.messages = summarized_messages;
                                     }
                                     _ => {
                                         unreachable!()
@@ -943,10 +1222,14 @@ impl Session {
                                     .await?;
                                 }
 
-                                if interactive {output::hide_thinking()};
-                                let _ = progress_bars.hide();
-                                output::render_message(&message, self.debug);
-                                if interactive {output::show_thinking()};
+                                if let Some(structured) = &mut self.structured {
+                                    structured.message(&message);
+                                } else {
+                                    if interactive {output::hide_thinking()};
+                                    let _ = progress_bars.hide();
+                                    output::render_message(&message, self.debug);
+                                    if interactive {output::show_thinking()};
+                                }
                             }
                         }
                         Some(Ok(AgentEvent::McpNotification((_id, message)))) => {
@@ -1062,11 +1345,31 @@ impl Session {
                             }
                         }
                         Some(Ok(AgentEvent::ModelChange { model, mode })) => {
-                            // Log model change if in debug mode
-                            if self.debug {
+                            if let Some(structured) = &self.structured {
+                                structured.emit(OutputEvent::ModelChange { model, mode });
+                            } else if self.debug {
+                                // Log model change if in debug mode
                                 eprintln!("Model changed to {} in {} mode", model, mode);
                             }
                         }
+                        Some(Ok(AgentEvent::LoopDetected(detection))) => {
+                            if let Some(structured) = &self.structured {
+                                structured.emit(OutputEvent::LoopDetected { detection });
+                            } else {
+                                output::render_text(&detection.message, Some(Color::Yellow), true);
+                            }
+                        }
+                        Some(Ok(AgentEvent::Stopped(reason))) => {
+                            if let Some(structured) = &mut self.structured {
+                                structured.stopped(reason);
+                            }
+                        }
+                        Some(Ok(AgentEvent::ToolCallTiming { request_id, duration_ms, .. })) => {
+                            // Log tool call durations if in debug mode
+                            if let Some(duration_ms) = duration_ms.filter(|_| self.debug) {
+                                eprintln!("Tool call {} finished in {}ms", request_id, duration_ms);
+                            }
+                        }
 
                         Some(Err(e)) => {
                             eprintln!("Error: {}", e);
@@ -1074,6 +1377,10 @@ impl Session {
                             if let Err(e) = self.handle_interrupted_messages(false).await {
                                 eprintln!("Error handling interruption: {}", e);
                             }
+                            if let Some(structured) = &mut self.structured {
+                                structured.error(e.to_string());
+                                break;
+                            }
                             output::render_error(
                                 "The error above was an exception we were not able to handle.\n\
                                 These errors are often related to connection or authentication\n\
@@ -1220,11 +1527,13 @@ impl Session {
     pub async fn update_completion_cache(&mut self) -> Result<()> {
         // Get fresh data
         let prompts = self.agent.list_extension_prompts().await;
+        let commands = custom_commands::load_commands();
 
         // Update the cache with write lock
         let mut cache = self.completion_cache.write().unwrap();
         cache.prompts.clear();
         cache.prompt_info.clear();
+        cache.commands = commands;
 
         for (extension, prompt_list) in prompts {
             let names: Vec<String> = prompt_list.iter().map(|p| p.name.clone()).collect();
@@ -1286,7 +1595,11 @@ impl Session {
     }
 
     pub fn get_metadata(&self) -> Result<session::SessionMetadata> {
-        if !self.session_file.as_ref().is_some_and(|f| f.exists()) {
+        if !self
+            .session_file
+            .as_ref()
+            .is_some_and(|f| session::session_exists(f))
+        {
             return Err(anyhow::anyhow!("Session file does not exist"));
         }
 
@@ -1380,6 +1693,41 @@ impl Session {
         Ok(())
     }
 
+    async fn handle_custom_command(&mut self, opts: input::CustomCommandOptions) -> Result<()> {
+        let Some(command) = custom_commands::find_command(&opts.name) else {
+            output::render_error(&format!("Command '/{}' not found", opts.name));
+            return Ok(());
+        };
+        let prompt = match command.render(&opts.arguments) {
+            Ok(prompt) => prompt,
+            Err(e) => {
+                output::render_error(&format!("{:#}", e));
+                return Ok(());
+            }
+        };
+
+        let message = Message::user().with_text(&prompt);
+        output::render_message(&message, self.debug);
+        self.messages.push(message);
+        if let Some(session_file) = &self.session_file {
+            session::persist_messages_with_schedule_id(
+                session_file,
+                &self.messages,
+                Some(self.agent.provider().await?),
+                self.scheduled_job_id.clone(),
+            )
+            .await?;
+        }
+
+        // Restrict the tools only while the command is answered
+        self.agent.set_extension_scope(command.extensions).await;
+        output::show_thinking();
+        let result = self.process_agent_response(true).await;
+        output::hide_thinking();
+        self.agent.set_extension_scope(None).await;
+        result
+    }
+
     /// Save a recipe to a file
     ///
     /// # Arguments
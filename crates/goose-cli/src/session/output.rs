@@ -1,7 +1,9 @@
+use crate::utils::safe_truncate;
 use bat::WrappingMode;
 use console::{style, Color};
 use goose::config::Config;
 use goose::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::session::branches::BranchInfo;
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mcp_core::prompt::PromptArgument;
 use mcp_core::tool::ToolCall;
@@ -307,6 +309,54 @@ pub fn render_prompt_info(info: &PromptInfo) {
     println!();
 }
 
+pub fn render_rewind_points(messages: &[Message]) {
+    println!();
+    let mut found = false;
+    for (index, message) in messages.iter().enumerate() {
+        if message.role != mcp_core::role::Role::User {
+            continue;
+        }
+        let text = message.as_concat_text();
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        found = true;
+        let first_line = text.lines().next().unwrap_or_default();
+        println!(
+            "  {} {}",
+            style(format!("[{}]", index)).cyan(),
+            safe_truncate(first_line, 80)
+        );
+    }
+    if found {
+        println!("\n  Use /rewind <n> to go back to before message n.");
+    } else {
+        println!("  Nothing to rewind to yet.");
+    }
+    println!();
+}
+
+pub fn render_branches(branches: &[BranchInfo]) {
+    println!();
+    for branch in branches {
+        let marker = if branch.active { "*" } else { " " };
+        let origin = branch
+            .parent
+            .as_ref()
+            .map(|parent| format!(" forked from {} at message {}", parent, branch.fork_point))
+            .unwrap_or_default();
+        println!(
+            "  {} {}{} {}",
+            marker,
+            style(&branch.id).cyan(),
+            origin,
+            style(format!("({} messages)", branch.message_count)).dim()
+        );
+    }
+    println!();
+}
+
 pub fn render_extension_success(name: &str) {
     println!();
     println!(
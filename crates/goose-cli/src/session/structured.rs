@@ -0,0 +1,309 @@
+use goose::agents::StopReason;
+use goose::message::{Message, MessageContent};
+use goose::session::SessionMetadata;
+use goose::tool_monitor::LoopDetection;
+use mcp_core::role::Role;
+use serde::Serialize;
+use serde_json::Value;
+
+/// How `goose run` prints what the agent does
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Rendered terminal text
+    #[default]
+    Text,
+    /// A single JSON object with the final result once the run is over
+    Json,
+    /// One JSON object per line for every agent event, ending with the result
+    StreamJson,
+}
+
+/// How a headless run ended, each with its own process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    Success,
+    Error,
+    MaxTurns,
+    ToolLoop,
+    ProviderError,
+}
+
+impl ExitReason {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitReason::Success => 0,
+            ExitReason::Error => 1,
+            ExitReason::MaxTurns => 3,
+            ExitReason::ToolLoop => 4,
+            ExitReason::ProviderError => 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Usage {
+    pub input_tokens: Option<i32>,
+    pub output_tokens: Option<i32>,
+    pub total_tokens: Option<i32>,
+}
+
+impl Usage {
+    fn from_metadata(metadata: &SessionMetadata) -> Self {
+        Self {
+            input_tokens: metadata.accumulated_input_tokens,
+            output_tokens: metadata.accumulated_output_tokens,
+            total_tokens: metadata.accumulated_total_tokens,
+        }
+    }
+}
+
+/// One line of `stream-json` output
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputEvent {
+    Start {
+        session_id: Option<String>,
+    },
+    Text {
+        role: Role,
+        text: String,
+    },
+    ToolRequest {
+        id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        arguments: Option<Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    ToolResult {
+        id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    ModelChange {
+        model: String,
+        mode: String,
+    },
+    LoopDetected {
+        detection: LoopDetection,
+    },
+    Usage {
+        #[serde(flatten)]
+        usage: Usage,
+    },
+    Error {
+        error: String,
+    },
+    Result {
+        exit_reason: ExitReason,
+        exit_code: i32,
+        session_id: Option<String>,
+        /// Text of the last assistant message
+        result: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        usage: Option<Usage>,
+    },
+}
+
+/// The events to print for a message the agent produced
+pub fn message_events(message: &Message) -> Vec<OutputEvent> {
+    message
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            MessageContent::Text(text) if !text.text.is_empty() => Some(OutputEvent::Text {
+                role: message.role.clone(),
+                text: text.text.clone(),
+            }),
+            MessageContent::ToolRequest(request) => Some(match &request.tool_call {
+                Ok(call) => OutputEvent::ToolRequest {
+                    id: request.id.clone(),
+                    name: Some(call.name.clone()),
+                    arguments: Some(call.arguments.clone()),
+                    error: None,
+                },
+                Err(e) => OutputEvent::ToolRequest {
+                    id: request.id.clone(),
+                    name: None,
+                    arguments: None,
+                    error: Some(e.to_string()),
+                },
+            }),
+            MessageContent::ToolResponse(response) => Some(match &response.tool_result {
+                Ok(contents) => OutputEvent::ToolResult {
+                    id: response.id.clone(),
+                    output: serde_json::to_value(contents).ok(),
+                    error: None,
+                },
+                Err(e) => OutputEvent::ToolResult {
+                    id: response.id.clone(),
+                    output: None,
+                    error: Some(e.to_string()),
+                },
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Machine-readable output of a headless run, used instead of rendered text
+pub struct StructuredOutput {
+    format: OutputFormat,
+    stop_reason: Option<StopReason>,
+    error: Option<String>,
+    final_text: Option<String>,
+}
+
+impl StructuredOutput {
+    /// None for the text format, which is rendered as usual
+    pub fn new(format: OutputFormat) -> Option<Self> {
+        match format {
+            OutputFormat::Text => None,
+            OutputFormat::Json | OutputFormat::StreamJson => Some(Self {
+                format,
+                stop_reason: None,
+                error: None,
+                final_text: None,
+            }),
+        }
+    }
+
+    /// Print an event as one line, only when streaming
+    pub fn emit(&self, event: OutputEvent) {
+        if self.format == OutputFormat::StreamJson {
+            Self::print(&event);
+        }
+    }
+
+    fn print(event: &OutputEvent) {
+        match serde_json::to_string(event) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Failed to serialize output event: {}", e),
+        }
+    }
+
+    pub fn message(&mut self, message: &Message) {
+        if message.role == Role::Assistant {
+            let text = message.as_concat_text();
+            if !text.is_empty() {
+                self.final_text = Some(text);
+            }
+        }
+        for event in message_events(message) {
+            self.emit(event);
+        }
+    }
+
+    pub fn stopped(&mut self, reason: StopReason) {
+        self.stop_reason = Some(reason);
+    }
+
+    pub fn error(&mut self, error: String) {
+        self.emit(OutputEvent::Error {
+            error: error.clone(),
+        });
+        self.error.get_or_insert(error);
+    }
+
+    pub fn exit_reason(&self) -> ExitReason {
+        match &self.stop_reason {
+            Some(StopReason::MaxTurns(_)) => ExitReason::MaxTurns,
+            Some(StopReason::ToolLoop(_)) => ExitReason::ToolLoop,
+            Some(StopReason::ProviderError(_)) => ExitReason::ProviderError,
+            None if self.error.is_some() => ExitReason::Error,
+            None => ExitReason::Success,
+        }
+    }
+
+    /// Print the final result, and the usage before it when streaming
+    pub fn finish(&self, session_id: Option<String>, metadata: Option<&SessionMetadata>) {
+        let usage = metadata.map(Usage::from_metadata);
+        if let Some(usage) = &usage {
+            self.emit(OutputEvent::Usage {
+                usage: usage.clone(),
+            });
+        }
+        let exit_reason = self.exit_reason();
+        let error = match &self.stop_reason {
+            Some(StopReason::ToolLoop(message)) | Some(StopReason::ProviderError(message)) => {
+                Some(message.clone())
+            }
+            _ => self.error.clone(),
+        };
+        Self::print(&OutputEvent::Result {
+            exit_reason,
+            exit_code: exit_reason.code(),
+            session_id,
+            result: self.final_text.clone(),
+            error,
+            usage,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::message::Message;
+    use mcp_core::{Content, ToolCall};
+    use serde_json::json;
+
+    #[test]
+    fn test_message_events_schema() {
+        let request = Message::assistant().with_text("Listing").with_tool_request(
+            "call_1",
+            Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+        );
+        let events: Vec<Value> = message_events(&request)
+            .iter()
+            .map(|e| serde_json::to_value(e).unwrap())
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                json!({"type": "text", "role": "assistant", "text": "Listing"}),
+                json!({
+                    "type": "tool_request",
+                    "id": "call_1",
+                    "name": "developer__shell",
+                    "arguments": {"command": "ls"}
+                }),
+            ]
+        );
+
+        let response =
+            Message::user().with_tool_response("call_1", Ok(vec![Content::text("Cargo.toml")]));
+        let value = serde_json::to_value(&message_events(&response)[0]).unwrap();
+        assert_eq!(value["type"], "tool_result");
+        assert_eq!(value["id"], "call_1");
+        assert_eq!(value["output"][0]["text"], "Cargo.toml");
+        assert!(value.get("error").is_none());
+    }
+
+    #[test]
+    fn test_exit_reasons() {
+        assert!(StructuredOutput::new(OutputFormat::Text).is_none());
+
+        let mut output = StructuredOutput::new(OutputFormat::Json).unwrap();
+        assert_eq!(output.exit_reason(), ExitReason::Success);
+        output.error("boom".to_string());
+        assert_eq!(output.exit_reason().code(), 1);
+
+        // A loop stop ends the stream with an error too, the stop reason wins
+        output.stopped(StopReason::ToolLoop("same call".to_string()));
+        assert_eq!(output.exit_reason(), ExitReason::ToolLoop);
+
+        let mut output = StructuredOutput::new(OutputFormat::StreamJson).unwrap();
+        output.stopped(StopReason::MaxTurns(10));
+        assert_eq!(output.exit_reason().code(), 3);
+        output.stopped(StopReason::ProviderError("rate limited".to_string()));
+        assert_eq!(output.exit_reason().code(), 5);
+    }
+}
@@ -269,6 +269,15 @@ pub unsafe extern "C" fn goose_agent_send_message(
                 Ok(AgentEvent::ModelChange { .. }) => {
                     // Model change events are informational, just continue
                 }
+                Ok(AgentEvent::LoopDetected(_)) => {
+                    // Loop detections are informational, the nudge reaches the model directly
+                }
+                Ok(AgentEvent::ToolCallTiming { .. }) => {
+                    // Tool timings are informational, just continue
+                }
+                Ok(AgentEvent::Stopped(_)) => {
+                    // The reason is already in the last message, just continue
+                }
 
                 Err(e) => {
                     full_response.push_str(&format!("\nError in message stream: {}", e));
@@ -39,7 +39,10 @@ use mcp_server::Router;
 use mcp_core::role::Role;
 
 use self::editor_models::{create_editor_model, EditorModel};
-use self::shell::{expand_path, get_shell_config, is_absolute_path, normalize_line_endings};
+use self::shell::{
+    expand_path, get_shell_config, get_shell_timeout, is_absolute_path, normalize_line_endings,
+    ProcessGroupGuard,
+};
 use indoc::indoc;
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
@@ -93,6 +96,60 @@ pub fn load_prompt_files() -> HashMap<String, Prompt> {
     prompts
 }
 
+/// The patterns of files the developer extension may not touch: the global and local
+/// `.gooseignore`, falling back to `.gitignore` and then to a few secret file defaults
+pub fn load_ignore_patterns(cwd: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(cwd);
+    let mut has_ignore_file = false;
+    // Initialize ignore patterns
+    // - macOS/Linux: ~/.config/goose/
+    // - Windows:     ~\AppData\Roaming\Block\goose\config\
+    let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
+        });
+
+    // Create the directory if it doesn't exist
+    let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
+
+    // Read global ignores if they exist
+    if global_ignore_path.is_file() {
+        let _ = builder.add(global_ignore_path);
+        has_ignore_file = true;
+    }
+
+    // Check for local ignores in current directory
+    let local_ignore_path = cwd.join(".gooseignore");
+
+    // Read local ignores if they exist
+    if local_ignore_path.is_file() {
+        let _ = builder.add(local_ignore_path);
+        has_ignore_file = true;
+    } else {
+        // If no .gooseignore exists, check for .gitignore as fallback
+        let gitignore_path = cwd.join(".gitignore");
+        if gitignore_path.is_file() {
+            tracing::debug!(
+                "No .gooseignore found, using .gitignore as fallback for ignore patterns"
+            );
+            let _ = builder.add(gitignore_path);
+            has_ignore_file = true;
+        }
+    }
+
+    // Only use default patterns if no .gooseignore files were found
+    // AND no .gitignore was used as fallback
+    if !has_ignore_file {
+        // Add some sensible defaults
+        let _ = builder.add_line(None, "**/.env");
+        let _ = builder.add_line(None, "**/.env.*");
+        let _ = builder.add_line(None, "**/secrets.*");
+    }
+
+    builder.build().expect("Failed to build ignore patterns")
+}
+
 pub struct DeveloperRouter {
     tools: Vec<Tool>,
     prompts: Arc<HashMap<String, Prompt>>,
@@ -430,55 +487,7 @@ impl DeveloperRouter {
             format!("{base_instructions}\n{hints}")
         };
 
-        let mut builder = GitignoreBuilder::new(cwd.clone());
-        let mut has_ignore_file = false;
-        // Initialize ignore patterns
-        // - macOS/Linux: ~/.config/goose/
-        // - Windows:     ~\AppData\Roaming\Block\goose\config\
-        let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
-            .map(|strategy| strategy.in_config_dir(".gooseignore"))
-            .unwrap_or_else(|_| {
-                PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
-            });
-
-        // Create the directory if it doesn't exist
-        let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
-
-        // Read global ignores if they exist
-        if global_ignore_path.is_file() {
-            let _ = builder.add(global_ignore_path);
-            has_ignore_file = true;
-        }
-
-        // Check for local ignores in current directory
-        let local_ignore_path = cwd.join(".gooseignore");
-
-        // Read local ignores if they exist
-        if local_ignore_path.is_file() {
-            let _ = builder.add(local_ignore_path);
-            has_ignore_file = true;
-        } else {
-            // If no .gooseignore exists, check for .gitignore as fallback
-            let gitignore_path = cwd.join(".gitignore");
-            if gitignore_path.is_file() {
-                tracing::debug!(
-                    "No .gooseignore found, using .gitignore as fallback for ignore patterns"
-                );
-                let _ = builder.add(gitignore_path);
-                has_ignore_file = true;
-            }
-        }
-
-        // Only use default patterns if no .gooseignore files were found
-        // AND no .gitignore was used as fallback
-        if !has_ignore_file {
-            // Add some sensible defaults
-            let _ = builder.add_line(None, "**/.env");
-            let _ = builder.add_line(None, "**/.env.*");
-            let _ = builder.add_line(None, "**/secrets.*");
-        }
-
-        let ignore_patterns = builder.build().expect("Failed to build ignore patterns");
+        let ignore_patterns = load_ignore_patterns(&cwd);
 
         Self {
             tools: vec![
@@ -558,15 +567,21 @@ impl DeveloperRouter {
         let shell_config = get_shell_config();
 
         // Execute the command using platform-specific shell
-        let mut child = Command::new(&shell_config.executable)
+        let mut shell_command = Command::new(&shell_config.executable);
+        shell_command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null())
             .kill_on_drop(true)
             .args(&shell_config.args)
-            .arg(command)
+            .arg(command);
+        // Give the command its own process group so it can be killed along with its children
+        #[cfg(unix)]
+        shell_command.process_group(0);
+        let mut child = shell_command
             .spawn()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let process_group = ProcessGroupGuard::new(child.id());
 
         let stdout = child.stdout.take().unwrap();
         let stderr = child.stderr.take().unwrap();
@@ -641,11 +656,23 @@ impl DeveloperRouter {
             Ok::<_, std::io::Error>(combined_output)
         });
 
-        // Wait for the command to complete and get output
-        child
-            .wait()
-            .await
-            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        // Wait for the command to complete and get output, killing it if it runs too long
+        let status = match get_shell_timeout() {
+            Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+                Ok(status) => status,
+                Err(_) => {
+                    drop(process_group);
+                    output_task.abort();
+                    return Err(ToolError::Timeout {
+                        tool: "shell".to_string(),
+                        seconds: limit.as_secs(),
+                    });
+                }
+            },
+            None => child.wait().await,
+        };
+        status.map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        process_group.disarm();
 
         let output_str = match output_task.await {
             Ok(result) => result.map_err(|e| ToolError::ExecutionError(e.to_string()))?,
@@ -1600,6 +1627,34 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    #[cfg(unix)]
+    async fn test_shell_timeout_kills_command() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+        std::env::set_var(shell::SHELL_TIMEOUT_ENV, "1");
+
+        let router = get_router().await;
+        let started = std::time::Instant::now();
+        let result = router
+            .call_tool(
+                "shell",
+                json!({ "command": "sleep 30 & sleep 30" }),
+                dummy_sender(),
+            )
+            .await;
+        std::env::remove_var(shell::SHELL_TIMEOUT_ENV);
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+        assert!(matches!(
+            result,
+            Err(ToolError::Timeout { ref tool, seconds: 1 }) if tool == "shell"
+        ));
+
+        temp_dir.close().unwrap();
+    }
+
     #[tokio::test]
     #[serial]
     #[cfg(windows)]
@@ -1,4 +1,13 @@
 use std::env;
+use std::time::Duration;
+
+#[cfg(unix)]
+use nix::sys::signal::{killpg, Signal};
+#[cfg(unix)]
+use nix::unistd::Pid;
+
+/// Environment variable holding the number of seconds a shell command may run before it is killed
+pub const SHELL_TIMEOUT_ENV: &str = "GOOSE_SHELL_TIMEOUT";
 
 #[derive(Debug, Clone)]
 pub struct ShellConfig {
@@ -105,3 +114,48 @@ pub fn normalize_line_endings(text: &str) -> String {
         text.replace("\r\n", "\n")
     }
 }
+
+/// Read the shell command time limit from GOOSE_SHELL_TIMEOUT, if one is set
+pub fn get_shell_timeout() -> Option<Duration> {
+    env::var(SHELL_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs)
+}
+
+/// Kills a shell command together with everything it started when dropped, unless the
+/// command finished on its own. On unix the command runs in its own process group so
+/// that the whole group can be signalled at once.
+pub struct ProcessGroupGuard {
+    pid: Option<u32>,
+}
+
+impl ProcessGroupGuard {
+    pub fn new(pid: Option<u32>) -> Self {
+        Self { pid }
+    }
+
+    /// Leave the processes running, e.g. because the command already exited
+    pub fn disarm(mut self) {
+        self.pid = None;
+    }
+}
+
+impl Drop for ProcessGroupGuard {
+    fn drop(&mut self) {
+        let Some(pid) = self.pid else {
+            return;
+        };
+
+        #[cfg(unix)]
+        {
+            let _ = killpg(Pid::from_raw(pid as i32), Signal::SIGKILL);
+        }
+
+        #[cfg(windows)]
+        {
+            let _ = kill_tree::blocking::kill_tree(pid);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use goose::config::Config;
+use http::{HeaderMap, StatusCode};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Config key holding the named API tokens
+pub const TOKENS_CONFIG_KEY: &str = "server_tokens";
+
+const TOKEN_PREFIX: &str = "goose_";
+const TOKEN_LENGTH: usize = 40;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// What an API token is allowed to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum Scope {
+    /// List and read sessions
+    ReadSessions,
+    /// Chat with agents, including rewinding sessions
+    Reply,
+    /// Read and change the goose configuration and add or remove extensions, which run commands
+    /// on the server
+    ManageConfig,
+    /// Create, change and run schedules
+    ManageSchedules,
+}
+
+/// A named API token, stored in the config with only the hash of its secret
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub name: String,
+    /// Hex encoded SHA-256 of the token
+    pub hash: String,
+    #[serde(default)]
+    pub scopes: Vec<Scope>,
+    /// Maximum requests per minute, unlimited when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<u32>,
+}
+
+/// Who made a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum Identity {
+    /// The shared secret key of the desktop app, which may do anything
+    SecretKey,
+    /// A named API token
+    Token { name: String },
+}
+
+impl Identity {
+    /// The user sessions are isolated by, None for the shared secret key
+    pub fn user(&self) -> Option<&str> {
+        match self {
+            Identity::SecretKey => None,
+            Identity::Token { name } => Some(name),
+        }
+    }
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Generate a new random token secret
+pub fn generate_token() -> String {
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+    format!("{}{}", TOKEN_PREFIX, secret)
+}
+
+pub fn load_tokens() -> Vec<ApiToken> {
+    Config::global()
+        .get_param(TOKENS_CONFIG_KEY)
+        .unwrap_or_default()
+}
+
+pub fn save_tokens(tokens: &[ApiToken]) -> anyhow::Result<()> {
+    Config::global().set_param(TOKENS_CONFIG_KEY, serde_json::to_value(tokens)?)?;
+    Ok(())
+}
+
+/// The credential presented with a request, as a bearer token or in `X-Secret-Key`
+fn presented_credential(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get("X-Secret-Key")
+                .and_then(|value| value.to_str().ok())
+        })
+}
+
+struct RateWindow {
+    started: Instant,
+    requests: u32,
+}
+
+type TokenSource = Box<dyn Fn() -> Vec<ApiToken> + Send + Sync>;
+
+/// Checks credentials against the shared secret key and the named API tokens
+pub struct Authenticator {
+    secret_key: String,
+    tokens: TokenSource,
+    windows: Mutex<HashMap<String, RateWindow>>,
+}
+
+impl Authenticator {
+    /// Read the tokens from the config on every check, so tokens created or revoked with
+    /// `goosed token` apply without restarting the server
+    pub fn from_config(secret_key: String) -> Self {
+        Self::with_source(secret_key, Box::new(load_tokens))
+    }
+
+    pub(crate) fn with_source(secret_key: String, tokens: TokenSource) -> Self {
+        Self {
+            secret_key,
+            tokens,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Identify the caller and check they may use `scope`
+    ///
+    /// Fails with 401 for unknown credentials, 403 when the token lacks the scope and 429 when
+    /// the token made too many requests in the last minute.
+    pub fn authorize(&self, headers: &HeaderMap, scope: Scope) -> Result<Identity, StatusCode> {
+        self.authorize_all(headers, &[scope])
+    }
+
+    /// Like `authorize`, for requests that need every one of `scopes`
+    pub fn authorize_all(
+        &self,
+        headers: &HeaderMap,
+        scopes: &[Scope],
+    ) -> Result<Identity, StatusCode> {
+        let credential = presented_credential(headers).ok_or(StatusCode::UNAUTHORIZED)?;
+        if credential == self.secret_key {
+            return Ok(Identity::SecretKey);
+        }
+
+        let hash = hash_token(credential);
+        let token = (self.tokens)()
+            .into_iter()
+            .find(|token| token.hash == hash)
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        if !scopes.iter().all(|scope| token.scopes.contains(scope)) {
+            return Err(StatusCode::FORBIDDEN);
+        }
+        if let Some(limit) = token.rate_limit {
+            self.check_rate_limit(&token.name, limit)?;
+        }
+
+        Ok(Identity::Token {
+            name: token.name.clone(),
+        })
+    }
+
+    fn check_rate_limit(&self, name: &str, limit: u32) -> Result<(), StatusCode> {
+        let mut windows = self.windows.lock().unwrap();
+        let window = windows.entry(name.to_string()).or_insert(RateWindow {
+            started: Instant::now(),
+            requests: 0,
+        });
+        if window.started.elapsed() >= RATE_LIMIT_WINDOW {
+            window.started = Instant::now();
+            window.requests = 0;
+        }
+        if window.requests >= limit {
+            return Err(StatusCode::TOO_MANY_REQUESTS);
+        }
+        window.requests += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(name: &str, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            http::HeaderName::from_bytes(name.as_bytes()).unwrap(),
+            value.parse().unwrap(),
+        );
+        headers
+    }
+
+    fn authenticator() -> Authenticator {
+        Authenticator::with_source(
+            "secret".to_string(),
+            Box::new(|| {
+                vec![ApiToken {
+                    name: "alice".to_string(),
+                    hash: hash_token("goose_alice"),
+                    scopes: vec![Scope::ReadSessions, Scope::Reply],
+                    rate_limit: Some(2),
+                }]
+            }),
+        )
+    }
+
+    #[test]
+    fn test_secret_key_has_every_scope() {
+        let auth = authenticator();
+        let headers = headers("X-Secret-Key", "secret");
+        assert_eq!(
+            auth.authorize(&headers, Scope::ManageConfig),
+            Ok(Identity::SecretKey)
+        );
+        assert_eq!(
+            auth.authorize(&HeaderMap::new(), Scope::Reply),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn test_token_scopes_and_rate_limit() {
+        let auth = authenticator();
+        let headers = headers("Authorization", "Bearer goose_alice");
+
+        let identity = auth.authorize(&headers, Scope::Reply).unwrap();
+        assert_eq!(identity.user(), Some("alice"));
+        assert_eq!(
+            auth.authorize(&headers, Scope::ManageSchedules),
+            Err(StatusCode::FORBIDDEN)
+        );
+        assert!(auth.authorize(&headers, Scope::ReadSessions).is_ok());
+        assert_eq!(
+            auth.authorize(&headers, Scope::Reply),
+            Err(StatusCode::TOO_MANY_REQUESTS)
+        );
+
+        let wrong = self::headers("Authorization", "Bearer goose_bob");
+        assert_eq!(
+            auth.authorize(&wrong, Scope::Reply),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn test_revoked_token_is_rejected_without_restart() {
+        let tokens = std::sync::Arc::new(Mutex::new(vec![ApiToken {
+            name: "ci".to_string(),
+            hash: hash_token("goose_ci"),
+            scopes: vec![Scope::Reply],
+            rate_limit: None,
+        }]));
+        let source = tokens.clone();
+        let auth = Authenticator::with_source(
+            "secret".to_string(),
+            Box::new(move || source.lock().unwrap().clone()),
+        );
+        let headers = headers("Authorization", "Bearer goose_ci");
+
+        assert!(auth.authorize(&headers, Scope::Reply).is_ok());
+        tokens.lock().unwrap().clear();
+        assert_eq!(
+            auth.authorize(&headers, Scope::Reply),
+            Err(StatusCode::UNAUTHORIZED)
+        );
+    }
+
+    #[test]
+    fn test_generated_tokens_hash_consistently() {
+        let token = generate_token();
+        assert!(token.starts_with(TOKEN_PREFIX));
+        assert_ne!(token, generate_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_eq!(hash_token(&token).len(), 64);
+    }
+}
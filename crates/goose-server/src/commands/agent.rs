@@ -5,7 +5,7 @@ use crate::state;
 use anyhow::Result;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::Agent;
-use goose::config::APP_STRATEGY;
+use goose::config::{Config, APP_STRATEGY};
 use goose::scheduler_factory::SchedulerFactory;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
@@ -31,9 +31,15 @@ pub async fn run() -> Result<()> {
         std::env::var("GOOSE_SERVER__SECRET_KEY").unwrap_or_else(|_| "test".to_string());
 
     let new_agent = Agent::new();
+    // The shared agent runs with the profile the server was started with, sessions created
+    // through the API may pick their own
+    new_agent
+        .set_profile(Config::global().active_profile())
+        .await;
     let agent_ref = Arc::new(new_agent);
 
     let app_state = state::AppState::new(agent_ref.clone(), secret_key.clone()).await;
+    app_state.spawn_idle_eviction();
 
     let schedule_file_path = choose_app_strategy(APP_STRATEGY.clone())?
         .data_dir()
@@ -0,0 +1,53 @@
+use crate::auth::{generate_token, hash_token, load_tokens, save_tokens, ApiToken, Scope};
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+pub fn create(name: &str, scopes: &[Scope], rate_limit: Option<u32>) -> Result<()> {
+    let mut tokens = load_tokens();
+    if tokens.iter().any(|token| token.name == name) {
+        bail!("A token named '{}' already exists", name);
+    }
+    if scopes.is_empty() {
+        bail!("A token needs at least one scope");
+    }
+
+    let token = generate_token();
+    tokens.push(ApiToken {
+        name: name.to_string(),
+        hash: hash_token(&token),
+        scopes: scopes.to_vec(),
+        rate_limit,
+    });
+    save_tokens(&tokens)?;
+
+    // Only the hash is stored, so this is the one chance to see the token
+    println!("{}", token);
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    for token in load_tokens() {
+        let scopes: Vec<String> = token
+            .scopes
+            .iter()
+            .filter_map(|scope| scope.to_possible_value())
+            .map(|value| value.get_name().to_string())
+            .collect();
+        let rate_limit = token
+            .rate_limit
+            .map(|limit| format!("{}/min", limit))
+            .unwrap_or_else(|| "unlimited".to_string());
+        println!("{}\t{}\t{}", token.name, scopes.join(","), rate_limit);
+    }
+    Ok(())
+}
+
+pub fn revoke(name: &str) -> Result<()> {
+    let mut tokens = load_tokens();
+    let count = tokens.len();
+    tokens.retain(|token| token.name != name);
+    if tokens.len() == count {
+        bail!("No token named '{}'", name);
+    }
+    save_tokens(&tokens)
+}
@@ -1,3 +1,4 @@
+mod auth;
 mod commands;
 mod configuration;
 mod error;
@@ -25,6 +26,33 @@ enum Commands {
         /// Name of the MCP server type
         name: String,
     },
+    /// Manage the API tokens clients can use instead of the secret key
+    Token {
+        #[command(subcommand)]
+        command: TokenCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum TokenCommand {
+    /// Create a token and print it, it cannot be shown again
+    Create {
+        /// Name of the token, sessions it starts are owned by this name
+        name: String,
+        /// What the token may do, can be repeated
+        #[arg(long = "scope", value_enum, required = true)]
+        scopes: Vec<auth::Scope>,
+        /// Maximum requests per minute
+        #[arg(long)]
+        rate_limit: Option<u32>,
+    },
+    /// List the tokens with their scopes and rate limits
+    List,
+    /// Revoke a token so it can no longer be used
+    Revoke {
+        /// Name of the token
+        name: String,
+    },
 }
 
 #[tokio::main]
@@ -38,6 +66,15 @@ async fn main() -> anyhow::Result<()> {
         Commands::Mcp { name } => {
             commands::mcp::run(name).await?;
         }
+        Commands::Token { command } => match command {
+            TokenCommand::Create {
+                name,
+                scopes,
+                rate_limit,
+            } => commands::token::create(name, scopes, *rate_limit)?,
+            TokenCommand::List => commands::token::list()?,
+            TokenCommand::Revoke { name } => commands::token::revoke(name)?,
+        },
     }
 
     Ok(())
@@ -1,16 +1,20 @@
 use goose::agents::extension::Envs;
 use goose::agents::extension::ToolInfo;
+use goose::agents::hooks::{HookEvent, HookOutcome, HookStatus};
 use goose::agents::ExtensionConfig;
 use goose::config::permission::PermissionLevel;
 use goose::config::ExtensionEntry;
+use goose::config::{ConfigIssue, ConfigSource, IssueKind, Profile};
 use goose::message::{
     ContextLengthExceeded, FrontendToolRequest, Message, MessageContent, RedactedThinkingContent,
     SummarizationRequested, ThinkingContent, ToolConfirmationRequest, ToolRequest, ToolResponse,
 };
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::providers::base::{ConfigKey, ModelInfo, ProviderMetadata};
+use goose::session::branches::BranchInfo;
 use goose::session::info::SessionInfo;
-use goose::session::SessionMetadata;
+use goose::session::search::{MatchField, SearchMatch, SearchResult};
+use goose::session::{ModelSwitch, SessionMetadata};
 use mcp_core::content::{Annotations, Content, EmbeddedResource, ImageContent, TextContent};
 use mcp_core::handler::ToolResultSchema;
 use mcp_core::resource::ResourceContents;
@@ -25,6 +29,7 @@ use utoipa::OpenApi;
         super::routes::config_management::backup_config,
         super::routes::config_management::recover_config,
         super::routes::config_management::validate_config,
+        super::routes::config_management::check_config,
         super::routes::config_management::init_config,
         super::routes::config_management::upsert_config,
         super::routes::config_management::remove_config,
@@ -35,11 +40,22 @@ use utoipa::OpenApi;
         super::routes::config_management::read_all_config,
         super::routes::config_management::providers,
         super::routes::config_management::upsert_permissions,
+        super::routes::config_management::get_profiles,
+        super::routes::config_management::upsert_profile,
+        super::routes::config_management::remove_profile,
+        super::routes::config_management::set_default_profile,
         super::routes::agent::get_tools,
+        super::routes::agent::create_session,
+        super::routes::agent::list_sessions,
+        super::routes::agent::delete_session,
         super::routes::reply::confirm_permission,
         super::routes::context::manage_context,
         super::routes::session::list_sessions,
+        super::routes::session::search_sessions,
         super::routes::session::get_session_history,
+        super::routes::session::list_branches,
+        super::routes::session::rewind_session,
+        super::routes::session::switch_branch,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
         super::routes::schedule::delete_schedule,
@@ -61,11 +77,24 @@ use utoipa::OpenApi;
         super::routes::config_management::ExtensionQuery,
         super::routes::config_management::ToolPermission,
         super::routes::config_management::UpsertPermissionsQuery,
+        super::routes::config_management::ProfilesResponse,
+        super::routes::config_management::DefaultProfileQuery,
+        Profile,
+        ConfigIssue,
+        IssueKind,
+        ConfigSource,
         super::routes::reply::PermissionConfirmationRequest,
         super::routes::context::ContextManageRequest,
         super::routes::context::ContextManageResponse,
+        super::routes::agent::CreateSessionRequest,
+        super::routes::agent::CreateSessionResponse,
+        super::routes::agent::ActiveSessionInfo,
         super::routes::session::SessionListResponse,
         super::routes::session::SessionHistoryResponse,
+        super::routes::session::SessionSearchResponse,
+        super::routes::session::BranchListResponse,
+        super::routes::session::RewindRequest,
+        super::routes::session::RewindResponse,
         Message,
         MessageContent,
         Content,
@@ -96,7 +125,15 @@ use utoipa::OpenApi;
         PrincipalType,
         ModelInfo,
         SessionInfo,
+        SearchResult,
+        SearchMatch,
+        MatchField,
         SessionMetadata,
+        ModelSwitch,
+        BranchInfo,
+        HookOutcome,
+        HookEvent,
+        HookStatus,
         super::routes::schedule::CreateScheduleRequest,
         super::routes::schedule::UpdateScheduleRequest,
         super::routes::schedule::KillJobResponse,
@@ -1,16 +1,22 @@
-use super::utils::verify_secret_key;
-use crate::state::AppState;
+use super::utils::{
+    agent_error_status, authorize, authorize_session, check_session_id_access, session_id,
+};
+use crate::auth::Scope;
+use crate::state::{AppState, SessionError};
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
+use goose::agents::{Agent, ExtensionConfig};
 use goose::config::Config;
 use goose::config::PermissionManager;
+use goose::config::{Profile, ProfileManager};
 use goose::model::ModelConfig;
 use goose::providers::create;
 use goose::recipe::Response;
+use goose::session;
 use goose::{
     agents::{extension::ToolInfo, extension_manager::get_parameter_names},
     config::permission::PermissionLevel,
@@ -18,6 +24,7 @@ use goose::{
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 #[derive(Serialize)]
 struct VersionsResponse {
@@ -66,6 +73,8 @@ struct UpdateProviderRequest {
 #[derive(Deserialize)]
 struct SessionConfigRequest {
     response: Option<Response>,
+    /// Per-tool time limits in seconds from the recipe settings, keyed by prefixed tool name
+    tool_timeouts: Option<HashMap<String, u64>>,
 }
 
 #[derive(Deserialize)]
@@ -73,6 +82,33 @@ pub struct GetToolsQuery {
     extension_name: Option<String>,
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct CreateSessionRequest {
+    /// Identifier for the session, generated when omitted
+    session_id: Option<String>,
+    /// Provider for the session's agent, defaults to GOOSE_PROVIDER
+    provider: Option<String>,
+    /// Model for the session's agent, defaults to GOOSE_MODEL
+    model: Option<String>,
+    /// Profile the session runs with and takes its default provider and model from
+    profile: Option<String>,
+    /// Extensions to start on the session's agent, adding any needs the manage_config scope
+    #[serde(default)]
+    extensions: Vec<ExtensionConfig>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateSessionResponse {
+    session_id: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ActiveSessionInfo {
+    session_id: String,
+    /// Seconds since the session's agent was last used
+    idle_seconds: u64,
+}
+
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
@@ -93,12 +129,12 @@ async fn extend_prompt(
     headers: HeaderMap,
     Json(payload): Json<ExtendPromptRequest>,
 ) -> Result<Json<ExtendPromptResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
     agent.extend_system_prompt(payload.extension.clone()).await;
     Ok(Json(ExtendPromptResponse { success: true }))
 }
@@ -144,14 +180,14 @@ async fn get_tools(
     headers: HeaderMap,
     Query(query): Query<GetToolsQuery>,
 ) -> Result<Json<Vec<ToolInfo>>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     let config = Config::global();
     let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
     let permission_manager = PermissionManager::default();
 
     let mut tools: Vec<ToolInfo> = agent
@@ -197,20 +233,12 @@ async fn update_agent_provider(
     headers: HeaderMap,
     Json(payload): Json<UpdateProviderRequest>,
 ) -> Result<StatusCode, StatusCode> {
-    // Verify secret key
-    let secret_key = headers
-        .get("X-Secret-Key")
-        .and_then(|value| value.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    if secret_key != state.secret_key {
-        return Err(StatusCode::UNAUTHORIZED);
-    }
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
 
     let config = Config::global();
     let model = payload.model.unwrap_or_else(|| {
@@ -240,18 +268,23 @@ async fn update_router_tool_selector(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<String>, Json<ErrorResponse>> {
-    verify_secret_key(&headers, &state).map_err(|_| {
-        Json(ErrorResponse {
-            error: "Unauthorized - Invalid or missing API key".to_string(),
-        })
-    })?;
+    authorize_session(&headers, &state, Scope::Reply)
+        .await
+        .map_err(|_| {
+            Json(ErrorResponse {
+                error: "Unauthorized - Invalid or missing API key".to_string(),
+            })
+        })?;
 
-    let agent = state.get_agent().await.map_err(|e| {
-        tracing::error!("Failed to get agent: {}", e);
-        Json(ErrorResponse {
-            error: format!("Failed to get agent: {}", e),
-        })
-    })?;
+    let agent = state
+        .get_session_agent(session_id(&headers))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get agent: {}", e);
+            Json(ErrorResponse {
+                error: format!("Failed to get agent: {}", e),
+            })
+        })?;
 
     agent
         .update_router_tool_selector(None, Some(true))
@@ -281,18 +314,32 @@ async fn update_session_config(
     headers: HeaderMap,
     Json(payload): Json<SessionConfigRequest>,
 ) -> Result<Json<String>, Json<ErrorResponse>> {
-    verify_secret_key(&headers, &state).map_err(|_| {
-        Json(ErrorResponse {
-            error: "Unauthorized - Invalid or missing API key".to_string(),
-        })
-    })?;
+    authorize_session(&headers, &state, Scope::Reply)
+        .await
+        .map_err(|_| {
+            Json(ErrorResponse {
+                error: "Unauthorized - Invalid or missing API key".to_string(),
+            })
+        })?;
 
-    let agent = state.get_agent().await.map_err(|e| {
-        tracing::error!("Failed to get agent: {}", e);
-        Json(ErrorResponse {
-            error: format!("Failed to get agent: {}", e),
-        })
-    })?;
+    let agent = state
+        .get_session_agent(session_id(&headers))
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get agent: {}", e);
+            Json(ErrorResponse {
+                error: format!("Failed to get agent: {}", e),
+            })
+        })?;
+
+    if payload.response.is_none() && payload.tool_timeouts.is_none() {
+        return Ok(Json("Nothing provided to update.".to_string()));
+    }
+
+    if let Some(tool_timeouts) = payload.tool_timeouts {
+        agent.set_tool_timeouts(tool_timeouts).await;
+        tracing::info!("Applied recipe tool timeouts");
+    }
 
     if let Some(response) = payload.response {
         agent.add_final_output_tool(response).await;
@@ -302,8 +349,164 @@ async fn update_session_config(
             "Session config updated with final output tool".to_string(),
         ))
     } else {
-        Ok(Json("Nothing provided to update.".to_string()))
+        Ok(Json("Session config updated".to_string()))
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/agent/sessions",
+    request_body = CreateSessionRequest,
+    responses(
+        (status = 200, description = "Session agent created", body = CreateSessionResponse),
+        (status = 400, description = "Unknown profile, no usable provider or an extension failed to start"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 403, description = "Extensions requested without the manage_config scope"),
+        (status = 409, description = "Session already has an agent"),
+        (status = 429, description = "Too many active sessions")
+    )
+)]
+async fn create_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateSessionRequest>,
+) -> Result<Json<CreateSessionResponse>, StatusCode> {
+    let identity = if payload.extensions.is_empty() {
+        authorize(&headers, &state, Scope::Reply)?
+    } else {
+        state
+            .auth
+            .authorize_all(&headers, &[Scope::Reply, Scope::ManageConfig])?
+    };
+    let session_id = payload
+        .session_id
+        .unwrap_or_else(session::generate_session_id);
+    check_session_id_access(&identity, &session_id)?;
+
+    let (profile_name, profile) = match payload.profile {
+        Some(name) => {
+            let profile = ProfileManager::get(&name)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .ok_or(StatusCode::BAD_REQUEST)?;
+            (Some(name), profile)
+        }
+        None => match state.get_agent().await {
+            Ok(shared) => (shared.profile().await, Profile::default()),
+            Err(_) => (None, Profile::default()),
+        },
+    };
+
+    let config = Config::global();
+    let provider_name = payload
+        .provider
+        .or(profile.provider)
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let model = payload
+        .model
+        .or(profile.model)
+        .or_else(|| config.get_param("GOOSE_MODEL").ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let provider = create(&provider_name, ModelConfig::new(model)).map_err(|e| {
+        tracing::error!("Failed to create provider for session: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+
+    let agent = Agent::new();
+    agent
+        .update_provider(provider)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    agent.set_profile(profile_name).await;
+    if let Ok(scheduler) = state.scheduler().await {
+        agent.set_scheduler(scheduler).await;
+    }
+    for extension in payload.extensions {
+        let name = extension.name();
+        if let Err(e) = agent.add_extension(extension).await {
+            tracing::error!("Failed to add extension {} to session: {}", name, e);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    state
+        .create_session(
+            session_id.clone(),
+            Arc::new(agent),
+            identity.user().map(str::to_string),
+        )
+        .await
+        .map_err(|e| {
+            tracing::warn!("Failed to create session agent: {}", e);
+            match e {
+                SessionError::AlreadyExists(_) => StatusCode::CONFLICT,
+                SessionError::LimitReached(_) => StatusCode::TOO_MANY_REQUESTS,
+                SessionError::NotFound(_) => StatusCode::NOT_FOUND,
+            }
+        })?;
+
+    Ok(Json(CreateSessionResponse { session_id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/agent/sessions",
+    responses(
+        (status = 200, description = "Sessions with their own agent", body = Vec<ActiveSessionInfo>),
+        (status = 401, description = "Unauthorized - invalid secret key")
+    )
+)]
+async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ActiveSessionInfo>>, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::Reply)?;
+
+    let sessions = state
+        .active_sessions()
+        .await
+        .into_iter()
+        .filter(|session| identity.user().is_none() || session.owner.as_deref() == identity.user())
+        .map(|session| ActiveSessionInfo {
+            session_id: session.session_id,
+            idle_seconds: session.idle.as_secs(),
+        })
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/agent/sessions/{session_id}",
+    params(
+        ("session_id" = String, Path, description = "Session whose agent should be dropped")
+    ),
+    responses(
+        (status = 200, description = "Session agent dropped"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "Session has no agent of its own")
+    )
+)]
+async fn delete_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::Reply)?;
+    check_session_id_access(&identity, &session_id)?;
+    if !state
+        .may_use_session_agent(&session_id, identity.user())
+        .await
+    {
+        return Err(StatusCode::NOT_FOUND);
     }
+
+    state
+        .remove_session(&session_id)
+        .await
+        .map(|_| StatusCode::OK)
+        .ok_or(StatusCode::NOT_FOUND)
 }
 
 pub fn routes(state: Arc<AppState>) -> Router {
@@ -318,5 +521,92 @@ pub fn routes(state: Arc<AppState>) -> Router {
             post(update_router_tool_selector),
         )
         .route("/agent/session_config", post(update_session_config))
+        .route("/agent/sessions", post(create_session).get(list_sessions))
+        .route("/agent/sessions/{session_id}", delete(delete_session))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{hash_token, ApiToken, Authenticator};
+
+    async fn token_state() -> Arc<AppState> {
+        let mut state =
+            (*AppState::new(Arc::new(Agent::new()), "secret".to_string()).await).clone();
+        state.auth = Arc::new(Authenticator::with_source(
+            "secret".to_string(),
+            Box::new(|| {
+                vec![ApiToken {
+                    name: "alice".to_string(),
+                    hash: hash_token("goose_alice"),
+                    scopes: vec![Scope::Reply],
+                    rate_limit: None,
+                }]
+            }),
+        ));
+        Arc::new(state)
+    }
+
+    fn extend_request() -> Json<ExtendPromptRequest> {
+        Json(ExtendPromptRequest {
+            extension: "Be brief".to_string(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_session_rejects_unknown_profile() {
+        let state = AppState::new(Arc::new(Agent::new()), "secret".to_string()).await;
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Secret-Key", "secret".parse().unwrap());
+        let request = CreateSessionRequest {
+            session_id: None,
+            provider: Some("openai".to_string()),
+            model: Some("gpt-4o".to_string()),
+            profile: Some("no-such-profile".to_string()),
+            extensions: Vec::new(),
+        };
+
+        let result = create_session(State(state.clone()), headers, Json(request)).await;
+        assert_eq!(result.err(), Some(StatusCode::BAD_REQUEST));
+        assert!(state.active_sessions().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_session_extensions_need_manage_config() {
+        let state = token_state().await;
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", "Bearer goose_alice".parse().unwrap());
+        let request = CreateSessionRequest {
+            session_id: None,
+            provider: Some("openai".to_string()),
+            model: Some("gpt-4o".to_string()),
+            profile: None,
+            extensions: vec![ExtensionConfig::Builtin {
+                name: "developer".to_string(),
+                display_name: None,
+                timeout: None,
+                bundled: None,
+            }],
+        };
+
+        let result = create_session(State(state.clone()), headers, Json(request)).await;
+        assert_eq!(result.err(), Some(StatusCode::FORBIDDEN));
+        assert!(state.active_sessions().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_token_without_session_cannot_use_shared_agent() {
+        let state = token_state().await;
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", "Bearer goose_alice".parse().unwrap());
+
+        let result = extend_prompt(State(state.clone()), headers, extend_request()).await;
+        assert_eq!(result.err(), Some(StatusCode::FORBIDDEN));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Secret-Key", "secret".parse().unwrap());
+        let result = extend_prompt(State(state), headers, extend_request()).await;
+        assert!(result.is_ok());
+    }
+}
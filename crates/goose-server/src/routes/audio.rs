@@ -2,7 +2,8 @@
 ///
 /// This module provides endpoints for audio transcription using OpenAI's Whisper API.
 /// The OpenAI API key must be configured in the backend for this to work.
-use super::utils::verify_secret_key;
+use super::utils::authorize;
+use crate::auth::Scope;
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -64,7 +65,7 @@ async fn transcribe_handler(
     headers: HeaderMap,
     Json(request): Json<TranscribeRequest>,
 ) -> Result<Json<TranscribeResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::Reply)?;
 
     // Validate input first before checking API key configuration
     // Decode the base64 audio data
@@ -175,7 +176,7 @@ async fn transcribe_elevenlabs_handler(
     headers: HeaderMap,
     Json(request): Json<TranscribeElevenLabsRequest>,
 ) -> Result<Json<TranscribeResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::Reply)?;
 
     // Validate input first before checking API key configuration
     // Decode the base64 audio data
@@ -325,7 +326,7 @@ async fn check_dictation_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::Reply)?;
 
     let config = goose::config::Config::global();
 
@@ -1,15 +1,17 @@
-use super::utils::verify_secret_key;
+use super::utils::authorize;
+use crate::auth::Scope;
 use crate::routes::utils::check_provider_configured;
 use crate::state::AppState;
 use axum::{
     extract::State,
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Json, Router,
 };
 use etcetera::{choose_app_strategy, AppStrategy};
-use goose::config::Config;
+use goose::config::profiles::PROFILE_CONFIG_KEY;
 use goose::config::APP_STRATEGY;
-use goose::config::{extensions::name_to_key, PermissionManager};
+use goose::config::{extensions::name_to_key, PermissionManager, Profile, ProfileManager};
+use goose::config::{Config, ConfigIssue, IssueKind};
 use goose::config::{ExtensionConfigManager, ExtensionEntry};
 use goose::model::ModelConfig;
 use goose::providers::base::ProviderMetadata;
@@ -75,6 +77,19 @@ pub struct ToolPermission {
     pub permission: PermissionLevel,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct ProfilesResponse {
+    pub profiles: HashMap<String, Profile>,
+    /// The profile currently applied to new sessions
+    pub active: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DefaultProfileQuery {
+    /// Profile to select by default, None clears the default
+    pub name: Option<String>,
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct UpsertPermissionsQuery {
     pub tool_permissions: Vec<ToolPermission>,
@@ -94,7 +109,7 @@ pub async fn upsert_config(
     headers: HeaderMap,
     Json(query): Json<UpsertConfigQuery>,
 ) -> Result<Json<Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config = Config::global();
     let result = config.set(&query.key, query.value, query.is_secret);
@@ -120,7 +135,7 @@ pub async fn remove_config(
     headers: HeaderMap,
     Json(query): Json<ConfigKeyQuery>,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config = Config::global();
 
@@ -150,7 +165,7 @@ pub async fn read_config(
     headers: HeaderMap,
     Json(query): Json<ConfigKeyQuery>,
 ) -> Result<Json<Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     if query.key == "model-limits" {
         let limits = ModelConfig::get_all_model_limits();
@@ -185,7 +200,7 @@ pub async fn get_extensions(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<ExtensionResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     match ExtensionConfigManager::get_all() {
         Ok(extensions) => Ok(Json(ExtensionResponse { extensions })),
@@ -218,7 +233,7 @@ pub async fn add_extension(
     headers: HeaderMap,
     Json(extension_query): Json<ExtensionQuery>,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let extensions =
         ExtensionConfigManager::get_all().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -255,7 +270,7 @@ pub async fn remove_extension(
     headers: HeaderMap,
     axum::extract::Path(name): axum::extract::Path<String>,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let key = name_to_key(&name);
     match ExtensionConfigManager::remove(&key) {
@@ -275,12 +290,12 @@ pub async fn read_all_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<ConfigResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config = Config::global();
 
     let values = config
-        .load_values()
+        .load_merged_values()
         .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
 
     Ok(Json(ConfigResponse { config: values }))
@@ -297,7 +312,7 @@ pub async fn providers(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<Vec<ProviderDetails>>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let providers_metadata = get_providers();
 
@@ -352,7 +367,7 @@ pub async fn get_pricing(
     headers: HeaderMap,
     Json(query): Json<PricingQuery>,
 ) -> Result<Json<PricingResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let configured_only = query.configured_only.unwrap_or(true);
 
@@ -450,7 +465,7 @@ pub async fn init_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config = Config::global();
 
@@ -484,7 +499,7 @@ pub async fn upsert_permissions(
     headers: HeaderMap,
     Json(query): Json<UpsertPermissionsQuery>,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let mut permission_manager = PermissionManager::default();
 
@@ -510,7 +525,7 @@ pub async fn backup_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config_dir = choose_app_strategy(APP_STRATEGY.clone())
         .expect("goose requires a home dir")
@@ -548,7 +563,7 @@ pub async fn recover_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config = Config::global();
 
@@ -585,7 +600,7 @@ pub async fn validate_config(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<String>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let config_dir = choose_app_strategy(APP_STRATEGY.clone())
         .expect("goose requires a home dir")
@@ -599,7 +614,26 @@ pub async fn validate_config(
 
     match std::fs::read_to_string(&config_path) {
         Ok(content) => match serde_yaml::from_str::<serde_yaml::Value>(&content) {
-            Ok(_) => Ok(Json("Config file is valid".to_string())),
+            Ok(_) => {
+                // Invalid values are reported without failing, a 422 makes clients recover
+                // the file from a backup
+                let invalid: Vec<String> = Config::global()
+                    .check()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|issue| issue.kind == IssueKind::Invalid)
+                    .map(|issue| issue.to_string())
+                    .collect();
+                if invalid.is_empty() {
+                    Ok(Json("Config file is valid".to_string()))
+                } else {
+                    Ok(Json(format!(
+                        "Config file has {} invalid values: {}",
+                        invalid.len(),
+                        invalid.join("; ")
+                    )))
+                }
+            }
             Err(e) => {
                 tracing::warn!("Config validation failed: {}", e);
                 Err(StatusCode::UNPROCESSABLE_ENTITY)
@@ -612,6 +646,26 @@ pub async fn validate_config(
     }
 }
 
+#[utoipa::path(
+    get,
+    path = "/config/check",
+    responses(
+        (status = 200, description = "Unknown, invalid and deprecated settings", body = [ConfigIssue]),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn check_config(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ConfigIssue>>, StatusCode> {
+    authorize(&headers, &state, Scope::ManageConfig)?;
+
+    Config::global()
+        .check()
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
 #[utoipa::path(
     get,
     path = "/config/current-model",
@@ -623,7 +677,7 @@ pub async fn get_current_model(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageConfig)?;
 
     let current_model = goose::providers::base::get_current_model();
 
@@ -632,6 +686,113 @@ pub async fn get_current_model(
     })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/config/profiles",
+    responses(
+        (status = 200, description = "All profiles retrieved successfully", body = ProfilesResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_profiles(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    authorize(&headers, &state, Scope::ManageConfig)?;
+
+    let profiles = ProfileManager::get_all().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let active = Config::global()
+        .active_profile()
+        .filter(|name| profiles.contains_key(name));
+
+    Ok(Json(ProfilesResponse { profiles, active }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/config/profiles/{name}",
+    request_body = Profile,
+    responses(
+        (status = 200, description = "Profile saved successfully", body = String),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn upsert_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(name): axum::extract::Path<String>,
+    Json(profile): Json<Profile>,
+) -> Result<Json<String>, StatusCode> {
+    authorize(&headers, &state, Scope::ManageConfig)?;
+
+    match ProfileManager::set(&name, profile) {
+        Ok(_) => Ok(Json(format!("Saved profile {}", name))),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/config/profiles/{name}",
+    responses(
+        (status = 200, description = "Profile removed successfully", body = String),
+        (status = 404, description = "Profile not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn remove_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> Result<Json<String>, StatusCode> {
+    authorize(&headers, &state, Scope::ManageConfig)?;
+
+    match ProfileManager::remove(&name) {
+        Ok(true) => Ok(Json(format!("Removed profile {}", name))),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/config/profiles/default",
+    request_body = DefaultProfileQuery,
+    responses(
+        (status = 200, description = "Default profile updated successfully", body = String),
+        (status = 404, description = "Profile not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn set_default_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<DefaultProfileQuery>,
+) -> Result<Json<String>, StatusCode> {
+    authorize(&headers, &state, Scope::ManageConfig)?;
+
+    let config = Config::global();
+    match query.name {
+        Some(name) => {
+            match ProfileManager::get(&name) {
+                Ok(Some(_)) => {}
+                Ok(None) => return Err(StatusCode::NOT_FOUND),
+                Err(_) => return Err(StatusCode::INTERNAL_SERVER_ERROR),
+            }
+            config
+                .set_param(PROFILE_CONFIG_KEY, Value::String(name.clone()))
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Ok(Json(format!("Default profile set to {}", name)))
+        }
+        None => {
+            config
+                .delete(PROFILE_CONFIG_KEY)
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Ok(Json("Default profile cleared".to_string()))
+        }
+    }
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/config", get(read_all_config))
@@ -647,8 +808,13 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/config/backup", post(backup_config))
         .route("/config/recover", post(recover_config))
         .route("/config/validate", get(validate_config))
+        .route("/config/check", get(check_config))
         .route("/config/permissions", post(upsert_permissions))
         .route("/config/current-model", get(get_current_model))
+        .route("/config/profiles", get(get_profiles))
+        .route("/config/profiles/default", post(set_default_profile))
+        .route("/config/profiles/{name}", put(upsert_profile))
+        .route("/config/profiles/{name}", delete(remove_profile))
         .with_state(state)
 }
 
@@ -1,4 +1,5 @@
-use super::utils::verify_secret_key;
+use super::utils::{agent_error_status, authorize_session, session_id};
+use crate::auth::Scope;
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -51,12 +52,12 @@ async fn manage_context(
     headers: HeaderMap,
     Json(request): Json<ContextManageRequest>,
 ) -> Result<Json<ContextManageResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
 
     let mut processed_messages: Vec<Message> = vec![];
     let mut token_counts: Vec<usize> = vec![];
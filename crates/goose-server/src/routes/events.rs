@@ -0,0 +1,382 @@
+use super::reply::SseResponse;
+use super::utils::{authorize, check_session_access};
+use crate::auth::{Identity, Scope};
+use crate::state::AppState;
+use axum::{
+    extract::{
+        ws::{Message as WsMessage, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use chrono::{DateTime, Utc};
+use goose::agents::observer::{self, Activity, ActivityKind, ScheduleRunStatus};
+use goose::agents::{AgentEvent, StopReason};
+use goose::message::Message;
+use goose::session;
+use goose::tool_monitor::LoopDetection;
+use mcp_core::protocol::JsonRpcMessage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    /// Only send events of this session
+    session_id: Option<String>,
+    /// Comma separated event types to send, all types when unset
+    types: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ObservedEvent {
+    Message {
+        message: Message,
+    },
+    ToolCall {
+        request_id: String,
+        started_at: DateTime<Utc>,
+        duration_ms: Option<u64>,
+    },
+    Notification {
+        request_id: String,
+        message: JsonRpcMessage,
+    },
+    ModelChange {
+        model: String,
+        mode: String,
+    },
+    LoopDetected {
+        detection: LoopDetection,
+    },
+    Stopped {
+        reason: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+    ScheduleRun {
+        schedule_id: String,
+        status: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// The observer fell behind and missed this many events
+    Lagged {
+        skipped: u64,
+    },
+}
+
+impl ObservedEvent {
+    fn type_name(&self) -> &'static str {
+        match self {
+            ObservedEvent::Message { .. } => "message",
+            ObservedEvent::ToolCall { .. } => "tool_call",
+            ObservedEvent::Notification { .. } => "notification",
+            ObservedEvent::ModelChange { .. } => "model_change",
+            ObservedEvent::LoopDetected { .. } => "loop_detected",
+            ObservedEvent::Stopped { .. } => "stopped",
+            ObservedEvent::ScheduleRun { .. } => "schedule_run",
+            ObservedEvent::Lagged { .. } => "lagged",
+        }
+    }
+}
+
+impl From<ActivityKind> for ObservedEvent {
+    fn from(kind: ActivityKind) -> Self {
+        match kind {
+            ActivityKind::Agent(AgentEvent::Message(message)) => ObservedEvent::Message { message },
+            ActivityKind::Agent(AgentEvent::ToolCallTiming {
+                request_id,
+                started_at,
+                duration_ms,
+            }) => ObservedEvent::ToolCall {
+                request_id,
+                started_at,
+                duration_ms,
+            },
+            ActivityKind::Agent(AgentEvent::McpNotification((request_id, message))) => {
+                ObservedEvent::Notification {
+                    request_id,
+                    message,
+                }
+            }
+            ActivityKind::Agent(AgentEvent::ModelChange { model, mode }) => {
+                ObservedEvent::ModelChange { model, mode }
+            }
+            ActivityKind::Agent(AgentEvent::LoopDetected(detection)) => {
+                ObservedEvent::LoopDetected { detection }
+            }
+            ActivityKind::Agent(AgentEvent::Stopped(reason)) => {
+                let detail = match &reason {
+                    StopReason::MaxTurns(_) => None,
+                    StopReason::ToolLoop(detail) | StopReason::ProviderError(detail) => {
+                        Some(detail.clone())
+                    }
+                };
+                ObservedEvent::Stopped {
+                    reason: reason.name(),
+                    detail,
+                }
+            }
+            ActivityKind::ScheduleRun {
+                schedule_id,
+                status,
+            } => {
+                let (status, error) = match status {
+                    ScheduleRunStatus::Started => ("started", None),
+                    ScheduleRunStatus::Finished { .. } => ("finished", None),
+                    ScheduleRunStatus::Failed { error } => ("failed", Some(error)),
+                };
+                ObservedEvent::ScheduleRun {
+                    schedule_id,
+                    status,
+                    error,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EventEnvelope {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_id: Option<String>,
+    timestamp: DateTime<Utc>,
+    #[serde(flatten)]
+    event: ObservedEvent,
+}
+
+/// Decides which activity one observer gets to see
+struct EventFilter {
+    identity: Identity,
+    session_id: Option<String>,
+    types: Option<HashSet<String>>,
+    /// Sessions a token user owns, remembered to avoid rereading session files
+    visible: HashSet<String>,
+}
+
+impl EventFilter {
+    fn new(identity: Identity, query: EventsQuery) -> Self {
+        let types = query.types.map(|types| {
+            types
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect()
+        });
+        Self {
+            identity,
+            session_id: query.session_id,
+            types,
+            visible: HashSet::new(),
+        }
+    }
+
+    fn may_see(&mut self, session_id: Option<&str>) -> bool {
+        if self.identity.user().is_none() {
+            return true;
+        }
+        // Token users only see activity of sessions they own
+        let Some(session_id) = session_id else {
+            return false;
+        };
+        if self.visible.contains(session_id) {
+            return true;
+        }
+        // A session without a file has no owner yet, so it stays hidden until one is recorded
+        let visible = session::get_path(session::Identifier::Name(session_id.to_string()))
+            .is_ok_and(|path| {
+                session::session_exists(&path)
+                    && check_session_access(&self.identity, &path).is_ok()
+            });
+        if visible {
+            self.visible.insert(session_id.to_string());
+        }
+        visible
+    }
+
+    /// The JSON to send for some activity, None if this observer should not get it
+    fn render(&mut self, activity: Activity) -> Option<String> {
+        if self.session_id.is_some() && activity.session_id != self.session_id {
+            return None;
+        }
+        if !self.may_see(activity.session_id.as_deref()) {
+            return None;
+        }
+        let event = ObservedEvent::from(activity.kind);
+        if let Some(types) = &self.types {
+            if !types.contains(event.type_name()) {
+                return None;
+            }
+        }
+        self.envelope(activity.session_id, activity.timestamp, event)
+    }
+
+    fn lagged(&self, skipped: u64) -> Option<String> {
+        self.envelope(None, Utc::now(), ObservedEvent::Lagged { skipped })
+    }
+
+    fn envelope(
+        &self,
+        session_id: Option<String>,
+        timestamp: DateTime<Utc>,
+        event: ObservedEvent,
+    ) -> Option<String> {
+        serde_json::to_string(&EventEnvelope {
+            session_id,
+            timestamp,
+            event,
+        })
+        .map_err(|e| tracing::warn!("Failed to serialize observed event: {}", e))
+        .ok()
+    }
+
+    /// Wait for the next event this observer should get, None once the publisher is gone
+    async fn next(&mut self, rx: &mut broadcast::Receiver<Activity>) -> Option<String> {
+        loop {
+            let json = match rx.recv().await {
+                Ok(activity) => self.render(activity),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => self.lagged(skipped),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            };
+            if json.is_some() {
+                return json;
+            }
+        }
+    }
+}
+
+/// Stream the activity of every agent in this server as server-sent events
+async fn events_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+) -> Result<SseResponse, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
+    let mut filter = EventFilter::new(identity, query);
+    let mut rx = observer::subscribe();
+
+    let (tx, stream_rx) = mpsc::channel(100);
+    tokio::spawn(async move {
+        while let Some(json) = filter.next(&mut rx).await {
+            if tx.send(format!("data: {}\n\n", json)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(SseResponse::new(ReceiverStream::new(stream_rx)))
+}
+
+/// The same events as `/events`, one JSON text frame each
+async fn events_ws_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
+    let filter = EventFilter::new(identity, query);
+    Ok(ws.on_upgrade(move |socket| send_events(socket, filter)))
+}
+
+async fn send_events(mut socket: WebSocket, mut filter: EventFilter) {
+    let mut rx = observer::subscribe();
+    loop {
+        tokio::select! {
+            json = filter.next(&mut rx) => {
+                let Some(json) = json else { break };
+                if socket.send(WsMessage::Text(json.into())).await.is_err() {
+                    break;
+                }
+            }
+            incoming = socket.recv() => {
+                // Observers only listen, anything but a close is ignored
+                if !matches!(incoming, Some(Ok(msg)) if !matches!(msg, WsMessage::Close(_))) {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/events", get(events_handler))
+        .route("/events/ws", get(events_ws_handler))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::agents::observer::ScheduleRunStatus;
+
+    fn activity(session_id: Option<&str>, kind: ActivityKind) -> Activity {
+        Activity {
+            session_id: session_id.map(str::to_string),
+            timestamp: Utc::now(),
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_filter_by_session_and_type() {
+        let mut filter = EventFilter::new(
+            Identity::SecretKey,
+            EventsQuery {
+                session_id: Some("a".to_string()),
+                types: Some("message, schedule_run".to_string()),
+            },
+        );
+        let message = || ActivityKind::Agent(AgentEvent::Message(Message::user().with_text("hi")));
+
+        let json = filter.render(activity(Some("a"), message())).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["type"], "message");
+        assert_eq!(value["session_id"], "a");
+
+        assert!(filter.render(activity(Some("b"), message())).is_none());
+        let model_change = ActivityKind::Agent(AgentEvent::ModelChange {
+            model: "m".to_string(),
+            mode: "auto".to_string(),
+        });
+        assert!(filter.render(activity(Some("a"), model_change)).is_none());
+
+        let failed = ActivityKind::ScheduleRun {
+            schedule_id: "nightly".to_string(),
+            status: ScheduleRunStatus::Failed {
+                error: "boom".to_string(),
+            },
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&filter.render(activity(Some("a"), failed)).unwrap()).unwrap();
+        assert_eq!(value["status"], "failed");
+        assert_eq!(value["error"], "boom");
+    }
+
+    #[test]
+    fn test_token_users_only_see_sessions() {
+        let mut filter = EventFilter::new(
+            Identity::Token {
+                name: "alice".to_string(),
+            },
+            EventsQuery::default(),
+        );
+        let started = ActivityKind::ScheduleRun {
+            schedule_id: "nightly".to_string(),
+            status: ScheduleRunStatus::Started,
+        };
+        assert!(filter.render(activity(None, started)).is_none());
+
+        let missing = format!("missing_{:08x}", rand::random::<u32>());
+        assert!(!filter.may_see(Some(&missing)));
+        assert!(filter.visible.is_empty());
+    }
+}
@@ -3,7 +3,8 @@ use std::path::Path;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
-use super::utils::verify_secret_key;
+use super::utils::{agent_error_status, authorize_session, session_id};
+use crate::auth::Scope;
 use crate::state::AppState;
 use axum::{extract::State, routing::post, Json, Router};
 use goose::agents::{extension::Envs, ExtensionConfig};
@@ -102,7 +103,7 @@ async fn add_extension(
     headers: HeaderMap,
     raw: axum::extract::Json<serde_json::Value>,
 ) -> Result<Json<ExtensionResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::ManageConfig).await?;
 
     // Log the raw request for debugging
     tracing::info!(
@@ -266,9 +267,9 @@ async fn add_extension(
 
     // Get a reference to the agent
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
     let response = agent.add_extension(extension_config).await;
 
     // Respond with the result.
@@ -296,13 +297,13 @@ async fn remove_extension(
     headers: HeaderMap,
     Json(name): Json<String>,
 ) -> Result<Json<ExtensionResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::ManageConfig).await?;
 
     // Get a reference to the agent
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
     match agent.remove_extension(&name).await {
         Ok(_) => Ok(Json(ExtensionResponse {
             error: false,
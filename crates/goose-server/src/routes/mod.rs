@@ -3,8 +3,10 @@ pub mod agent;
 pub mod audio;
 pub mod config_management;
 pub mod context;
+pub mod events;
 pub mod extension;
 pub mod health;
+pub mod openai;
 pub mod recipe;
 pub mod reply;
 pub mod schedule;
@@ -19,9 +21,11 @@ pub fn configure(state: Arc<crate::state::AppState>) -> Router {
     Router::new()
         .merge(health::routes())
         .merge(reply::routes(state.clone()))
+        .merge(openai::routes(state.clone()))
         .merge(agent::routes(state.clone()))
         .merge(audio::routes(state.clone()))
         .merge(context::routes(state.clone()))
+        .merge(events::routes(state.clone()))
         .merge(extension::routes(state.clone()))
         .merge(config_management::routes(state.clone()))
         .merge(recipe::routes(state.clone()))
@@ -0,0 +1,770 @@
+use super::reply::SseResponse;
+use super::utils::{authorize, claim_session_access};
+use crate::auth::{Identity, Scope};
+use crate::state::AppState;
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::Utc;
+use futures::StreamExt;
+use goose::agents::{Agent, AgentEvent, ExtensionConfig, SessionConfig};
+use goose::config::{Config, ExtensionConfigManager, Profile, ProfileManager};
+use goose::message::{Message, MessageContent};
+use goose::model::ModelConfig;
+use goose::permission::permission_confirmation::PrincipalType;
+use goose::permission::{Permission, PermissionConfirmation};
+use goose::providers::{base::Provider, create};
+use goose::recipe::Recipe;
+use goose::session;
+use mcp_core::role::Role;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Model id that runs the agent with the server's current provider and model
+const DEFAULT_MODEL: &str = "goose";
+/// Prefix of model ids that run the agent with a configured profile
+const PROFILE_PREFIX: &str = "profile/";
+/// Prefix of model ids that run the agent with a recipe from `GOOSE_RECIPE_PATH`
+const RECIPE_PREFIX: &str = "recipe/";
+const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "yml", "json"];
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ChatCompletionRequest {
+    model: String,
+    messages: Vec<ChatCompletionMessage>,
+    #[serde(default)]
+    stream: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct StreamOptions {
+    #[serde(default)]
+    include_usage: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ChatCompletionMessage {
+    role: String,
+    /// Either a string or a list of content parts, only text parts are used
+    #[serde(default)]
+    content: Value,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct CompletionUsage {
+    prompt_tokens: i32,
+    completion_tokens: i32,
+    total_tokens: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChatCompletion {
+    id: String,
+    object: String,
+    created: i64,
+    model: String,
+    choices: Vec<CompletionChoice>,
+    usage: CompletionUsage,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CompletionChoice {
+    index: u32,
+    message: ChatCompletionMessage,
+    finish_reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletionChunk<'a> {
+    id: &'a str,
+    object: &'static str,
+    created: i64,
+    model: &'a str,
+    choices: Vec<ChunkChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<CompletionUsage>,
+}
+
+#[derive(Debug, Serialize)]
+struct ChunkChoice {
+    index: u32,
+    delta: ChunkDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ChunkDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelList {
+    object: &'static str,
+    data: Vec<ModelInfo>,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelInfo {
+    id: String,
+    object: &'static str,
+    created: i64,
+    owned_by: &'static str,
+}
+
+/// What a requested model id runs the agent with
+enum ModelTarget {
+    Default,
+    Profile(Profile),
+    Recipe(Box<Recipe>),
+}
+
+/// Directories listed in `GOOSE_RECIPE_PATH`
+fn recipe_dirs() -> Vec<PathBuf> {
+    Config::global()
+        .get_param::<String>("GOOSE_RECIPE_PATH")
+        .map(|paths| std::env::split_paths(&paths).collect())
+        .unwrap_or_default()
+}
+
+/// Recipes that can back a model, recipes with parameters need input a chat client can't give
+fn list_recipes() -> Vec<(String, Recipe)> {
+    let mut recipes = Vec::new();
+    for dir in recipe_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let is_recipe = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| RECIPE_FILE_EXTENSIONS.contains(&ext));
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            if !is_recipe || recipes.iter().any(|(existing, _)| existing == name) {
+                continue;
+            }
+            match Recipe::from_file(&path) {
+                Ok(recipe) if recipe.parameters.as_ref().is_none_or(|p| p.is_empty()) => {
+                    recipes.push((name.to_string(), recipe));
+                }
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping unreadable recipe {}: {}", path.display(), e),
+            }
+        }
+    }
+    recipes
+}
+
+fn resolve_model(model: &str) -> Option<ModelTarget> {
+    if model == DEFAULT_MODEL {
+        return Some(ModelTarget::Default);
+    }
+    if let Some(name) = model.strip_prefix(PROFILE_PREFIX) {
+        return ProfileManager::get(name)
+            .ok()
+            .flatten()
+            .map(ModelTarget::Profile);
+    }
+    let name = model.strip_prefix(RECIPE_PREFIX)?;
+    list_recipes()
+        .into_iter()
+        .find(|(recipe_name, _)| recipe_name == name)
+        .map(|(_, recipe)| ModelTarget::Recipe(Box::new(recipe)))
+}
+
+/// Pick the provider for a model, the default model reuses the server agent's provider if set
+async fn target_provider(
+    state: &AppState,
+    target: &ModelTarget,
+) -> Result<Arc<dyn Provider>, StatusCode> {
+    let (provider, model, temperature) = match target {
+        ModelTarget::Default => {
+            if let Ok(agent) = state.get_agent().await {
+                if let Ok(provider) = agent.provider().await {
+                    return Ok(provider);
+                }
+            }
+            (None, None, None)
+        }
+        ModelTarget::Profile(profile) => (profile.provider.clone(), profile.model.clone(), None),
+        ModelTarget::Recipe(recipe) => match &recipe.settings {
+            Some(settings) => (
+                settings.goose_provider.clone(),
+                settings.goose_model.clone(),
+                settings.temperature,
+            ),
+            None => (None, None, None),
+        },
+    };
+
+    let config = Config::global();
+    let provider = provider
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
+        .ok_or(StatusCode::PRECONDITION_FAILED)?;
+    let model = model
+        .or_else(|| config.get_param("GOOSE_MODEL").ok())
+        .ok_or(StatusCode::PRECONDITION_FAILED)?;
+    create(
+        &provider,
+        ModelConfig::new(model).with_temperature(temperature),
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to create provider for completion: {}", e);
+        StatusCode::PRECONDITION_FAILED
+    })
+}
+
+/// Extensions a model runs with: the recipe's, the profile's or the enabled ones
+fn target_extensions(target: &ModelTarget) -> Result<Vec<ExtensionConfig>, StatusCode> {
+    if let ModelTarget::Recipe(recipe) = target {
+        if let Some(extensions) = &recipe.extensions {
+            return Ok(extensions.clone());
+        }
+    }
+    let enabled_keys = match target {
+        ModelTarget::Profile(profile) => profile.extensions.clone(),
+        _ => None,
+    };
+    Ok(ExtensionConfigManager::get_all()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter(|entry| match &enabled_keys {
+            Some(keys) => keys.contains(&entry.config.key()),
+            None => entry.enabled,
+        })
+        .map(|entry| entry.config)
+        .collect())
+}
+
+/// Build a fresh agent for one completion, so requests never change the shared agent
+///
+/// Every request starts its own copies of the extensions and stops them when it finishes.
+/// This keeps requests from seeing each other's tool state and always uses the current
+/// configuration, at the cost of the extensions' startup time on each completion.
+async fn build_agent(
+    state: &AppState,
+    target: &ModelTarget,
+    extensions: Vec<ExtensionConfig>,
+    system: Option<String>,
+) -> Result<Agent, StatusCode> {
+    let agent = Agent::new();
+    agent
+        .update_provider(target_provider(state, target).await?)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Completions run with the server's profile, like the shared agent
+    if let Ok(shared) = state.get_agent().await {
+        agent.set_profile(shared.profile().await).await;
+    }
+
+    // Recipe tool timeouts have to be set before the extensions are started
+    if let ModelTarget::Recipe(recipe) = target {
+        if let Some(tool_timeouts) = recipe
+            .settings
+            .as_ref()
+            .and_then(|s| s.tool_timeouts.clone())
+        {
+            agent.set_tool_timeouts(tool_timeouts).await;
+        }
+    }
+
+    for extension in extensions {
+        let name = extension.name();
+        if let Err(e) = agent.add_extension(extension).await {
+            tracing::warn!("Failed to start extension {} for completion: {}", name, e);
+        }
+    }
+
+    if let ModelTarget::Recipe(recipe) = target {
+        if let Some(instructions) = recipe.instructions.as_ref().or(recipe.prompt.as_ref()) {
+            agent.extend_system_prompt(instructions.clone()).await;
+        }
+    }
+    if let Some(system) = system {
+        agent.extend_system_prompt(system).await;
+    }
+
+    Ok(agent)
+}
+
+/// The text of a message's content, which is either a string or a list of parts
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part.get("text").and_then(Value::as_str))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Split chat messages into the system instructions and the conversation
+fn convert_messages(
+    messages: &[ChatCompletionMessage],
+) -> Result<(Option<String>, Vec<Message>), StatusCode> {
+    let mut system = Vec::new();
+    let mut conversation = Vec::new();
+    for message in messages {
+        let text = content_text(&message.content);
+        match message.role.as_str() {
+            "system" | "developer" => system.push(text),
+            "user" => conversation.push(Message::user().with_text(text)),
+            "assistant" if !text.is_empty() => {
+                conversation.push(Message::assistant().with_text(text))
+            }
+            // Tool calls of earlier turns ran inside goose, the client has nothing to replay
+            "assistant" | "tool" => {}
+            _ => return Err(StatusCode::BAD_REQUEST),
+        }
+    }
+    if conversation.last().map(|m| &m.role) != Some(&Role::User) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let system = (!system.is_empty()).then(|| system.join("\n\n"));
+    Ok((system, conversation))
+}
+
+fn completion_usage(session_path: &std::path::Path) -> CompletionUsage {
+    let metadata = session::read_metadata(session_path).unwrap_or_default();
+    let prompt_tokens = metadata.accumulated_input_tokens.unwrap_or(0);
+    let completion_tokens = metadata.accumulated_output_tokens.unwrap_or(0);
+    CompletionUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: metadata
+            .accumulated_total_tokens
+            .unwrap_or(prompt_tokens + completion_tokens),
+    }
+}
+
+/// Between the text parts of a completion, whether streamed or returned at once
+const TEXT_SEPARATOR: &str = "\n";
+
+/// One agent run for a completion request, recorded as its own session
+struct Completion {
+    id: String,
+    created: i64,
+    model: String,
+    agent: Agent,
+    messages: Vec<Message>,
+    session_id: String,
+    working_dir: PathBuf,
+}
+
+impl Completion {
+    /// Run the agent to the end, passing each piece of assistant text to `tx` as it arrives
+    ///
+    /// Tool calls that need approval under the configured `GOOSE_MODE` are denied, since an
+    /// API client has no way to answer the confirmation.
+    async fn run(
+        &self,
+        tx: Option<&mpsc::Sender<String>>,
+    ) -> Result<(String, CompletionUsage), StatusCode> {
+        let session_path = session::get_path(session::Identifier::Name(self.session_id.clone()))
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let provider = self.agent.provider().await.ok();
+        // Write the request first so the agent can record token usage against the session
+        if let Err(e) = session::persist_messages(&session_path, &self.messages, None).await {
+            tracing::error!("Failed to store completion request: {:?}", e);
+        }
+
+        let mut stream = self
+            .agent
+            .reply(
+                &self.messages,
+                Some(SessionConfig {
+                    id: session::Identifier::Name(self.session_id.clone()),
+                    working_dir: self.working_dir.clone(),
+                    schedule_id: None,
+                    execution_mode: None,
+                    max_turns: None,
+                    profile: self.agent.profile().await,
+                }),
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to start completion: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+
+        let mut all_messages = self.messages.clone();
+        let mut texts = Vec::new();
+        while let Some(event) = stream.next().await {
+            let message = match event {
+                Ok(AgentEvent::Message(message)) => message,
+                Ok(_) => continue,
+                Err(e) => {
+                    tracing::error!("Error during completion: {}", e);
+                    return Err(StatusCode::INTERNAL_SERVER_ERROR);
+                }
+            };
+            for content in &message.content {
+                match content {
+                    MessageContent::ToolConfirmationRequest(request) => {
+                        self.agent
+                            .handle_confirmation(
+                                request.id.clone(),
+                                PermissionConfirmation {
+                                    principal_type: PrincipalType::Tool,
+                                    permission: Permission::DenyOnce,
+                                },
+                            )
+                            .await;
+                    }
+                    MessageContent::Text(text) if message.role == Role::Assistant => {
+                        if let Some(tx) = tx {
+                            // Separate the parts like the joined non-streaming content
+                            let content = if texts.is_empty() {
+                                text.text.clone()
+                            } else {
+                                format!("{}{}", TEXT_SEPARATOR, text.text)
+                            };
+                            let delta = ChunkDelta {
+                                content: Some(content),
+                                ..Default::default()
+                            };
+                            let _ = tx.send(self.chunk(Some(delta), None, None)).await;
+                        }
+                        texts.push(text.text.clone());
+                    }
+                    _ => {}
+                }
+            }
+            all_messages.push(message);
+        }
+
+        let usage = completion_usage(&session_path);
+        tokio::spawn(async move {
+            if let Err(e) = session::persist_messages(&session_path, &all_messages, provider).await
+            {
+                tracing::error!("Failed to store completion history: {:?}", e);
+            }
+        });
+
+        Ok((texts.join(TEXT_SEPARATOR), usage))
+    }
+
+    /// An SSE event with one streamed chunk, usage-only chunks have no delta
+    fn chunk(
+        &self,
+        delta: Option<ChunkDelta>,
+        finish_reason: Option<&str>,
+        usage: Option<CompletionUsage>,
+    ) -> String {
+        let chunk = ChatCompletionChunk {
+            id: &self.id,
+            object: "chat.completion.chunk",
+            created: self.created,
+            model: &self.model,
+            choices: delta
+                .map(|delta| ChunkChoice {
+                    index: 0,
+                    delta,
+                    finish_reason: finish_reason.map(str::to_string),
+                })
+                .into_iter()
+                .collect(),
+            usage,
+        };
+        format!("data: {}\n\n", serde_json::to_string(&chunk).unwrap())
+    }
+}
+
+async fn chat_completions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ChatCompletionRequest>,
+) -> Result<Response, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::Reply)?;
+
+    let target = resolve_model(&request.model).ok_or(StatusCode::NOT_FOUND)?;
+    let extensions = target_extensions(&target)?;
+    complete(&state, &identity, request, &target, extensions).await
+}
+
+/// Answer a completion request with an agent running `extensions`
+async fn complete(
+    state: &AppState,
+    identity: &Identity,
+    request: ChatCompletionRequest,
+    target: &ModelTarget,
+    extensions: Vec<ExtensionConfig>,
+) -> Result<Response, StatusCode> {
+    let (system, messages) = convert_messages(&request.messages)?;
+
+    let working_dir = std::env::current_dir().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let session_id = format!(
+        "openai_{}_{:08x}",
+        session::generate_session_id(),
+        rand::random::<u32>()
+    );
+    claim_session_access(identity, &session_id, &working_dir.to_string_lossy())?;
+
+    let completion = Completion {
+        id: format!("chatcmpl-{}", session_id),
+        created: Utc::now().timestamp(),
+        model: request.model,
+        agent: build_agent(state, target, extensions, system).await?,
+        messages,
+        session_id,
+        working_dir,
+    };
+
+    if !request.stream {
+        let (text, usage) = completion.run(None).await?;
+        return Ok(Json(ChatCompletion {
+            id: completion.id,
+            object: "chat.completion".to_string(),
+            created: completion.created,
+            model: completion.model,
+            choices: vec![CompletionChoice {
+                index: 0,
+                message: ChatCompletionMessage {
+                    role: "assistant".to_string(),
+                    content: Value::String(text),
+                },
+                finish_reason: "stop".to_string(),
+            }],
+            usage,
+        })
+        .into_response());
+    }
+
+    let include_usage = request.stream_options.is_some_and(|o| o.include_usage);
+    let (tx, rx) = mpsc::channel(100);
+    tokio::spawn(async move {
+        let role = ChunkDelta {
+            role: Some("assistant"),
+            ..Default::default()
+        };
+        let _ = tx.send(completion.chunk(Some(role), None, None)).await;
+
+        let (finish_reason, usage) = match completion.run(Some(&tx)).await {
+            Ok((_, usage)) => ("stop", usage),
+            Err(_) => ("error", CompletionUsage::default()),
+        };
+        let done = completion.chunk(Some(ChunkDelta::default()), Some(finish_reason), None);
+        let _ = tx.send(done).await;
+        if include_usage {
+            let _ = tx.send(completion.chunk(None, None, Some(usage))).await;
+        }
+        let _ = tx.send("data: [DONE]\n\n".to_string()).await;
+    });
+
+    Ok(SseResponse::new(ReceiverStream::new(rx)).into_response())
+}
+
+async fn list_models(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ModelList>, StatusCode> {
+    authorize(&headers, &state, Scope::Reply)?;
+
+    let created = Utc::now().timestamp();
+    let model = |id: String| ModelInfo {
+        id,
+        object: "model",
+        created,
+        owned_by: "goose",
+    };
+
+    let mut data = vec![model(DEFAULT_MODEL.to_string())];
+    let mut profiles: Vec<String> = ProfileManager::get_all()
+        .map(|profiles| profiles.into_keys().collect())
+        .unwrap_or_default();
+    profiles.sort();
+    data.extend(
+        profiles
+            .into_iter()
+            .map(|name| model(format!("{}{}", PROFILE_PREFIX, name))),
+    );
+    data.extend(
+        list_recipes()
+            .into_iter()
+            .map(|(name, _)| model(format!("{}{}", RECIPE_PREFIX, name))),
+    );
+
+    Ok(Json(ModelList {
+        object: "list",
+        data,
+    }))
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/models", get(list_models))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use goose::providers::{
+        base::{ProviderUsage, Usage},
+        errors::ProviderError,
+    };
+    use mcp_core::tool::Tool;
+    use serde_json::json;
+    use serial_test::serial;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    #[derive(Clone)]
+    struct MockProvider {
+        model_config: ModelConfig,
+    }
+
+    #[async_trait::async_trait]
+    impl Provider for MockProvider {
+        fn metadata() -> goose::providers::base::ProviderMetadata {
+            goose::providers::base::ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            self.model_config.clone()
+        }
+
+        async fn complete(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> anyhow::Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text("Mock").with_text("response"),
+                ProviderUsage::new("mock".to_string(), Usage::new(Some(12), Some(3), Some(15))),
+            ))
+        }
+    }
+
+    async fn state() -> Arc<AppState> {
+        let agent = Agent::new();
+        let _ = agent
+            .update_provider(Arc::new(MockProvider {
+                model_config: ModelConfig::new("test-model".to_string()),
+            }))
+            .await;
+        AppState::new(Arc::new(agent), "test-secret".to_string()).await
+    }
+
+    /// Complete a request for the default model without any extensions and read the response,
+    /// keeping the session it records in a temporary data directory
+    async fn completion(stream: bool) -> String {
+        let data_dir = TempDir::new().unwrap();
+        temp_env::async_with_vars(
+            [("XDG_DATA_HOME", Some(data_dir.path().as_os_str()))],
+            async {
+                let response = complete_default(stream).await;
+                assert_eq!(response.status(), StatusCode::OK);
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                String::from_utf8(body.to_vec()).unwrap()
+            },
+        )
+        .await
+    }
+
+    async fn complete_default(stream: bool) -> Response {
+        let request: ChatCompletionRequest = serde_json::from_value(json!({
+            "model": "goose",
+            "stream": stream,
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": [{"type": "text", "text": "hello"}]}
+            ]
+        }))
+        .unwrap();
+        let state = state().await;
+        complete(
+            &state,
+            &Identity::SecretKey,
+            request,
+            &ModelTarget::Default,
+            Vec::new(),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn test_convert_messages() {
+        let messages: Vec<ChatCompletionMessage> = serde_json::from_value(json!([
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": "hi"},
+            {"role": "assistant", "content": null, "tool_calls": []},
+            {"role": "assistant", "content": "hello"},
+            {"role": "user", "content": [{"type": "text", "text": "again"}]}
+        ]))
+        .unwrap();
+        let (system, conversation) = convert_messages(&messages).unwrap();
+        assert_eq!(system.as_deref(), Some("Be brief."));
+        assert_eq!(conversation.len(), 3);
+        assert_eq!(conversation[2].as_concat_text(), "again");
+
+        let ends_with_assistant: Vec<ChatCompletionMessage> =
+            serde_json::from_value(json!([{"role": "assistant", "content": "hi"}])).unwrap();
+        assert_eq!(
+            convert_messages(&ends_with_assistant).unwrap_err(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_chat_completion() {
+        let body = completion(false).await;
+        let completion: ChatCompletion = serde_json::from_str(&body).unwrap();
+        assert_eq!(completion.model, "goose");
+        assert_eq!(
+            completion.choices[0].message.content,
+            Value::String("Mock\nresponse".to_string())
+        );
+        assert_eq!(completion.usage.total_tokens, 15);
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_chat_completion_stream() {
+        let body = completion(true).await;
+        // The streamed parts add up to the content of a non-streaming completion
+        assert!(body.contains(r#""content":"Mock""#));
+        assert!(body.contains(r#""content":"\nresponse""#));
+        assert!(body.contains(r#""finish_reason":"stop""#));
+        assert!(body.ends_with("data: [DONE]\n\n"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_model() {
+        let request = Request::builder()
+            .uri("/v1/chat/completions")
+            .method("POST")
+            .header("content-type", "application/json")
+            .header("x-secret-key", "test-secret")
+            .body(Body::from(
+                json!({"model": "recipe/missing", "messages": [{"role": "user", "content": "hi"}]})
+                    .to_string(),
+            ))
+            .unwrap();
+        let response = routes(state().await).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,10 +1,18 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
 use goose::message::Message;
-use goose::recipe::Recipe;
+use goose::recipe::{validate_parameter_values, ParameterValueError, Recipe, RecipeParameter};
 use serde::{Deserialize, Serialize};
 
+use super::utils::{agent_error_status, authorize, authorize_session, session_id};
+use crate::auth::Scope;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -37,16 +45,29 @@ pub struct CreateRecipeResponse {
 /// Create a Recipe configuration from the current state of an agent
 async fn create_recipe(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(request): Json<CreateRecipeRequest>,
 ) -> Result<Json<CreateRecipeResponse>, (StatusCode, Json<CreateRecipeResponse>)> {
+    authorize_session(&headers, &state, Scope::Reply)
+        .await
+        .map_err(|status| {
+            (
+                status,
+                Json(CreateRecipeResponse {
+                    recipe: None,
+                    error: Some("Unauthorized".to_string()),
+                }),
+            )
+        })?;
+
     let error_response = CreateRecipeResponse {
         recipe: None,
         error: Some("Missing agent".to_string()),
     };
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| (StatusCode::PRECONDITION_FAILED, Json(error_response)))?;
+        .map_err(|e| (agent_error_status(e), Json(error_response)))?;
 
     // Create base recipe from agent state and messages
     let recipe_result = agent.create_recipe(request.messages).await;
@@ -84,8 +105,103 @@ async fn create_recipe(
     }
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct ValidateParametersRequest {
+    parameters: Vec<RecipeParameter>,
+    #[serde(default)]
+    values: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValidateParametersResponse {
+    valid: bool,
+    errors: Vec<ParameterValueError>,
+}
+
+/// Check recipe parameter definitions and the values given for them before the recipe is rendered
+async fn validate_parameters(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<ValidateParametersRequest>,
+) -> Result<Json<ValidateParametersResponse>, StatusCode> {
+    authorize(&headers, &state, Scope::Reply)?;
+
+    let mut errors: Vec<ParameterValueError> = request
+        .parameters
+        .iter()
+        .filter_map(|parameter| {
+            parameter
+                .validate_definition()
+                .err()
+                .map(|message| ParameterValueError {
+                    key: parameter.key.clone(),
+                    message,
+                })
+        })
+        .collect();
+    errors.extend(validate_parameter_values(
+        &request.parameters,
+        &request.values,
+    ));
+    Ok(Json(ValidateParametersResponse {
+        valid: errors.is_empty(),
+        errors,
+    }))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/recipe/create", post(create_recipe))
+        .route("/recipe/parameters/validate", post(validate_parameters))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::agents::Agent;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_validate_parameters() {
+        let request: ValidateParametersRequest = serde_json::from_value(json!({
+            "parameters": [
+                {
+                    "key": "env",
+                    "input_type": "select",
+                    "requirement": "required",
+                    "description": "Target environment",
+                    "options": ["staging", "production"]
+                },
+                {
+                    "key": "retries",
+                    "input_type": "number",
+                    "requirement": "optional",
+                    "description": "Retries",
+                    "default": "9",
+                    "max": 5
+                }
+            ],
+            "values": {"env": "dev", "retries": "2"}
+        }))
+        .unwrap();
+
+        let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+        let unauthorized = validate_parameters(
+            State(state.clone()),
+            HeaderMap::new(),
+            Json(request.clone()),
+        )
+        .await;
+        assert_eq!(unauthorized.unwrap_err(), StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Secret-Key", "test-secret".parse().unwrap());
+        let Json(response) = validate_parameters(State(state), headers, Json(request))
+            .await
+            .unwrap();
+        assert!(!response.valid);
+        let keys: Vec<_> = response.errors.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, vec!["retries", "env"]);
+    }
+}
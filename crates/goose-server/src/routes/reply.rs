@@ -1,4 +1,5 @@
-use super::utils::verify_secret_key;
+use super::utils::{agent_error_status, authorize_session, claim_session_access, session_id};
+use crate::auth::Scope;
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -8,11 +9,13 @@ use axum::{
     Json, Router,
 };
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use futures::{stream::StreamExt, Stream};
 use goose::{
     agents::{AgentEvent, SessionConfig},
     message::{Message, MessageContent},
     permission::permission_confirmation::PrincipalType,
+    tool_monitor::LoopDetection,
 };
 use goose::{
     permission::{Permission, PermissionConfirmation},
@@ -48,7 +51,7 @@ pub struct SseResponse {
 }
 
 impl SseResponse {
-    fn new(rx: ReceiverStream<String>) -> Self {
+    pub fn new(rx: ReceiverStream<String>) -> Self {
         Self { rx }
     }
 }
@@ -97,6 +100,12 @@ enum MessageEvent {
         request_id: String,
         message: JsonRpcMessage,
     },
+    ToolCallTiming {
+        request_id: String,
+        started_at: DateTime<Utc>,
+        duration_ms: Option<u64>,
+    },
+    LoopDetected(LoopDetection),
 }
 
 async fn stream_event(
@@ -112,12 +121,28 @@ async fn stream_event(
     tx.send(format!("data: {}\n\n", json)).await
 }
 
+/// The session a reply is stored in and the session agent it uses. Only the `X-Session-Id`
+/// header selects a session agent, a session id in the body alone uses the shared agent.
+fn reply_session(
+    headers: &HeaderMap,
+    body_session_id: Option<String>,
+) -> Result<(String, Option<String>), StatusCode> {
+    let agent_session = session_id(headers).map(str::to_string);
+    let session_id = match (body_session_id, &agent_session) {
+        (Some(id), Some(header)) if &id != header => return Err(StatusCode::BAD_REQUEST),
+        (Some(id), _) => id,
+        (None, Some(header)) => header.clone(),
+        (None, None) => session::generate_session_id(),
+    };
+    Ok((session_id, agent_session))
+}
+
 async fn handler(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
     Json(request): Json<ChatRequest>,
 ) -> Result<SseResponse, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    let identity = authorize_session(&headers, &state, Scope::Reply).await?;
 
     let (tx, rx) = mpsc::channel(100);
     let stream = ReceiverStream::new(rx);
@@ -125,54 +150,31 @@ async fn handler(
     let messages = request.messages;
     let session_working_dir = request.session_working_dir;
 
-    let session_id = request
-        .session_id
-        .unwrap_or_else(session::generate_session_id);
+    let (session_id, agent_session) = reply_session(&headers, request.session_id)?;
+    claim_session_access(&identity, &session_id, &session_working_dir)?;
+    let agent = state
+        .get_session_agent(agent_session.as_deref())
+        .await
+        .map_err(agent_error_status)?;
 
     tokio::spawn(async move {
-        let agent = state.get_agent().await;
-        let agent = match agent {
-            Ok(agent) => {
-                let provider = agent.provider().await;
-                match provider {
-                    Ok(_) => agent,
-                    Err(_) => {
-                        let _ = stream_event(
-                            MessageEvent::Error {
-                                error: "No provider configured".to_string(),
-                            },
-                            &tx,
-                        )
-                        .await;
-                        let _ = stream_event(
-                            MessageEvent::Finish {
-                                reason: "error".to_string(),
-                            },
-                            &tx,
-                        )
-                        .await;
-                        return;
-                    }
-                }
-            }
-            Err(_) => {
-                let _ = stream_event(
-                    MessageEvent::Error {
-                        error: "No agent configured".to_string(),
-                    },
-                    &tx,
-                )
-                .await;
-                let _ = stream_event(
-                    MessageEvent::Finish {
-                        reason: "error".to_string(),
-                    },
-                    &tx,
-                )
-                .await;
-                return;
-            }
-        };
+        if agent.provider().await.is_err() {
+            let _ = stream_event(
+                MessageEvent::Error {
+                    error: "No provider configured".to_string(),
+                },
+                &tx,
+            )
+            .await;
+            let _ = stream_event(
+                MessageEvent::Finish {
+                    reason: "error".to_string(),
+                },
+                &tx,
+            )
+            .await;
+            return;
+        }
 
         let provider = agent.provider().await;
 
@@ -185,6 +187,7 @@ async fn handler(
                     schedule_id: request.scheduled_job_id.clone(),
                     execution_mode: None,
                     max_turns: None,
+                    profile: agent.profile().await,
                 }),
             )
             .await
@@ -226,6 +229,7 @@ async fn handler(
             }
         };
 
+        let mut finish_reason = "stop";
         loop {
             tokio::select! {
                 response = timeout(Duration::from_millis(500), stream.next()) => {
@@ -264,6 +268,31 @@ async fn handler(
                                 ).await;
                             }
                         }
+                        Ok(Some(Ok(AgentEvent::LoopDetected(detection)))) => {
+                            if let Err(e) = stream_event(MessageEvent::LoopDetected(detection), &tx).await {
+                                tracing::error!("Error sending loop detection through channel: {}", e);
+                                let _ = stream_event(
+                                    MessageEvent::Error {
+                                        error: e.to_string(),
+                                    },
+                                    &tx,
+                                ).await;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::ToolCallTiming { request_id, started_at, duration_ms }))) => {
+                            if let Err(e) = stream_event(MessageEvent::ToolCallTiming { request_id, started_at, duration_ms }, &tx).await {
+                                tracing::error!("Error sending tool call timing through channel: {}", e);
+                                let _ = stream_event(
+                                    MessageEvent::Error {
+                                        error: e.to_string(),
+                                    },
+                                    &tx,
+                                ).await;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::Stopped(reason)))) => {
+                            finish_reason = reason.name();
+                        }
                         Ok(Some(Ok(AgentEvent::McpNotification((request_id, n))))) => {
                             if let Err(e) = stream_event(MessageEvent::Notification{
                                 request_id: request_id.clone(),
@@ -305,7 +334,7 @@ async fn handler(
 
         let _ = stream_event(
             MessageEvent::Finish {
-                reason: "stop".to_string(),
+                reason: finish_reason.to_string(),
             },
             &tx,
         )
@@ -333,18 +362,17 @@ async fn ask_handler(
     headers: HeaderMap,
     Json(request): Json<AskRequest>,
 ) -> Result<Json<AskResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    let identity = authorize_session(&headers, &state, Scope::Reply).await?;
 
     let session_working_dir = request.session_working_dir;
 
-    let session_id = request
-        .session_id
-        .unwrap_or_else(session::generate_session_id);
+    let (session_id, agent_session) = reply_session(&headers, request.session_id)?;
+    claim_session_access(&identity, &session_id, &session_working_dir)?;
 
     let agent = state
-        .get_agent()
+        .get_session_agent(agent_session.as_deref())
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
 
     let provider = agent.provider().await;
 
@@ -360,6 +388,7 @@ async fn ask_handler(
                 schedule_id: request.scheduled_job_id.clone(),
                 execution_mode: None,
                 max_turns: None,
+                profile: agent.profile().await,
             }),
         )
         .await
@@ -395,6 +424,15 @@ async fn ask_handler(
                 // Handle notifications if needed
                 tracing::info!("Received notification: {:?}", n);
             }
+            Ok(AgentEvent::LoopDetected(detection)) => {
+                tracing::warn!("Loop detected: {}", detection.message);
+            }
+            Ok(AgentEvent::ToolCallTiming { .. }) => {
+                // Tool timings only matter to streaming clients
+            }
+            Ok(AgentEvent::Stopped(reason)) => {
+                tracing::info!("Reply stopped early: {:?}", reason);
+            }
 
             Err(e) => {
                 tracing::error!("Error processing as_ai message: {}", e);
@@ -458,12 +496,12 @@ pub async fn confirm_permission(
     headers: HeaderMap,
     Json(request): Json<PermissionConfirmationRequest>,
 ) -> Result<Json<Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
 
     let permission = match request.action.as_str() {
         "always_allow" => Permission::AlwaysAllow,
@@ -495,7 +533,7 @@ async fn submit_tool_result(
     headers: HeaderMap,
     raw: axum::extract::Json<serde_json::Value>,
 ) -> Result<Json<Value>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize_session(&headers, &state, Scope::Reply).await?;
 
     tracing::info!(
         "Received tool result request: {}",
@@ -515,9 +553,9 @@ async fn submit_tool_result(
     };
 
     let agent = state
-        .get_agent()
+        .get_session_agent(session_id(&headers))
         .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+        .map_err(agent_error_status)?;
     agent.handle_tool_result(payload.id, payload.result).await;
     Ok(Json(json!({"status": "ok"})))
 }
@@ -617,5 +655,32 @@ mod tests {
 
             assert_eq!(response.status(), StatusCode::OK);
         }
+
+        #[tokio::test]
+        async fn test_unknown_session_agent_is_not_found() {
+            let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/ask")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .header("x-session-id", "evicted-session")
+                .body(Body::from(
+                    serde_json::to_string(&AskRequest {
+                        prompt: "test prompt".to_string(),
+                        session_id: None,
+                        session_working_dir: "test-working-dir".to_string(),
+                        scheduled_job_id: None,
+                    })
+                    .unwrap(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
     }
 }
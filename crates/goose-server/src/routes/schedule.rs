@@ -10,7 +10,8 @@ use serde::{Deserialize, Serialize};
 
 use chrono::NaiveDateTime;
 
-use crate::routes::utils::verify_secret_key;
+use crate::auth::Scope;
+use crate::routes::utils::authorize;
 use crate::state::AppState;
 use goose::scheduler::ScheduledJob;
 
@@ -107,7 +108,7 @@ async fn create_schedule(
     headers: HeaderMap,
     Json(req): Json<CreateScheduleRequest>,
 ) -> Result<Json<ScheduledJob>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -158,7 +159,7 @@ async fn list_schedules(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<ListSchedulesResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -191,7 +192,7 @@ async fn delete_schedule(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<StatusCode, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -225,7 +226,7 @@ async fn run_now_handler(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<Json<RunNowResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -276,7 +277,7 @@ async fn sessions_handler(
     Path(schedule_id_param): Path<String>, // Renamed to avoid confusion with session_id
     Query(query_params): Query<SessionsQuery>,
 ) -> Result<Json<Vec<SessionDisplayInfo>>, StatusCode> {
-    verify_secret_key(&headers, &state)?; // Added this line
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -337,7 +338,7 @@ async fn pause_schedule(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<StatusCode, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -373,7 +374,7 @@ async fn unpause_schedule(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<StatusCode, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -411,7 +412,7 @@ async fn update_schedule(
     Path(id): Path<String>,
     Json(req): Json<UpdateScheduleRequest>,
 ) -> Result<Json<ScheduledJob>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -457,7 +458,7 @@ pub async fn kill_running_job(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<Json<KillJobResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
@@ -496,7 +497,7 @@ pub async fn inspect_running_job(
     headers: HeaderMap,
     Path(id): Path<String>,
 ) -> Result<Json<InspectJobResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    authorize(&headers, &state, Scope::ManageSchedules)?;
     let scheduler = state
         .scheduler()
         .await
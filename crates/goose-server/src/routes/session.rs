@@ -1,18 +1,21 @@
-use super::utils::verify_secret_key;
+use super::utils::{agent_error_status, authorize, authorize_session, check_session_access};
 use std::sync::Arc;
 
+use crate::auth::{Identity, Scope};
 use crate::state::AppState;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use goose::message::Message;
 use goose::session;
+use goose::session::branches::BranchInfo;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
+use goose::session::search::{parse_date_filter, SearchQuery, SearchResult};
 use goose::session::SessionMetadata;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 #[derive(Serialize, ToSchema)]
@@ -22,6 +25,30 @@ pub struct SessionListResponse {
     sessions: Vec<SessionInfo>,
 }
 
+#[derive(Deserialize, ToSchema, utoipa::IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchQuery {
+    /// Words that all have to appear in a session, case-insensitive
+    q: String,
+    /// Only sessions modified on or after this date, YYYY-MM-DD or RFC 3339
+    since: Option<String>,
+    /// Only sessions modified on or before this date, YYYY-MM-DD or RFC 3339
+    until: Option<String>,
+    /// Only sessions run in this directory or below it
+    working_dir: Option<String>,
+    /// Only sessions started by this schedule
+    schedule_id: Option<String>,
+    /// Maximum number of sessions returned
+    limit: Option<usize>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchResponse {
+    /// Matching sessions, newest first
+    results: Vec<SearchResult>,
+}
+
 #[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SessionHistoryResponse {
@@ -33,6 +60,53 @@ pub struct SessionHistoryResponse {
     messages: Vec<Message>,
 }
 
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchListResponse {
+    /// Identifier of the branch whose messages are in the session history
+    active: String,
+    /// All branches of the session
+    branches: Vec<BranchInfo>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RewindRequest {
+    /// Number of messages to keep, the conversation resumes from this message
+    message_index: usize,
+    /// Keep the later messages as a new branch instead of discarding them
+    #[serde(default = "default_fork")]
+    fork: bool,
+    /// Undo the text_editor edits made after the rewind point
+    #[serde(default)]
+    restore_files: bool,
+}
+
+fn default_fork() -> bool {
+    true
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RewindResponse {
+    /// Messages remaining on the active branch
+    messages: Vec<Message>,
+    /// Branch holding the later messages, if they were forked
+    branch: Option<String>,
+    /// Files whose edits were undone, once per edit
+    restored_files: Vec<String>,
+}
+
+fn session_path(session_id: &str, identity: &Identity) -> Result<std::path::PathBuf, StatusCode> {
+    let path = session::get_path(session::Identifier::Name(session_id.to_string()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session::session_exists(&path) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    check_session_access(identity, &path)?;
+    Ok(path)
+}
+
 #[utoipa::path(
     get,
     path = "/sessions",
@@ -51,14 +125,71 @@ async fn list_sessions(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<SessionListResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
 
     let sessions = get_valid_sorted_sessions(SortOrder::Descending)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .into_iter()
+        .filter(|info| session::can_access(&info.metadata, identity.user()))
+        .collect();
 
     Ok(Json(SessionListResponse { sessions }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/search",
+    params(SessionSearchQuery),
+    responses(
+        (status = 200, description = "Sessions matching the search", body = SessionSearchResponse),
+        (status = 400, description = "Empty query or invalid date"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Search the messages, tool calls and working directories of all sessions
+async fn search_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SessionSearchQuery>,
+) -> Result<Json<SessionSearchResponse>, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
+
+    if query.q.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let parse_date = |value: Option<String>, end_of_day| {
+        value
+            .map(|value| parse_date_filter(&value, end_of_day))
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)
+    };
+    // Sessions the caller may not see are dropped before the limit is applied
+    let search_query = SearchQuery {
+        text: query.q,
+        since: parse_date(query.since, false)?,
+        until: parse_date(query.until, true)?,
+        working_dir: query.working_dir.map(std::path::PathBuf::from),
+        schedule_id: query.schedule_id,
+        limit: None,
+    };
+    let results = session::search::search_sessions(&search_query)
+        .map_err(|e| {
+            tracing::error!("Failed to search sessions: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .into_iter()
+        .filter(|result| session::can_access(&result.metadata, identity.user()))
+        .take(query.limit.unwrap_or(usize::MAX))
+        .collect();
+
+    Ok(Json(SessionSearchResponse { results }))
+}
+
 #[utoipa::path(
     get,
     path = "/sessions/{session_id}",
@@ -82,12 +213,13 @@ async fn get_session_history(
     headers: HeaderMap,
     Path(session_id): Path<String>,
 ) -> Result<Json<SessionHistoryResponse>, StatusCode> {
-    verify_secret_key(&headers, &state)?;
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
 
     let session_path = match session::get_path(session::Identifier::Name(session_id.clone())) {
         Ok(path) => path,
         Err(_) => return Err(StatusCode::BAD_REQUEST),
     };
+    check_session_access(&identity, &session_path)?;
 
     let metadata = session::read_metadata(&session_path).map_err(|_| StatusCode::NOT_FOUND)?;
 
@@ -106,10 +238,153 @@ async fn get_session_history(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/sessions/{session_id}/branches",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    responses(
+        (status = 200, description = "Session branches retrieved successfully", body = BranchListResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// List the branches of a session
+async fn list_branches(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<Json<BranchListResponse>, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::ReadSessions)?;
+
+    let session_path = session_path(&session_id, &identity)?;
+    let branches = session::branches::list_branches(&session_path).map_err(|e| {
+        tracing::error!("Failed to list session branches: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+    let active = branches
+        .iter()
+        .find(|branch| branch.active)
+        .map(|branch| branch.id.clone())
+        .unwrap_or_else(|| session::branches::MAIN_BRANCH.to_string());
+
+    Ok(Json(BranchListResponse { active, branches }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/rewind",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session")
+    ),
+    request_body = RewindRequest,
+    responses(
+        (status = 200, description = "Session rewound successfully", body = RewindResponse),
+        (status = 400, description = "Invalid rewind point"),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session not found"),
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Rewind a session to an earlier message
+async fn rewind_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    Json(request): Json<RewindRequest>,
+) -> Result<Json<RewindResponse>, StatusCode> {
+    let identity = authorize_session(&headers, &state, Scope::Reply).await?;
+
+    let session_path = session_path(&session_id, &identity)?;
+    // Look the agent up first so a missing session agent fails before anything is rewound
+    let agent = if request.restore_files {
+        Some(
+            state
+                .get_session_agent(super::utils::session_id(&headers))
+                .await
+                .map_err(agent_error_status)?,
+        )
+    } else {
+        None
+    };
+    let rewind = session::branches::rewind(&session_path, request.message_index, request.fork)
+        .map_err(|e| {
+            tracing::warn!("Failed to rewind session: {:?}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+
+    let restored_files = match agent {
+        Some(agent) => agent.undo_file_edits(&rewind.discarded).await,
+        None => Vec::new(),
+    };
+
+    Ok(Json(RewindResponse {
+        messages: rewind.messages,
+        branch: rewind.branch,
+        restored_files,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/sessions/{session_id}/branches/{branch_id}",
+    params(
+        ("session_id" = String, Path, description = "Unique identifier for the session"),
+        ("branch_id" = String, Path, description = "Branch to make active")
+    ),
+    responses(
+        (status = 200, description = "Switched branch successfully", body = SessionHistoryResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 404, description = "Session or branch not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Make another branch the active one
+async fn switch_branch(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path((session_id, branch_id)): Path<(String, String)>,
+) -> Result<Json<SessionHistoryResponse>, StatusCode> {
+    let identity = authorize(&headers, &state, Scope::Reply)?;
+
+    let session_path = session_path(&session_id, &identity)?;
+    let messages = session::branches::switch_branch(&session_path, &branch_id).map_err(|e| {
+        tracing::warn!("Failed to switch session branch: {:?}", e);
+        StatusCode::NOT_FOUND
+    })?;
+    let metadata = session::read_metadata(&session_path).map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(SessionHistoryResponse {
+        session_id,
+        metadata,
+        messages,
+    }))
+}
+
 // Configure routes for this module
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
+        .route("/sessions/search", get(search_sessions))
         .route("/sessions/{session_id}", get(get_session_history))
+        .route("/sessions/{session_id}/branches", get(list_branches))
+        .route(
+            "/sessions/{session_id}/branches/{branch_id}",
+            post(switch_branch),
+        )
+        .route("/sessions/{session_id}/rewind", post(rewind_session))
         .with_state(state)
 }
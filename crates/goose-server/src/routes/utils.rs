@@ -1,10 +1,13 @@
-use crate::state::AppState;
+use crate::auth::{Identity, Scope};
+use crate::state::{AppState, SessionError};
 use goose::config::Config;
 use goose::providers::base::{ConfigKey, ProviderMetadata};
+use goose::session;
 use http::{HeaderMap, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::error::Error;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum KeyLocation {
@@ -23,18 +26,103 @@ pub struct KeyInfo {
     pub value: Option<String>, // Only populated for non-secret keys that are set
 }
 
-pub fn verify_secret_key(headers: &HeaderMap, state: &AppState) -> Result<StatusCode, StatusCode> {
-    // Verify secret key
-    let secret_key = headers
-        .get("X-Secret-Key")
+/// Authenticate a request and check it may use `scope`
+pub fn authorize(
+    headers: &HeaderMap,
+    state: &AppState,
+    scope: Scope,
+) -> Result<Identity, StatusCode> {
+    state.auth.authorize(headers, scope)
+}
+
+/// Header selecting which session's agent a request is for
+pub const SESSION_ID_HEADER: &str = "X-Session-Id";
+
+/// The session a request targets, secret key requests without one use the shared agent
+pub fn session_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SESSION_ID_HEADER)
         .and_then(|value| value.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+        .filter(|value| !value.is_empty())
+}
+
+/// Authenticate a request for `scope` and check the caller may use the session named by its
+/// `X-Session-Id` header, both its session file and its agent. Only the secret key may fall back
+/// to the shared agent, token callers must name a session
+pub async fn authorize_session(
+    headers: &HeaderMap,
+    state: &AppState,
+    scope: Scope,
+) -> Result<Identity, StatusCode> {
+    let identity = authorize(headers, state, scope)?;
+    if let Some(session_id) = session_id(headers) {
+        check_session_id_access(&identity, session_id)?;
+        if !state
+            .may_use_session_agent(session_id, identity.user())
+            .await
+        {
+            return Err(StatusCode::NOT_FOUND);
+        }
+    } else if identity.user().is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(identity)
+}
+
+/// Status for a failed agent lookup, a named session without an agent is not found
+pub fn agent_error_status(error: anyhow::Error) -> StatusCode {
+    match error.downcast_ref::<SessionError>() {
+        Some(SessionError::NotFound(_)) => StatusCode::NOT_FOUND,
+        _ => StatusCode::PRECONDITION_FAILED,
+    }
+}
 
-    if secret_key != state.secret_key {
-        Err(StatusCode::UNAUTHORIZED)
+/// Check the caller may see a session, sessions of other token users look missing
+pub fn check_session_access(identity: &Identity, session_path: &Path) -> Result<(), StatusCode> {
+    let Some(user) = identity.user() else {
+        return Ok(());
+    };
+    let metadata = session::read_metadata(session_path).map_err(|_| StatusCode::NOT_FOUND)?;
+    if session::can_access(&metadata, Some(user)) {
+        Ok(())
     } else {
-        Ok(StatusCode::OK)
+        Err(StatusCode::NOT_FOUND)
+    }
+}
+
+/// Check the caller may use a session by id, sessions nobody has started yet are open to all
+pub fn check_session_id_access(identity: &Identity, session_id: &str) -> Result<(), StatusCode> {
+    if identity.user().is_none() {
+        return Ok(());
+    }
+    let session_path = session::get_path(session::Identifier::Name(session_id.to_string()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session::session_exists(&session_path) {
+        return Ok(());
+    }
+    check_session_access(identity, &session_path)
+}
+
+/// Make a token user the owner of a session they start, or check they may use an existing one
+pub fn claim_session_access(
+    identity: &Identity,
+    session_id: &str,
+    working_dir: &str,
+) -> Result<(), StatusCode> {
+    let Some(user) = identity.user() else {
+        return Ok(());
+    };
+    let session_path = session::get_path(session::Identifier::Name(session_id.to_string()))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    if !session::session_exists(&session_path) {
+        return session::claim_session(&session_path, user, PathBuf::from(working_dir)).map_err(
+            |e| {
+                tracing::error!("Failed to create session: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            },
+        );
     }
+    check_session_access(identity, &session_path)
 }
 
 /// Inspects a configuration key to determine if it's set, its location, and value (for non-secret keys)
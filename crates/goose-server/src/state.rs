@@ -1,32 +1,234 @@
+use crate::auth::Authenticator;
 use goose::agents::Agent;
+use goose::config::Config;
 use goose::scheduler_trait::SchedulerTrait;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
 use tokio::sync::Mutex;
 
 pub type AgentRef = Arc<Agent>;
 
+/// Default number of sessions that can have their own agent at once
+pub const DEFAULT_MAX_SESSIONS: usize = 16;
+/// Default time in seconds after which an unused session agent is dropped
+pub const DEFAULT_SESSION_IDLE_TIMEOUT: u64 = 3600;
+
+/// Limits on the agents the server keeps for individual sessions
+#[derive(Debug, Clone, Copy)]
+pub struct SessionLimits {
+    pub max_sessions: usize,
+    pub idle_timeout: Duration,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        Self {
+            max_sessions: DEFAULT_MAX_SESSIONS,
+            idle_timeout: Duration::from_secs(DEFAULT_SESSION_IDLE_TIMEOUT),
+        }
+    }
+}
+
+impl SessionLimits {
+    /// Read the limits from `GOOSE_SERVER_MAX_SESSIONS` and `GOOSE_SERVER_SESSION_IDLE_TIMEOUT`
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        let defaults = Self::default();
+        Self {
+            max_sessions: config
+                .get_param("GOOSE_SERVER_MAX_SESSIONS")
+                .unwrap_or(defaults.max_sessions),
+            idle_timeout: config
+                .get_param("GOOSE_SERVER_SESSION_IDLE_TIMEOUT")
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.idle_timeout),
+        }
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SessionError {
+    #[error("Session {0} already has an agent")]
+    AlreadyExists(String),
+    #[error("Too many active sessions, the limit is {0}")]
+    LimitReached(usize),
+    #[error("Session {0} has no agent")]
+    NotFound(String),
+}
+
+struct SessionAgent {
+    agent: AgentRef,
+    last_used: Instant,
+    /// Token user who created the session, None for the shared secret key
+    owner: Option<String>,
+}
+
+impl SessionAgent {
+    /// A session is idle when nothing but the map holds its agent, e.g. no reply is streaming
+    fn is_idle(&self, idle_timeout: Duration) -> bool {
+        Arc::strong_count(&self.agent) == 1 && self.last_used.elapsed() >= idle_timeout
+    }
+}
+
+/// A session with its own agent, as reported by the sessions routes
+#[derive(Debug, Clone)]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub idle: Duration,
+    pub owner: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct AppState {
     agent: Option<AgentRef>,
-    pub secret_key: String,
+    sessions: Arc<Mutex<HashMap<String, SessionAgent>>>,
+    limits: SessionLimits,
+    pub auth: Arc<Authenticator>,
     pub scheduler: Arc<Mutex<Option<Arc<dyn SchedulerTrait>>>>,
 }
 
 impl AppState {
     pub async fn new(agent: AgentRef, secret_key: String) -> Arc<AppState> {
+        Self::with_limits(agent, secret_key, SessionLimits::from_config()).await
+    }
+
+    pub async fn with_limits(
+        agent: AgentRef,
+        secret_key: String,
+        limits: SessionLimits,
+    ) -> Arc<AppState> {
         Arc::new(Self {
             agent: Some(agent.clone()),
-            secret_key,
+            sessions: Arc::new(Mutex::new(HashMap::new())),
+            limits,
+            auth: Arc::new(Authenticator::from_config(secret_key)),
             scheduler: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// The shared agent used by requests that are not tied to a session of their own
     pub async fn get_agent(&self) -> Result<Arc<Agent>, anyhow::Error> {
         self.agent
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Agent needs to be created first."))
     }
 
+    /// The agent of a session created through the sessions routes, or the shared agent when no
+    /// session is named. Naming a session without an agent, e.g. an evicted one, is an error
+    /// rather than a fallback to the shared agent.
+    pub async fn get_session_agent(
+        &self,
+        session_id: Option<&str>,
+    ) -> Result<Arc<Agent>, anyhow::Error> {
+        let Some(session_id) = session_id else {
+            return self.get_agent().await;
+        };
+        let mut sessions = self.sessions.lock().await;
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| SessionError::NotFound(session_id.to_string()))?;
+        session.last_used = Instant::now();
+        Ok(session.agent.clone())
+    }
+
+    /// Give a session its own agent, evicting idle sessions first if the limit is reached
+    pub async fn create_session(
+        &self,
+        session_id: String,
+        agent: AgentRef,
+        owner: Option<String>,
+    ) -> Result<(), SessionError> {
+        let mut sessions = self.sessions.lock().await;
+        if sessions.contains_key(&session_id) {
+            return Err(SessionError::AlreadyExists(session_id));
+        }
+        if sessions.len() >= self.limits.max_sessions {
+            sessions.retain(|_, session| !session.is_idle(self.limits.idle_timeout));
+            if sessions.len() >= self.limits.max_sessions {
+                return Err(SessionError::LimitReached(self.limits.max_sessions));
+            }
+        }
+
+        sessions.insert(
+            session_id,
+            SessionAgent {
+                agent,
+                last_used: Instant::now(),
+                owner,
+            },
+        );
+        Ok(())
+    }
+
+    /// Whether a user may use the agent of a session, the shared secret key (no user) may use
+    /// any and token users only the ones they created. Sessions without an agent are allowed,
+    /// looking up their agent fails instead.
+    pub async fn may_use_session_agent(&self, session_id: &str, user: Option<&str>) -> bool {
+        let Some(user) = user else {
+            return true;
+        };
+        match self.sessions.lock().await.get(session_id) {
+            Some(session) => session.owner.as_deref() == Some(user),
+            None => true,
+        }
+    }
+
+    /// Drop the agent of a session, returning it if the session had one
+    pub async fn remove_session(&self, session_id: &str) -> Option<AgentRef> {
+        self.sessions
+            .lock()
+            .await
+            .remove(session_id)
+            .map(|session| session.agent)
+    }
+
+    pub async fn active_sessions(&self) -> Vec<ActiveSession> {
+        let mut sessions: Vec<ActiveSession> = self
+            .sessions
+            .lock()
+            .await
+            .iter()
+            .map(|(session_id, session)| ActiveSession {
+                session_id: session_id.clone(),
+                idle: session.last_used.elapsed(),
+                owner: session.owner.clone(),
+            })
+            .collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
+    /// Drop the agents of sessions that have not been used for the idle timeout
+    pub async fn evict_idle_sessions(&self) -> Vec<String> {
+        let mut sessions = self.sessions.lock().await;
+        let idle: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| session.is_idle(self.limits.idle_timeout))
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in &idle {
+            sessions.remove(session_id);
+        }
+        idle
+    }
+
+    /// Periodically evict idle sessions for as long as the server runs
+    pub fn spawn_idle_eviction(self: &Arc<Self>) {
+        let state = Arc::clone(self);
+        let period = (state.limits.idle_timeout / 4).max(Duration::from_secs(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                for session_id in state.evict_idle_sessions().await {
+                    tracing::info!("Evicted idle session agent {}", session_id);
+                }
+            }
+        });
+    }
+
     pub async fn set_scheduler(&self, sched: Arc<dyn SchedulerTrait>) {
         let mut guard = self.scheduler.lock().await;
         *guard = Some(sched);
@@ -40,3 +242,113 @@ impl AppState {
             .ok_or_else(|| anyhow::anyhow!("Scheduler not initialized"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max_sessions: usize, idle_timeout: Duration) -> SessionLimits {
+        SessionLimits {
+            max_sessions,
+            idle_timeout,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_agents_are_separate() {
+        let shared = Arc::new(Agent::new());
+        let state = AppState::new(shared.clone(), "secret".to_string()).await;
+
+        let session_agent = Arc::new(Agent::new());
+        state
+            .create_session("a".to_string(), session_agent.clone(), None)
+            .await
+            .unwrap();
+
+        let agent = state.get_session_agent(Some("a")).await.unwrap();
+        assert!(Arc::ptr_eq(&agent, &session_agent));
+        let Err(missing) = state.get_session_agent(Some("b")).await else {
+            panic!("an unknown session must not fall back to the shared agent");
+        };
+        assert_eq!(
+            missing.downcast_ref::<SessionError>(),
+            Some(&SessionError::NotFound("b".to_string()))
+        );
+        let agent = state.get_session_agent(None).await.unwrap();
+        assert!(Arc::ptr_eq(&agent, &shared));
+
+        assert_eq!(
+            state
+                .create_session("a".to_string(), Arc::new(Agent::new()), None)
+                .await,
+            Err(SessionError::AlreadyExists("a".to_string()))
+        );
+
+        state
+            .create_session(
+                "c".to_string(),
+                Arc::new(Agent::new()),
+                Some("alice".to_string()),
+            )
+            .await
+            .unwrap();
+        assert!(state.may_use_session_agent("c", Some("alice")).await);
+        assert!(!state.may_use_session_agent("c", Some("bob")).await);
+        assert!(!state.may_use_session_agent("a", Some("alice")).await);
+        assert!(state.may_use_session_agent("c", None).await);
+
+        assert!(state.remove_session("a").await.is_some());
+        assert!(state.remove_session("a").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_limit() {
+        let state = AppState::with_limits(
+            Arc::new(Agent::new()),
+            "secret".to_string(),
+            limits(1, Duration::from_secs(3600)),
+        )
+        .await;
+
+        state
+            .create_session("a".to_string(), Arc::new(Agent::new()), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            state
+                .create_session("b".to_string(), Arc::new(Agent::new()), None)
+                .await,
+            Err(SessionError::LimitReached(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idle_sessions_are_evicted() {
+        let state = AppState::with_limits(
+            Arc::new(Agent::new()),
+            "secret".to_string(),
+            limits(1, Duration::ZERO),
+        )
+        .await;
+
+        state
+            .create_session("a".to_string(), Arc::new(Agent::new()), None)
+            .await
+            .unwrap();
+
+        // A session whose agent is in use is never evicted
+        let in_use = state.get_session_agent(Some("a")).await.unwrap();
+        assert!(state.evict_idle_sessions().await.is_empty());
+        drop(in_use);
+
+        // Reaching the limit makes room by evicting idle sessions
+        state
+            .create_session("b".to_string(), Arc::new(Agent::new()), None)
+            .await
+            .unwrap();
+        assert_eq!(state.active_sessions().await.len(), 1);
+
+        assert_eq!(state.evict_idle_sessions().await, vec!["b".to_string()]);
+        assert!(state.active_sessions().await.is_empty());
+    }
+}
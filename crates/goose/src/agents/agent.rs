@@ -1,19 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
 use futures::stream::BoxStream;
 use futures::{stream, FutureExt, Stream, StreamExt, TryStreamExt};
 use mcp_core::protocol::JsonRpcMessage;
+use mcp_core::role::Role;
 
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
 use crate::agents::sub_recipe_execution_tool::sub_recipe_execute_task_tool::{
     self, SUB_RECIPE_EXECUTE_TASK_TOOL_NAME,
 };
 use crate::agents::sub_recipe_manager::SubRecipeManager;
-use crate::config::{Config, ExtensionConfigManager, PermissionManager};
+use crate::config::{registry, Config, ExtensionConfigManager, PermissionManager};
 use crate::message::Message;
 use crate::permission::permission_judge::check_tool_permissions;
 use crate::permission::PermissionConfirmation;
@@ -21,7 +23,7 @@ use crate::providers::base::Provider;
 use crate::providers::errors::ProviderError;
 use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
 use crate::scheduler_trait::SchedulerTrait;
-use crate::tool_monitor::{ToolCall, ToolMonitor};
+use crate::tool_monitor::{LoopAction, LoopDetection, LoopDetectionConfig, ToolCall, ToolMonitor};
 use regex::Regex;
 use serde_json::Value;
 use tokio::sync::{mpsc, Mutex, RwLock};
@@ -29,6 +31,8 @@ use tracing::{debug, error, instrument};
 
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
+use crate::agents::hooks::{self, HookEvent, HookInput, HookOutcome, HookResult, HooksConfig};
+use crate::agents::observer;
 use crate::agents::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
     PLATFORM_MANAGE_SCHEDULE_TOOL_NAME, PLATFORM_READ_RESOURCE_TOOL_NAME,
@@ -54,7 +58,10 @@ use super::platform_tools;
 use super::router_tools;
 use super::subagent_manager::SubAgentManager;
 use super::subagent_tools;
-use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DECLINED_RESPONSE};
+use super::tool_execution::{
+    schedule_tool_streams, ScheduledToolItem, ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE,
+    DECLINED_RESPONSE,
+};
 
 const DEFAULT_MAX_TURNS: u32 = 1000;
 
@@ -76,13 +83,55 @@ pub struct Agent {
     pub(super) scheduler_service: Mutex<Option<Arc<dyn SchedulerTrait>>>,
     pub(super) subagent_manager: Mutex<Option<SubAgentManager>>,
     pub(super) mcp_notification_rx: Arc<Mutex<mpsc::Receiver<JsonRpcMessage>>>,
+    pub(super) hooks: Mutex<Option<HooksConfig>>,
+    pub(super) hook_session: Mutex<Option<SessionConfig>>,
+    pub(super) hook_outcomes: Mutex<Vec<HookOutcome>>,
+    /// Extensions whose tools the model may use, all extensions when None
+    pub(super) extension_scope: Mutex<Option<Vec<String>>>,
+    /// Configuration profile the agent's sessions run with
+    pub(super) profile: Mutex<Option<String>>,
 }
 
 #[derive(Clone, Debug)]
 pub enum AgentEvent {
     Message(Message),
     McpNotification((String, JsonRpcMessage)),
-    ModelChange { model: String, mode: String },
+    ModelChange {
+        model: String,
+        mode: String,
+    },
+    /// The tool monitor found the agent repeating itself
+    LoopDetected(LoopDetection),
+    /// Emitted when a tool call starts running and again with its duration once it finishes
+    ToolCallTiming {
+        request_id: String,
+        started_at: DateTime<Utc>,
+        duration_ms: Option<u64>,
+    },
+    /// The reply ended before the agent was done, sent right before the stream ends
+    Stopped(StopReason),
+}
+
+/// Why a reply was cut short
+#[derive(Clone, Debug, PartialEq)]
+pub enum StopReason {
+    /// The agent took the maximum number of turns without user input
+    MaxTurns(u32),
+    /// The tool monitor stopped the agent for repeating itself
+    ToolLoop(String),
+    /// The provider failed with an error the agent could not recover from
+    ProviderError(String),
+}
+
+impl StopReason {
+    /// Stable snake_case name for machine-readable output
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopReason::MaxTurns(_) => "max_turns",
+            StopReason::ToolLoop(_) => "tool_loop",
+            StopReason::ProviderError(_) => "provider_error",
+        }
+    }
 }
 
 impl Default for Agent {
@@ -125,6 +174,16 @@ where
     })
 }
 
+/// Whether a scope of extensions allows a tool, tools of no loaded extension are always allowed
+fn scope_allows(scope: &[String], extensions: &[String], tool_name: &str) -> bool {
+    match tool_name.split_once("__") {
+        Some((extension, _)) if extensions.iter().any(|e| e == extension) => {
+            scope.iter().any(|allowed| allowed == extension)
+        }
+        _ => true,
+    }
+}
+
 impl Agent {
     pub fn new() -> Self {
         // Create channels with buffer size 32 (adjust if needed)
@@ -151,12 +210,28 @@ impl Agent {
             // Initialize with MCP notification support
             subagent_manager: Mutex::new(Some(SubAgentManager::new(mcp_tx))),
             mcp_notification_rx: Arc::new(Mutex::new(mcp_rx)),
+            hooks: Mutex::new(None),
+            hook_session: Mutex::new(None),
+            hook_outcomes: Mutex::new(Vec::new()),
+            extension_scope: Mutex::new(None),
+            profile: Mutex::new(None),
         }
     }
 
     pub async fn configure_tool_monitor(&self, max_repetitions: Option<u32>) {
+        let mut monitor = ToolMonitor::new(max_repetitions);
+        if let Some(loop_detection) = LoopDetectionConfig::from_config() {
+            monitor = monitor.with_loop_detection(loop_detection);
+        }
         let mut tool_monitor = self.tool_monitor.lock().await;
-        *tool_monitor = Some(ToolMonitor::new(max_repetitions));
+        *tool_monitor = Some(monitor);
+    }
+
+    /// Set per-tool time limits in seconds, keyed by prefixed tool name. Extensions added
+    /// afterwards pick these up when their clients are created.
+    pub async fn set_tool_timeouts(&self, timeouts: HashMap<String, u64>) {
+        let mut extension_manager = self.extension_manager.write().await;
+        extension_manager.set_tool_timeouts(timeouts);
     }
 
     pub async fn get_tool_stats(&self) -> Option<HashMap<String, u32>> {
@@ -170,6 +245,92 @@ impl Agent {
         }
     }
 
+    /// Replace the lifecycle hooks, instead of loading them from the `hooks` config key
+    pub async fn configure_hooks(&self, hooks: HooksConfig) {
+        *self.hooks.lock().await = Some(hooks);
+    }
+
+    /// Run the hooks configured for an event. Outcomes are kept until they are recorded
+    /// in the session by the reply loop.
+    pub(super) async fn run_hooks(&self, input: HookInput) -> HookResult {
+        let configured = {
+            let mut hooks = self.hooks.lock().await;
+            let hooks = hooks.get_or_insert_with(|| {
+                HooksConfig::from_config().unwrap_or_else(|e| {
+                    error!("Failed to load hooks from config: {}", e);
+                    HooksConfig::default()
+                })
+            });
+            hooks.for_event(input.event).to_vec()
+        };
+        if configured.is_empty() {
+            return HookResult::default();
+        }
+
+        let input = match self.hook_session.lock().await.as_ref() {
+            Some(session) => input.with_session(session),
+            None => input,
+        };
+
+        let extension_manager = self.extension_manager.read().await;
+        let result = hooks::run_hooks(&configured, input, Some(&extension_manager)).await;
+        self.hook_outcomes
+            .lock()
+            .await
+            .extend(result.outcomes.iter().cloned());
+        result
+    }
+
+    /// Run the post_tool_call hooks for a finished call. A blocking hook replaces the result
+    /// with an error for the model, and any context the hooks return is appended to it.
+    pub(super) async fn run_post_tool_call_hooks(
+        &self,
+        tool_call: &mcp_core::tool::ToolCall,
+        output: ToolResult<Vec<Content>>,
+    ) -> ToolResult<Vec<Content>> {
+        let tool_response = match &output {
+            Ok(content) => serde_json::to_value(content).unwrap_or(Value::Null),
+            Err(e) => serde_json::json!({ "error": e.to_string() }),
+        };
+        let result = self
+            .run_hooks(
+                HookInput::new(HookEvent::PostToolCall)
+                    .with_tool_call(tool_call)
+                    .with_tool_response(tool_response),
+            )
+            .await;
+
+        if let Some(reason) = result.blocked {
+            return Err(ToolError::ExecutionError(format!(
+                "Tool result blocked by hook: {}",
+                reason
+            )));
+        }
+        output.map(|mut content| {
+            content.extend(result.context.into_iter().map(Content::text));
+            content
+        })
+    }
+
+    /// Run the `session_end` hooks; frontends call this when a session is closed
+    pub async fn run_session_end_hooks(&self, session: Option<SessionConfig>) -> Result<()> {
+        if session.is_some() {
+            *self.hook_session.lock().await = session.clone();
+        }
+        self.run_hooks(HookInput::new(HookEvent::SessionEnd)).await;
+        self.flush_hook_outcomes(session.as_ref()).await
+    }
+
+    /// Select the profile recorded for the sessions this agent runs
+    pub async fn set_profile(&self, profile: Option<String>) {
+        *self.profile.lock().await = profile;
+    }
+
+    /// The profile the agent's sessions run with, None without a named profile
+    pub async fn profile(&self) -> Option<String> {
+        self.profile.lock().await.clone()
+    }
+
     /// Set the scheduler service for this agent
     pub async fn set_scheduler(&self, scheduler: Arc<dyn SchedulerTrait>) {
         let mut scheduler_service = self.scheduler_service.lock().await;
@@ -229,7 +390,7 @@ impl Agent {
     #[instrument(skip(self, tool_call, request_id), fields(input, output))]
     pub async fn dispatch_tool_call(
         &self,
-        tool_call: mcp_core::tool::ToolCall,
+        mut tool_call: mcp_core::tool::ToolCall,
         request_id: String,
     ) -> (String, Result<ToolCallResult, ToolError>) {
         // Check if this tool call should be allowed based on repetition monitoring
@@ -246,6 +407,32 @@ impl Agent {
             }
         }
 
+        let hook_result = self
+            .run_hooks(HookInput::new(HookEvent::PreToolCall).with_tool_call(&tool_call))
+            .await;
+        if let Some(reason) = hook_result.blocked {
+            return (
+                request_id,
+                Err(ToolError::ExecutionError(format!(
+                    "Tool call blocked by hook: {}",
+                    reason
+                ))),
+            );
+        }
+        if let Some(arguments) = hook_result.tool_input {
+            tool_call.arguments = arguments;
+        }
+
+        if !self.in_extension_scope(&tool_call.name).await {
+            return (
+                request_id,
+                Err(ToolError::ExecutionError(format!(
+                    "Tool {} is not available for this request",
+                    tool_call.name
+                ))),
+            );
+        }
+
         if tool_call.name == PLATFORM_MANAGE_SCHEDULE_TOOL_NAME {
             let result = self
                 .handle_schedule_management(tool_call.arguments, request_id.clone())
@@ -645,6 +832,30 @@ impl Agent {
         Ok(())
     }
 
+    /// Limit the extension tools the model can use until the scope is cleared with None.
+    /// Tools that do not belong to an extension stay available.
+    pub async fn set_extension_scope(&self, extensions: Option<Vec<String>>) {
+        *self.extension_scope.lock().await = extensions;
+    }
+
+    /// Drop the tools outside the current extension scope
+    pub(super) async fn retain_scoped_tools(&self, tools: &mut Vec<Tool>) {
+        let scope = self.extension_scope.lock().await.clone();
+        let Some(scope) = scope else {
+            return;
+        };
+        let extensions = self.list_extensions().await;
+        tools.retain(|tool| scope_allows(&scope, &extensions, &tool.name));
+    }
+
+    async fn in_extension_scope(&self, tool_name: &str) -> bool {
+        let scope = self.extension_scope.lock().await.clone();
+        match scope {
+            Some(scope) => scope_allows(&scope, &self.list_extensions().await, tool_name),
+            None => true,
+        }
+    }
+
     pub async fn list_extensions(&self) -> Vec<String> {
         let extension_manager = self.extension_manager.read().await;
         extension_manager
@@ -681,15 +892,18 @@ impl Agent {
             self.prepare_tools_and_prompt().await?;
 
         // Get goose_mode from config, but override with execution_mode if provided in session config
-        let mut goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
+        let mut goose_mode = config
+            .get_key(&registry::GOOSE_MODE)
+            .unwrap_or_else(|_| "auto".to_string());
 
-        // If this is a scheduled job with an execution_mode, override the goose_mode
+        // A session's own mode or a scheduled job's execution_mode overrides the goose_mode
         if let Some(session_config) = &session {
             if let Some(execution_mode) = &session_config.execution_mode {
                 // Map "foreground" to "auto" and "background" to "chat"
                 goose_mode = match execution_mode.as_str() {
                     "foreground" => "auto".to_string(),
                     "background" => "chat".to_string(),
+                    mode if registry::MODES.contains(&mode) => mode.to_string(),
                     _ => goose_mode,
                 };
                 tracing::info!(
@@ -702,6 +916,18 @@ impl Agent {
 
         let (tools_with_readonly_annotation, tools_without_annotation) =
             Self::categorize_tools_by_annotation(&tools);
+        let parallel_safe_tools = Self::parallel_safe_tools(&tools);
+
+        // Loop detection can be switched on from config without a repetition limit
+        {
+            let mut tool_monitor = self.tool_monitor.lock().await;
+            if tool_monitor.is_none() {
+                if let Some(loop_detection) = LoopDetectionConfig::from_config() {
+                    *tool_monitor =
+                        Some(ToolMonitor::new(None).with_loop_detection(loop_detection));
+                }
+            }
+        }
 
         if let Some(content) = messages
             .last()
@@ -711,14 +937,39 @@ impl Agent {
             debug!("user_message" = &content);
         }
 
-        Ok(Box::pin(async_stream::try_stream! {
+        let observed_session = session.as_ref().and_then(observer::session_name);
+        let stream = Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
+
+            *self.hook_session.lock().await = session.clone();
+            let mut pre_turn_input = HookInput::new(HookEvent::PreTurn);
+            if let Some(prompt) = messages
+                .last()
+                .filter(|m| m.role == Role::User)
+                .map(|m| m.as_concat_text())
+            {
+                pre_turn_input = pre_turn_input.with_prompt(prompt);
+            }
+            let pre_turn = self.run_hooks(pre_turn_input).await;
+            self.flush_hook_outcomes(session.as_ref()).await?;
+            if let Some(reason) = pre_turn.blocked {
+                yield AgentEvent::Message(Message::assistant().with_text(
+                    format!("This request was blocked by a hook: {}", reason)
+                ));
+                return;
+            }
+            if !pre_turn.context.is_empty() {
+                if let Some(last) = messages.last_mut().filter(|m| m.role == Role::User) {
+                    *last = last.clone().with_text(pre_turn.context.join("\n"));
+                }
+            }
+
             let mut turns_taken = 0u32;
             let max_turns = session
                 .as_ref()
                 .and_then(|s| s.max_turns)
                 .unwrap_or_else(|| {
-                    config.get_param("GOOSE_MAX_TURNS").unwrap_or(DEFAULT_MAX_TURNS)
+                    config.get_key(&registry::GOOSE_MAX_TURNS).unwrap_or(DEFAULT_MAX_TURNS)
                 });
 
             loop {
@@ -727,6 +978,7 @@ impl Agent {
                     yield AgentEvent::Message(Message::assistant().with_text(
                         "I've reached the maximum number of actions I can do without user input. Would you like me to continue?"
                     ));
+                    yield AgentEvent::Stopped(StopReason::MaxTurns(max_turns));
                     break;
                 }
 
@@ -850,6 +1102,9 @@ impl Agent {
                             yield AgentEvent::Message(msg);
                         }
 
+                        // Tool calls and their results, for loop detection at the end of the turn
+                        let mut turn_results = Vec::new();
+
                         // Clone goose_mode once before the match to avoid move issues
                         let mode = goose_mode.clone();
                         if mode.as_str() == "chat" {
@@ -929,44 +1184,118 @@ impl Agent {
                                 futures_lock.drain(..).collect::<Vec<_>>()
                             };
 
-                            let with_id = tool_futures
-                                .into_iter()
-                                .map(|(request_id, stream)| {
-                                    stream.map(move |item| (request_id.clone(), item))
+                            // Read-only and idempotent calls run concurrently, anything else runs on its own
+                            let parallel_request_ids = remaining_requests
+                                .iter()
+                                .filter(|request| {
+                                    request
+                                        .tool_call
+                                        .as_ref()
+                                        .is_ok_and(|call| parallel_safe_tools.contains(&call.name))
                                 })
-                                .collect::<Vec<_>>();
-
-                            let mut combined = stream::select_all(with_id);
+                                .map(|request| request.id.clone())
+                                .collect::<HashSet<_>>();
+                            let mut combined = schedule_tool_streams(tool_futures, &parallel_request_ids);
 
                             let mut all_install_successful = true;
+                            let mut tool_outputs = Vec::new();
 
                             while let Some((request_id, item)) = combined.next().await {
                                 match item {
-                                    ToolStreamItem::Result(output) => {
+                                    ScheduledToolItem::Started(started_at) => {
+                                        yield AgentEvent::ToolCallTiming {
+                                            request_id,
+                                            started_at,
+                                            duration_ms: None,
+                                        };
+                                    }
+                                    ScheduledToolItem::Stream(ToolStreamItem::Result(output)) => {
                                         if enable_extension_request_ids.contains(&request_id) && output.is_err(){
                                             all_install_successful = false;
                                         }
-                                        let mut response = message_tool_response.lock().await;
-                                        *response = response.clone().with_tool_response(request_id, output);
+                                        tool_outputs.push((request_id, output));
                                     },
-                                    ToolStreamItem::Message(msg) => {
+                                    ScheduledToolItem::Stream(ToolStreamItem::Message(msg)) => {
                                         yield AgentEvent::McpNotification((request_id, msg))
                                     }
+                                    ScheduledToolItem::Finished(started_at, duration) => {
+                                        yield AgentEvent::ToolCallTiming {
+                                            request_id,
+                                            started_at,
+                                            duration_ms: Some(duration.as_millis() as u64),
+                                        };
+                                    }
                                 }
                             }
 
+                            // post_tool_call hooks run once every call has finished, so a hook that
+                            // calls back into an extension never waits on an in-flight tool
+                            for (request_id, output) in tool_outputs {
+                                let tool_call = remaining_requests
+                                    .iter()
+                                    .find(|r| r.id == request_id)
+                                    .and_then(|r| r.tool_call.as_ref().ok());
+                                let output = match tool_call {
+                                    Some(tool_call) => {
+                                        let output = self.run_post_tool_call_hooks(tool_call, output).await;
+                                        turn_results.push((
+                                            ToolCall::new(tool_call.name.clone(), tool_call.arguments.clone()),
+                                            output.clone(),
+                                        ));
+                                        output
+                                    }
+                                    None => output,
+                                };
+                                let mut response = message_tool_response.lock().await;
+                                *response = response.clone().with_tool_response(request_id, output);
+                            }
+
                             // Update system prompt and tools if installations were successful
                             if all_install_successful {
                                 (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
                             }
                         }
 
+                        self.flush_hook_outcomes(session.as_ref()).await?;
+
+                        let loop_detection = match self.tool_monitor.lock().await.as_mut() {
+                            Some(monitor) => {
+                                for (tool_call, output) in &turn_results {
+                                    monitor.record_result(tool_call, output);
+                                }
+                                monitor.end_turn()
+                            }
+                            None => None,
+                        };
+                        if let Some(detection) = &loop_detection {
+                            yield AgentEvent::LoopDetected(detection.clone());
+                            if detection.action == LoopAction::Nudge {
+                                let mut response = message_tool_response.lock().await;
+                                *response = response.clone().with_text(detection.nudge());
+                            }
+                        }
+
                         let final_message_tool_resp = message_tool_response.lock().await.clone();
                         yield AgentEvent::Message(final_message_tool_resp.clone());
 
                         messages.push(response);
                         messages.push(final_message_tool_resp);
 
+                        match loop_detection.map(|detection| (detection.action, detection.message)) {
+                            Some((LoopAction::Pause, message)) => {
+                                yield AgentEvent::Message(Message::assistant().with_text(format!(
+                                    "{} I seem to be stuck in a loop, so I've paused. How would you like me to continue?",
+                                    message
+                                )));
+                                break;
+                            }
+                            Some((LoopAction::Stop, message)) => {
+                                yield AgentEvent::Stopped(StopReason::ToolLoop(message.clone()));
+                                Err(anyhow!("Stopped because the agent is stuck in a loop: {}", message))?;
+                            }
+                            _ => {}
+                        }
+
                         // Check for MCP notifications from subagents again before next iteration
                         // Note: These are already handled as McpNotification events above,
                         // so we don't need to convert them to assistant messages here.
@@ -1005,6 +1334,7 @@ impl Agent {
                         // Create an error message & terminate the stream
                         error!("Error: {}", e);
                         yield AgentEvent::Message(Message::assistant().with_text(format!("Ran into this error: {e}.\n\nPlease retry if you think this is a transient or recoverable error.")));
+                        yield AgentEvent::Stopped(StopReason::ProviderError(e.to_string()));
                         break;
                     }
                 }
@@ -1012,7 +1342,11 @@ impl Agent {
                 // Yield control back to the scheduler to prevent blocking
                 tokio::task::yield_now().await;
             }
-        }))
+
+            self.run_hooks(HookInput::new(HookEvent::PostTurn)).await;
+            self.flush_hook_outcomes(session.as_ref()).await?;
+        });
+        Ok(observer::observe(observed_session, stream))
     }
 
     /// Extend the system prompt with one line of additional instruction
@@ -1309,6 +1643,7 @@ impl Agent {
             goose_provider: Some(provider_name.clone()),
             goose_model: Some(model_name.clone()),
             temperature: Some(model_config.temperature.unwrap_or(0.0)),
+            tool_timeouts: None,
         };
 
         let recipe = Recipe::builder()
@@ -1366,4 +1701,14 @@ mod tests {
         assert!(system_prompt.contains(&final_output_tool_system_prompt));
         Ok(())
     }
+
+    #[test]
+    fn test_scope_allows() {
+        let scope = vec!["developer".to_string()];
+        let extensions = vec!["developer".to_string(), "github".to_string()];
+        assert!(scope_allows(&scope, &extensions, "developer__shell"));
+        assert!(!scope_allows(&scope, &extensions, "github__create_issue"));
+        // Platform tools are not part of any extension
+        assert!(scope_allows(&scope, &extensions, "platform__read_resource"));
+    }
 }
@@ -7,7 +7,7 @@ use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::time::Duration;
-use tokio::sync::Mutex;
+use tokio::sync::{RwLock, Semaphore};
 use tokio::task;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, warn};
@@ -27,13 +27,19 @@ use serde_json::Value;
 static DEFAULT_TIMESTAMP: LazyLock<DateTime<Utc>> =
     LazyLock::new(|| Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
 
-type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
+/// Default number of tool calls that can be in flight at once for a single extension
+pub const DEFAULT_TOOL_CONCURRENCY: usize = 4;
+
+type McpClientBox = Arc<RwLock<Box<dyn McpClientTrait>>>;
 
 /// Manages Goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     clients: HashMap<String, McpClientBox>,
     instructions: HashMap<String, String>,
     resource_capable_extensions: HashSet<String>,
+    tool_permits: HashMap<String, Arc<Semaphore>>,
+    extension_timeouts: HashMap<String, u64>,
+    tool_timeouts: HashMap<String, u64>,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -83,6 +89,31 @@ fn normalize(input: String) -> String {
     result.to_lowercase()
 }
 
+/// Per-tool time limits from the `tool_timeouts` config, keyed by prefixed tool name
+fn configured_tool_timeouts() -> HashMap<String, u64> {
+    Config::global()
+        .get_param::<HashMap<String, u64>>("tool_timeouts")
+        .unwrap_or_default()
+}
+
+/// Number of concurrent tool calls allowed for an extension. A per-extension limit under
+/// `extension_concurrency` takes precedence over GOOSE_MAX_TOOL_CONCURRENCY.
+fn tool_concurrency_limit(extension_name: &str) -> usize {
+    let config = Config::global();
+    let limit = config
+        .get_param::<HashMap<String, usize>>("extension_concurrency")
+        .ok()
+        .and_then(|limits| {
+            limits
+                .into_iter()
+                .find(|(name, _)| normalize(name.clone()) == extension_name)
+                .map(|(_, limit)| limit)
+        })
+        .or_else(|| config.get_param::<usize>("GOOSE_MAX_TOOL_CONCURRENCY").ok())
+        .unwrap_or(DEFAULT_TOOL_CONCURRENCY);
+    limit.max(1)
+}
+
 pub fn get_parameter_names(tool: &Tool) -> Vec<String> {
     tool.input_schema
         .get("properties")
@@ -104,9 +135,39 @@ impl ExtensionManager {
             clients: HashMap::new(),
             instructions: HashMap::new(),
             resource_capable_extensions: HashSet::new(),
+            tool_permits: HashMap::new(),
+            extension_timeouts: HashMap::new(),
+            tool_timeouts: HashMap::new(),
         }
     }
 
+    /// Override the time limit of individual tools, keyed by prefixed tool name (for example
+    /// `developer__shell`). Overrides take precedence over the `tool_timeouts` config.
+    pub fn set_tool_timeouts(&mut self, timeouts: HashMap<String, u64>) {
+        self.tool_timeouts = timeouts;
+    }
+
+    /// Seconds a call to the given prefixed tool may run before it is cancelled
+    fn tool_timeout(&self, tool_name: &str, extension_timeout: u64) -> u64 {
+        self.tool_timeouts
+            .get(tool_name)
+            .copied()
+            .or_else(|| configured_tool_timeouts().get(tool_name).copied())
+            .unwrap_or(extension_timeout)
+    }
+
+    /// The longest time limit of any tool of an extension, used as the request timeout of its
+    /// client so that the per-tool limits enforced in dispatch_tool_call are the ones that apply
+    fn longest_tool_timeout(&self, extension_name: &str, extension_timeout: u64) -> u64 {
+        let prefix = format!("{}__", extension_name);
+        configured_tool_timeouts()
+            .iter()
+            .chain(self.tool_timeouts.iter())
+            .filter(|(tool_name, _)| tool_name.starts_with(&prefix))
+            .map(|(_, timeout)| *timeout)
+            .fold(extension_timeout, u64::max)
+    }
+
     pub fn supports_resources(&self) -> bool {
         !self.resource_capable_extensions.is_empty()
     }
@@ -174,74 +235,58 @@ impl ExtensionManager {
             Ok(all_envs)
         }
 
+        let extension_timeout = match &config {
+            ExtensionConfig::Sse { timeout, .. }
+            | ExtensionConfig::StreamableHttp { timeout, .. }
+            | ExtensionConfig::Stdio { timeout, .. }
+            | ExtensionConfig::Builtin { timeout, .. } => *timeout,
+            ExtensionConfig::Frontend { .. } => None,
+        }
+        .unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT);
+        let client_timeout =
+            Duration::from_secs(self.longest_tool_timeout(&sanitized_name, extension_timeout));
+
         let mut client: Box<dyn McpClientTrait> = match &config {
             ExtensionConfig::Sse {
                 uri,
                 envs,
                 env_keys,
-                timeout,
                 ..
             } => {
                 let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
                 let transport = SseTransport::new(uri, all_envs);
                 let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
+                Box::new(McpClient::connect(handle, client_timeout).await?)
             }
             ExtensionConfig::StreamableHttp {
                 uri,
                 envs,
                 env_keys,
                 headers,
-                timeout,
                 ..
             } => {
                 let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
                 let transport =
                     StreamableHttpTransport::with_headers(uri, all_envs, headers.clone());
                 let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
+                Box::new(McpClient::connect(handle, client_timeout).await?)
             }
             ExtensionConfig::Stdio {
                 cmd,
                 args,
                 envs,
                 env_keys,
-                timeout,
                 ..
             } => {
                 let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
                 let transport = StdioTransport::new(cmd, args.to_vec(), all_envs);
                 let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
+                Box::new(McpClient::connect(handle, client_timeout).await?)
             }
             ExtensionConfig::Builtin {
                 name,
                 display_name: _,
-                timeout,
+                timeout: _,
                 bundled: _,
             } => {
                 let cmd = std::env::current_exe()
@@ -249,21 +294,16 @@ impl ExtensionManager {
                     .to_str()
                     .expect("should resolve executable to string path")
                     .to_string();
+                // Builtin shells kill their commands themselves once the shell tool's time runs out
+                let shell_timeout =
+                    self.tool_timeout(&format!("{}__shell", sanitized_name), extension_timeout);
                 let transport = StdioTransport::new(
                     &cmd,
                     vec!["mcp".to_string(), name.clone()],
-                    HashMap::new(),
+                    HashMap::from([("GOOSE_SHELL_TIMEOUT".to_string(), shell_timeout.to_string())]),
                 );
                 let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
+                Box::new(McpClient::connect(handle, client_timeout).await?)
             }
             _ => unreachable!(),
         };
@@ -290,8 +330,14 @@ impl ExtensionManager {
                 .insert(sanitized_name.clone());
         }
 
+        self.extension_timeouts
+            .insert(sanitized_name.clone(), extension_timeout);
+        self.tool_permits.insert(
+            sanitized_name.clone(),
+            Arc::new(Semaphore::new(tool_concurrency_limit(&sanitized_name))),
+        );
         self.clients
-            .insert(sanitized_name.clone(), Arc::new(Mutex::new(client)));
+            .insert(sanitized_name.clone(), Arc::new(RwLock::new(client)));
 
         Ok(())
     }
@@ -315,6 +361,8 @@ impl ExtensionManager {
         self.clients.remove(&sanitized_name);
         self.instructions.remove(&sanitized_name);
         self.resource_capable_extensions.remove(&sanitized_name);
+        self.tool_permits.remove(&sanitized_name);
+        self.extension_timeouts.remove(&sanitized_name);
         Ok(())
     }
 
@@ -374,7 +422,7 @@ impl ExtensionManager {
 
             task::spawn(async move {
                 let mut tools = Vec::new();
-                let client_guard = client.lock().await;
+                let client_guard = client.read().await;
                 let mut client_tools = client_guard.list_tools(None).await?;
 
                 loop {
@@ -420,7 +468,7 @@ impl ExtensionManager {
         let mut result: Vec<ResourceItem> = Vec::new();
 
         for (name, client) in &self.clients {
-            let client_guard = client.lock().await;
+            let client_guard = client.read().await;
             let resources = client_guard.list_resources(None).await?;
 
             for resource in resources.resources {
@@ -541,7 +589,7 @@ impl ExtensionManager {
             .get(extension_name)
             .ok_or(ToolError::InvalidParameters(error_msg))?;
 
-        let client_guard = client.lock().await;
+        let client_guard = client.read().await;
         let read_result = client_guard.read_resource(uri).await.map_err(|_| {
             ToolError::ExecutionError(format!("Could not read resource with uri: {}", uri))
         })?;
@@ -567,7 +615,7 @@ impl ExtensionManager {
             ToolError::InvalidParameters(format!("Extension {} is not valid", extension_name))
         })?;
 
-        let client_guard = client.lock().await;
+        let client_guard = client.read().await;
         client_guard
             .list_resources(None)
             .await
@@ -655,15 +703,38 @@ impl ExtensionManager {
 
         let arguments = tool_call.arguments.clone();
         let client = client.clone();
-        let notifications_receiver = client.lock().await.subscribe().await;
+        let permits = self.tool_permits.get(client_name).cloned();
+        let extension_timeout = self
+            .extension_timeouts
+            .get(client_name)
+            .copied()
+            .unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT);
+        let timeout = self.tool_timeout(&tool_call.name, extension_timeout);
+        let prefixed_name = tool_call.name.clone();
+        let notifications_receiver = client.read().await.subscribe().await;
 
         let fut = async move {
-            let client_guard = client.lock().await;
-            client_guard
-                .call_tool(&tool_name, arguments)
-                .await
-                .map(|call| call.content)
-                .map_err(|e| ToolError::ExecutionError(e.to_string()))
+            // Hold a permit for the whole call so the extension's concurrency limit is respected
+            let _permit = match permits {
+                Some(permits) => Some(
+                    permits
+                        .acquire_owned()
+                        .await
+                        .map_err(|e| ToolError::ExecutionError(e.to_string()))?,
+                ),
+                None => None,
+            };
+            let client_guard = client.read().await;
+            let call = client_guard.call_tool(&tool_name, arguments);
+            match tokio::time::timeout(Duration::from_secs(timeout), call).await {
+                Ok(result) => result
+                    .map(|call| call.content)
+                    .map_err(|e| ToolError::ExecutionError(e.to_string())),
+                Err(_) => Err(ToolError::Timeout {
+                    tool: prefixed_name,
+                    seconds: timeout,
+                }),
+            }
         };
 
         Ok(ToolCallResult {
@@ -680,7 +751,7 @@ impl ExtensionManager {
             ToolError::InvalidParameters(format!("Extension {} is not valid", extension_name))
         })?;
 
-        let client_guard = client.lock().await;
+        let client_guard = client.read().await;
         client_guard
             .list_prompts(None)
             .await
@@ -746,7 +817,7 @@ impl ExtensionManager {
             .get(extension_name)
             .ok_or_else(|| anyhow::anyhow!("Extension {} not found", extension_name))?;
 
-        let client_guard = client.lock().await;
+        let client_guard = client.read().await;
         client_guard
             .get_prompt(name, arguments)
             .await
@@ -869,6 +940,13 @@ mod tests {
                     content: vec![],
                     is_error: None,
                 }),
+                "slow_tool" => {
+                    tokio::time::sleep(Duration::from_secs(30)).await;
+                    Ok(CallToolResult {
+                        content: vec![],
+                        is_error: None,
+                    })
+                }
                 _ => Err(Error::NotInitialized),
             }
         }
@@ -900,22 +978,22 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         extension_manager.clients.insert(
             normalize("__client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         // Test basic case
@@ -939,6 +1017,59 @@ mod tests {
             .is_some());
     }
 
+    #[test]
+    fn test_tool_timeout_overrides() {
+        let mut extension_manager = ExtensionManager::new();
+        assert_eq!(
+            extension_manager.tool_timeout("test_client__tool", 300),
+            300
+        );
+
+        extension_manager.set_tool_timeouts(HashMap::from([
+            ("test_client__tool".to_string(), 10),
+            ("test_client__build".to_string(), 900),
+        ]));
+        assert_eq!(extension_manager.tool_timeout("test_client__tool", 300), 10);
+        assert_eq!(extension_manager.tool_timeout("other__tool", 300), 300);
+
+        // The client timeout covers the slowest tool of the extension
+        assert_eq!(
+            extension_manager.longest_tool_timeout("test_client", 300),
+            900
+        );
+        assert_eq!(extension_manager.longest_tool_timeout("other", 300), 300);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_tool_call_timeout() {
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager.clients.insert(
+            normalize("test_client".to_string()),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
+        );
+        extension_manager
+            .set_tool_timeouts(HashMap::from([("test_client__slow_tool".to_string(), 1)]));
+
+        let tool_call = ToolCall {
+            name: "test_client__slow_tool".to_string(),
+            arguments: json!({}),
+        };
+        let result = extension_manager
+            .dispatch_tool_call(tool_call)
+            .await
+            .unwrap()
+            .result
+            .await;
+
+        assert_eq!(
+            result.unwrap_err(),
+            ToolError::Timeout {
+                tool: "test_client__slow_tool".to_string(),
+                seconds: 1,
+            }
+        );
+    }
+
     #[tokio::test]
     async fn test_dispatch_tool_call() {
         // test that dispatch_tool_call parses out the sanitized name correctly, and extracts
@@ -948,17 +1079,17 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(RwLock::new(Box::new(MockClient {}))),
         );
 
         // verify a normal tool call
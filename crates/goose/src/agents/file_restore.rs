@@ -0,0 +1,127 @@
+use std::collections::HashSet;
+
+use mcp_core::tool::ToolCall;
+use serde_json::json;
+
+use crate::message::{Message, MessageContent};
+
+use super::super::agents::Agent;
+
+/// text_editor commands that record the previous file contents in the undo history
+const UNDOABLE_COMMANDS: [&str; 3] = ["str_replace", "edit_file", "insert"];
+
+/// Successful text_editor edits in `messages` as (tool name, path) pairs, newest first
+fn undoable_edits(messages: &[Message]) -> Vec<(String, String)> {
+    let succeeded: HashSet<&str> = messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(response) if response.tool_result.is_ok() => {
+                Some(response.id.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut edits: Vec<(String, String)> = messages
+        .iter()
+        .flat_map(|message| message.content.iter())
+        .filter_map(|content| {
+            let MessageContent::ToolRequest(request) = content else {
+                return None;
+            };
+            let tool_call = request.tool_call.as_ref().ok()?;
+            if !tool_call.name.ends_with("__text_editor")
+                || !succeeded.contains(request.id.as_str())
+            {
+                return None;
+            }
+            let command = tool_call.arguments.get("command")?.as_str()?;
+            if !UNDOABLE_COMMANDS.contains(&command) {
+                return None;
+            }
+            let path = tool_call.arguments.get("path")?.as_str()?;
+            Some((tool_call.name.clone(), path.to_string()))
+        })
+        .collect();
+
+    edits.reverse();
+    edits
+}
+
+impl Agent {
+    /// Undo the text_editor edits made by the tool calls in `messages`, newest first
+    ///
+    /// This relies on the undo history of the extension that made the edits, so only edits made
+    /// while that extension has been running can be restored. Returns the paths that were
+    /// restored, once per undone edit.
+    pub async fn undo_file_edits(&self, messages: &[Message]) -> Vec<String> {
+        let extension_manager = self.extension_manager.read().await;
+        let mut restored = Vec::new();
+
+        for (tool_name, path) in undoable_edits(messages) {
+            let tool_call = ToolCall::new(
+                tool_name,
+                json!({
+                    "command": "undo_edit",
+                    "path": path,
+                }),
+            );
+            let result = match extension_manager.dispatch_tool_call(tool_call).await {
+                Ok(call_result) => call_result.result.await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            match result {
+                Ok(_) => restored.push(path),
+                Err(e) => tracing::warn!("Failed to restore {}: {}", path, e),
+            }
+        }
+
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::handler::ToolError;
+
+    fn edit(id: &str, command: &str, path: &str) -> Message {
+        Message::assistant().with_tool_request(
+            id,
+            Ok(ToolCall::new(
+                "developer__text_editor",
+                json!({"command": command, "path": path}),
+            )),
+        )
+    }
+
+    #[test]
+    fn test_undoable_edits_newest_first() {
+        let messages = vec![
+            edit("1", "str_replace", "/a.rs"),
+            Message::user().with_tool_response("1", Ok(vec![])),
+            edit("2", "view", "/a.rs"),
+            Message::user().with_tool_response("2", Ok(vec![])),
+            edit("3", "insert", "/b.rs"),
+            Message::user().with_tool_response("3", Ok(vec![])),
+            edit("4", "str_replace", "/c.rs"),
+            Message::user()
+                .with_tool_response("4", Err(ToolError::ExecutionError("no match".to_string()))),
+            Message::assistant().with_tool_request(
+                "5",
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({"command": "insert", "path": "/d.rs"}),
+                )),
+            ),
+            Message::user().with_tool_response("5", Ok(vec![])),
+        ];
+
+        let edits: Vec<String> = undoable_edits(&messages)
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect();
+        assert_eq!(edits, vec!["/b.rs", "/a.rs"]);
+    }
+}
@@ -0,0 +1,596 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use mcp_core::{Content, ToolCall};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use utoipa::ToSchema;
+
+use crate::agents::extension_manager::ExtensionManager;
+use crate::agents::types::SessionConfig;
+use crate::config::{Config, ConfigError};
+use crate::session::Identifier;
+
+/// Key under which hooks are configured in config.yaml
+pub const HOOKS_CONFIG_KEY: &str = "hooks";
+/// Default time a single hook may run before it is abandoned
+pub const DEFAULT_HOOK_TIMEOUT: u64 = 30;
+/// Exit code a command hook uses to block the action it was invoked for
+pub const HOOK_BLOCK_EXIT_CODE: i32 = 2;
+
+/// Points in the agent lifecycle at which hooks run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    PreToolCall,
+    PostToolCall,
+    PreTurn,
+    PostTurn,
+    SessionEnd,
+}
+
+/// What a hook runs: either a shell command or a tool from an enabled extension
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum HookAction {
+    /// A shell command which receives the hook input as JSON on stdin
+    Command { command: String },
+    /// A prefixed extension tool, e.g. `developer__shell`. When no arguments are
+    /// given the hook input is passed as the tool arguments.
+    Tool {
+        tool: String,
+        #[serde(default)]
+        arguments: Option<Value>,
+    },
+}
+
+/// A single configured hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HookConfig {
+    /// Optional name used when reporting outcomes
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Regex matched against the tool name; only applies to tool call events
+    #[serde(default)]
+    pub matcher: Option<String>,
+    #[serde(flatten)]
+    pub action: HookAction,
+    /// Timeout in seconds, defaults to DEFAULT_HOOK_TIMEOUT
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::PreToolCall => "pre_tool_call",
+            HookEvent::PostToolCall => "post_tool_call",
+            HookEvent::PreTurn => "pre_turn",
+            HookEvent::PostTurn => "post_turn",
+            HookEvent::SessionEnd => "session_end",
+        }
+    }
+}
+
+impl HookConfig {
+    pub fn display_name(&self) -> String {
+        match (&self.name, &self.action) {
+            (Some(name), _) => name.clone(),
+            (None, HookAction::Command { command }) => command.clone(),
+            (None, HookAction::Tool { tool, .. }) => tool.clone(),
+        }
+    }
+
+    fn matches_tool(&self, tool_name: Option<&str>) -> bool {
+        match (&self.matcher, tool_name) {
+            (None, _) => true,
+            (Some(_), None) => true,
+            (Some(pattern), Some(name)) => match Regex::new(pattern) {
+                Ok(re) => re.is_match(name),
+                Err(e) => {
+                    tracing::warn!("Invalid hook matcher '{}': {}", pattern, e);
+                    false
+                }
+            },
+        }
+    }
+}
+
+/// Hooks grouped by the event they run on, as stored under the `hooks` config key
+///
+/// ```yaml
+/// hooks:
+///   pre_tool_call:
+///     - matcher: "developer__text_editor"
+///       command: "./scripts/check-protected-paths.sh"
+///   post_turn:
+///     - tool: "developer__shell"
+///       arguments:
+///         command: "cargo fmt"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub pre_tool_call: Vec<HookConfig>,
+    #[serde(default)]
+    pub post_tool_call: Vec<HookConfig>,
+    #[serde(default)]
+    pub pre_turn: Vec<HookConfig>,
+    #[serde(default)]
+    pub post_turn: Vec<HookConfig>,
+    #[serde(default)]
+    pub session_end: Vec<HookConfig>,
+}
+
+impl HooksConfig {
+    /// Load the hooks from the global config, returning no hooks when none are configured
+    pub fn from_config() -> Result<Self, ConfigError> {
+        match Config::global().get_param::<HooksConfig>(HOOKS_CONFIG_KEY) {
+            Ok(hooks) => Ok(hooks),
+            Err(ConfigError::NotFound(_)) => Ok(HooksConfig::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn for_event(&self, event: HookEvent) -> &[HookConfig] {
+        match event {
+            HookEvent::PreToolCall => &self.pre_tool_call,
+            HookEvent::PostToolCall => &self.post_tool_call,
+            HookEvent::PreTurn => &self.pre_turn,
+            HookEvent::PostTurn => &self.post_turn,
+            HookEvent::SessionEnd => &self.session_end,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre_tool_call.is_empty()
+            && self.post_tool_call.is_empty()
+            && self.pre_turn.is_empty()
+            && self.post_turn.is_empty()
+            && self.session_end.is_empty()
+    }
+}
+
+/// The JSON document handed to every hook
+#[derive(Debug, Clone, Serialize)]
+pub struct HookInput {
+    pub event: HookEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl HookInput {
+    pub fn new(event: HookEvent) -> Self {
+        Self {
+            event,
+            session_id: None,
+            working_dir: None,
+            tool_name: None,
+            tool_input: None,
+            tool_response: None,
+            prompt: None,
+        }
+    }
+
+    pub fn with_session(mut self, session: &SessionConfig) -> Self {
+        self.session_id = Some(match &session.id {
+            Identifier::Name(name) => name.clone(),
+            Identifier::Path(path) => path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        });
+        self.working_dir = Some(session.working_dir.clone());
+        self
+    }
+
+    pub fn with_tool_call(mut self, tool_call: &ToolCall) -> Self {
+        self.tool_name = Some(tool_call.name.clone());
+        self.tool_input = Some(tool_call.arguments.clone());
+        self
+    }
+
+    pub fn with_tool_response(mut self, response: Value) -> Self {
+        self.tool_response = Some(response);
+        self
+    }
+
+    pub fn with_prompt(mut self, prompt: String) -> Self {
+        self.prompt = Some(prompt);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum HookDecision {
+    Allow,
+    Block,
+}
+
+/// Optional JSON a hook can print on stdout (or return as tool text) to steer the agent
+#[derive(Debug, Default, Deserialize)]
+struct HookResponse {
+    decision: Option<HookDecision>,
+    reason: Option<String>,
+    /// Replacement tool arguments, only honoured for pre_tool_call hooks
+    tool_input: Option<Value>,
+    /// Extra text added to the tool result or the user prompt
+    context: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStatus {
+    Allowed,
+    Modified,
+    Blocked,
+    Error,
+}
+
+/// Record of a single hook run, persisted in the session metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct HookOutcome {
+    pub event: HookEvent,
+    pub hook: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    pub status: HookStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Unix timestamp (seconds) when the hook finished
+    pub timestamp: i64,
+    pub duration_ms: u64,
+}
+
+/// Combined result of running every hook configured for an event
+#[derive(Debug, Default)]
+pub struct HookResult {
+    /// Reason given by the first hook that blocked the action, if any
+    pub blocked: Option<String>,
+    /// Replacement tool arguments produced by pre_tool_call hooks
+    pub tool_input: Option<Value>,
+    /// Additional context returned by the hooks, in order
+    pub context: Vec<String>,
+    pub outcomes: Vec<HookOutcome>,
+}
+
+impl HookResult {
+    pub fn is_blocked(&self) -> bool {
+        self.blocked.is_some()
+    }
+}
+
+/// Run all hooks for `input.event`, in configuration order, stopping at the first block.
+///
+/// Hooks that fail (spawn errors, timeouts, unexpected exit codes) are recorded but do not
+/// block the action, so a broken hook never wedges a session.
+pub async fn run_hooks(
+    hooks: &[HookConfig],
+    mut input: HookInput,
+    extension_manager: Option<&ExtensionManager>,
+) -> HookResult {
+    let mut result = HookResult::default();
+
+    for hook in hooks {
+        if !hook.matches_tool(input.tool_name.as_deref()) {
+            continue;
+        }
+
+        let started = Instant::now();
+        let response = run_hook(hook, &input, extension_manager).await;
+
+        let mut outcome = HookOutcome {
+            event: input.event,
+            hook: hook.display_name(),
+            tool_name: input.tool_name.clone(),
+            status: HookStatus::Allowed,
+            message: None,
+            timestamp: Utc::now().timestamp(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+
+        match response {
+            Ok(response) => {
+                if response.decision == Some(HookDecision::Block) {
+                    let reason = response
+                        .reason
+                        .unwrap_or_else(|| format!("blocked by hook '{}'", outcome.hook));
+                    outcome.status = HookStatus::Blocked;
+                    outcome.message = Some(reason.clone());
+                    result.outcomes.push(outcome);
+                    result.blocked = Some(reason);
+                    return result;
+                }
+
+                if let Some(tool_input) = response.tool_input {
+                    if input.event == HookEvent::PreToolCall {
+                        outcome.status = HookStatus::Modified;
+                        input.tool_input = Some(tool_input.clone());
+                        result.tool_input = Some(tool_input);
+                    }
+                }
+                if let Some(context) = response.context {
+                    if outcome.status == HookStatus::Allowed {
+                        outcome.status = HookStatus::Modified;
+                    }
+                    result.context.push(context);
+                }
+                outcome.message = response.reason;
+            }
+            Err(e) => {
+                tracing::warn!("Hook '{}' failed: {}", outcome.hook, e);
+                outcome.status = HookStatus::Error;
+                outcome.message = Some(e);
+            }
+        }
+
+        result.outcomes.push(outcome);
+    }
+
+    result
+}
+
+async fn run_hook(
+    hook: &HookConfig,
+    input: &HookInput,
+    extension_manager: Option<&ExtensionManager>,
+) -> Result<HookResponse, String> {
+    let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT));
+
+    match &hook.action {
+        HookAction::Command { command } => {
+            match tokio::time::timeout(timeout, run_command_hook(command, input)).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+            }
+        }
+        HookAction::Tool { tool, arguments } => {
+            let extension_manager = extension_manager
+                .ok_or_else(|| "extension tools are not available for this hook".to_string())?;
+            let arguments = match arguments {
+                Some(arguments) => arguments.clone(),
+                None => serde_json::to_value(input).map_err(|e| e.to_string())?,
+            };
+            let call = ToolCall::new(tool, arguments);
+            let fut = async {
+                let call_result = extension_manager
+                    .dispatch_tool_call(call)
+                    .await
+                    .map_err(|e| e.to_string())?;
+                call_result.result.await.map_err(|e| e.to_string())
+            };
+            match tokio::time::timeout(timeout, fut).await {
+                Ok(Ok(content)) => Ok(parse_response(&concat_text(&content))),
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+            }
+        }
+    }
+}
+
+async fn run_command_hook(command: &str, input: &HookInput) -> Result<HookResponse, String> {
+    let payload = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+
+    if let Some(dir) = input.working_dir.as_ref().filter(|d| d.is_dir()) {
+        cmd.current_dir(dir);
+    }
+    cmd.env("GOOSE_HOOK_EVENT", input.event.as_str());
+    if let Some(session_id) = &input.session_id {
+        cmd.env("GOOSE_SESSION_ID", session_id);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("failed to spawn '{}': {}", command, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that ignores its input may exit before reading it, so a broken pipe is fine
+        let _ = stdin.write_all(&payload).await;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("failed to wait for '{}': {}", command, e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+    match output.status.code() {
+        Some(0) => Ok(parse_response(&stdout)),
+        Some(HOOK_BLOCK_EXIT_CODE) => Ok(HookResponse {
+            decision: Some(HookDecision::Block),
+            reason: Some(if stderr.is_empty() { stdout } else { stderr }).filter(|r| !r.is_empty()),
+            ..Default::default()
+        }),
+        code => Err(format!(
+            "exited with {}{}",
+            code.map(|c| c.to_string())
+                .unwrap_or_else(|| "signal".to_string()),
+            if stderr.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", stderr)
+            }
+        )),
+    }
+}
+
+/// Hooks may print a JSON response; anything else is treated as a plain allow
+fn parse_response(output: &str) -> HookResponse {
+    if output.is_empty() {
+        return HookResponse::default();
+    }
+    serde_json::from_str(output).unwrap_or_default()
+}
+
+fn concat_text(content: &[Content]) -> String {
+    content
+        .iter()
+        .filter_map(|c| c.as_text())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn command_hook(command: &str) -> HookConfig {
+        HookConfig {
+            name: None,
+            matcher: None,
+            action: HookAction::Command {
+                command: command.to_string(),
+            },
+            timeout: None,
+        }
+    }
+
+    fn pre_tool_call_input() -> HookInput {
+        HookInput::new(HookEvent::PreToolCall)
+            .with_tool_call(&ToolCall::new("developer__shell", json!({"command": "ls"})))
+    }
+
+    #[test]
+    fn test_parse_hooks_config() {
+        let yaml = r#"
+pre_tool_call:
+  - name: protect
+    matcher: "developer__text_editor"
+    command: "./check.sh"
+    timeout: 5
+post_turn:
+  - tool: "developer__shell"
+    arguments:
+      command: "cargo fmt"
+"#;
+        let hooks: HooksConfig = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(hooks.pre_tool_call.len(), 1);
+        assert_eq!(hooks.pre_tool_call[0].display_name(), "protect");
+        assert_eq!(
+            hooks.pre_tool_call[0].action,
+            HookAction::Command {
+                command: "./check.sh".to_string()
+            }
+        );
+        assert_eq!(
+            hooks.for_event(HookEvent::PostTurn)[0].action,
+            HookAction::Tool {
+                tool: "developer__shell".to_string(),
+                arguments: Some(json!({"command": "cargo fmt"})),
+            }
+        );
+        assert!(hooks.session_end.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_hook_allows() {
+        let result = run_hooks(
+            &[command_hook("cat > /dev/null")],
+            pre_tool_call_input(),
+            None,
+        )
+        .await;
+        assert!(!result.is_blocked());
+        assert_eq!(result.outcomes.len(), 1);
+        assert_eq!(result.outcomes[0].status, HookStatus::Allowed);
+    }
+
+    #[tokio::test]
+    async fn test_command_hook_blocks_with_exit_code() {
+        let hooks = vec![
+            command_hook("echo 'protected path' >&2; exit 2"),
+            command_hook("echo never runs"),
+        ];
+        let result = run_hooks(&hooks, pre_tool_call_input(), None).await;
+        assert_eq!(result.blocked.as_deref(), Some("protected path"));
+        assert_eq!(result.outcomes.len(), 1);
+        assert_eq!(result.outcomes[0].status, HookStatus::Blocked);
+    }
+
+    #[tokio::test]
+    async fn test_command_hook_receives_input_and_modifies_call() {
+        // Echo the received command back as a modified argument
+        let hook = command_hook(
+            r#"cmd=$(sed -n 's/.*"command":"\([^"]*\)".*/\1/p'); printf '{"tool_input": {"command": "%s -la"}}' "$cmd""#,
+        );
+        let result = run_hooks(&[hook], pre_tool_call_input(), None).await;
+        assert!(!result.is_blocked());
+        assert_eq!(result.tool_input, Some(json!({"command": "ls -la"})));
+        assert_eq!(result.outcomes[0].status, HookStatus::Modified);
+    }
+
+    #[tokio::test]
+    async fn test_matcher_filters_tools() {
+        let mut hook = command_hook("exit 2");
+        hook.matcher = Some("^developer__text_editor$".to_string());
+        let result = run_hooks(&[hook], pre_tool_call_input(), None).await;
+        assert!(!result.is_blocked());
+        assert!(result.outcomes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_hook_does_not_block() {
+        let mut slow = command_hook("sleep 5");
+        slow.timeout = Some(1);
+        let hooks = vec![command_hook("exit 1"), slow];
+        let result = run_hooks(&hooks, HookInput::new(HookEvent::PostTurn), None).await;
+        assert!(!result.is_blocked());
+        assert_eq!(result.outcomes.len(), 2);
+        assert!(result
+            .outcomes
+            .iter()
+            .all(|o| o.status == HookStatus::Error));
+        assert!(result.outcomes[1]
+            .message
+            .as_deref()
+            .unwrap()
+            .contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn test_tool_hook_without_extension_manager_errors() {
+        let hook = HookConfig {
+            name: Some("fmt".to_string()),
+            matcher: None,
+            action: HookAction::Tool {
+                tool: "developer__shell".to_string(),
+                arguments: None,
+            },
+            timeout: None,
+        };
+        let result = run_hooks(&[hook], HookInput::new(HookEvent::SessionEnd), None).await;
+        assert_eq!(result.outcomes[0].status, HookStatus::Error);
+    }
+}
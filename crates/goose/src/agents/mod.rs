@@ -2,8 +2,11 @@ mod agent;
 mod context;
 pub mod extension;
 pub mod extension_manager;
+mod file_restore;
 pub mod final_output_tool;
+pub mod hooks;
 mod large_response_handler;
+pub mod observer;
 pub mod platform_tools;
 pub mod prompt_manager;
 mod recipe_tools;
@@ -23,7 +26,7 @@ mod tool_router_index_manager;
 pub(crate) mod tool_vectordb;
 mod types;
 
-pub use agent::{Agent, AgentEvent};
+pub use agent::{Agent, AgentEvent, StopReason};
 pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
 pub use prompt_manager::PromptManager;
@@ -0,0 +1,114 @@
+use chrono::{DateTime, Utc};
+use futures::stream::{BoxStream, StreamExt};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+
+use crate::agents::agent::AgentEvent;
+use crate::agents::types::SessionConfig;
+use crate::session::Identifier;
+
+/// Events kept for observers that fall behind before the oldest are dropped
+const ACTIVITY_CAPACITY: usize = 1024;
+
+static ACTIVITY: Lazy<broadcast::Sender<Activity>> =
+    Lazy::new(|| broadcast::channel(ACTIVITY_CAPACITY).0);
+
+/// Outcome of a scheduled job run
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleRunStatus {
+    Started,
+    Finished { session_id: String },
+    Failed { error: String },
+}
+
+/// Something an agent in this process did, published for observers that did not start it
+#[derive(Debug, Clone)]
+pub enum ActivityKind {
+    Agent(AgentEvent),
+    ScheduleRun {
+        schedule_id: String,
+        status: ScheduleRunStatus,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Activity {
+    /// Session the activity belongs to, if the agent was run with one
+    pub session_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub kind: ActivityKind,
+}
+
+/// Receive all agent activity published from now on
+pub fn subscribe() -> broadcast::Receiver<Activity> {
+    ACTIVITY.subscribe()
+}
+
+/// Publish activity to current observers, cheap when nobody is listening
+pub fn publish(session_id: Option<String>, kind: ActivityKind) {
+    if ACTIVITY.receiver_count() == 0 {
+        return;
+    }
+    let _ = ACTIVITY.send(Activity {
+        session_id,
+        timestamp: Utc::now(),
+        kind,
+    });
+}
+
+/// The name a session is known by, the file stem for sessions given by path
+pub fn session_name(session: &SessionConfig) -> Option<String> {
+    match &session.id {
+        Identifier::Name(name) => Some(name.clone()),
+        Identifier::Path(path) => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned()),
+    }
+}
+
+/// Publish every event of a reply stream as it passes through
+pub(crate) fn observe<'a>(
+    session_id: Option<String>,
+    stream: BoxStream<'a, anyhow::Result<AgentEvent>>,
+) -> BoxStream<'a, anyhow::Result<AgentEvent>> {
+    stream
+        .inspect(move |event| {
+            if let Ok(event) = event {
+                publish(session_id.clone(), ActivityKind::Agent(event.clone()));
+            }
+        })
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use futures::stream;
+
+    #[tokio::test]
+    async fn test_observed_stream_is_published() {
+        let mut rx = subscribe();
+        let events = stream::iter(vec![Ok(AgentEvent::Message(
+            Message::assistant().with_text("hi"),
+        ))])
+        .boxed();
+
+        let passed: Vec<_> = observe(Some("observed-session".to_string()), events)
+            .collect()
+            .await;
+        assert_eq!(passed.len(), 1);
+
+        // Other tests may publish concurrently, so look for ours
+        loop {
+            let activity = rx.recv().await.unwrap();
+            if activity.session_id.as_deref() == Some("observed-session") {
+                assert!(matches!(
+                    activity.kind,
+                    ActivityKind::Agent(AgentEvent::Message(_))
+                ));
+                break;
+            }
+        }
+    }
+}
@@ -1,11 +1,14 @@
-use std::{collections::HashMap, fs};
+use std::collections::HashMap;
+use std::path::Path;
 
 use anyhow::Result;
 use mcp_core::tool::{Tool, ToolAnnotations};
 use serde_json::{json, Map, Value};
 
 use crate::agents::sub_recipe_execution_tool::lib::Task;
-use crate::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement, SubRecipe};
+use crate::recipe::{
+    Recipe, RecipeParameter, RecipeParameterInputType, RecipeParameterRequirement, SubRecipe,
+};
 
 pub const SUB_RECIPE_TASK_TOOL_NAME_PREFIX: &str = "subrecipe__create_task";
 
@@ -28,12 +31,44 @@ pub fn create_sub_recipe_task_tool(sub_recipe: &SubRecipe) -> Tool {
 fn get_sub_recipe_parameter_definition(
     sub_recipe: &SubRecipe,
 ) -> Result<Option<Vec<RecipeParameter>>> {
-    let content = fs::read_to_string(sub_recipe.path.clone())
-        .map_err(|e| anyhow::anyhow!("Failed to read recipe file {}: {}", sub_recipe.path, e))?;
-    let recipe = Recipe::from_content(&content)?;
+    let recipe = Recipe::from_file(Path::new(&sub_recipe.path))?;
     Ok(recipe.parameters)
 }
 
+fn parameter_schema(param: &RecipeParameter) -> Value {
+    let mut schema = match param.input_type {
+        RecipeParameterInputType::Number => json!({"type": "number"}),
+        RecipeParameterInputType::Boolean => json!({"type": "boolean"}),
+        RecipeParameterInputType::Date => json!({"type": "string", "format": "date"}),
+        RecipeParameterInputType::List => json!({"type": "array", "items": {"type": "string"}}),
+        RecipeParameterInputType::String
+        | RecipeParameterInputType::File
+        | RecipeParameterInputType::Select => json!({"type": "string"}),
+    };
+    schema["description"] = json!(param.description);
+    let mut value_schema = Map::new();
+    if let Some(options) = &param.options {
+        value_schema.insert("enum".to_string(), json!(options));
+    }
+    if let Some(pattern) = &param.pattern {
+        value_schema.insert("pattern".to_string(), json!(pattern));
+    }
+    let target = match param.input_type {
+        RecipeParameterInputType::List => &mut schema["items"],
+        _ => &mut schema,
+    };
+    if let Some(target) = target.as_object_mut() {
+        target.extend(value_schema);
+    }
+    if let Some(min) = param.min {
+        schema["minimum"] = json!(min);
+    }
+    if let Some(max) = param.max {
+        schema["maximum"] = json!(max);
+    }
+    schema
+}
+
 fn get_input_schema(sub_recipe: &SubRecipe) -> Result<Value> {
     let mut sub_recipe_params_map = HashMap::<String, String>::new();
     if let Some(params_with_value) = &sub_recipe.values {
@@ -49,13 +84,7 @@ fn get_input_schema(sub_recipe: &SubRecipe) -> Result<Value> {
             if sub_recipe_params_map.contains_key(&param.key) {
                 continue;
             }
-            properties.insert(
-                param.key.clone(),
-                json!({
-                    "type": param.input_type.to_string(),
-                    "description": param.description.clone(),
-                }),
-            );
+            properties.insert(param.key.clone(), parameter_schema(&param));
             if !matches!(param.requirement, RecipeParameterRequirement::Optional) {
                 required.push(param.key);
             }
@@ -57,6 +57,7 @@ mod tests {
             },
             recipe::SubRecipe,
         };
+        use serde_json::json;
 
         #[test]
         fn test_get_input_schema_with_parameters() {
@@ -151,5 +152,63 @@ mod tests {
             assert_eq!(result["required"].as_array().unwrap().len(), 1);
             assert_eq!(result["required"][0], "key1");
         }
+
+        #[test]
+        fn test_get_input_schema_with_constrained_parameters() {
+            let sub_recipe_file_content = r#"{
+                "version": "1.0.0",
+                "title": "Test Recipe",
+                "description": "A test recipe",
+                "prompt": "Test prompt",
+                "parameters": [
+                    {
+                        "key": "env",
+                        "input_type": "select",
+                        "requirement": "required",
+                        "description": "Target environment",
+                        "options": ["staging", "production"]
+                    },
+                    {
+                        "key": "tickets",
+                        "input_type": "list",
+                        "requirement": "required",
+                        "description": "Tickets to look at",
+                        "pattern": "[A-Z]+-[0-9]+"
+                    },
+                    {
+                        "key": "retries",
+                        "input_type": "number",
+                        "requirement": "required",
+                        "description": "Retries",
+                        "min": 0,
+                        "max": 5
+                    }
+                ]
+            }"#;
+
+            let temp_dir = tempfile::tempdir().unwrap();
+            let temp_file = temp_dir.path().join("test_sub_recipe.yaml");
+            std::fs::write(&temp_file, sub_recipe_file_content).unwrap();
+            let sub_recipe = SubRecipe {
+                name: "test_sub_recipe".to_string(),
+                path: temp_file.to_string_lossy().to_string(),
+                values: None,
+            };
+
+            let result = get_input_schema(&sub_recipe).unwrap();
+            let properties = &result["properties"];
+            assert_eq!(
+                properties["env"],
+                json!({
+                    "type": "string",
+                    "description": "Target environment",
+                    "enum": ["staging", "production"]
+                })
+            );
+            assert_eq!(properties["tickets"]["type"], "array");
+            assert_eq!(properties["tickets"]["items"]["pattern"], "[A-Z]+-[0-9]+");
+            assert_eq!(properties["retries"]["minimum"], 0.0);
+            assert_eq!(properties["retries"]["maximum"], 5.0);
+        }
     }
 }
@@ -45,6 +45,7 @@ impl Agent {
             }
             _ => self.list_tools(None).await,
         };
+        self.retain_scoped_tools(&mut tools).await;
         // Add frontend tools
         let frontend_tools = self.frontend_tools.lock().await;
         for frontend_tool in frontend_tools.values() {
@@ -105,6 +106,22 @@ impl Agent {
             })
     }
 
+    /// Names of the tools that can run concurrently with each other within a turn: tools
+    /// annotated as read-only, and idempotent tools that are not destructive
+    pub(crate) fn parallel_safe_tools(tools: &[Tool]) -> HashSet<String> {
+        tools
+            .iter()
+            .filter(|tool| match &tool.annotations {
+                Some(annotations) => {
+                    annotations.read_only_hint
+                        || (annotations.idempotent_hint && !annotations.destructive_hint)
+                }
+                None => false,
+            })
+            .map(|tool| tool.name.clone())
+            .collect()
+    }
+
     /// Generate a response from the LLM provider
     /// Handles toolshim transformations if needed
     pub(crate) async fn generate_response_from_provider(
@@ -231,6 +248,9 @@ impl Agent {
         let mut metadata = session::storage::read_metadata(&session_file_path)?;
 
         metadata.schedule_id = session_config.schedule_id.clone();
+        if metadata.profile.is_none() {
+            metadata.profile = session_config.profile.clone();
+        }
 
         metadata.total_tokens = usage.usage.total_tokens;
         metadata.input_tokens = usage.usage.input_tokens;
@@ -253,6 +273,27 @@ impl Agent {
             usage.usage.output_tokens,
         );
 
+        session::storage::update_metadata(&session_file_path, &metadata).await?;
+        session::storage::record_usage(&session_file_path, usage)?;
+
+        Ok(())
+    }
+
+    /// Record the outcomes of hooks that ran since the last flush in the session file
+    pub(crate) async fn flush_hook_outcomes(
+        &self,
+        session_config: Option<&crate::agents::types::SessionConfig>,
+    ) -> Result<()> {
+        let outcomes = std::mem::take(&mut *self.hook_outcomes.lock().await);
+        let session_config = match session_config {
+            Some(session_config) if !outcomes.is_empty() => session_config,
+            _ => return Ok(()),
+        };
+
+        let session_file_path = session::storage::get_path(session_config.id.clone())
+            .map_err(|e| anyhow::anyhow!("Failed to get session file path: {}", e))?;
+        let mut metadata = session::storage::read_metadata(&session_file_path)?;
+        metadata.hook_outcomes.extend(outcomes);
         session::storage::update_metadata(&session_file_path, &metadata).await?;
 
         Ok(())
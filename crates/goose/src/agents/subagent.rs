@@ -438,7 +438,20 @@ impl SubAgent {
                                     .dispatch_tool_call(tool_call.clone())
                                     .await
                                 {
-                                    Ok(result) => result.result.await,
+                                    Ok(result) => match self.recipe_tool_timeout(&tool_call.name) {
+                                        Some(secs) => tokio::time::timeout(
+                                            std::time::Duration::from_secs(secs),
+                                            result.result,
+                                        )
+                                        .await
+                                        .unwrap_or_else(|_| {
+                                            Err(ToolError::Timeout {
+                                                tool: tool_call.name.clone(),
+                                                seconds: secs,
+                                            })
+                                        }),
+                                        None => result.result.await,
+                                    },
                                     Err(e) => Err(ToolError::ExecutionError(e.to_string())),
                                 }
                             };
@@ -579,6 +592,19 @@ impl SubAgent {
         self.missing_extensions.lock().await.clone()
     }
 
+    /// Time limit the subagent's recipe sets for a tool, on top of the parent's own limits
+    fn recipe_tool_timeout(&self, tool_name: &str) -> Option<u64> {
+        self.config
+            .recipe
+            .as_ref()?
+            .settings
+            .as_ref()?
+            .tool_timeouts
+            .as_ref()?
+            .get(tool_name)
+            .copied()
+    }
+
     /// Filter out subagent spawning tools to prevent infinite recursion
     fn filter_subagent_tools(tools: Vec<Tool>) -> Vec<Tool> {
         let original_count = tools.len();
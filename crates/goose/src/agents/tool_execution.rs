@@ -1,7 +1,10 @@
+use std::collections::HashSet;
 use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_stream::try_stream;
+use chrono::{DateTime, Utc};
 use futures::stream::{self, BoxStream};
 use futures::{Stream, StreamExt};
 use mcp_core::protocol::JsonRpcMessage;
@@ -29,9 +32,59 @@ impl From<ToolResult<Vec<Content>>> for ToolCallResult {
     }
 }
 
-use super::agent::{tool_stream, ToolStream};
+use super::agent::{tool_stream, ToolStream, ToolStreamItem};
 use crate::agents::Agent;
 
+/// An item produced while running the tool calls of a turn, tagged with timing markers so
+/// callers can report when each call started and how long it took
+pub(crate) enum ScheduledToolItem {
+    Started(DateTime<Utc>),
+    Stream(ToolStreamItem<ToolResult<Vec<Content>>>),
+    Finished(DateTime<Utc>, Duration),
+}
+
+fn timed_tool_stream(
+    request_id: String,
+    tool_stream: ToolStream,
+) -> BoxStream<'static, (String, ScheduledToolItem)> {
+    Box::pin(async_stream::stream! {
+        // The body only runs once the stream is first polled, which is when the call starts
+        let started_at = Utc::now();
+        let started = Instant::now();
+        yield (request_id.clone(), ScheduledToolItem::Started(started_at));
+
+        let mut tool_stream = tool_stream;
+        while let Some(item) = tool_stream.next().await {
+            yield (request_id.clone(), ScheduledToolItem::Stream(item));
+        }
+
+        yield (request_id, ScheduledToolItem::Finished(started_at, started.elapsed()));
+    })
+}
+
+/// Run the tool calls of a turn. Consecutive calls listed in `parallel_request_ids` run
+/// concurrently; any other call waits for the calls before it to finish and holds back the
+/// calls after it, so destructive tools still run in the order the model requested them.
+pub(crate) fn schedule_tool_streams(
+    tool_streams: Vec<(String, ToolStream)>,
+    parallel_request_ids: &HashSet<String>,
+) -> BoxStream<'static, (String, ScheduledToolItem)> {
+    let mut batches: Vec<Vec<BoxStream<'static, (String, ScheduledToolItem)>>> = Vec::new();
+    let mut last_parallel = false;
+
+    for (request_id, tool_stream) in tool_streams {
+        let parallel = parallel_request_ids.contains(&request_id);
+        let timed = timed_tool_stream(request_id, tool_stream);
+        match batches.last_mut() {
+            Some(batch) if parallel && last_parallel => batch.push(timed),
+            _ => batches.push(vec![timed]),
+        }
+        last_parallel = parallel;
+    }
+
+    stream::iter(batches).flat_map(stream::select_all).boxed()
+}
+
 pub const DECLINED_RESPONSE: &str = "The user has declined to run this tool. \
     DO NOT attempt to call this tool again. \
     If there are no alternative methods to proceed, clearly explain the situation and STOP.";
@@ -127,3 +180,85 @@ impl Agent {
         .boxed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sleeping_tool(millis: u64) -> ToolStream {
+        tool_stream(Box::new(stream::empty()), async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            Ok(vec![Content::text("done")])
+        })
+    }
+
+    async fn run_schedule(
+        calls: Vec<(&str, u64)>,
+        parallel: &[&str],
+    ) -> Vec<(String, &'static str)> {
+        let tool_streams = calls
+            .into_iter()
+            .map(|(id, millis)| (id.to_string(), sleeping_tool(millis)))
+            .collect();
+        let parallel_request_ids = parallel.iter().map(|id| id.to_string()).collect();
+
+        schedule_tool_streams(tool_streams, &parallel_request_ids)
+            .filter_map(|(request_id, item)| async move {
+                match item {
+                    ScheduledToolItem::Started(_) => Some((request_id, "started")),
+                    ScheduledToolItem::Finished(_, _) => Some((request_id, "finished")),
+                    ScheduledToolItem::Stream(_) => None,
+                }
+            })
+            .collect()
+            .await
+    }
+
+    fn position(events: &[(String, &str)], id: &str, kind: &str) -> usize {
+        events
+            .iter()
+            .position(|(request_id, event)| request_id == id && *event == kind)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_parallel_calls_overlap() {
+        let events = run_schedule(vec![("a", 50), ("b", 10)], &["a", "b"]).await;
+
+        // Both calls start before either finishes, and the shorter one finishes first
+        assert!(position(&events, "b", "started") < position(&events, "a", "finished"));
+        assert!(position(&events, "b", "finished") < position(&events, "a", "finished"));
+    }
+
+    #[tokio::test]
+    async fn test_serial_call_waits_for_previous_calls() {
+        let events = run_schedule(vec![("a", 30), ("b", 10), ("c", 10)], &["a", "b"]).await;
+
+        let c_started = position(&events, "c", "started");
+        assert!(position(&events, "a", "finished") < c_started);
+        assert!(position(&events, "b", "finished") < c_started);
+    }
+
+    #[tokio::test]
+    async fn test_serial_call_holds_back_later_calls() {
+        let events = run_schedule(vec![("a", 30), ("b", 10), ("c", 10)], &["b", "c"]).await;
+
+        assert!(position(&events, "a", "finished") < position(&events, "b", "started"));
+        assert!(position(&events, "c", "started") < position(&events, "b", "finished"));
+    }
+
+    #[tokio::test]
+    async fn test_timing_reports_duration() {
+        let tool_streams = vec![("a".to_string(), sleeping_tool(20))];
+        let items: Vec<_> = schedule_tool_streams(tool_streams, &HashSet::new())
+            .collect()
+            .await;
+
+        match items.last() {
+            Some((_, ScheduledToolItem::Finished(_, duration))) => {
+                assert!(*duration >= Duration::from_millis(20));
+            }
+            _ => panic!("expected the last item to be the finished marker"),
+        }
+    }
+}
@@ -24,8 +24,11 @@ pub struct SessionConfig {
     pub working_dir: PathBuf,
     /// ID of the schedule that triggered this session, if any
     pub schedule_id: Option<String>,
-    /// Execution mode for scheduled jobs: "foreground" or "background"
+    /// Goose mode of this session instead of GOOSE_MODE, or "foreground" or "background" for
+    /// scheduled jobs
     pub execution_mode: Option<String>,
     /// Maximum number of turns (iterations) allowed without user input
     pub max_turns: Option<u32>,
+    /// Configuration profile the session runs with, recorded in its metadata
+    pub profile: Option<String>,
 }
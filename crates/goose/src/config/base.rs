@@ -1,14 +1,24 @@
+use super::layers::{
+    find_project_config_dir, is_trusted_project, merge_layer, parse_layer, record_sources,
+    system_config_dir, ConfigEntry, ConfigLayer, ConfigSource, TRUSTED_PROJECTS_CONFIG_KEY,
+};
+use super::profiles::{Profile, PROFILES_CONFIG_KEY, PROFILE_CONFIG_KEY};
+use super::registry::{self, ConfigIssue, KeySpec, CONFIG_KEYS};
+use super::secrets::{
+    select_secret_store, FileStore, KeyringStore, SecretStore, SECRET_STORE_CONFIG_KEY,
+};
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
 use fs2::FileExt;
-use keyring::Entry;
 use once_cell::sync::{Lazy, OnceCell};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
 use thiserror::Error;
 
 pub static APP_STRATEGY: Lazy<AppStrategyArgs> = Lazy::new(|| AppStrategyArgs {
@@ -18,7 +28,6 @@ pub static APP_STRATEGY: Lazy<AppStrategyArgs> = Lazy::new(|| AppStrategyArgs {
 });
 
 const KEYRING_SERVICE: &str = "goose";
-const KEYRING_USERNAME: &str = "secrets";
 
 #[cfg(test)]
 const TEST_KEYRING_SERVICE: &str = "goose-test";
@@ -37,6 +46,8 @@ pub enum ConfigError {
     KeyringError(String),
     #[error("Failed to lock config file: {0}")]
     LockError(String),
+    #[error("Failed to access secret store: {0}")]
+    SecretStoreError(String),
 }
 
 impl From<serde_json::Error> for ConfigError {
@@ -65,17 +76,25 @@ impl From<keyring::Error> for ConfigError {
 /// - Environment variable overrides
 /// - YAML-based configuration file storage
 /// - Hot reloading of configuration changes
-/// - Secure secret storage in system keyring
+/// - Secure secret storage in the system keyring or another secret store
 ///
 /// Configuration values are loaded with the following precedence:
 /// 1. Environment variables (exact key match)
-/// 2. Configuration file (~/.config/goose/config.yaml by default)
+/// 2. The active profile, selected with GOOSE_PROFILE or [`set_profile`](Config::set_profile)
+/// 3. Project configuration (`.goose/config.yaml` in the working directory or a parent), once
+///    the project is trusted with [`trust_project`](Config::trust_project)
+/// 4. User configuration file (~/.config/goose/config.yaml by default)
+/// 5. System configuration (/etc/goose/config.yaml on Unix)
+///
+/// Higher layers replace values of lower ones, except for the maps in
+/// [`MERGED_KEYS`](super::layers::MERGED_KEYS) which are merged entry by entry. Only the user
+/// configuration file is ever written.
 ///
 /// Secrets are loaded with the following precedence:
 /// 1. Environment variables (exact key match)
-/// 2. System keyring (which can be disabled with GOOSE_DISABLE_KEYRING)
-/// 3. If the keyring is disabled, secrets are stored in a secrets file
-///    (~/.config/goose/secrets.yaml by default)
+/// 2. The secret store selected with GOOSE_SECRET_STORE, see [`SecretStoreConfig`](super::SecretStoreConfig)
+/// 3. Otherwise the system keyring, or when GOOSE_DISABLE_KEYRING is set a plain text secrets
+///    file (~/.config/goose/secrets.yaml by default)
 ///
 /// # Examples
 ///
@@ -105,12 +124,15 @@ impl From<keyring::Error> for ConfigError {
 /// For Goose-specific configuration, consider prefixing with "goose_" to avoid conflicts.
 pub struct Config {
     config_path: PathBuf,
-    secrets: SecretStorage,
-}
-
-enum SecretStorage {
-    Keyring { service: String },
-    File { path: PathBuf },
+    secrets: Box<dyn SecretStore>,
+    /// System and project files read around the user's config file
+    layers: Vec<ConfigLayer>,
+    /// Project config file found but not loaded because the project is not trusted
+    untrusted_project: Option<PathBuf>,
+    /// Profile selected for this process, overriding GOOSE_PROFILE
+    profile: RwLock<Option<String>>,
+    /// Whether the user's config file has been checked against the registry yet
+    checked: AtomicBool,
 }
 
 // Global instance
@@ -129,18 +151,57 @@ impl Default for Config {
 
         let config_path = config_dir.join("config.yaml");
 
-        let secrets = match env::var("GOOSE_DISABLE_KEYRING") {
-            Ok(_) => SecretStorage::File {
-                path: config_dir.join("secrets.yaml"),
-            },
-            Err(_) => SecretStorage::Keyring {
-                service: KEYRING_SERVICE.to_string(),
-            },
+        let system_path = system_config_dir().join("config.yaml");
+        let mut layers = vec![ConfigLayer {
+            source: ConfigSource::System,
+            path: system_path.clone(),
+        }];
+        let mut untrusted_project = None;
+        if let Some(project_config_dir) = env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_config_dir(&cwd, Some(&config_dir)))
+        {
+            let path = project_config_dir.join("config.yaml");
+            let project_dir = project_config_dir.parent().unwrap_or(&project_config_dir);
+            // A cloned repository could otherwise add extensions or hooks that run commands
+            if is_trusted_project(project_dir, &[&system_path, &config_path]) {
+                layers.push(ConfigLayer {
+                    source: ConfigSource::Project,
+                    path,
+                });
+            } else if path.exists() {
+                untrusted_project = Some(path);
+            }
+        }
+
+        // Only the user's own files may choose a store that runs commands, a cloned
+        // repository or an inherited environment must not receive every stored secret
+        let (setting, allow_commands) = match env::var(SECRET_STORE_CONFIG_KEY) {
+            Ok(val) => (
+                Some(serde_json::from_str(&val).unwrap_or(Value::String(val))),
+                false,
+            ),
+            Err(_) => (
+                parse_layer(&config_path)
+                    .remove(SECRET_STORE_CONFIG_KEY)
+                    .or_else(|| parse_layer(&system_path).remove(SECRET_STORE_CONFIG_KEY)),
+                true,
+            ),
         };
-        Config {
+        let secrets = select_secret_store(setting, allow_commands, &config_dir, KEYRING_SERVICE);
+
+        let config = Config {
             config_path,
             secrets,
+            layers,
+            untrusted_project,
+            profile: RwLock::new(None),
+            checked: AtomicBool::new(false),
+        };
+        if let Err(e) = config.migrate_file() {
+            tracing::warn!("Failed to migrate {}: {}", config.config_path.display(), e);
         }
+        config
     }
 }
 
@@ -160,9 +221,11 @@ impl Config {
     pub fn new<P: AsRef<Path>>(config_path: P, service: &str) -> Result<Self, ConfigError> {
         Ok(Config {
             config_path: config_path.as_ref().to_path_buf(),
-            secrets: SecretStorage::Keyring {
-                service: service.to_string(),
-            },
+            secrets: Box::new(KeyringStore::new(service)),
+            layers: Vec::new(),
+            untrusted_project: None,
+            profile: RwLock::new(None),
+            checked: AtomicBool::new(false),
         })
     }
 
@@ -176,12 +239,66 @@ impl Config {
     ) -> Result<Self, ConfigError> {
         Ok(Config {
             config_path: config_path.as_ref().to_path_buf(),
-            secrets: SecretStorage::File {
-                path: secrets_path.as_ref().to_path_buf(),
-            },
+            secrets: Box::new(FileStore::new(secrets_path)),
+            layers: Vec::new(),
+            untrusted_project: None,
+            profile: RwLock::new(None),
+            checked: AtomicBool::new(false),
         })
     }
 
+    /// Create a new configuration instance keeping its secrets in `secrets`
+    pub fn new_with_secret_store<P: AsRef<Path>>(
+        config_path: P,
+        secrets: Box<dyn SecretStore>,
+    ) -> Result<Self, ConfigError> {
+        Ok(Config {
+            config_path: config_path.as_ref().to_path_buf(),
+            secrets,
+            layers: Vec::new(),
+            untrusted_project: None,
+            profile: RwLock::new(None),
+            checked: AtomicBool::new(false),
+        })
+    }
+
+    /// Read system or project configuration files around the user's config file
+    pub fn with_layers(mut self, layers: Vec<ConfigLayer>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// The system and project configuration files this config reads
+    pub fn layers(&self) -> &[ConfigLayer] {
+        &self.layers
+    }
+
+    /// Project config file of the working directory that is ignored until the project is trusted
+    pub fn untrusted_project(&self) -> Option<&Path> {
+        self.untrusted_project.as_deref()
+    }
+
+    /// Trust a project directory, loading its `.goose/config.yaml` from the next start on
+    pub fn trust_project(&self, project_dir: &Path) -> Result<(), ConfigError> {
+        let project_dir = project_dir
+            .canonicalize()
+            .unwrap_or_else(|_| project_dir.to_path_buf());
+        let mut values = self.load_values()?;
+        let trusted = values
+            .entry(TRUSTED_PROJECTS_CONFIG_KEY.to_string())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if !trusted.is_array() {
+            *trusted = Value::Array(Vec::new());
+        }
+        let dir = Value::String(project_dir.to_string_lossy().into_owned());
+        if let Value::Array(dirs) = trusted {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+        self.save_values(values)
+    }
+
     /// Check if this config already exists
     pub fn exists(&self) -> bool {
         self.config_path.exists()
@@ -254,7 +371,7 @@ impl Config {
 
         // First attempt: try to parse the current config
         match self.parse_yaml_content(&file_content) {
-            Ok(values) => Ok(values),
+            Ok(values) => Ok(self.migrate_and_check(values)),
             Err(parse_error) => {
                 tracing::warn!(
                     "Config file appears corrupted, attempting recovery: {}",
@@ -280,6 +397,56 @@ impl Config {
         }
     }
 
+    /// Rename outdated keys in the user's config file and save it
+    ///
+    /// This runs once when the global config is created, holding a lock so that processes
+    /// starting together do not each rewrite the file. Reads migrate in memory only.
+    pub fn migrate_file(&self) -> Result<Vec<String>, ConfigError> {
+        if !self.config_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let lock_path = self.config_path.with_extension("yaml.lock");
+        let lock = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&lock_path)?;
+        lock.lock_exclusive()
+            .map_err(|e| ConfigError::LockError(e.to_string()))?;
+
+        let mut values = self.parse_yaml_content(&std::fs::read_to_string(&self.config_path)?)?;
+        let changes = registry::migrate(&mut values);
+        if !changes.is_empty() {
+            for change in &changes {
+                tracing::info!("Migrated {}: {}", self.config_path.display(), change);
+            }
+            self.save_values(values)?;
+        }
+        Ok(changes)
+    }
+
+    // Migrate renamed keys in memory and warn once about invalid values
+    fn migrate_and_check(&self, mut values: HashMap<String, Value>) -> HashMap<String, Value> {
+        registry::migrate(&mut values);
+
+        if !self.checked.swap(true, Ordering::Relaxed) {
+            let issues = registry::validate_values(
+                &values,
+                &HashSet::new(),
+                ConfigSource::User,
+                Some(self.config_path.clone()),
+            );
+            for issue in issues
+                .iter()
+                .filter(|issue| issue.kind == registry::IssueKind::Invalid)
+            {
+                tracing::warn!("Invalid config value {}", issue);
+            }
+        }
+        values
+    }
+
     // Parse YAML content into HashMap
     fn parse_yaml_content(&self, content: &str) -> Result<HashMap<String, Value>, ConfigError> {
         if content.trim().is_empty() {
@@ -363,6 +530,142 @@ impl Config {
         paths
     }
 
+    /// Select the profile for this process, None falls back to GOOSE_PROFILE
+    pub fn set_profile(&self, name: Option<String>) {
+        *self.profile.write().unwrap() = name;
+    }
+
+    /// Name of the active profile, from [`set_profile`](Self::set_profile), the environment or
+    /// the config files in that order
+    pub fn active_profile(&self) -> Option<String> {
+        if let Some(name) = self.profile.read().unwrap().clone() {
+            return Some(name);
+        }
+        if let Ok(name) = env::var(PROFILE_CONFIG_KEY) {
+            return Some(name);
+        }
+        self.load_file_values()
+            .ok()?
+            .get(PROFILE_CONFIG_KEY)?
+            .as_str()
+            .map(str::to_string)
+    }
+
+    /// The values set by the active profile, if it exists in `values`
+    fn profile_overlay(&self, values: &HashMap<String, Value>) -> Option<HashMap<String, Value>> {
+        let name = match self.profile.read().unwrap().clone() {
+            Some(name) => name,
+            None => env::var(PROFILE_CONFIG_KEY)
+                .ok()
+                .or_else(|| values.get(PROFILE_CONFIG_KEY)?.as_str().map(str::to_string))?,
+        };
+        let profile = values.get(PROFILES_CONFIG_KEY)?.get(&name)?.clone();
+        let profile: Profile = match serde_json::from_value(profile) {
+            Ok(profile) => profile,
+            Err(e) => {
+                tracing::warn!("Ignoring invalid profile {}: {}", name, e);
+                return None;
+            }
+        };
+        Some(profile.overlay(values.get("extensions")))
+    }
+
+    /// Load the values of every layer merged in order of precedence, including the active profile
+    pub fn load_merged_values(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let mut values = self.load_file_values()?;
+        if let Some(overlay) = self.profile_overlay(&values) {
+            merge_layer(&mut values, overlay);
+        }
+        Ok(values)
+    }
+
+    fn load_file_values(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let mut values = HashMap::new();
+        for layer in self.layers_below(ConfigSource::User) {
+            merge_layer(&mut values, layer.read());
+        }
+        merge_layer(&mut values, self.load_values()?);
+        for layer in self.layers_above(ConfigSource::User) {
+            merge_layer(&mut values, layer.read());
+        }
+        Ok(values)
+    }
+
+    fn layers_below(&self, source: ConfigSource) -> impl Iterator<Item = &ConfigLayer> {
+        self.layers
+            .iter()
+            .filter(move |layer| layer.source < source)
+    }
+
+    fn layers_above(&self, source: ConfigSource) -> impl Iterator<Item = &ConfigLayer> {
+        self.layers
+            .iter()
+            .filter(move |layer| layer.source > source)
+    }
+
+    /// Every effective value with the layer it came from, sorted by key
+    ///
+    /// Entries of merged keys are listed one by one, e.g. `extensions.developer`. Environment
+    /// variables are included when they override a file value or start with `GOOSE_`.
+    pub fn effective_values(&self) -> Result<Vec<ConfigEntry>, ConfigError> {
+        let mut sources = HashMap::new();
+        for layer in self.layers_below(ConfigSource::User) {
+            record_sources(&mut sources, &layer.read(), layer.source, Some(&layer.path));
+        }
+        record_sources(
+            &mut sources,
+            &self.load_values()?,
+            ConfigSource::User,
+            Some(&self.config_path),
+        );
+        for layer in self.layers_above(ConfigSource::User) {
+            record_sources(&mut sources, &layer.read(), layer.source, Some(&layer.path));
+        }
+        if let Some(overlay) = self.profile_overlay(&self.load_file_values()?) {
+            record_sources(&mut sources, &overlay, ConfigSource::Profile, None);
+        }
+
+        let values = self.load_merged_values()?;
+        let mut entries: Vec<ConfigEntry> = sources
+            .into_iter()
+            .filter_map(|(key, (source, path))| {
+                let value = match key.split_once('.') {
+                    Some((parent, name)) if !values.contains_key(&key) => {
+                        values.get(parent)?.get(name)?.clone()
+                    }
+                    _ => values.get(&key)?.clone(),
+                };
+                Some(ConfigEntry {
+                    key,
+                    value,
+                    source,
+                    path,
+                })
+            })
+            .collect();
+
+        for entry in entries.iter_mut() {
+            if let Ok(val) = env::var(entry.key.to_uppercase()) {
+                entry.value = serde_json::from_str(&val).unwrap_or(Value::String(val));
+                entry.source = ConfigSource::Env;
+                entry.path = None;
+            }
+        }
+        for (key, val) in env::vars() {
+            if key.starts_with("GOOSE_") && !entries.iter().any(|entry| entry.key == key) {
+                entries.push(ConfigEntry {
+                    key,
+                    value: serde_json::from_str(&val).unwrap_or(Value::String(val)),
+                    source: ConfigSource::Env,
+                    path: None,
+                });
+            }
+        }
+
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
+
     // Try to load init-config.yaml from workspace root if it exists
     fn load_init_config_if_exists(&self) -> Result<HashMap<String, Value>, ConfigError> {
         load_init_config_from_workspace()
@@ -476,35 +779,9 @@ impl Config {
         Ok(())
     }
 
-    // Load current secrets from the keyring
+    // Load current secrets from the secret store
     pub fn load_secrets(&self) -> Result<HashMap<String, Value>, ConfigError> {
-        match &self.secrets {
-            SecretStorage::Keyring { service } => {
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
-
-                match entry.get_password() {
-                    Ok(content) => {
-                        let values: HashMap<String, Value> = serde_json::from_str(&content)?;
-                        Ok(values)
-                    }
-                    Err(keyring::Error::NoEntry) => Ok(HashMap::new()),
-                    Err(e) => Err(ConfigError::KeyringError(e.to_string())),
-                }
-            }
-            SecretStorage::File { path } => {
-                if path.exists() {
-                    let file_content = std::fs::read_to_string(path)?;
-                    let yaml_value: serde_yaml::Value = serde_yaml::from_str(&file_content)?;
-                    let json_value: Value = serde_json::to_value(yaml_value)?;
-                    match json_value {
-                        Value::Object(map) => Ok(map.into_iter().collect()),
-                        _ => Ok(HashMap::new()),
-                    }
-                } else {
-                    Ok(HashMap::new())
-                }
-            }
-        }
+        self.secrets.load()
     }
 
     // check all possible places for a parameter
@@ -529,7 +806,7 @@ impl Config {
     ///
     /// This will attempt to get the value from:
     /// 1. Environment variable with the exact key name
-    /// 2. Configuration file
+    /// 2. Configuration files, merged from the system, user and project layers
     ///
     /// The value will be deserialized into the requested type. This works with
     /// both simple types (String, i32, etc.) and complex types that implement
@@ -550,8 +827,8 @@ impl Config {
             return Ok(serde_json::from_value(value)?);
         }
 
-        // Load current values from file
-        let values = self.load_values()?;
+        // Load current values from the config files
+        let values = self.load_merged_values()?;
 
         // Then check our stored values
         values
@@ -560,6 +837,107 @@ impl Config {
             .and_then(|v| Ok(serde_json::from_value(v.clone())?))
     }
 
+    /// Get a value from the user's configuration file only.
+    ///
+    /// Environment variables and system or project files are ignored. Use this when reading a
+    /// value to modify and write back with [`set_param`](Self::set_param), so values from
+    /// other layers are not copied into the user's file.
+    pub fn get_user_param<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, ConfigError> {
+        self.load_values()?
+            .get(key)
+            .ok_or_else(|| ConfigError::NotFound(key.to_string()))
+            .and_then(|v| Ok(serde_json::from_value(v.clone())?))
+    }
+
+    /// Get the value of a key from the registry, or its default when unset.
+    ///
+    /// Unlike [`get_param`](Self::get_param) the value is checked against the key's type first,
+    /// so an invalid value is reported with the key name and what was expected.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ConfigError if:
+    /// - The key is unset and has no default
+    /// - The value does not have the key's type
+    pub fn get_key<T: for<'de> Deserialize<'de>>(&self, key: &KeySpec) -> Result<T, ConfigError> {
+        let value = match self.get_param::<Value>(key.name) {
+            Ok(value) => value,
+            Err(ConfigError::NotFound(_)) => key
+                .default
+                .map(|default| default.to_value())
+                .ok_or_else(|| ConfigError::NotFound(key.name.to_string()))?,
+            Err(e) => return Err(e),
+        };
+        key.check(&value)
+            .map_err(|e| ConfigError::DeserializeError(format!("{}: {}", key.name, e)))?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Check every configuration layer and environment variable against the registry
+    ///
+    /// Keys are known when they are in the registry, are settings of a provider or are
+    /// environment variables an extension needs. Everything else is reported as unknown.
+    pub fn check(&self) -> Result<Vec<ConfigIssue>, ConfigError> {
+        let merged = self.load_file_values()?;
+        let mut known: HashSet<String> = crate::providers::providers()
+            .into_iter()
+            .flat_map(|metadata| metadata.config_keys)
+            .map(|key| key.name)
+            .collect();
+        if let Some(Value::Object(extensions)) = merged.get("extensions") {
+            known.extend(
+                extensions
+                    .values()
+                    .filter_map(|entry| entry.get("env_keys")?.as_array().cloned())
+                    .flatten()
+                    .filter_map(|key| key.as_str().map(str::to_string)),
+            );
+        }
+
+        let mut issues = Vec::new();
+        for layer in self.layers_below(ConfigSource::User) {
+            issues.extend(registry::validate_values(
+                &parse_layer(&layer.path),
+                &known,
+                layer.source,
+                Some(layer.path.clone()),
+            ));
+        }
+        issues.extend(registry::validate_values(
+            &self.load_values()?,
+            &known,
+            ConfigSource::User,
+            Some(self.config_path.clone()),
+        ));
+        for layer in self.layers_above(ConfigSource::User) {
+            issues.extend(registry::validate_values(
+                &parse_layer(&layer.path),
+                &known,
+                layer.source,
+                Some(layer.path.clone()),
+            ));
+        }
+
+        let env_values: HashMap<String, Value> = CONFIG_KEYS
+            .iter()
+            .filter_map(|key| {
+                let val = env::var(key.name.to_uppercase()).ok()?;
+                let value = serde_json::from_str(&val).unwrap_or(Value::String(val));
+                Some((key.name.to_string(), value))
+            })
+            .collect();
+        issues.extend(registry::validate_values(
+            &env_values,
+            &known,
+            ConfigSource::Env,
+            None,
+        ));
+        Ok(issues)
+    }
+
     /// Set a configuration value in the config file (non-secret).
     ///
     /// This will immediately write the value to the config file. The value
@@ -608,7 +986,7 @@ impl Config {
     ///
     /// This will attempt to get the value from:
     /// 1. Environment variable with the exact key name
-    /// 2. System keyring
+    /// 2. The secret store, the system keyring by default
     ///
     /// The value will be deserialized into the requested type. This works with
     /// both simple types (String, i32, etc.) and complex types that implement
@@ -617,9 +995,9 @@ impl Config {
     /// # Errors
     ///
     /// Returns a ConfigError if:
-    /// - The key doesn't exist in either environment or keyring
+    /// - The key doesn't exist in either environment or secret store
     /// - The value cannot be deserialized into the requested type
-    /// - There is an error accessing the keyring
+    /// - There is an error accessing the secret store
     pub fn get_secret<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<T, ConfigError> {
         // First check environment variables (convert to uppercase)
         let env_key = key.to_uppercase();
@@ -628,7 +1006,7 @@ impl Config {
             return Ok(serde_json::from_value(value)?);
         }
 
-        // Then check keyring
+        // Then check the secret store
         let values = self.load_secrets()?;
         values
             .get(key)
@@ -636,9 +1014,9 @@ impl Config {
             .and_then(|v| Ok(serde_json::from_value(v.clone())?))
     }
 
-    /// Set a secret value in the system keyring.
+    /// Set a secret value in the secret store.
     ///
-    /// This will store the value in a single JSON object in the system keyring,
+    /// This will store the value in a single map in the secret store,
     /// alongside any other secrets. The value can be any type that can be
     /// serialized to JSON.
     ///
@@ -648,52 +1026,30 @@ impl Config {
     /// # Errors
     ///
     /// Returns a ConfigError if:
-    /// - There is an error accessing the keyring
+    /// - There is an error accessing the secret store
     /// - There is an error serializing the value
     pub fn set_secret(&self, key: &str, value: Value) -> Result<(), ConfigError> {
         let mut values = self.load_secrets()?;
         values.insert(key.to_string(), value);
 
-        match &self.secrets {
-            SecretStorage::Keyring { service } => {
-                let json_value = serde_json::to_string(&values)?;
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
-                entry.set_password(&json_value)?;
-            }
-            SecretStorage::File { path } => {
-                let yaml_value = serde_yaml::to_string(&values)?;
-                std::fs::write(path, yaml_value)?;
-            }
-        };
-        Ok(())
+        self.secrets.save(&values)
     }
 
-    /// Delete a secret from the system keyring.
+    /// Delete a secret from the secret store.
     ///
-    /// This will remove the specified key from the JSON object in the system keyring.
+    /// This will remove the specified key from the map in the secret store.
     /// Other secrets will remain unchanged.
     ///
     /// # Errors
     ///
     /// Returns a ConfigError if:
-    /// - There is an error accessing the keyring
+    /// - There is an error accessing the secret store
     /// - There is an error serializing the remaining values
     pub fn delete_secret(&self, key: &str) -> Result<(), ConfigError> {
         let mut values = self.load_secrets()?;
         values.remove(key);
 
-        match &self.secrets {
-            SecretStorage::Keyring { service } => {
-                let json_value = serde_json::to_string(&values)?;
-                let entry = Entry::new(service, KEYRING_USERNAME)?;
-                entry.set_password(&json_value)?;
-            }
-            SecretStorage::File { path } => {
-                let yaml_value = serde_yaml::to_string(&values)?;
-                std::fs::write(path, yaml_value)?;
-            }
-        };
-        Ok(())
+        self.secrets.save(&values)
     }
 }
 
@@ -753,7 +1109,9 @@ pub fn load_init_config_from_workspace() -> Result<HashMap<String, Value>, Confi
 
 #[cfg(test)]
 mod tests {
+    use super::super::secrets::KEYRING_USERNAME;
     use super::*;
+    use keyring::Entry;
     use serial_test::serial;
     use tempfile::NamedTempFile;
 
@@ -786,6 +1144,126 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[serial]
+    fn test_layered_config() -> Result<(), ConfigError> {
+        let dir = tempfile::tempdir().unwrap();
+        let system = dir.path().join("system.yaml");
+        let user = dir.path().join("user.yaml");
+        let project = dir.path().join("project.yaml");
+        std::fs::write(
+            &system,
+            "LAYER_MODEL: system-model\nLAYER_MODE: approve\nextensions:\n  memory:\n    enabled: true\n",
+        )?;
+        std::fs::write(
+            &user,
+            "LAYER_MODEL: user-model\nextensions:\n  developer:\n    enabled: true\n",
+        )?;
+        std::fs::write(
+            &project,
+            "LAYER_MODEL: project-model\nextensions:\n  memory:\n    enabled: false\n",
+        )?;
+
+        let config = Config::new(&user, TEST_KEYRING_SERVICE)?.with_layers(vec![
+            ConfigLayer {
+                source: ConfigSource::Project,
+                path: project.clone(),
+            },
+            ConfigLayer {
+                source: ConfigSource::System,
+                path: system.clone(),
+            },
+        ]);
+
+        let model: String = config.get_param("LAYER_MODEL")?;
+        assert_eq!(model, "project-model");
+        let mode: String = config.get_param("LAYER_MODE")?;
+        assert_eq!(mode, "approve");
+        let extensions: Value = config.get_param("extensions")?;
+        assert_eq!(
+            extensions,
+            serde_json::json!({
+                "developer": {"enabled": true},
+                "memory": {"enabled": false},
+            })
+        );
+
+        // Writes only touch the user's file
+        config.set_param("LAYER_OTHER", Value::Bool(true))?;
+        let user_values = config.load_values()?;
+        assert!(!user_values.contains_key("LAYER_MODE"));
+        let user_extensions: Value = config.get_user_param("extensions")?;
+        assert_eq!(
+            user_extensions,
+            serde_json::json!({"developer": {"enabled": true}})
+        );
+
+        std::env::set_var("LAYER_MODE", "auto");
+        let entries = config.effective_values()?;
+        std::env::remove_var("LAYER_MODE");
+        let source = |key: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.key == key)
+                .map(|entry| (entry.source, entry.value.clone()))
+        };
+        assert_eq!(
+            source("LAYER_MODEL"),
+            Some((ConfigSource::Project, Value::from("project-model")))
+        );
+        assert_eq!(
+            source("LAYER_MODE"),
+            Some((ConfigSource::Env, Value::from("auto")))
+        );
+        assert_eq!(
+            source("extensions.developer"),
+            Some((ConfigSource::User, serde_json::json!({"enabled": true})))
+        );
+        assert_eq!(
+            source("extensions.memory"),
+            Some((ConfigSource::Project, serde_json::json!({"enabled": false})))
+        );
+        assert_eq!(
+            source("LAYER_OTHER"),
+            Some((ConfigSource::User, Value::Bool(true)))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_profile_overrides_files() -> Result<(), ConfigError> {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(
+            temp_file.path(),
+            "GOOSE_PROVIDER: openai\nGOOSE_MODEL: gpt-4o\nprofiles:\n  local:\n    provider: ollama\n    model: qwen2.5\n",
+        )?;
+        let config = Config::new(temp_file.path(), TEST_KEYRING_SERVICE)?;
+
+        let provider: String = config.get_param("GOOSE_PROVIDER")?;
+        assert_eq!(provider, "openai");
+        assert_eq!(config.active_profile(), None);
+
+        config.set_profile(Some("local".to_string()));
+        assert_eq!(config.active_profile().as_deref(), Some("local"));
+        let provider: String = config.get_param("GOOSE_PROVIDER")?;
+        assert_eq!(provider, "ollama");
+        let model: String = config.get_param("GOOSE_MODEL")?;
+        assert_eq!(model, "qwen2.5");
+
+        let entries = config.effective_values()?;
+        let model = entries.iter().find(|e| e.key == "GOOSE_MODEL").unwrap();
+        assert_eq!(model.source, ConfigSource::Profile);
+
+        // A profile is never written into the user's file
+        config.set_param("GOOSE_MODE", Value::from("auto"))?;
+        let provider: String = config.get_user_param("GOOSE_PROVIDER")?;
+        assert_eq!(provider, "openai");
+
+        Ok(())
+    }
+
     #[test]
     fn test_complex_type() -> Result<(), ConfigError> {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -838,6 +1316,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_migrate_file_only_on_request() -> Result<(), ConfigError> {
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), "GOOSE_PROVIDER__TYPE: openai\n")?;
+        let config = Config::new(temp_file.path(), TEST_KEYRING_SERVICE)?;
+
+        let value: String = config.get_param("GOOSE_PROVIDER")?;
+        assert_eq!(value, "openai");
+        let content = std::fs::read_to_string(temp_file.path())?;
+        assert!(content.contains("GOOSE_PROVIDER__TYPE"));
+
+        assert_eq!(config.migrate_file()?.len(), 1);
+        let content = std::fs::read_to_string(temp_file.path())?;
+        assert!(!content.contains("GOOSE_PROVIDER__TYPE"));
+        assert!(config.migrate_file()?.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_value_management() -> Result<(), ConfigError> {
         let temp_file = NamedTempFile::new().unwrap();
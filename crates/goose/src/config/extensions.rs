@@ -85,7 +85,7 @@ impl ExtensionConfigManager {
         let config = Config::global();
 
         let mut extensions: HashMap<String, ExtensionEntry> = config
-            .get_param("extensions")
+            .get_user_param("extensions")
             .unwrap_or_else(|_| HashMap::new());
 
         let key = entry.config.key();
@@ -100,7 +100,7 @@ impl ExtensionConfigManager {
         let config = Config::global();
 
         let mut extensions: HashMap<String, ExtensionEntry> = config
-            .get_param("extensions")
+            .get_user_param("extensions")
             .unwrap_or_else(|_| HashMap::new());
 
         extensions.remove(key);
@@ -113,7 +113,7 @@ impl ExtensionConfigManager {
         let config = Config::global();
 
         let mut extensions: HashMap<String, ExtensionEntry> = config
-            .get_param("extensions")
+            .get_user_param("extensions")
             .unwrap_or_else(|_| HashMap::new());
 
         if let Some(entry) = extensions.get_mut(key) {
@@ -0,0 +1,304 @@
+use super::registry::migrate;
+use super::secrets::SECRET_STORE_CONFIG_KEY;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Directory holding project configuration, found by walking up from the working directory
+pub const PROJECT_CONFIG_DIR: &str = ".goose";
+
+/// Keys whose values are maps merged entry by entry across layers instead of replaced whole
+pub const MERGED_KEYS: &[&str] = &["extensions", "experiments"];
+
+/// Config key listing the project directories whose `.goose/config.yaml` is loaded
+pub const TRUSTED_PROJECTS_CONFIG_KEY: &str = "GOOSE_TRUSTED_PROJECTS";
+
+/// Keys a project file may not set even once trusted: they decide which projects are trusted,
+/// where secrets are read from and who may use the server
+pub const USER_ONLY_KEYS: &[&str] = &[
+    TRUSTED_PROJECTS_CONFIG_KEY,
+    SECRET_STORE_CONFIG_KEY,
+    "server_tokens",
+];
+
+/// Where a configuration value comes from, ordered from lowest to highest precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSource {
+    /// Machine wide settings, e.g. /etc/goose/config.yaml
+    System,
+    /// The user's own config.yaml, the only layer goose writes to
+    User,
+    /// `.goose/config.yaml` in the working directory or one of its parents
+    Project,
+    /// The active profile
+    Profile,
+    /// Environment variables
+    Env,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigSource::System => "system",
+            ConfigSource::User => "user",
+            ConfigSource::Project => "project",
+            ConfigSource::Profile => "profile",
+            ConfigSource::Env => "env",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A configuration file read in addition to the user's config.yaml
+#[derive(Debug, Clone)]
+pub struct ConfigLayer {
+    pub source: ConfigSource,
+    pub path: PathBuf,
+}
+
+impl ConfigLayer {
+    /// Read the values of the layer, without the [`USER_ONLY_KEYS`] for project files
+    pub fn read(&self) -> HashMap<String, Value> {
+        let mut values = read_layer(&self.path);
+        if self.source == ConfigSource::Project {
+            values.retain(|key, _| {
+                let allowed = !USER_ONLY_KEYS.contains(&key.as_str());
+                if !allowed {
+                    tracing::warn!(
+                        "Ignoring {} in project config {}, it can only be set in the user config",
+                        key,
+                        self.path.display()
+                    );
+                }
+                allowed
+            });
+        }
+        values
+    }
+}
+
+/// An effective configuration value and where it came from
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigEntry {
+    /// The key, with the entry name appended for merged keys, e.g. `extensions.developer`
+    pub key: String,
+    pub value: Value,
+    pub source: ConfigSource,
+    /// File the value was read from, None for profiles and environment variables
+    pub path: Option<PathBuf>,
+}
+
+/// Directory of the machine wide configuration, `GOOSE_SYSTEM_CONFIG_DIR` overrides it
+pub fn system_config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("GOOSE_SYSTEM_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    if cfg!(windows) {
+        let program_data =
+            std::env::var("PROGRAMDATA").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+        PathBuf::from(program_data).join("Block").join("goose")
+    } else {
+        PathBuf::from("/etc/goose")
+    }
+}
+
+/// The nearest `.goose` directory at or above `start`, skipping `exclude`
+///
+/// The user's config directory is passed as `exclude`, so when it is itself named `.goose`
+/// (e.g. `GOOSE_CONFIG_DIR` pointing at `~/.goose`) the user's config is not read a second
+/// time as a project.
+pub fn find_project_config_dir(start: &Path, exclude: Option<&Path>) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_DIR))
+        .filter(|dir| dir.is_dir())
+        .find(|dir| exclude.is_none_or(|exclude| !same_path(dir, exclude)))
+}
+
+/// Whether `project_dir` is listed under [`TRUSTED_PROJECTS_CONFIG_KEY`] in any of `files`
+///
+/// Only the system and user files are passed, so a project can never trust itself.
+pub fn is_trusted_project(project_dir: &Path, files: &[&Path]) -> bool {
+    files
+        .iter()
+        .filter_map(|file| parse_layer(file).remove(TRUSTED_PROJECTS_CONFIG_KEY))
+        .filter_map(|value| match value {
+            Value::Array(dirs) => Some(dirs),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|dir| dir.as_str().map(PathBuf::from))
+        .any(|dir| same_path(&dir, project_dir))
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Read the values of a layer file with renamed keys migrated, see [`migrate`]
+pub fn read_layer(path: &Path) -> HashMap<String, Value> {
+    let mut values = parse_layer(path);
+    migrate(&mut values);
+    values
+}
+
+/// Read the values of a layer file as written, a missing or unreadable file contributes nothing
+pub fn parse_layer(path: &Path) -> HashMap<String, Value> {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return HashMap::new();
+    };
+    if content.trim().is_empty() {
+        return HashMap::new();
+    }
+
+    let parsed = serde_yaml::from_str::<serde_yaml::Value>(&content)
+        .map_err(|e| e.to_string())
+        .and_then(|yaml| serde_json::to_value(yaml).map_err(|e| e.to_string()));
+    match parsed {
+        Ok(Value::Object(map)) => map.into_iter().collect(),
+        Ok(_) => HashMap::new(),
+        Err(e) => {
+            tracing::warn!("Ignoring invalid config file {}: {}", path.display(), e);
+            HashMap::new()
+        }
+    }
+}
+
+/// Put the values of a higher layer over `values`
+///
+/// Keys in [`MERGED_KEYS`] are merged entry by entry, so a project can add an extension
+/// without repeating the user's. Any other value replaces the lower one whole.
+pub fn merge_layer(values: &mut HashMap<String, Value>, layer: HashMap<String, Value>) {
+    for (key, value) in layer {
+        match (values.get_mut(&key), value) {
+            (Some(Value::Object(existing)), Value::Object(entries))
+                if MERGED_KEYS.contains(&key.as_str()) =>
+            {
+                existing.extend(entries);
+            }
+            (_, value) => {
+                values.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Record where each effective value of a layer came from, see [`merge_layer`]
+pub fn record_sources(
+    sources: &mut HashMap<String, (ConfigSource, Option<PathBuf>)>,
+    layer: &HashMap<String, Value>,
+    source: ConfigSource,
+    path: Option<&Path>,
+) {
+    for (key, value) in layer {
+        match value {
+            Value::Object(entries) if MERGED_KEYS.contains(&key.as_str()) => {
+                for name in entries.keys() {
+                    sources.insert(
+                        format!("{}.{}", key, name),
+                        (source, path.map(Path::to_path_buf)),
+                    );
+                }
+            }
+            _ => {
+                sources.insert(key.clone(), (source, path.map(Path::to_path_buf)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn values(value: Value) -> HashMap<String, Value> {
+        match value {
+            Value::Object(map) => map.into_iter().collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_merge_replaces_scalars_and_merges_extensions() {
+        let mut merged = values(json!({
+            "GOOSE_MODEL": "gpt-4o",
+            "GOOSE_MODE": "approve",
+            "extensions": {"developer": {"enabled": true}, "memory": {"enabled": true}},
+        }));
+        merge_layer(
+            &mut merged,
+            values(json!({
+                "GOOSE_MODEL": "claude",
+                "extensions": {"memory": {"enabled": false}, "jira": {"enabled": true}},
+            })),
+        );
+
+        assert_eq!(merged["GOOSE_MODEL"], json!("claude"));
+        assert_eq!(merged["GOOSE_MODE"], json!("approve"));
+        assert_eq!(
+            merged["extensions"],
+            json!({
+                "developer": {"enabled": true},
+                "memory": {"enabled": false},
+                "jira": {"enabled": true},
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_project_config_dir_walks_up() {
+        let root = TempDir::new().unwrap();
+        let project = root.path().join("repo");
+        let nested = project.join("src").join("module");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(find_project_config_dir(&nested, None), None);
+
+        std::fs::create_dir(project.join(PROJECT_CONFIG_DIR)).unwrap();
+        assert_eq!(
+            find_project_config_dir(&nested, None),
+            Some(project.join(PROJECT_CONFIG_DIR))
+        );
+        assert_eq!(
+            find_project_config_dir(&nested, Some(&project.join(PROJECT_CONFIG_DIR))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_project_trust_and_user_only_keys() {
+        let root = TempDir::new().unwrap();
+        let project = root.path().join("repo");
+        std::fs::create_dir_all(project.join(PROJECT_CONFIG_DIR)).unwrap();
+        let layer = ConfigLayer {
+            source: ConfigSource::Project,
+            path: project.join(PROJECT_CONFIG_DIR).join("config.yaml"),
+        };
+        std::fs::write(
+            &layer.path,
+            "GOOSE_MODEL: claude\nGOOSE_SECRET_STORE: {command: {read_command: cat}}\nGOOSE_TRUSTED_PROJECTS: [/]\n",
+        )
+        .unwrap();
+
+        let user = root.path().join("config.yaml");
+        std::fs::write(
+            &user,
+            format!("GOOSE_TRUSTED_PROJECTS: [{}]\n", project.display()),
+        )
+        .unwrap();
+        assert!(is_trusted_project(&project, &[&user]));
+
+        let values = layer.read();
+        assert_eq!(values.get("GOOSE_MODEL"), Some(&json!("claude")));
+        assert!(!values.contains_key(SECRET_STORE_CONFIG_KEY));
+        assert!(!values.contains_key(TRUSTED_PROJECTS_CONFIG_KEY));
+    }
+}
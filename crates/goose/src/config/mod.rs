@@ -1,13 +1,21 @@
 pub mod base;
 mod experiments;
 pub mod extensions;
+pub mod layers;
 pub mod permission;
+pub mod profiles;
+pub mod registry;
+pub mod secrets;
 
 pub use crate::agents::ExtensionConfig;
 pub use base::{Config, ConfigError, APP_STRATEGY};
 pub use experiments::ExperimentManager;
 pub use extensions::{ExtensionConfigManager, ExtensionEntry};
+pub use layers::{ConfigEntry, ConfigLayer, ConfigSource};
 pub use permission::PermissionManager;
+pub use profiles::{Profile, ProfileManager};
+pub use registry::{ConfigIssue, IssueKind, KeySpec};
+pub use secrets::{SecretStore, SecretStoreConfig};
 
 pub use extensions::DEFAULT_DISPLAY_NAME;
 pub use extensions::DEFAULT_EXTENSION;
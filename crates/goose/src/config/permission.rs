@@ -1,3 +1,5 @@
+use super::layers::{find_project_config_dir, system_config_dir};
+use super::profiles::ProfileManager;
 use super::APP_STRATEGY;
 use etcetera::{choose_app_strategy, AppStrategy};
 use serde::{Deserialize, Serialize};
@@ -15,6 +17,16 @@ pub enum PermissionLevel {
     NeverAllow,  // Tool is never allowed to be used
 }
 
+impl PermissionLevel {
+    fn strictness(&self) -> u8 {
+        match self {
+            PermissionLevel::AlwaysAllow => 0,
+            PermissionLevel::AskBefore => 1,
+            PermissionLevel::NeverAllow => 2,
+        }
+    }
+}
+
 /// Struct representing the configuration of permissions, categorized by level.
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 pub struct PermissionConfig {
@@ -28,6 +40,8 @@ pub struct PermissionConfig {
 pub struct PermissionManager {
     config_path: PathBuf, // Path to the permission configuration file
     permission_map: HashMap<String, PermissionConfig>, // Mapping of permission names to configurations
+    layers: Vec<HashMap<String, PermissionConfig>>, // System and project permissions, which can only make tools stricter
+    profile_permissions: HashMap<String, PermissionLevel>, // User permissions of the active profile
 }
 
 // Constants representing specific permission categories
@@ -56,10 +70,26 @@ impl Default for PermissionManager {
             HashMap::new() // No config file, create an empty map
         };
 
+        // System and project permission files sit next to their config.yaml
+        let mut layer_paths = vec![system_config_dir().join("permission.yaml")];
+        if let Some(project_dir) = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_config_dir(&cwd, Some(&config_dir)))
+        {
+            layer_paths.push(project_dir.join("permission.yaml"));
+        }
+
+        let profile_permissions = ProfileManager::active()
+            .map(|(_, profile)| profile.permissions)
+            .unwrap_or_default();
+
         PermissionManager {
             config_path,
             permission_map,
+            layers: Vec::new(),
+            profile_permissions,
         }
+        .with_layers(&layer_paths)
     }
 }
 
@@ -81,9 +111,34 @@ impl PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            layers: Vec::new(),
+            profile_permissions: HashMap::new(),
         }
     }
 
+    /// Use a profile's permissions in place of the user's own for the tools it lists
+    pub fn with_profile_permissions(
+        mut self,
+        permissions: HashMap<String, PermissionLevel>,
+    ) -> Self {
+        self.profile_permissions = permissions;
+        self
+    }
+
+    /// Enforce the permissions of other permission files on top of the user's own.
+    ///
+    /// These files can only make a tool stricter: `ask_before` and `never_allow` entries apply
+    /// when stricter than the user's setting, while `always_allow` entries are ignored. Missing
+    /// or invalid files are skipped.
+    pub fn with_layers(mut self, paths: &[PathBuf]) -> Self {
+        self.layers = paths
+            .iter()
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|contents| serde_yaml::from_str(&contents).ok())
+            .collect();
+        self
+    }
+
     /// Returns a list of all the names (keys) in the permission map.
     pub fn get_permission_names(&self) -> Vec<String> {
         self.permission_map.keys().cloned().collect()
@@ -101,8 +156,33 @@ impl PermissionManager {
 
     /// Helper function to retrieve the permission level for a specific permission category and tool.
     fn get_permission(&self, name: &str, principal_name: &str) -> Option<PermissionLevel> {
+        let own = match self.profile_permissions.get(principal_name) {
+            Some(level) if name == USER_PERMISSION => Some(level.clone()),
+            _ => Self::lookup_permission(&self.permission_map, name, principal_name),
+        };
+        let enforced = self
+            .layers
+            .iter()
+            .filter_map(|layer| Self::lookup_permission(layer, name, principal_name))
+            .filter(|level| *level != PermissionLevel::AlwaysAllow)
+            .max_by_key(PermissionLevel::strictness);
+
+        match (own, enforced) {
+            (Some(own), Some(enforced)) if enforced.strictness() > own.strictness() => {
+                Some(enforced)
+            }
+            (None, enforced) => enforced,
+            (own, _) => own,
+        }
+    }
+
+    fn lookup_permission(
+        permission_map: &HashMap<String, PermissionConfig>,
+        name: &str,
+        principal_name: &str,
+    ) -> Option<PermissionLevel> {
         // Check if the permission category exists in the map
-        if let Some(permission_config) = self.permission_map.get(name) {
+        if let Some(permission_config) = permission_map.get(name) {
             // Check the permission levels for the given tool
             if permission_config
                 .always_allow
@@ -305,4 +385,51 @@ mod tests {
             .always_allow
             .contains(&"nonprefix__tool2".to_string()));
     }
+
+    #[test]
+    fn test_layers_only_make_tools_stricter() {
+        let layer = NamedTempFile::new().unwrap();
+        fs::write(
+            layer.path(),
+            "user:\n  always_allow: [shell]\n  ask_before: [edit]\n  never_allow: [rm]\n",
+        )
+        .unwrap();
+
+        let mut manager = create_test_permission_manager().with_layers(&[layer.path().into()]);
+        manager.update_user_permission("edit", PermissionLevel::AlwaysAllow);
+        manager.update_user_permission("rm", PermissionLevel::AlwaysAllow);
+        manager.update_user_permission("read", PermissionLevel::AlwaysAllow);
+
+        assert_eq!(manager.get_user_permission("shell"), None);
+        assert_eq!(
+            manager.get_user_permission("edit"),
+            Some(PermissionLevel::AskBefore)
+        );
+        assert_eq!(
+            manager.get_user_permission("rm"),
+            Some(PermissionLevel::NeverAllow)
+        );
+        assert_eq!(
+            manager.get_user_permission("read"),
+            Some(PermissionLevel::AlwaysAllow)
+        );
+    }
+
+    #[test]
+    fn test_profile_permissions_replace_user_permissions() {
+        let mut manager = create_test_permission_manager().with_profile_permissions(HashMap::from(
+            [("shell".to_string(), PermissionLevel::NeverAllow)],
+        ));
+        manager.update_user_permission("shell", PermissionLevel::AlwaysAllow);
+        manager.update_user_permission("edit", PermissionLevel::AskBefore);
+
+        assert_eq!(
+            manager.get_user_permission("shell"),
+            Some(PermissionLevel::NeverAllow)
+        );
+        assert_eq!(
+            manager.get_user_permission("edit"),
+            Some(PermissionLevel::AskBefore)
+        );
+    }
 }
@@ -0,0 +1,160 @@
+use super::base::Config;
+use super::permission::PermissionLevel;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// Config key holding the named profiles
+pub const PROFILES_CONFIG_KEY: &str = "profiles";
+/// Config key and environment variable selecting the active profile
+pub const PROFILE_CONFIG_KEY: &str = "GOOSE_PROFILE";
+
+/// A named bundle of settings that can be switched per session
+///
+/// Unset fields leave the value from the config files alone.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Goose mode, e.g. "auto" or "smart_approve"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// Keys of the extensions to enable, every other configured extension is disabled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<String>>,
+    /// Tool permissions that replace the user's own for these tools
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub permissions: HashMap<String, PermissionLevel>,
+}
+
+impl Profile {
+    /// The config values this profile sets, given the configured `extensions` map
+    pub fn overlay(&self, extensions: Option<&Value>) -> HashMap<String, Value> {
+        let mut values = HashMap::new();
+        if let Some(provider) = &self.provider {
+            values.insert("GOOSE_PROVIDER".to_string(), Value::from(provider.as_str()));
+        }
+        if let Some(model) = &self.model {
+            values.insert("GOOSE_MODEL".to_string(), Value::from(model.as_str()));
+        }
+        if let Some(mode) = &self.mode {
+            values.insert("GOOSE_MODE".to_string(), Value::from(mode.as_str()));
+        }
+
+        if let (Some(enabled), Some(Value::Object(configured))) = (&self.extensions, extensions) {
+            let entries = configured
+                .iter()
+                .map(|(key, entry)| {
+                    let mut entry = entry.clone();
+                    if let Some(entry) = entry.as_object_mut() {
+                        entry.insert("enabled".to_string(), Value::Bool(enabled.contains(key)));
+                    }
+                    (key.clone(), entry)
+                })
+                .collect();
+            values.insert("extensions".to_string(), Value::Object(entries));
+        }
+
+        values
+    }
+}
+
+/// Profile configuration management
+pub struct ProfileManager;
+
+impl ProfileManager {
+    /// All profiles defined in any config layer
+    pub fn get_all() -> Result<HashMap<String, Profile>> {
+        Ok(Config::global()
+            .get_param(PROFILES_CONFIG_KEY)
+            .unwrap_or_default())
+    }
+
+    pub fn get(name: &str) -> Result<Option<Profile>> {
+        Ok(Self::get_all()?.remove(name))
+    }
+
+    /// Create or replace a profile in the user's config file
+    pub fn set(name: &str, profile: Profile) -> Result<()> {
+        let config = Config::global();
+        let mut profiles: HashMap<String, Profile> = config
+            .get_user_param(PROFILES_CONFIG_KEY)
+            .unwrap_or_default();
+        profiles.insert(name.to_string(), profile);
+        config.set_param(PROFILES_CONFIG_KEY, serde_json::to_value(profiles)?)?;
+        Ok(())
+    }
+
+    /// Remove a profile from the user's config file, returning whether it existed there
+    pub fn remove(name: &str) -> Result<bool> {
+        let config = Config::global();
+        let mut profiles: HashMap<String, Profile> = config
+            .get_user_param(PROFILES_CONFIG_KEY)
+            .unwrap_or_default();
+        if profiles.remove(name).is_none() {
+            return Ok(false);
+        }
+        config.set_param(PROFILES_CONFIG_KEY, serde_json::to_value(profiles)?)?;
+        if config
+            .get_user_param::<String>(PROFILE_CONFIG_KEY)
+            .ok()
+            .as_deref()
+            == Some(name)
+        {
+            config.delete(PROFILE_CONFIG_KEY)?;
+        }
+        Ok(true)
+    }
+
+    /// The name and settings of the active profile, if one is selected
+    pub fn active() -> Option<(String, Profile)> {
+        let name = Config::global().active_profile()?;
+        let profile = Self::get(&name).ok()??;
+        Some((name, profile))
+    }
+
+    /// Make `name` the active profile of this process, failing if it does not exist
+    pub fn activate(name: &str) -> Result<()> {
+        if Self::get(name)?.is_none() {
+            return Err(anyhow!("Unknown profile '{}'", name));
+        }
+        Config::global().set_profile(Some(name.to_string()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_overlay_sets_fields_and_extensions() {
+        let profile = Profile {
+            provider: Some("ollama".to_string()),
+            model: Some("qwen2.5".to_string()),
+            extensions: Some(vec!["developer".to_string()]),
+            ..Default::default()
+        };
+        let extensions = json!({
+            "developer": {"enabled": false, "name": "developer"},
+            "jira": {"enabled": true, "name": "jira"},
+        });
+
+        let overlay = profile.overlay(Some(&extensions));
+        assert_eq!(overlay["GOOSE_PROVIDER"], json!("ollama"));
+        assert_eq!(overlay["GOOSE_MODEL"], json!("qwen2.5"));
+        assert!(!overlay.contains_key("GOOSE_MODE"));
+        assert_eq!(
+            overlay["extensions"],
+            json!({
+                "developer": {"enabled": true, "name": "developer"},
+                "jira": {"enabled": false, "name": "jira"},
+            })
+        );
+    }
+}
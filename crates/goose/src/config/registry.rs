@@ -0,0 +1,605 @@
+use super::extensions::ExtensionEntry;
+use super::layers::ConfigSource;
+use super::profiles::Profile;
+use super::secrets::SecretStoreConfig;
+use crate::agents::hooks::HooksConfig;
+use crate::tool_monitor::LoopDetectionConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+/// Config key recording which migrations have been applied to a config file
+pub const CONFIG_VERSION_KEY: &str = "GOOSE_CONFIG_VERSION";
+/// Version written by the latest migration in [`RENAMED_KEYS`]
+pub const CURRENT_CONFIG_VERSION: u64 = 1;
+
+/// Keys that were renamed, as `(version, old name, new name)`
+///
+/// Config files older than `version` have the old key moved to the new name when they are
+/// loaded, unless the new key is already set.
+pub const RENAMED_KEYS: &[(u64, &str, &str)] = &[
+    (1, "GOOSE_PROVIDER__TYPE", "GOOSE_PROVIDER"),
+    (1, "GOOSE_PROVIDER__MODEL", "GOOSE_MODEL"),
+];
+
+/// The type a config value must have
+#[derive(Clone, Copy)]
+pub enum ValueType {
+    String,
+    /// A non-negative integer
+    Integer,
+    Number,
+    Boolean,
+    /// One of a fixed set of strings, compared case insensitively
+    Choice(&'static [&'static str]),
+    List,
+    /// A structured value, checked by deserializing it into the type that reads it
+    Schema(fn(&Value) -> Result<(), String>),
+}
+
+/// Default of a known key, as documented and used when the key is unset
+#[derive(Debug, Clone, Copy)]
+pub enum DefaultValue {
+    Str(&'static str),
+    Int(u64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl DefaultValue {
+    pub fn to_value(self) -> Value {
+        match self {
+            DefaultValue::Str(value) => Value::from(value),
+            DefaultValue::Int(value) => Value::from(value),
+            DefaultValue::Float(value) => Value::from(value),
+            DefaultValue::Bool(value) => Value::from(value),
+        }
+    }
+}
+
+/// A configuration key goose knows about
+#[derive(Clone, Copy)]
+pub struct KeySpec {
+    pub name: &'static str,
+    pub value_type: ValueType,
+    pub default: Option<DefaultValue>,
+    pub description: &'static str,
+}
+
+impl KeySpec {
+    const fn new(name: &'static str, value_type: ValueType, description: &'static str) -> Self {
+        Self {
+            name,
+            value_type,
+            default: None,
+            description,
+        }
+    }
+
+    const fn with_default(mut self, default: DefaultValue) -> Self {
+        self.default = Some(default);
+        self
+    }
+
+    /// Check that `value` has this key's type, describing the problem if not
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        let valid = match self.value_type {
+            ValueType::String => value.is_string(),
+            ValueType::Integer => value.is_u64(),
+            ValueType::Number => value.is_number(),
+            ValueType::Boolean => value.is_boolean(),
+            ValueType::List => value.is_array(),
+            ValueType::Choice(choices) => {
+                let Some(value) = value.as_str() else {
+                    return Err(format!(
+                        "expected one of {}, found {}",
+                        choices.join(", "),
+                        describe(value)
+                    ));
+                };
+                if choices.iter().any(|c| c.eq_ignore_ascii_case(value)) {
+                    return Ok(());
+                }
+                return Err(format!(
+                    "expected one of {}, found \"{}\"",
+                    choices.join(", "),
+                    value
+                ));
+            }
+            ValueType::Schema(check) => return check(value),
+        };
+        if valid {
+            return Ok(());
+        }
+        let expected = match self.value_type {
+            ValueType::String => "a string",
+            ValueType::Integer => "a non-negative integer",
+            ValueType::Number => "a number",
+            ValueType::Boolean => "true or false",
+            _ => "a list",
+        };
+        Err(format!("expected {}, found {}", expected, describe(value)))
+    }
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => format!("\"{}\"", s),
+        Value::Array(_) => "a list".to_string(),
+        Value::Object(_) => "a map".to_string(),
+    }
+}
+
+fn parses<T: DeserializeOwned>(value: &Value) -> Result<(), String> {
+    serde_json::from_value::<T>(value.clone())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+fn secret_store(value: &Value) -> Result<(), String> {
+    SecretStoreConfig::from_value(value.clone())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Values of GOOSE_MODE
+pub const MODES: &[&str] = &["auto", "approve", "smart_approve", "chat"];
+
+pub const GOOSE_PROVIDER: KeySpec =
+    KeySpec::new("GOOSE_PROVIDER", ValueType::String, "Primary LLM provider");
+pub const GOOSE_MODEL: KeySpec =
+    KeySpec::new("GOOSE_MODEL", ValueType::String, "Default model to use");
+pub const GOOSE_TEMPERATURE: KeySpec = KeySpec::new(
+    "GOOSE_TEMPERATURE",
+    ValueType::Number,
+    "Model response randomness",
+);
+pub const GOOSE_MODE: KeySpec = KeySpec::new(
+    "GOOSE_MODE",
+    ValueType::Choice(MODES),
+    "Tool execution behavior",
+)
+.with_default(DefaultValue::Str("auto"));
+pub const GOOSE_MAX_TURNS: KeySpec = KeySpec::new(
+    "GOOSE_MAX_TURNS",
+    ValueType::Integer,
+    "Turns the agent may take before asking the user to continue",
+)
+.with_default(DefaultValue::Int(1000));
+pub const GOOSE_LEAD_PROVIDER: KeySpec = KeySpec::new(
+    "GOOSE_LEAD_PROVIDER",
+    ValueType::String,
+    "Provider of the lead model, defaults to GOOSE_PROVIDER",
+);
+pub const GOOSE_LEAD_MODEL: KeySpec = KeySpec::new(
+    "GOOSE_LEAD_MODEL",
+    ValueType::String,
+    "Lead model, setting it turns on lead/worker mode",
+);
+pub const GOOSE_LEAD_TURNS: KeySpec = KeySpec::new(
+    "GOOSE_LEAD_TURNS",
+    ValueType::Integer,
+    "Turns to use the lead model at the start of a session",
+)
+.with_default(DefaultValue::Int(3));
+pub const GOOSE_LEAD_FAILURE_THRESHOLD: KeySpec = KeySpec::new(
+    "GOOSE_LEAD_FAILURE_THRESHOLD",
+    ValueType::Integer,
+    "Failed worker turns before falling back to the lead model",
+)
+.with_default(DefaultValue::Int(2));
+pub const GOOSE_LEAD_FALLBACK_TURNS: KeySpec = KeySpec::new(
+    "GOOSE_LEAD_FALLBACK_TURNS",
+    ValueType::Integer,
+    "Turns to use the lead model after a fallback",
+)
+.with_default(DefaultValue::Int(2));
+pub const GOOSE_PLANNER_PROVIDER: KeySpec = KeySpec::new(
+    "GOOSE_PLANNER_PROVIDER",
+    ValueType::String,
+    "Provider for planning mode, defaults to GOOSE_PROVIDER",
+);
+pub const GOOSE_PLANNER_MODEL: KeySpec = KeySpec::new(
+    "GOOSE_PLANNER_MODEL",
+    ValueType::String,
+    "Model for planning mode, defaults to GOOSE_MODEL",
+);
+pub const GOOSE_TOOLSHIM: KeySpec = KeySpec::new(
+    "GOOSE_TOOLSHIM",
+    ValueType::Boolean,
+    "Interpret tool calls for models without native tool support",
+)
+.with_default(DefaultValue::Bool(false));
+pub const GOOSE_TOOLSHIM_OLLAMA_MODEL: KeySpec = KeySpec::new(
+    "GOOSE_TOOLSHIM_OLLAMA_MODEL",
+    ValueType::String,
+    "Model used for tool interpretation",
+);
+pub const GOOSE_CLI_MIN_PRIORITY: KeySpec = KeySpec::new(
+    "GOOSE_CLI_MIN_PRIORITY",
+    ValueType::Number,
+    "Minimum priority of tool output shown in the CLI",
+)
+.with_default(DefaultValue::Float(0.0));
+pub const GOOSE_CLI_THEME: KeySpec = KeySpec::new(
+    "GOOSE_CLI_THEME",
+    ValueType::Choice(&["light", "dark", "ansi"]),
+    "Color theme of the CLI",
+)
+.with_default(DefaultValue::Str("dark"));
+pub const GOOSE_CLI_TOOL_PARAMS_TRUNCATION_MAX_LENGTH: KeySpec = KeySpec::new(
+    "GOOSE_CLI_TOOL_PARAMS_TRUNCATION_MAX_LENGTH",
+    ValueType::Integer,
+    "Length after which tool parameters are truncated in the CLI",
+)
+.with_default(DefaultValue::Int(40));
+pub const GOOSE_CONTEXT_STRATEGY: KeySpec = KeySpec::new(
+    "GOOSE_CONTEXT_STRATEGY",
+    ValueType::Choice(&["summarize", "truncate", "clear", "prompt"]),
+    "What to do when the context limit is reached",
+);
+pub const GOOSE_ROUTER_TOOL_SELECTION_STRATEGY: KeySpec = KeySpec::new(
+    "GOOSE_ROUTER_TOOL_SELECTION_STRATEGY",
+    ValueType::Choice(&["default", "vector", "llm"]),
+    "How tools are selected for the model",
+)
+.with_default(DefaultValue::Str("default"));
+pub const GOOSE_SCHEDULER_TYPE: KeySpec = KeySpec::new(
+    "GOOSE_SCHEDULER_TYPE",
+    ValueType::Choice(&["legacy", "temporal"]),
+    "Scheduler running scheduled recipes",
+)
+.with_default(DefaultValue::Str("legacy"));
+pub const GOOSE_SESSION_STORE: KeySpec = KeySpec::new(
+    "GOOSE_SESSION_STORE",
+    ValueType::Choice(&["jsonl", "sqlite"]),
+    "Backend storing session messages and metadata",
+)
+.with_default(DefaultValue::Str("jsonl"));
+pub const GOOSE_SYSTEM_PROMPT_FILE_PATH: KeySpec = KeySpec::new(
+    "GOOSE_SYSTEM_PROMPT_FILE_PATH",
+    ValueType::String,
+    "File or URL with a system prompt replacing the built in one",
+);
+pub const GOOSE_MAX_TOOL_CONCURRENCY: KeySpec = KeySpec::new(
+    "GOOSE_MAX_TOOL_CONCURRENCY",
+    ValueType::Integer,
+    "Tool calls that can run at once per extension",
+)
+.with_default(DefaultValue::Int(4));
+pub const GOOSE_VECTOR_DB_PATH: KeySpec = KeySpec::new(
+    "GOOSE_VECTOR_DB_PATH",
+    ValueType::String,
+    "Directory of the vector database used for tool selection",
+);
+pub const GOOSE_SERVER_MAX_SESSIONS: KeySpec = KeySpec::new(
+    "GOOSE_SERVER_MAX_SESSIONS",
+    ValueType::Integer,
+    "Sessions goose-server keeps loaded at once",
+)
+.with_default(DefaultValue::Int(16));
+pub const GOOSE_SERVER_SESSION_IDLE_TIMEOUT: KeySpec = KeySpec::new(
+    "GOOSE_SERVER_SESSION_IDLE_TIMEOUT",
+    ValueType::Integer,
+    "Seconds before goose-server unloads an idle session",
+)
+.with_default(DefaultValue::Int(3600));
+pub const GOOSE_ALLOWLIST: KeySpec = KeySpec::new(
+    "GOOSE_ALLOWLIST",
+    ValueType::String,
+    "URL of the list of allowed extensions",
+);
+pub const GOOSE_RECIPE_GITHUB_REPO: KeySpec = KeySpec::new(
+    "GOOSE_RECIPE_GITHUB_REPO",
+    ValueType::String,
+    "GitHub repository to look up recipes in, as org/repo",
+);
+pub const ALPHA_FEATURES: KeySpec = KeySpec::new(
+    "ALPHA_FEATURES",
+    ValueType::Boolean,
+    "Turn on features that are still in development",
+)
+.with_default(DefaultValue::Bool(false));
+pub const GOOSE_PROFILE: KeySpec = KeySpec::new(
+    "GOOSE_PROFILE",
+    ValueType::String,
+    "Profile selected by default",
+);
+pub const GOOSE_SECRET_STORE: KeySpec = KeySpec::new(
+    "GOOSE_SECRET_STORE",
+    ValueType::Schema(secret_store),
+    "Where secrets are stored",
+);
+pub const GOOSE_TRUSTED_PROJECTS: KeySpec = KeySpec::new(
+    super::layers::TRUSTED_PROJECTS_CONFIG_KEY,
+    ValueType::List,
+    "Project directories whose .goose/config.yaml is loaded",
+);
+pub const GOOSE_CONFIG_VERSION: KeySpec = KeySpec::new(
+    CONFIG_VERSION_KEY,
+    ValueType::Integer,
+    "Version of the config file, maintained by goose",
+);
+pub const EXTENSIONS: KeySpec = KeySpec::new(
+    "extensions",
+    ValueType::Schema(parses::<HashMap<String, ExtensionEntry>>),
+    "Configured extensions",
+);
+pub const EXPERIMENTS: KeySpec = KeySpec::new(
+    "experiments",
+    ValueType::Schema(parses::<HashMap<String, bool>>),
+    "Experimental features turned on or off",
+);
+pub const PROFILES: KeySpec = KeySpec::new(
+    "profiles",
+    ValueType::Schema(parses::<HashMap<String, Profile>>),
+    "Named profiles",
+);
+pub const HOOKS: KeySpec = KeySpec::new(
+    "hooks",
+    ValueType::Schema(parses::<HooksConfig>),
+    "Commands and tools run around agent actions",
+);
+pub const LOOP_DETECTION: KeySpec = KeySpec::new(
+    "loop_detection",
+    ValueType::Schema(parses::<LoopDetectionConfig>),
+    "Thresholds for detecting a stuck agent",
+);
+pub const TOOL_TIMEOUTS: KeySpec = KeySpec::new(
+    "tool_timeouts",
+    ValueType::Schema(parses::<HashMap<String, u64>>),
+    "Time limits in seconds for individual tools",
+);
+pub const EXTENSION_CONCURRENCY: KeySpec = KeySpec::new(
+    "extension_concurrency",
+    ValueType::Schema(parses::<HashMap<String, usize>>),
+    "Tool calls that can run at once for individual extensions",
+);
+pub const SERVER_TOKENS: KeySpec = KeySpec::new(
+    "server_tokens",
+    ValueType::List,
+    "API tokens accepted by goose-server",
+);
+
+/// Every key in the registry
+pub const CONFIG_KEYS: &[KeySpec] = &[
+    GOOSE_PROVIDER,
+    GOOSE_MODEL,
+    GOOSE_TEMPERATURE,
+    GOOSE_MODE,
+    GOOSE_MAX_TURNS,
+    GOOSE_LEAD_PROVIDER,
+    GOOSE_LEAD_MODEL,
+    GOOSE_LEAD_TURNS,
+    GOOSE_LEAD_FAILURE_THRESHOLD,
+    GOOSE_LEAD_FALLBACK_TURNS,
+    GOOSE_PLANNER_PROVIDER,
+    GOOSE_PLANNER_MODEL,
+    GOOSE_TOOLSHIM,
+    GOOSE_TOOLSHIM_OLLAMA_MODEL,
+    GOOSE_CLI_MIN_PRIORITY,
+    GOOSE_CLI_THEME,
+    GOOSE_CLI_TOOL_PARAMS_TRUNCATION_MAX_LENGTH,
+    GOOSE_CONTEXT_STRATEGY,
+    GOOSE_ROUTER_TOOL_SELECTION_STRATEGY,
+    GOOSE_SCHEDULER_TYPE,
+    GOOSE_SESSION_STORE,
+    GOOSE_SYSTEM_PROMPT_FILE_PATH,
+    GOOSE_MAX_TOOL_CONCURRENCY,
+    GOOSE_VECTOR_DB_PATH,
+    GOOSE_SERVER_MAX_SESSIONS,
+    GOOSE_SERVER_SESSION_IDLE_TIMEOUT,
+    GOOSE_ALLOWLIST,
+    GOOSE_RECIPE_GITHUB_REPO,
+    ALPHA_FEATURES,
+    GOOSE_PROFILE,
+    GOOSE_SECRET_STORE,
+    GOOSE_TRUSTED_PROJECTS,
+    GOOSE_CONFIG_VERSION,
+    EXTENSIONS,
+    EXPERIMENTS,
+    PROFILES,
+    HOOKS,
+    LOOP_DETECTION,
+    TOOL_TIMEOUTS,
+    EXTENSION_CONCURRENCY,
+    SERVER_TOKENS,
+];
+
+/// The registry entry of a key
+pub fn lookup(name: &str) -> Option<&'static KeySpec> {
+    CONFIG_KEYS.iter().find(|key| key.name == name)
+}
+
+/// Move renamed keys to their new names, returning a description of each change
+///
+/// Values are left alone when they are already at [`CURRENT_CONFIG_VERSION`]. The version is
+/// recorded only when something changed, so files that never needed a migration stay as
+/// they are.
+pub fn migrate(values: &mut HashMap<String, Value>) -> Vec<String> {
+    let version = values
+        .get(CONFIG_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .unwrap_or(0);
+    if version >= CURRENT_CONFIG_VERSION {
+        return Vec::new();
+    }
+
+    let mut changes = Vec::new();
+    for (since, old, new) in RENAMED_KEYS {
+        if *since <= version {
+            continue;
+        }
+        let Some(value) = values.remove(*old) else {
+            continue;
+        };
+        if values.contains_key(*new) {
+            changes.push(format!("removed {}, {} is already set", old, new));
+        } else {
+            values.insert(new.to_string(), value);
+            changes.push(format!("renamed {} to {}", old, new));
+        }
+    }
+
+    if !changes.is_empty() {
+        values.insert(
+            CONFIG_VERSION_KEY.to_string(),
+            Value::from(CURRENT_CONFIG_VERSION),
+        );
+    }
+    changes
+}
+
+/// What is wrong with a configuration value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    /// The value does not have the key's type
+    Invalid,
+    /// The key is not used by goose, possibly a typo
+    Unknown,
+    /// The key was renamed and is migrated when the file is loaded
+    Deprecated,
+}
+
+/// A problem found when checking the configuration
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ConfigIssue {
+    pub key: String,
+    pub kind: IssueKind,
+    pub message: String,
+    pub source: ConfigSource,
+    /// File holding the value, None for environment variables
+    #[schema(value_type = Option<String>)]
+    pub path: Option<PathBuf>,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}: {}", path.display(), self.key, self.message),
+            None => write!(f, "{} ({}): {}", self.key, self.source, self.message),
+        }
+    }
+}
+
+/// Check the values of one layer against the registry
+///
+/// `known` holds names that are valid without being in the registry, such as provider settings
+/// and extension environment variables.
+pub fn validate_values(
+    values: &HashMap<String, Value>,
+    known: &HashSet<String>,
+    source: ConfigSource,
+    path: Option<PathBuf>,
+) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = values
+        .iter()
+        .filter_map(|(key, value)| {
+            let (kind, message) = if let Some(spec) = lookup(key) {
+                (IssueKind::Invalid, spec.check(value).err()?)
+            } else if let Some((_, _, new)) = RENAMED_KEYS.iter().find(|(_, old, _)| old == key) {
+                (IssueKind::Deprecated, format!("renamed to {}", new))
+            } else if known.contains(key) {
+                return None;
+            } else {
+                (IssueKind::Unknown, "not a known setting".to_string())
+            };
+            Some(ConfigIssue {
+                key: key.clone(),
+                kind,
+                message,
+                source,
+                path: path.clone(),
+            })
+        })
+        .collect();
+    issues.sort_by(|a, b| a.key.cmp(&b.key));
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn values(value: Value) -> HashMap<String, Value> {
+        match value {
+            Value::Object(map) => map.into_iter().collect(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_invalid_and_unknown_keys() {
+        let known = HashSet::from(["OPENAI_HOST".to_string()]);
+        let issues = validate_values(
+            &values(json!({
+                "GOOSE_MODE": "yolo",
+                "GOOSE_LEAD_TURNS": "three",
+                "GOOSE_MODEL": "gpt-4o",
+                "GOOSE_PROVDER": "openai",
+                "GOOSE_PROVIDER__TYPE": "openai",
+                "OPENAI_HOST": "https://example.com",
+                "loop_detection": {"action": "explode"},
+            })),
+            &known,
+            ConfigSource::User,
+            None,
+        );
+
+        let found: Vec<_> = issues
+            .iter()
+            .map(|issue| (issue.key.as_str(), issue.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("GOOSE_LEAD_TURNS", IssueKind::Invalid),
+                ("GOOSE_MODE", IssueKind::Invalid),
+                ("GOOSE_PROVDER", IssueKind::Unknown),
+                ("GOOSE_PROVIDER__TYPE", IssueKind::Deprecated),
+                ("loop_detection", IssueKind::Invalid),
+            ]
+        );
+        assert_eq!(
+            issues[1].message,
+            "expected one of auto, approve, smart_approve, chat, found \"yolo\""
+        );
+        assert_eq!(
+            issues[0].message,
+            "expected a non-negative integer, found \"three\""
+        );
+    }
+
+    #[test]
+    fn test_migrate_renames_keys_once() {
+        let mut old = values(json!({
+            "GOOSE_PROVIDER__TYPE": "openai",
+            "GOOSE_PROVIDER__MODEL": "gpt-4o",
+            "GOOSE_MODEL": "gpt-4.1",
+        }));
+        let changes = migrate(&mut old);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(
+            old,
+            values(json!({
+                "GOOSE_PROVIDER": "openai",
+                "GOOSE_MODEL": "gpt-4.1",
+                "GOOSE_CONFIG_VERSION": CURRENT_CONFIG_VERSION,
+            }))
+        );
+        assert!(migrate(&mut old).is_empty());
+
+        let mut current = values(json!({"GOOSE_PROVIDER": "openai"}));
+        assert!(migrate(&mut current).is_empty());
+        assert!(!current.contains_key(CONFIG_VERSION_KEY));
+    }
+}
@@ -0,0 +1,695 @@
+use super::base::ConfigError;
+use age::secrecy::SecretString;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+/// Config key and environment variable selecting the secret store
+pub const SECRET_STORE_CONFIG_KEY: &str = "GOOSE_SECRET_STORE";
+/// Environment variable holding the passphrase of an encrypted secrets file
+pub const SECRETS_PASSPHRASE_ENV: &str = "GOOSE_SECRETS_PASSPHRASE";
+
+pub(crate) const KEYRING_USERNAME: &str = "secrets";
+
+/// Storage for the secrets behind [`Config::get_secret`](super::Config::get_secret)
+///
+/// Secrets are kept as a single map which is read and written whole, so a backend only needs
+/// to store one document.
+pub trait SecretStore: Send + Sync {
+    /// Read every stored secret, an empty store returns an empty map
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError>;
+
+    /// Replace the stored secrets with `values`
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError>;
+}
+
+/// Secrets stored as one JSON entry in the system keyring
+pub struct KeyringStore {
+    service: String,
+}
+
+impl KeyringStore {
+    pub fn new(service: &str) -> Self {
+        Self {
+            service: service.to_string(),
+        }
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let entry = Entry::new(&self.service, KEYRING_USERNAME)?;
+
+        match entry.get_password() {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(keyring::Error::NoEntry) => Ok(HashMap::new()),
+            Err(e) => Err(ConfigError::KeyringError(e.to_string())),
+        }
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let json_value = serde_json::to_string(values)?;
+        let entry = Entry::new(&self.service, KEYRING_USERNAME)?;
+        entry.set_password(&json_value)?;
+        Ok(())
+    }
+}
+
+/// Secrets stored in plain text in a YAML file
+pub struct FileStore {
+    path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SecretStore for FileStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        parse_yaml_map(&std::fs::read_to_string(&self.path)?)
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        std::fs::write(&self.path, serde_yaml::to_string(values)?)?;
+        Ok(())
+    }
+}
+
+/// Where the passphrase of an [`EncryptedFileStore`] comes from
+#[derive(Debug, Clone)]
+pub enum PassphraseSource {
+    /// The `GOOSE_SECRETS_PASSPHRASE` environment variable
+    Env,
+    /// The first line printed by a shell command, e.g. `pass show goose/passphrase`
+    Command(String),
+    /// A fixed passphrase, mainly for tests
+    Value(String),
+}
+
+impl PassphraseSource {
+    fn passphrase(&self) -> Result<SecretString, ConfigError> {
+        let passphrase = match self {
+            PassphraseSource::Env => env::var(SECRETS_PASSPHRASE_ENV).map_err(|_| {
+                ConfigError::SecretStoreError(format!(
+                    "{} must be set to unlock the encrypted secrets file",
+                    SECRETS_PASSPHRASE_ENV
+                ))
+            })?,
+            PassphraseSource::Command(command) => run_command(command, None)?
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            PassphraseSource::Value(value) => value.clone(),
+        };
+        if passphrase.is_empty() {
+            return Err(ConfigError::SecretStoreError(
+                "The passphrase for the encrypted secrets file is empty".to_string(),
+            ));
+        }
+        Ok(SecretString::from(passphrase))
+    }
+}
+
+/// Secrets stored in a YAML file encrypted with an age passphrase
+///
+/// The file can be decrypted by hand with `age -d`.
+pub struct EncryptedFileStore {
+    path: PathBuf,
+    passphrase: PassphraseSource,
+}
+
+impl EncryptedFileStore {
+    pub fn new<P: AsRef<Path>>(path: P, passphrase: PassphraseSource) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            passphrase,
+        }
+    }
+}
+
+impl SecretStore for EncryptedFileStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let encrypted = std::fs::read(&self.path)?;
+        let identity = age::scrypt::Identity::new(self.passphrase.passphrase()?);
+        let decrypted = age::decrypt(&identity, &encrypted).map_err(|e| {
+            ConfigError::SecretStoreError(format!(
+                "Failed to decrypt {}: {}",
+                self.path.display(),
+                e
+            ))
+        })?;
+        let content = String::from_utf8(decrypted)
+            .map_err(|e| ConfigError::DeserializeError(e.to_string()))?;
+        parse_yaml_map(&content)
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let recipient = age::scrypt::Recipient::new(self.passphrase.passphrase()?);
+        let encrypted = age::encrypt(&recipient, serde_yaml::to_string(values)?.as_bytes())
+            .map_err(|e| ConfigError::SecretStoreError(format!("Failed to encrypt: {}", e)))?;
+        write_private(&self.path, &encrypted)
+    }
+}
+
+/// Keeps the secrets of a slow store in memory after the first read
+///
+/// Decrypting a file or running a command on every lookup would make each `get_secret` take
+/// a second or more, so the map is read once per process and updated on save.
+struct CachedStore {
+    inner: Box<dyn SecretStore>,
+    cache: Mutex<Option<HashMap<String, Value>>>,
+}
+
+impl CachedStore {
+    fn new(inner: Box<dyn SecretStore>) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(None),
+        }
+    }
+}
+
+impl SecretStore for CachedStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(values) = cache.as_ref() {
+            return Ok(values.clone());
+        }
+        let values = self.inner.load()?;
+        *cache = Some(values.clone());
+        Ok(values)
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        self.inner.save(values)?;
+        *cache = Some(values.clone());
+        Ok(())
+    }
+}
+
+/// Secrets read from and written to external commands, e.g. a password manager
+///
+/// The read command prints the secrets as a YAML or JSON map on stdout. The write command
+/// receives the same map on stdin; without one the store is read only.
+pub struct CommandStore {
+    read: String,
+    write: Option<String>,
+}
+
+impl CommandStore {
+    pub fn new(read: &str, write: Option<&str>) -> Self {
+        Self {
+            read: read.to_string(),
+            write: write.map(str::to_string),
+        }
+    }
+}
+
+impl SecretStore for CommandStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        parse_yaml_map(&run_command(&self.read, None)?)
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let Some(write) = &self.write else {
+            return Err(ConfigError::SecretStoreError(
+                "The secret store command has no write command configured, set the secret in \
+                 your password manager instead"
+                    .to_string(),
+            ));
+        };
+        run_command(write, Some(&serde_yaml::to_string(values)?))?;
+        Ok(())
+    }
+}
+
+/// Secrets stored as `KEY=value` lines, the format read by most dotenv tools
+pub struct EnvFileStore {
+    path: PathBuf,
+}
+
+impl EnvFileStore {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SecretStore for EnvFileStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        Ok(parse_env_file(&std::fs::read_to_string(&self.path)?))
+    }
+
+    fn save(&self, values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        let mut keys: Vec<_> = values.keys().collect();
+        keys.sort();
+        let content: String = keys
+            .into_iter()
+            .map(|key| format!("{}={}\n", key, format_env_value(&values[key])))
+            .collect();
+        write_private(&self.path, content.as_bytes())
+    }
+}
+
+/// A store that could not be set up, every access reports why
+struct UnavailableStore {
+    reason: String,
+}
+
+impl SecretStore for UnavailableStore {
+    fn load(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        Err(ConfigError::SecretStoreError(self.reason.clone()))
+    }
+
+    fn save(&self, _values: &HashMap<String, Value>) -> Result<(), ConfigError> {
+        Err(ConfigError::SecretStoreError(self.reason.clone()))
+    }
+}
+
+/// The kinds of secret store that can be selected in the configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretStoreKind {
+    #[default]
+    Keyring,
+    File,
+    EncryptedFile,
+    Command,
+    EnvFile,
+}
+
+/// The `GOOSE_SECRET_STORE` setting
+///
+/// It is either the name of a kind, using default paths, or a map with the kind under `type`:
+///
+/// ```yaml
+/// GOOSE_SECRET_STORE:
+///   type: command
+///   read_command: pass show goose/secrets
+///   write_command: pass insert --multiline --force goose/secrets
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SecretStoreConfig {
+    #[serde(rename = "type")]
+    pub kind: SecretStoreKind,
+    /// File of the `file`, `encrypted_file` and `env_file` stores
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Command printing the passphrase of an `encrypted_file` store
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passphrase_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_command: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_command: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SecretStoreSetting {
+    Kind(SecretStoreKind),
+    Config(SecretStoreConfig),
+}
+
+impl SecretStoreConfig {
+    /// Parse the setting, accepting a bare kind name as well as the full map
+    pub fn from_value(value: Value) -> Result<Self, ConfigError> {
+        match serde_json::from_value(value)? {
+            SecretStoreSetting::Kind(kind) => Ok(SecretStoreConfig {
+                kind,
+                ..Default::default()
+            }),
+            SecretStoreSetting::Config(config) => Ok(config),
+        }
+    }
+
+    /// Whether the store runs commands from the setting, which then receive every secret
+    pub fn runs_commands(&self) -> bool {
+        self.kind == SecretStoreKind::Command || self.passphrase_command.is_some()
+    }
+
+    /// Create the store, with default file names inside `config_dir`
+    pub fn build(
+        &self,
+        config_dir: &Path,
+        keyring_service: &str,
+    ) -> Result<Box<dyn SecretStore>, ConfigError> {
+        let path = |default: &str| {
+            self.path
+                .as_deref()
+                .map(expand_home)
+                .unwrap_or_else(|| config_dir.join(default))
+        };
+        Ok(match self.kind {
+            SecretStoreKind::Keyring => Box::new(KeyringStore::new(keyring_service)),
+            SecretStoreKind::File => Box::new(FileStore::new(path("secrets.yaml"))),
+            SecretStoreKind::EncryptedFile => {
+                let passphrase = match &self.passphrase_command {
+                    Some(command) => PassphraseSource::Command(command.clone()),
+                    None => PassphraseSource::Env,
+                };
+                Box::new(CachedStore::new(Box::new(EncryptedFileStore::new(
+                    path("secrets.age"),
+                    passphrase,
+                ))))
+            }
+            SecretStoreKind::Command => {
+                let read = self.read_command.as_deref().ok_or_else(|| {
+                    ConfigError::SecretStoreError(
+                        "The command secret store needs a read_command".to_string(),
+                    )
+                })?;
+                Box::new(CachedStore::new(Box::new(CommandStore::new(
+                    read,
+                    self.write_command.as_deref(),
+                ))))
+            }
+            SecretStoreKind::EnvFile => Box::new(EnvFileStore::new(path("secrets.env"))),
+        })
+    }
+}
+
+/// The store selected by `GOOSE_SECRET_STORE`, falling back to the keyring
+///
+/// `GOOSE_DISABLE_KEYRING` still selects the plain text file when no store is configured. A
+/// store that is configured but invalid fails on use rather than silently using another.
+/// Stores that run commands are only accepted with `allow_commands`, which callers set when
+/// the setting comes from the user's own config files.
+pub fn select_secret_store(
+    setting: Option<Value>,
+    allow_commands: bool,
+    config_dir: &Path,
+    keyring_service: &str,
+) -> Box<dyn SecretStore> {
+    let config = match setting {
+        Some(value) => SecretStoreConfig::from_value(value),
+        None if env::var("GOOSE_DISABLE_KEYRING").is_ok() => Ok(SecretStoreConfig {
+            kind: SecretStoreKind::File,
+            ..Default::default()
+        }),
+        None => Ok(SecretStoreConfig::default()),
+    };
+
+    let config = config.and_then(|config| {
+        if config.runs_commands() && !allow_commands {
+            return Err(ConfigError::SecretStoreError(
+                "stores that run commands can only be configured in the user or system config file"
+                    .to_string(),
+            ));
+        }
+        Ok(config)
+    });
+    match config.and_then(|config| config.build(config_dir, keyring_service)) {
+        Ok(store) => store,
+        Err(e) => {
+            let reason = format!("Invalid {}: {}", SECRET_STORE_CONFIG_KEY, e);
+            tracing::error!("{}", reason);
+            Box::new(UnavailableStore { reason })
+        }
+    }
+}
+
+fn parse_yaml_map(content: &str) -> Result<HashMap<String, Value>, ConfigError> {
+    if content.trim().is_empty() {
+        return Ok(HashMap::new());
+    }
+    let yaml_value: serde_yaml::Value = serde_yaml::from_str(content)?;
+    match serde_json::to_value(yaml_value)? {
+        Value::Object(map) => Ok(map.into_iter().collect()),
+        _ => Ok(HashMap::new()),
+    }
+}
+
+fn parse_env_file(content: &str) -> HashMap<String, Value> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), parse_env_value(value.trim())))
+        })
+        .collect()
+}
+
+fn parse_env_value(raw: &str) -> Value {
+    let value = if let Some(quoted) = raw.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        let mut value = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                value.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(other) => value.push(other),
+                None => value.push('\\'),
+            }
+        }
+        value
+    } else if let Some(quoted) = raw.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        quoted.to_string()
+    } else {
+        raw.to_string()
+    };
+
+    // Maps and lists are written as JSON, see format_env_value
+    if value.starts_with('{') || value.starts_with('[') {
+        if let Ok(parsed) = serde_json::from_str(&value) {
+            return parsed;
+        }
+    }
+    Value::String(value)
+}
+
+fn format_env_value(value: &Value) -> String {
+    let value = match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let plain = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_./:+=@,".contains(c));
+    if plain {
+        return value;
+    }
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn run_command(command: &str, stdin: Option<&str>) -> Result<String, ConfigError> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    let mut child = cmd
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            ConfigError::SecretStoreError(format!("Failed to run '{}': {}", command, e))
+        })?;
+
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(ConfigError::SecretStoreError(format!(
+            "'{}' failed with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(|e| ConfigError::DeserializeError(e.to_string()))
+}
+
+/// Write a file only the current user can read
+fn write_private(path: &Path, content: &[u8]) -> Result<(), ConfigError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn sample() -> HashMap<String, Value> {
+        HashMap::from([
+            ("OPENAI_API_KEY".to_string(), json!("sk-test 123")),
+            ("multi".to_string(), json!("line one\nline \"two\"")),
+            ("headers".to_string(), json!({"x-token": "abc"})),
+        ])
+    }
+
+    #[test]
+    fn test_env_file_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let store = EnvFileStore::new(dir.path().join("secrets.env"));
+        assert!(store.load().unwrap().is_empty());
+
+        store.save(&sample()).unwrap();
+        assert_eq!(store.load().unwrap(), sample());
+
+        std::fs::write(
+            dir.path().join("secrets.env"),
+            "# comment\nexport PLAIN=value\nSINGLE='a b'\n",
+        )
+        .unwrap();
+        let values = store.load().unwrap();
+        assert_eq!(values["PLAIN"], json!("value"));
+        assert_eq!(values["SINGLE"], json!("a b"));
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("secrets.age");
+        let store = EncryptedFileStore::new(&path, PassphraseSource::Value("hunter2".to_string()));
+
+        store.save(&sample()).unwrap();
+        assert_eq!(store.load().unwrap(), sample());
+        let raw = std::fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("sk-test"));
+
+        let wrong = EncryptedFileStore::new(&path, PassphraseSource::Value("wrong".to_string()));
+        assert!(matches!(
+            wrong.load(),
+            Err(ConfigError::SecretStoreError(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_store() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("store.yaml");
+        std::fs::write(&file, "").unwrap();
+        let store = CommandStore::new(
+            &format!("cat '{}'", file.display()),
+            Some(&format!("cat > '{}'", file.display())),
+        );
+
+        store.save(&sample()).unwrap();
+        assert_eq!(store.load().unwrap(), sample());
+
+        let read_only = CommandStore::new("echo 'KEY: value'", None);
+        assert_eq!(read_only.load().unwrap()["KEY"], json!("value"));
+        assert!(read_only.save(&sample()).is_err());
+        assert!(CommandStore::new("exit 3", None).load().is_err());
+    }
+
+    #[test]
+    fn test_cached_store_reads_once() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("secrets.yaml");
+        std::fs::write(&file, "KEY: first\n").unwrap();
+        let store = CachedStore::new(Box::new(CommandStore::new(
+            &format!("cat '{}'", file.display()),
+            None,
+        )));
+        assert_eq!(store.load().unwrap()["KEY"], json!("first"));
+
+        std::fs::write(&file, "KEY: second\n").unwrap();
+        assert_eq!(store.load().unwrap()["KEY"], json!("first"));
+        assert!(store.save(&sample()).is_err());
+        assert_eq!(store.load().unwrap()["KEY"], json!("first"));
+    }
+
+    #[test]
+    fn test_select_secret_store_config() {
+        let config = SecretStoreConfig::from_value(json!("env_file")).unwrap();
+        assert_eq!(config.kind, SecretStoreKind::EnvFile);
+        assert_eq!(config.path, None);
+
+        let config = SecretStoreConfig::from_value(json!({
+            "type": "command",
+            "read_command": "pass show goose/secrets",
+        }))
+        .unwrap();
+        assert_eq!(config.kind, SecretStoreKind::Command);
+        assert_eq!(
+            config.read_command.as_deref(),
+            Some("pass show goose/secrets")
+        );
+
+        let dir = TempDir::new().unwrap();
+        let store = select_secret_store(Some(json!({"type": "command"})), true, dir.path(), "test");
+        assert!(matches!(
+            store.load(),
+            Err(ConfigError::SecretStoreError(_))
+        ));
+
+        let command = json!({"type": "command", "read_command": "echo 'KEY: value'"});
+        let store = select_secret_store(Some(command.clone()), false, dir.path(), "test");
+        assert!(matches!(
+            store.load(),
+            Err(ConfigError::SecretStoreError(_))
+        ));
+        let store = select_secret_store(Some(command), true, dir.path(), "test");
+        assert_eq!(store.load().unwrap()["KEY"], json!("value"));
+    }
+}
@@ -52,7 +52,7 @@ impl BedrockProvider {
             }
         };
 
-        set_aws_env_vars(config.load_values());
+        set_aws_env_vars(config.load_merged_values());
         set_aws_env_vars(config.load_secrets());
 
         let sdk_config = futures::executor::block_on(aws_config::load_from_env());
@@ -20,6 +20,7 @@ use super::{
     venice::VeniceProvider,
     xai::XaiProvider,
 };
+use crate::config::registry::{self, KeySpec};
 use crate::model::ModelConfig;
 use anyhow::Result;
 
@@ -28,16 +29,6 @@ use super::errors::ProviderError;
 #[cfg(test)]
 use mcp_core::tool::Tool;
 
-fn default_lead_turns() -> usize {
-    3
-}
-fn default_failure_threshold() -> usize {
-    2
-}
-fn default_fallback_turns() -> usize {
-    2
-}
-
 pub fn providers() -> Vec<ProviderMetadata> {
     vec![
         AnthropicProvider::metadata(),
@@ -88,15 +79,9 @@ fn create_lead_worker_from_env(
         .unwrap_or_else(|_| default_provider_name.to_string());
 
     // Get configuration parameters with defaults
-    let lead_turns = config
-        .get_param::<usize>("GOOSE_LEAD_TURNS")
-        .unwrap_or(default_lead_turns());
-    let failure_threshold = config
-        .get_param::<usize>("GOOSE_LEAD_FAILURE_THRESHOLD")
-        .unwrap_or(default_failure_threshold());
-    let fallback_turns = config
-        .get_param::<usize>("GOOSE_LEAD_FALLBACK_TURNS")
-        .unwrap_or(default_fallback_turns());
+    let lead_turns = lead_worker_setting(&registry::GOOSE_LEAD_TURNS);
+    let failure_threshold = lead_worker_setting(&registry::GOOSE_LEAD_FAILURE_THRESHOLD);
+    let fallback_turns = lead_worker_setting(&registry::GOOSE_LEAD_FALLBACK_TURNS);
 
     // Create model configs
     let lead_model_config = ModelConfig::new(lead_model_name.to_string());
@@ -116,6 +101,20 @@ fn create_lead_worker_from_env(
     )))
 }
 
+/// A lead/worker setting, falling back to its default when the configured value is invalid
+fn lead_worker_setting(key: &KeySpec) -> usize {
+    let default = key
+        .default
+        .and_then(|default| serde_json::from_value(default.to_value()).ok())
+        .unwrap_or_default();
+    crate::config::Config::global()
+        .get_key(key)
+        .unwrap_or_else(|e| {
+            tracing::warn!("Invalid {}, using {}: {}", key.name, default, e);
+            default
+        })
+}
+
 fn create_provider(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     // We use Arc instead of Box to be able to clone for multiple async tasks
     match name {
@@ -147,6 +146,7 @@ mod tests {
     use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
     use chrono::Utc;
     use mcp_core::{content::TextContent, Role};
+    use serial_test::serial;
     use std::env;
 
     #[allow(dead_code)]
@@ -198,6 +198,18 @@ mod tests {
     }
 
     #[test]
+    #[serial]
+    fn test_invalid_lead_worker_setting_uses_default() {
+        temp_env::with_var("GOOSE_LEAD_TURNS", Some("many"), || {
+            assert_eq!(lead_worker_setting(&registry::GOOSE_LEAD_TURNS), 3);
+        });
+        temp_env::with_var("GOOSE_LEAD_TURNS", Some("5"), || {
+            assert_eq!(lead_worker_setting(&registry::GOOSE_LEAD_TURNS), 5);
+        });
+    }
+
+    #[test]
+    #[serial]
     fn test_create_lead_worker_provider() {
         // Save current env vars
         let saved_lead = env::var("GOOSE_LEAD_MODEL").ok();
@@ -246,6 +258,7 @@ mod tests {
     }
 
     #[test]
+    #[serial]
     fn test_lead_model_env_vars_with_defaults() {
         // Save current env vars
         let saved_vars = [
@@ -303,6 +316,7 @@ mod tests {
     }
 
     #[test]
+    #[serial]
     fn test_create_regular_provider_without_lead_config() {
         // Save current env vars
         let saved_lead = env::var("GOOSE_LEAD_MODEL").ok();
@@ -6,6 +6,7 @@ use anyhow::{anyhow, Result};
 use mcp_core::content::Content;
 use mcp_core::role::Role;
 use mcp_core::tool::{Tool, ToolCall};
+use mcp_core::ToolError;
 use serde_json::{json, Value};
 use std::collections::HashSet;
 
@@ -233,6 +234,102 @@ pub fn response_to_message(response: Value) -> Result<Message> {
     Ok(message)
 }
 
+/// The text and images of a `tool_result` block
+fn tool_result_contents(content: &Value) -> Vec<Content> {
+    match content {
+        Value::String(text) => vec![Content::text(text)],
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => block
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .map(Content::text),
+                Some("image") => {
+                    let source = block.get("source")?;
+                    Some(Content::image(
+                        source.get("data")?.as_str()?,
+                        source.get("media_type")?.as_str()?,
+                    ))
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn user_content_to_message(content: &Value) -> Message {
+    let mut message = Message::user();
+    let blocks = match content {
+        Value::String(text) => return message.with_text(text),
+        Value::Array(blocks) => blocks,
+        _ => return message,
+    };
+    for block in blocks {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => {
+                if let Some(text) = block.get("text").and_then(|t| t.as_str()) {
+                    message = message.with_text(text);
+                }
+            }
+            Some("image") => {
+                let source = &block["source"];
+                match (source["data"].as_str(), source["media_type"].as_str()) {
+                    (Some(data), Some(media_type)) => {
+                        message = message.with_image(data, media_type)
+                    }
+                    // Images by URL are only kept by their address
+                    _ => {
+                        let url = source["url"].as_str().unwrap_or("unknown");
+                        message = message.with_text(format!("[image: {}]", url));
+                    }
+                }
+            }
+            Some("tool_result") => {
+                let id = block["tool_use_id"].as_str().unwrap_or_default();
+                let contents = tool_result_contents(&block["content"]);
+                let result = if block["is_error"].as_bool().unwrap_or(false) {
+                    let text = contents
+                        .iter()
+                        .filter_map(|c| c.as_text())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    Err(ToolError::ExecutionError(text))
+                } else {
+                    Ok(contents)
+                };
+                message = message.with_tool_response(id, result);
+            }
+            _ => {}
+        }
+    }
+    message
+}
+
+/// Convert the messages of a Messages API request or log back into goose messages
+///
+/// Assistant messages go through [`response_to_message`], user messages keep their text,
+/// images and tool results.
+pub fn messages_from_anthropic(messages: &[Value]) -> Result<Vec<Message>> {
+    messages
+        .iter()
+        .enumerate()
+        .map(|(index, message)| match message["role"].as_str() {
+            Some("user") => Ok(user_content_to_message(&message["content"])),
+            Some("assistant") => {
+                let content = match &message["content"] {
+                    Value::String(text) => json!([{"type": "text", "text": text}]),
+                    content => content.clone(),
+                };
+                response_to_message(json!({ "content": content }))
+            }
+            Some(role) => Err(anyhow!("Message {} has unknown role '{}'", index, role)),
+            None => Err(anyhow!("Message {} has no role", index)),
+        })
+        .collect()
+}
+
 /// Extract usage information from Anthropic's API response
 pub fn get_usage(data: &Value) -> Result<Usage> {
     // Extract usage data if available
@@ -692,4 +789,35 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_messages_from_anthropic() -> Result<()> {
+        let messages = vec![
+            json!({"role": "user", "content": "Read the config"}),
+            json!({"role": "assistant", "content": [
+                {"type": "text", "text": "Reading it."},
+                {"type": "tool_use", "id": "toolu_1", "name": "read_file", "input": {"path": "config.yaml"}}
+            ]}),
+            json!({"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": "no such file", "is_error": true}
+            ]}),
+            json!({"role": "assistant", "content": "The config file is missing."}),
+        ];
+        let converted = messages_from_anthropic(&messages)?;
+
+        assert_eq!(converted.len(), 4);
+        assert_eq!(converted[0].as_concat_text(), "Read the config");
+        assert!(converted[1].is_tool_call());
+        if let MessageContent::ToolResponse(response) = &converted[2].content[0] {
+            assert_eq!(response.id, "toolu_1");
+            assert!(response.tool_result.is_err());
+        } else {
+            panic!("Expected a tool response");
+        }
+        assert_eq!(converted[3].role, Role::Assistant);
+        assert_eq!(converted[3].as_concat_text(), "The config file is missing.");
+
+        assert!(messages_from_anthropic(&[json!({"role": "system", "content": "x"})]).is_err());
+        Ok(())
+    }
 }
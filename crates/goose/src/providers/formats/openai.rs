@@ -281,6 +281,92 @@ pub fn response_to_message(response: Value) -> anyhow::Result<Message> {
     })
 }
 
+/// The text and images of a chat-completions user message, whose content is a string or a list of parts
+fn user_content_to_message(content: &Value) -> Message {
+    let mut message = Message::user();
+    match content {
+        Value::String(text) => message = message.with_text(text),
+        Value::Array(parts) => {
+            for part in parts {
+                match part["type"].as_str() {
+                    Some("text") => {
+                        message = message.with_text(part["text"].as_str().unwrap_or_default())
+                    }
+                    Some("image_url") => {
+                        let url = part["image_url"]["url"].as_str().unwrap_or_default();
+                        // Inline images are kept, linked ones only by their address
+                        match url
+                            .strip_prefix("data:")
+                            .and_then(|data| data.split_once(";base64,"))
+                        {
+                            Some((mime_type, data)) => {
+                                message = message.with_image(data, mime_type)
+                            }
+                            None => message = message.with_text(format!("[image: {}]", url)),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+    message
+}
+
+/// Convert the messages of a chat-completions request or log back into goose messages
+///
+/// Assistant messages go through [`response_to_message`], the results of tool calls made in
+/// one turn are gathered into one user message, and system messages are left out.
+pub fn messages_from_openai(messages: &[Value]) -> anyhow::Result<Vec<Message>> {
+    let mut converted: Vec<Message> = Vec::new();
+    for (index, message) in messages.iter().enumerate() {
+        match message["role"].as_str() {
+            Some("system") | Some("developer") => {}
+            Some("user") => converted.push(user_content_to_message(&message["content"])),
+            Some("assistant") => {
+                let mut message = message.clone();
+                // Logs may keep assistant text as parts, the response parser wants a string
+                if let Some(parts) = message["content"].as_array() {
+                    let text = parts
+                        .iter()
+                        .filter_map(|part| part["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    message["content"] = json!(text);
+                }
+                converted.push(response_to_message(
+                    json!({"choices": [{"message": message}]}),
+                )?);
+            }
+            Some("tool") => {
+                let id = message["tool_call_id"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("Tool message {} has no tool_call_id", index))?;
+                let output = match &message["content"] {
+                    Value::String(text) => text.clone(),
+                    Value::Array(parts) => parts
+                        .iter()
+                        .filter_map(|part| part["text"].as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                    _ => String::new(),
+                };
+                let result = Ok(vec![Content::text(output)]);
+                match converted.last_mut() {
+                    Some(last) if last.role == Role::User && last.is_tool_response() => {
+                        last.content.push(MessageContent::tool_response(id, result));
+                    }
+                    _ => converted.push(Message::user().with_tool_response(id, result)),
+                }
+            }
+            Some(role) => return Err(anyhow!("Message {} has unknown role '{}'", index, role)),
+            None => return Err(anyhow!("Message {} has no role", index)),
+        }
+    }
+    Ok(converted)
+}
+
 pub fn get_usage(data: &Value) -> Result<Usage, ProviderError> {
     let usage = data
         .get("usage")
@@ -942,4 +1028,36 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_messages_from_openai() -> anyhow::Result<()> {
+        let messages = vec![
+            json!({"role": "system", "content": "You are helpful"}),
+            json!({"role": "user", "content": [
+                {"type": "text", "text": "What is in this directory?"},
+                {"type": "image_url", "image_url": {"url": "data:image/png;base64,aGVsbG8="}}
+            ]}),
+            json!({"role": "assistant", "content": null, "tool_calls": [
+                {"id": "call_1", "type": "function", "function": {"name": "shell", "arguments": "{\"command\": \"ls\"}"}},
+                {"id": "call_2", "type": "function", "function": {"name": "shell", "arguments": "{\"command\": \"pwd\"}"}}
+            ]}),
+            json!({"role": "tool", "tool_call_id": "call_1", "content": "Cargo.toml"}),
+            json!({"role": "tool", "tool_call_id": "call_2", "content": "/work"}),
+            json!({"role": "assistant", "content": "One file in /work."}),
+        ];
+        let converted = messages_from_openai(&messages)?;
+
+        assert_eq!(converted.len(), 4);
+        assert_eq!(converted[0].content.len(), 2);
+        assert!(matches!(converted[0].content[1], MessageContent::Image(_)));
+        assert!(converted[1].is_tool_call());
+        // Both results of the turn are in one message
+        assert_eq!(converted[2].role, Role::User);
+        assert_eq!(converted[2].content.len(), 2);
+        assert!(converted[2].is_tool_response());
+        assert_eq!(converted[3].as_concat_text(), "One file in /work.");
+
+        assert!(messages_from_openai(&[json!({"role": "tool", "content": "x"})]).is_err());
+        Ok(())
+    }
 }
@@ -51,7 +51,7 @@ impl SageMakerTgiProvider {
             }
         };
 
-        set_aws_env_vars(config.load_values());
+        set_aws_env_vars(config.load_merged_values());
         set_aws_env_vars(config.load_secrets());
 
         let aws_config = futures::executor::block_on(aws_config::load_from_env());
@@ -2,6 +2,7 @@ use anyhow::Result;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 use crate::agents::extension::ExtensionConfig;
 use serde::de::Deserializer;
@@ -30,6 +31,7 @@ fn default_version() -> String {
 /// * `author` - Information about the Recipe's creator and metadata
 /// * `parameters` - Additional parameters for the Recipe
 /// * `response` - Response configuration including JSON schema validation
+/// * `extends` - Base recipe this one builds on, see [`Recipe::inherit_from`]
 ///
 /// # Example
 ///
@@ -59,6 +61,7 @@ fn default_version() -> String {
 ///     parameters: None,
 ///     response: None,
 ///     sub_recipes: None,
+///     extends: None,
 /// };
 ///
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -102,6 +105,9 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sub_recipes: Option<Vec<SubRecipe>>, // sub-recipes for the recipe
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>, // path or name of the base recipe
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -123,6 +129,9 @@ pub struct Settings {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_timeouts: Option<HashMap<String, u64>>, // seconds, keyed by prefixed tool name
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -190,6 +199,10 @@ pub enum RecipeParameterInputType {
     Boolean,
     Date,
     File,
+    /// One of the parameter's `options`
+    Select,
+    /// Comma separated strings, or a JSON list of strings
+    List,
 }
 
 impl fmt::Display for RecipeParameterInputType {
@@ -210,6 +223,151 @@ pub struct RecipeParameter {
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<String>,
+    /// Allowed values of a select parameter, or of the items of a list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// Regular expression a string value, or every item of a list, has to match in full
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Smallest value of a number parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Largest value of a number parameter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+/// A parameter value that does not fit its parameter definition
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ParameterValueError {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParameterValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// The items of a list parameter value
+pub fn list_items(value: &str) -> Vec<String> {
+    if let Ok(items) = serde_json::from_str::<Vec<String>>(value) {
+        return items;
+    }
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl RecipeParameter {
+    fn pattern_regex(&self) -> Result<Option<regex::Regex>, String> {
+        self.pattern
+            .as_ref()
+            .map(|pattern| {
+                regex::Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| format!("invalid pattern '{}': {}", pattern, e))
+            })
+            .transpose()
+    }
+
+    /// Checks the definition itself, including that its default is a valid value
+    pub fn validate_definition(&self) -> Result<(), String> {
+        if matches!(self.input_type, RecipeParameterInputType::Select)
+            && self
+                .options
+                .as_ref()
+                .is_none_or(|options| options.is_empty())
+        {
+            return Err("select parameters need a list of options".to_string());
+        }
+        self.pattern_regex()?;
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                return Err(format!("min {} is larger than max {}", min, max));
+            }
+        }
+        if let Some(default) = &self.default {
+            self.validate_value(default)
+                .map_err(|e| format!("default '{}' is invalid: {}", default, e))?;
+        }
+        Ok(())
+    }
+
+    /// Checks a value given for the parameter against its type, options, pattern and range
+    ///
+    /// Select and list values are always checked. The older types only check the options,
+    /// pattern and range a parameter sets, so recipes written before values were checked, with
+    /// values like `today` or `True`, keep working.
+    pub fn validate_value(&self, value: &str) -> Result<(), String> {
+        let pattern = self.pattern_regex()?;
+        let check_item = |item: &str| -> Result<(), String> {
+            if let Some(options) = &self.options {
+                if !options.iter().any(|option| option == item) {
+                    return Err(format!("'{}' is not one of: {}", item, options.join(", ")));
+                }
+            }
+            if let Some(pattern) = &pattern {
+                if !pattern.is_match(item) {
+                    return Err(format!(
+                        "'{}' does not match the pattern {}",
+                        item,
+                        self.pattern.as_deref().unwrap_or_default()
+                    ));
+                }
+            }
+            Ok(())
+        };
+
+        match self.input_type {
+            RecipeParameterInputType::Number if self.min.is_some() || self.max.is_some() => {
+                check_item(value)?;
+                let number: f64 = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("'{}' is not a number", value))?;
+                if self.min.is_some_and(|min| number < min) {
+                    return Err(format!("{} is less than {}", number, self.min.unwrap()));
+                }
+                if self.max.is_some_and(|max| number > max) {
+                    return Err(format!("{} is more than {}", number, self.max.unwrap()));
+                }
+                Ok(())
+            }
+            RecipeParameterInputType::List => list_items(value)
+                .iter()
+                .try_for_each(|item| check_item(item)),
+            RecipeParameterInputType::String
+            | RecipeParameterInputType::Number
+            | RecipeParameterInputType::Boolean
+            | RecipeParameterInputType::Date
+            | RecipeParameterInputType::File
+            | RecipeParameterInputType::Select => check_item(value),
+        }
+    }
+}
+
+/// Check the given values of the parameters, parameters without a value are skipped
+pub fn validate_parameter_values(
+    parameters: &[RecipeParameter],
+    values: &HashMap<String, String>,
+) -> Vec<ParameterValueError> {
+    parameters
+        .iter()
+        .filter_map(|parameter| {
+            let value = values.get(&parameter.key)?;
+            parameter
+                .validate_value(value)
+                .err()
+                .map(|message| ParameterValueError {
+                    key: parameter.key.clone(),
+                    message,
+                })
+        })
+        .collect()
 }
 
 /// Builder for creating Recipe instances
@@ -230,6 +388,7 @@ pub struct RecipeBuilder {
     parameters: Option<Vec<RecipeParameter>>,
     response: Option<Response>,
     sub_recipes: Option<Vec<SubRecipe>>,
+    extends: Option<String>,
 }
 
 impl Recipe {
@@ -262,6 +421,7 @@ impl Recipe {
             parameters: None,
             response: None,
             sub_recipes: None,
+            extends: None,
         }
     }
     pub fn from_content(content: &str) -> Result<Self> {
@@ -275,6 +435,216 @@ impl Recipe {
             ))
         }
     }
+
+    /// Merges this recipe over the base recipe it extends.
+    ///
+    /// Title, description, prompt, author and response come from this recipe when set.
+    /// Instructions of the base come first, followed by these. Extensions, parameters and
+    /// sub-recipes are combined by name, an entry here replacing the base entry of the same
+    /// name. Settings are merged field by field, and context and activities are appended.
+    pub fn inherit_from(self, base: Recipe) -> Recipe {
+        let instructions = match (base.instructions, self.instructions) {
+            (Some(base), Some(own)) => Some(format!("{}\n\n{}", base.trim_end(), own)),
+            (base, own) => own.or(base),
+        };
+        let settings = match (base.settings, self.settings) {
+            (Some(base), Some(own)) => Some(Settings {
+                goose_provider: own.goose_provider.or(base.goose_provider),
+                goose_model: own.goose_model.or(base.goose_model),
+                temperature: own.temperature.or(base.temperature),
+                tool_timeouts: match (base.tool_timeouts, own.tool_timeouts) {
+                    (Some(mut timeouts), Some(own)) => {
+                        timeouts.extend(own);
+                        Some(timeouts)
+                    }
+                    (base, own) => own.or(base),
+                },
+            }),
+            (base, own) => own.or(base),
+        };
+
+        Recipe {
+            version: self.version,
+            title: self.title,
+            description: self.description,
+            instructions,
+            prompt: self.prompt.or(base.prompt),
+            extensions: merge_by_key(base.extensions, self.extensions, |e| e.name()),
+            context: append_distinct(base.context, self.context),
+            settings,
+            activities: append_distinct(base.activities, self.activities),
+            author: self.author.or(base.author),
+            parameters: merge_by_key(base.parameters, self.parameters, |p| p.key.clone()),
+            response: self.response.or(base.response),
+            sub_recipes: merge_by_key(base.sub_recipes, self.sub_recipes, |s| s.name.clone()),
+            extends: None,
+        }
+    }
+
+    /// Read a recipe file and merge in the base recipes it extends, found with
+    /// [`find_base_recipe_file`]
+    ///
+    /// Files are read as they are, so templated bases and the CLI's recipe search path are only
+    /// available when running the recipe with the CLI.
+    pub fn from_file(path: &Path) -> Result<Recipe> {
+        let chain = extends_chain(
+            RecipeFile::read(path)?,
+            |file| Ok(Recipe::from_content(&file.content)?.extends),
+            find_base_recipe_file,
+        )?;
+        let recipes = chain
+            .iter()
+            .map(|file| Recipe::from_content(&file.content))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Recipe::merge_chain(recipes))
+    }
+
+    /// Merge a recipe followed by the base recipes it extends into a single recipe, starting
+    /// from the last base
+    pub fn merge_chain(recipes: Vec<Recipe>) -> Recipe {
+        recipes
+            .into_iter()
+            .rev()
+            .reduce(|base, recipe| recipe.inherit_from(base))
+            .expect("chain starts with the recipe")
+    }
+}
+
+/// A recipe file as read from disk, before any templates in it are rendered
+#[derive(Debug, Clone)]
+pub struct RecipeFile {
+    pub content: String,
+    pub parent_dir: PathBuf,
+    pub file_path: PathBuf,
+}
+
+impl RecipeFile {
+    /// Read the recipe file at `path`, which is kept canonicalized
+    pub fn read(path: &Path) -> Result<RecipeFile> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read recipe file {}: {}", path.display(), e))?;
+        let file_path = path.canonicalize().map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to resolve absolute path for {}: {}",
+                path.display(),
+                e
+            )
+        })?;
+        let parent_dir = file_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Resolved path has no parent: {}", file_path.display()))?
+            .to_path_buf();
+        Ok(RecipeFile {
+            content,
+            parent_dir,
+            file_path,
+        })
+    }
+}
+
+/// The recipe file followed by the base recipe files it extends, each base after the recipe
+/// extending it
+///
+/// `extends` reads the name of the base a file extends, and `find_base` looks that name up
+/// from the file naming it. A base that is already in the chain is reported as a cycle.
+pub fn extends_chain(
+    recipe: RecipeFile,
+    mut extends: impl FnMut(&RecipeFile) -> Result<Option<String>>,
+    mut find_base: impl FnMut(&str, &RecipeFile) -> Result<RecipeFile>,
+) -> Result<Vec<RecipeFile>> {
+    let mut chain = vec![recipe];
+    loop {
+        let current = chain.last().expect("chain starts with the recipe");
+        let Some(base_name) = extends(current)? else {
+            return Ok(chain);
+        };
+        let base = find_base(&base_name, current).map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to load base recipe '{}' of {}: {}",
+                base_name,
+                current.file_path.display(),
+                e
+            )
+        })?;
+        if chain.iter().any(|r| r.file_path == base.file_path) {
+            let cycle = chain
+                .iter()
+                .chain(std::iter::once(&base))
+                .map(|r| r.file_path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(anyhow::anyhow!("Recipe extends itself: {}", cycle));
+        }
+        chain.push(base);
+    }
+}
+
+/// Find the base recipe `name` next to the recipe naming it, by a path relative to its
+/// directory, an absolute or `~` path, or a name with a `.yaml`, `.yml` or `.json` file there
+pub fn find_base_recipe_file(name: &str, extending: &RecipeFile) -> Result<RecipeFile> {
+    let path = base_recipe_path(name, &extending.parent_dir).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Base recipe '{}' not found in {}",
+            name,
+            extending.parent_dir.display()
+        )
+    })?;
+    RecipeFile::read(&path)
+}
+
+/// The file of the base recipe `name`, as given by a recipe in `dir`
+fn base_recipe_path(name: &str, dir: &Path) -> Option<PathBuf> {
+    let path = match (name.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => dir.join(name),
+    };
+    let candidates = if path.extension().is_some() {
+        vec![path]
+    } else {
+        ["yaml", "yml", "json"]
+            .iter()
+            .map(|ext| path.with_extension(ext))
+            .collect()
+    };
+    candidates
+        .into_iter()
+        .find(|path| path.is_file())
+        .and_then(|path| path.canonicalize().ok())
+}
+
+/// Base entries in order with same-keyed ones replaced, followed by the new entries
+fn merge_by_key<T>(
+    base: Option<Vec<T>>,
+    own: Option<Vec<T>>,
+    key: impl Fn(&T) -> String,
+) -> Option<Vec<T>> {
+    let (mut merged, own) = match (base, own) {
+        (Some(base), Some(own)) => (base, own),
+        (base, own) => return own.or(base),
+    };
+    for item in own {
+        match merged
+            .iter()
+            .position(|existing| key(existing) == key(&item))
+        {
+            Some(index) => merged[index] = item,
+            None => merged.push(item),
+        }
+    }
+    Some(merged)
+}
+
+fn append_distinct(base: Option<Vec<String>>, own: Option<Vec<String>>) -> Option<Vec<String>> {
+    let (mut merged, own) = match (base, own) {
+        (Some(base), Some(own)) => (base, own),
+        (base, own) => return own.or(base),
+    };
+    for item in own {
+        if !merged.contains(&item) {
+            merged.push(item);
+        }
+    }
+    Some(merged)
 }
 
 impl RecipeBuilder {
@@ -352,6 +722,12 @@ impl RecipeBuilder {
         self
     }
 
+    /// Sets the base recipe the Recipe extends
+    pub fn extends(mut self, extends: impl Into<String>) -> Self {
+        self.extends = Some(extends.into());
+        self
+    }
+
     /// Builds the Recipe instance
     ///
     /// Returns an error if any required fields are missing
@@ -377,6 +753,7 @@ impl RecipeBuilder {
             parameters: self.parameters,
             response: self.response,
             sub_recipes: self.sub_recipes,
+            extends: self.extends,
         })
     }
 }
@@ -621,4 +998,194 @@ sub_recipes:
         let activities = recipe.activities.unwrap();
         assert_eq!(activities, vec!["activity1", "activity2"]);
     }
+    #[test]
+    fn test_inherit_from_base_recipe() {
+        let base = Recipe::from_content(
+            r#"
+title: Team base
+description: Shared setup
+instructions: Follow the team style guide.
+extensions:
+  - type: builtin
+    name: developer
+  - type: stdio
+    name: jira
+    cmd: jira-mcp
+    args: []
+settings:
+  goose_provider: anthropic
+  temperature: 0.2
+parameters:
+  - key: project
+    input_type: string
+    requirement: required
+    description: Project key
+context: ["style.md"]
+"#,
+        )
+        .unwrap();
+        let recipe = Recipe::from_content(
+            r#"
+title: Triage
+description: Triage new tickets
+extends: base.yaml
+instructions: Label every new ticket.
+extensions:
+  - type: stdio
+    name: jira
+    cmd: jira-mcp
+    args: ["--read-only"]
+settings:
+  goose_model: claude-sonnet-4
+parameters:
+  - key: project
+    input_type: string
+    requirement: optional
+    default: OPS
+    description: Project key
+context: ["style.md", "labels.md"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(recipe.extends.as_deref(), Some("base.yaml"));
+
+        let resolved = recipe.inherit_from(base);
+        assert_eq!(resolved.title, "Triage");
+        assert!(resolved.extends.is_none());
+        assert_eq!(
+            resolved.instructions.as_deref(),
+            Some("Follow the team style guide.\n\nLabel every new ticket.")
+        );
+
+        let extensions = resolved.extensions.unwrap();
+        let names: Vec<_> = extensions.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["developer", "jira"]);
+        assert!(matches!(
+            &extensions[1],
+            ExtensionConfig::Stdio { args, .. } if args == &vec!["--read-only".to_string()]
+        ));
+
+        let settings = resolved.settings.unwrap();
+        assert_eq!(settings.goose_provider.as_deref(), Some("anthropic"));
+        assert_eq!(settings.goose_model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(settings.temperature, Some(0.2));
+
+        let parameters = resolved.parameters.unwrap();
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(parameters[0].default.as_deref(), Some("OPS"));
+        assert_eq!(resolved.context.unwrap(), vec!["style.md", "labels.md"]);
+    }
+
+    #[test]
+    fn test_resolve_extends_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("shared")).unwrap();
+        std::fs::write(
+            dir.path().join("shared/base.yaml"),
+            "title: Base\ndescription: Base\ninstructions: Be careful.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("triage.yaml"),
+            "title: Triage\ndescription: Triage\nextends: shared/base.yaml\ninstructions: Label tickets.\n",
+        )
+        .unwrap();
+
+        let recipe = Recipe::from_file(&dir.path().join("triage.yaml")).unwrap();
+        assert!(recipe.extends.is_none());
+        assert_eq!(
+            recipe.instructions.as_deref(),
+            Some("Be careful.\n\nLabel tickets.")
+        );
+
+        std::fs::write(
+            dir.path().join("shared/base.yaml"),
+            "title: Base\ndescription: Base\nextends: ../triage\ninstructions: Loop.\n",
+        )
+        .unwrap();
+        let err = Recipe::from_file(&dir.path().join("triage.yaml")).unwrap_err();
+        assert!(err.to_string().contains("Recipe extends itself"));
+
+        std::fs::write(
+            dir.path().join("missing.yaml"),
+            "title: Missing\ndescription: Missing base\nextends: missing-base\ninstructions: none\n",
+        )
+        .unwrap();
+        assert!(Recipe::from_file(&dir.path().join("missing.yaml")).is_err());
+    }
+
+    fn parameter(input_type: RecipeParameterInputType) -> RecipeParameter {
+        RecipeParameter {
+            key: "param".to_string(),
+            input_type,
+            requirement: RecipeParameterRequirement::Required,
+            description: "A test parameter".to_string(),
+            default: None,
+            options: None,
+            pattern: None,
+            min: None,
+            max: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_parameter_value() {
+        let mut number = parameter(RecipeParameterInputType::Number);
+        number.min = Some(1.0);
+        number.max = Some(10.0);
+        assert!(number.validate_value("2.5").is_ok());
+        assert!(number
+            .validate_value("11")
+            .unwrap_err()
+            .contains("more than 10"));
+        assert!(number.validate_value("ten").is_err());
+
+        let mut select = parameter(RecipeParameterInputType::Select);
+        assert!(select.validate_definition().is_err());
+        select.options = Some(vec!["staging".to_string(), "production".to_string()]);
+        assert!(select.validate_value("staging").is_ok());
+        assert_eq!(
+            select.validate_value("dev").unwrap_err(),
+            "'dev' is not one of: staging, production"
+        );
+
+        let mut list = parameter(RecipeParameterInputType::List);
+        list.pattern = Some("[A-Z]+-[0-9]+".to_string());
+        assert!(list.validate_value("OPS-1, OPS-22").is_ok());
+        assert!(list.validate_value(r#"["OPS-1", "OPS-22"]"#).is_ok());
+        assert!(list.validate_value("OPS-1, ops").is_err());
+        assert_eq!(list_items(" a, ,b "), vec!["a", "b"]);
+
+        // Older types accept what they did before unless the parameter adds constraints
+        let mut date = parameter(RecipeParameterInputType::Date);
+        date.default = Some("today".to_string());
+        assert!(date.validate_definition().is_ok());
+        date.pattern = Some(r"\d{4}-\d{2}-\d{2}".to_string());
+        assert!(date.validate_value("2025-06-30").is_ok());
+        assert!(date.validate_value("30/06/2025").is_err());
+        assert!(parameter(RecipeParameterInputType::Boolean)
+            .validate_value("True")
+            .is_ok());
+        assert!(parameter(RecipeParameterInputType::Number)
+            .validate_value("ten")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_validate_parameter_values() {
+        let parameters = vec![
+            RecipeParameter {
+                min: Some(0.0),
+                ..parameter(RecipeParameterInputType::Number)
+            },
+            RecipeParameter {
+                key: "flag".to_string(),
+                ..parameter(RecipeParameterInputType::Boolean)
+            },
+        ];
+        let values = HashMap::from([("param".to_string(), "abc".to_string())]);
+        let errors = validate_parameter_values(&parameters, &values);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].to_string(), "param: 'abc' is not a number");
+    }
 }
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 
+use crate::agents::observer::{self, ActivityKind, ScheduleRunStatus};
 use crate::agents::AgentEvent;
 use crate::agents::{Agent, SessionConfig};
 use crate::config::{self, Config};
@@ -231,11 +232,30 @@ impl Scheduler {
             )));
         }
 
+        // The copy can't find base recipes next to the original, so they are merged in first
+        let extends = fs::read_to_string(original_recipe_path)
+            .ok()
+            .and_then(|content| Recipe::from_content(&content).ok())
+            .is_some_and(|recipe| recipe.extends.is_some());
+        let merged_recipe = if extends {
+            Some(Recipe::from_file(original_recipe_path).map_err(|e| {
+                SchedulerError::RecipeLoadError(format!(
+                    "Failed to resolve the recipes {} extends: {}",
+                    original_job_spec.source, e
+                ))
+            })?)
+        } else {
+            None
+        };
+
         let scheduled_recipes_dir = get_default_scheduled_recipes_dir()?;
-        let original_extension = original_recipe_path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("yaml");
+        let original_extension = match merged_recipe {
+            Some(_) => "yaml",
+            None => original_recipe_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or("yaml"),
+        };
 
         let destination_filename = format!("{}.{}", original_job_spec.id, original_extension);
         let destination_recipe_path = scheduled_recipes_dir.join(destination_filename);
@@ -245,7 +265,13 @@ impl Scheduler {
             original_recipe_path.display(),
             destination_recipe_path.display()
         );
-        fs::copy(original_recipe_path, &destination_recipe_path).map_err(|e| {
+        let copied = match &merged_recipe {
+            Some(recipe) => serde_yaml::to_string(recipe)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .and_then(|content| fs::write(&destination_recipe_path, content)),
+            None => fs::copy(original_recipe_path, &destination_recipe_path).map(|_| ()),
+        };
+        copied.map_err(|e| {
             SchedulerError::StorageError(io::Error::new(
                 e.kind(),
                 format!(
@@ -649,30 +675,9 @@ impl Scheduler {
         sched_id: &str,
         limit: usize,
     ) -> Result<Vec<(String, SessionMetadata)>, SchedulerError> {
-        // Changed return type
-        let all_session_files = session::storage::list_sessions()
+        let mut schedule_sessions = session::list_sessions_for_schedule(sched_id)
             .map_err(|e| SchedulerError::StorageError(io::Error::other(e)))?;
 
-        let mut schedule_sessions: Vec<(String, SessionMetadata)> = Vec::new();
-
-        for (session_name, session_path) in all_session_files {
-            match session::storage::read_metadata(&session_path) {
-                Ok(metadata) => {
-                    // metadata is not mutable here, and SessionMetadata is original
-                    if metadata.schedule_id.as_deref() == Some(sched_id) {
-                        schedule_sessions.push((session_name, metadata)); // Keep the tuple
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to read metadata for session file {}: {}. Skipping.",
-                        session_path.display(),
-                        e
-                    );
-                }
-            }
-        }
-
         schedule_sessions.sort_by(|a, b| b.0.cmp(&a.0)); // Sort by session_name (timestamp string)
 
         // Keep the tuple, just take the limit
@@ -1063,7 +1068,47 @@ struct JobExecutionError {
     error: String,
 }
 
+/// Run a scheduled job, telling observers when it starts and how it ended
 async fn run_scheduled_job_internal(
+    job: ScheduledJob,
+    provider_override: Option<Arc<dyn GooseProvider>>,
+    jobs_arc: Option<Arc<Mutex<JobsMap>>>,
+    job_id: Option<String>,
+) -> std::result::Result<String, JobExecutionError> {
+    let schedule_id = job.id.clone();
+    observer::publish(
+        None,
+        ActivityKind::ScheduleRun {
+            schedule_id: schedule_id.clone(),
+            status: ScheduleRunStatus::Started,
+        },
+    );
+    let result = execute_scheduled_job(job, provider_override, jobs_arc, job_id).await;
+    let (session_id, status) = match &result {
+        Ok(session_id) => (
+            Some(session_id.clone()),
+            ScheduleRunStatus::Finished {
+                session_id: session_id.clone(),
+            },
+        ),
+        Err(e) => (
+            None,
+            ScheduleRunStatus::Failed {
+                error: e.error.clone(),
+            },
+        ),
+    };
+    observer::publish(
+        session_id,
+        ActivityKind::ScheduleRun {
+            schedule_id,
+            status,
+        },
+    );
+    result
+}
+
+async fn execute_scheduled_job(
     job: ScheduledJob,
     provider_override: Option<Arc<dyn GooseProvider>>, // New optional parameter
     jobs_arc: Option<Arc<Mutex<JobsMap>>>,
@@ -1112,6 +1157,17 @@ async fn run_scheduled_job_internal(
             }),
         }
     }?;
+    let recipe = if recipe.extends.is_some() {
+        Recipe::from_file(recipe_path).map_err(|e| JobExecutionError {
+            job_id: job.id.clone(),
+            error: format!(
+                "Failed to resolve the recipes '{}' extends: {}",
+                job.source, e
+            ),
+        })?
+    } else {
+        recipe
+    };
 
     let agent: Agent = Agent::new();
 
@@ -1158,6 +1214,14 @@ async fn run_scheduled_job_internal(
     }
     tracing::info!("Agent configured with provider for job '{}'", job.id);
 
+    if let Some(tool_timeouts) = recipe
+        .settings
+        .as_ref()
+        .and_then(|s| s.tool_timeouts.clone())
+    {
+        agent.set_tool_timeouts(tool_timeouts).await;
+    }
+
     // Log the execution mode
     let execution_mode = job.execution_mode.as_deref().unwrap_or("background");
     tracing::info!("Job '{}' running in {} mode", job.id, execution_mode);
@@ -1204,6 +1268,7 @@ async fn run_scheduled_job_internal(
             schedule_id: Some(job.id.clone()),
             execution_mode: job.execution_mode.clone(),
             max_turns: None,
+            profile: Config::global().active_profile(),
         };
 
         match agent
@@ -1230,6 +1295,15 @@ async fn run_scheduled_job_internal(
                         Ok(AgentEvent::ModelChange { .. }) => {
                             // Model change events are informational, just continue
                         }
+                        Ok(AgentEvent::LoopDetected(detection)) => {
+                            tracing::warn!("[Job {}] Loop detected: {}", job.id, detection.message);
+                        }
+                        Ok(AgentEvent::ToolCallTiming { .. }) => {
+                            // Tool timings are informational, just continue
+                        }
+                        Ok(AgentEvent::Stopped(reason)) => {
+                            tracing::warn!("[Job {}] Reply stopped early: {:?}", job.id, reason);
+                        }
 
                         Err(e) => {
                             tracing::error!(
@@ -1274,6 +1348,10 @@ async fn run_scheduled_job_internal(
                             accumulated_total_tokens: None,
                             accumulated_input_tokens: None,
                             accumulated_output_tokens: None,
+                            hook_outcomes: Vec::new(),
+                            owner: None,
+                            profile: None,
+                            model_switches: Vec::new(),
                         };
                         if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
@@ -1420,6 +1498,7 @@ mod tests {
             settings: None,
             response: None,
             sub_recipes: None,
+            extends: None,
         };
         let mut recipe_file = File::create(&recipe_filename)?;
         writeln!(
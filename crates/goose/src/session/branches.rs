@@ -0,0 +1,354 @@
+// Conversation branches for a session.
+//
+// The session file always holds the messages of the active branch, so everything that reads
+// sessions keeps working unchanged. All other branches live in a `.branches.json` file next to
+// the session file. Each branch records the branch it was forked from and the message index it
+// forked at, which makes the branches a tree rooted at `main`.
+
+use crate::message::Message;
+use crate::session::storage::{
+    get_path, read_messages, read_metadata, save_messages_with_metadata, Identifier,
+};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Id of the branch every session starts on
+pub const MAIN_BRANCH: &str = "main";
+
+/// A branch of the conversation stored alongside the session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionBranch {
+    pub id: String,
+    /// The branch this one was forked from, None for the main branch
+    pub parent: Option<String>,
+    /// Number of messages shared with the parent branch
+    pub fork_point: usize,
+    pub created_at: DateTime<Utc>,
+    /// Messages of the branch. Empty for the active branch, whose messages are in the session file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub messages: Vec<Message>,
+}
+
+impl SessionBranch {
+    fn new(id: String, parent: Option<String>, fork_point: usize) -> Self {
+        Self {
+            id,
+            parent,
+            fork_point,
+            created_at: Utc::now(),
+            messages: Vec::new(),
+        }
+    }
+}
+
+/// All branches of a session and which one is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchTree {
+    pub active: String,
+    pub branches: Vec<SessionBranch>,
+}
+
+impl Default for BranchTree {
+    fn default() -> Self {
+        Self {
+            active: MAIN_BRANCH.to_string(),
+            branches: vec![SessionBranch::new(MAIN_BRANCH.to_string(), None, 0)],
+        }
+    }
+}
+
+impl BranchTree {
+    fn get_mut(&mut self, id: &str) -> Option<&mut SessionBranch> {
+        self.branches.iter_mut().find(|branch| branch.id == id)
+    }
+
+    fn next_id(&self) -> String {
+        (1..)
+            .map(|n| format!("branch-{}", n))
+            .find(|id| !self.branches.iter().any(|branch| &branch.id == id))
+            .expect("an unused branch id")
+    }
+}
+
+/// Summary of a branch for listings
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BranchInfo {
+    /// Branch identifier, `main` for the original conversation
+    pub id: String,
+    /// The branch this one was forked from
+    pub parent: Option<String>,
+    /// Number of messages shared with the parent branch
+    pub fork_point: usize,
+    /// Number of messages in the branch
+    pub message_count: usize,
+    #[schema(value_type = String, format = "date-time")]
+    pub created_at: DateTime<Utc>,
+    /// Whether this branch is the one in the session file
+    pub active: bool,
+}
+
+/// Result of rewinding a session
+#[derive(Debug, Clone)]
+pub struct Rewind {
+    /// Messages kept on the active branch
+    pub messages: Vec<Message>,
+    /// Messages that came after the rewind point
+    pub discarded: Vec<Message>,
+    /// The branch holding the discarded messages, when they were forked rather than dropped
+    pub branch: Option<String>,
+}
+
+/// Path of the file holding the inactive branches of a session
+pub fn branches_path(session_file: &Path) -> PathBuf {
+    session_file.with_extension("branches.json")
+}
+
+/// Read the branch tree of a session, a session that never branched only has `main`
+pub fn read_branches(session_file: &Path) -> Result<BranchTree> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    let path = branches_path(&secure_path);
+    if !path.exists() {
+        return Ok(BranchTree::default());
+    }
+
+    let content = fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        tracing::error!("Failed to parse session branches: {}", e);
+        anyhow!("Failed to read session branches")
+    })
+}
+
+fn save_branches(session_file: &Path, tree: &BranchTree) -> Result<()> {
+    let path = branches_path(session_file);
+    let temp_file = path.with_extension("tmp");
+    fs::write(&temp_file, serde_json::to_vec(tree)?)?;
+    fs::rename(&temp_file, &path).map_err(|e| {
+        tracing::error!("Failed to move temporary branches file: {}", e);
+        let _ = fs::remove_file(&temp_file);
+        anyhow!("Failed to save session branches")
+    })
+}
+
+fn write_active_messages(session_file: &Path, messages: &[Message]) -> Result<()> {
+    let mut metadata = read_metadata(session_file)?;
+    metadata.message_count = messages.len();
+    save_messages_with_metadata(session_file, &metadata, messages)
+}
+
+/// List the branches of a session
+pub fn list_branches(session_file: &Path) -> Result<Vec<BranchInfo>> {
+    let tree = read_branches(session_file)?;
+    let active_count = read_messages(session_file)?.len();
+
+    Ok(tree
+        .branches
+        .iter()
+        .map(|branch| {
+            let active = branch.id == tree.active;
+            BranchInfo {
+                id: branch.id.clone(),
+                parent: branch.parent.clone(),
+                fork_point: branch.fork_point,
+                message_count: if active {
+                    active_count
+                } else {
+                    branch.messages.len()
+                },
+                created_at: branch.created_at,
+                active,
+            }
+        })
+        .collect())
+}
+
+/// Rewind the active branch so only its first `message_index` messages remain
+///
+/// With `fork` the messages after that point are kept as a new branch forked from the active
+/// one, otherwise they are dropped. Rewinding between a tool request and its response is
+/// rejected, since the conversation could not be continued from there.
+pub fn rewind(session_file: &Path, message_index: usize, fork: bool) -> Result<Rewind> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    let mut messages = read_messages(&secure_path)?;
+
+    if message_index > messages.len() {
+        return Err(anyhow!(
+            "Cannot rewind to message {}, the session only has {} messages",
+            message_index,
+            messages.len()
+        ));
+    }
+    if messages
+        .get(message_index)
+        .is_some_and(|message| message.is_tool_response())
+    {
+        return Err(anyhow!(
+            "Cannot rewind to message {}, it would separate a tool call from its result",
+            message_index
+        ));
+    }
+
+    let mut tree = read_branches(&secure_path)?;
+    let discarded = messages.split_off(message_index);
+    let mut branch = None;
+
+    if fork && !discarded.is_empty() {
+        let id = tree.next_id();
+        let mut forked = SessionBranch::new(id.clone(), Some(tree.active.clone()), message_index);
+        forked.messages = messages.iter().chain(discarded.iter()).cloned().collect();
+        tree.branches.push(forked);
+        save_branches(&secure_path, &tree)?;
+        branch = Some(id);
+    }
+
+    write_active_messages(&secure_path, &messages)?;
+
+    Ok(Rewind {
+        messages,
+        discarded,
+        branch,
+    })
+}
+
+/// Make another branch the active one, returning its messages
+///
+/// The messages of the previously active branch are stored in the branch tree so they can be
+/// switched back to later.
+pub fn switch_branch(session_file: &Path, branch_id: &str) -> Result<Vec<Message>> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    let mut tree = read_branches(&secure_path)?;
+    let current = read_messages(&secure_path)?;
+
+    if tree.active == branch_id {
+        return Ok(current);
+    }
+
+    let target = tree
+        .get_mut(branch_id)
+        .ok_or_else(|| anyhow!("No branch named '{}' in this session", branch_id))?;
+    let messages = std::mem::take(&mut target.messages);
+
+    let active = tree.active.clone();
+    match tree.get_mut(&active) {
+        Some(branch) => branch.messages = current,
+        None => {
+            // Sessions that branched before `main` was recorded still keep their messages
+            let mut branch = SessionBranch::new(active, None, 0);
+            branch.messages = current;
+            tree.branches.push(branch);
+        }
+    }
+    tree.active = branch_id.to_string();
+
+    write_active_messages(&secure_path, &messages)?;
+    save_branches(&secure_path, &tree)?;
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::storage::{delete_session, persist_messages};
+    use mcp_core::tool::ToolCall;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn conversation() -> Vec<Message> {
+        vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("one"),
+            Message::user().with_text("second"),
+            Message::assistant().with_tool_request(
+                "call",
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            ),
+            Message::user().with_tool_response("call", Ok(vec![])),
+            Message::assistant().with_text("two"),
+        ]
+    }
+
+    fn texts(messages: &[Message]) -> Vec<String> {
+        messages.iter().map(|m| m.as_concat_text()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rewind_discards_messages() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("session.jsonl");
+        persist_messages(&file, &conversation(), None).await?;
+
+        let result = rewind(&file, 2, false)?;
+        assert_eq!(texts(&result.messages), vec!["first", "one"]);
+        assert_eq!(result.discarded.len(), 4);
+        assert!(result.branch.is_none());
+
+        assert_eq!(read_messages(&file)?.len(), 2);
+        assert_eq!(read_metadata(&file)?.message_count, 2);
+        assert!(!branches_path(&file).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rewind_forks_and_switches_back() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("session.jsonl");
+        persist_messages(&file, &conversation(), None).await?;
+
+        let result = rewind(&file, 2, true)?;
+        let branch = result.branch.expect("a forked branch");
+        assert_eq!(branch, "branch-1");
+
+        // Continue the rewound conversation on main
+        let mut messages = result.messages;
+        messages.push(Message::user().with_text("different"));
+        persist_messages(&file, &messages, None).await?;
+
+        let branches = list_branches(&file)?;
+        assert_eq!(branches.len(), 2);
+        assert!(branches[0].active);
+        assert_eq!(branches[0].message_count, 3);
+        assert_eq!(branches[1].parent.as_deref(), Some(MAIN_BRANCH));
+        assert_eq!(branches[1].fork_point, 2);
+        assert_eq!(branches[1].message_count, 6);
+
+        let restored = switch_branch(&file, &branch)?;
+        assert_eq!(restored.len(), 6);
+        assert_eq!(read_messages(&file)?.len(), 6);
+
+        let main = switch_branch(&file, MAIN_BRANCH)?;
+        assert_eq!(texts(&main), vec!["first", "one", "different"]);
+        assert_eq!(read_branches(&file)?.active, MAIN_BRANCH);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_session_removes_branches() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("session.jsonl");
+        persist_messages(&file, &conversation(), None).await?;
+        rewind(&file, 2, true)?;
+        assert!(branches_path(&file).exists());
+
+        delete_session(&file)?;
+        assert!(!file.exists());
+        assert!(!branches_path(&file).exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rewind_rejects_invalid_points() -> Result<()> {
+        let dir = tempdir()?;
+        let file = dir.path().join("session.jsonl");
+        persist_messages(&file, &conversation(), None).await?;
+
+        assert!(rewind(&file, 4, true).is_err());
+        assert!(rewind(&file, 7, true).is_err());
+        assert!(switch_branch(&file, "missing").is_err());
+        assert_eq!(read_messages(&file)?.len(), 6);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use serde_json::Value;
+
+use crate::message::Message;
+use crate::providers::formats::{anthropic, openai};
+use crate::session::{self, Identifier, SessionMetadata};
+
+/// Longest description taken from the first request of an imported conversation
+const MAX_DESCRIPTION_CHARS: usize = 80;
+
+/// Transcript formats sessions can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    /// Chat completions messages, with `tool_calls` and `tool` role results
+    OpenAi,
+    /// Messages API messages, with `tool_use` and `tool_result` blocks
+    Anthropic,
+}
+
+/// The messages of a transcript, either a bare list or the body of a request
+fn transcript_messages(transcript: &Value) -> Result<Vec<Value>> {
+    let mut messages = match transcript {
+        Value::Array(messages) => messages.clone(),
+        Value::Object(body) => body
+            .get("messages")
+            .and_then(|m| m.as_array())
+            .cloned()
+            .ok_or_else(|| anyhow!("Transcript has no messages list"))?,
+        _ => return Err(anyhow!("Transcript must be a JSON list or object")),
+    };
+    // A logged request may carry the response it got
+    if let Some(reply) = transcript
+        .get("choices")
+        .and_then(|choices| choices.get(0))
+        .and_then(|choice| choice.get("message"))
+    {
+        messages.push(reply.clone());
+    }
+    Ok(messages)
+}
+
+/// Guess the format from what only one of them has, plain text conversations read the same in both
+pub fn detect_format(transcript: &Value) -> ImportFormat {
+    if transcript
+        .get("system")
+        .is_some_and(|s| s.is_string() || s.is_array())
+    {
+        return ImportFormat::Anthropic;
+    }
+    let messages = transcript_messages(transcript).unwrap_or_default();
+    let anthropic_blocks = messages.iter().any(|message| {
+        message["content"].as_array().is_some_and(|blocks| {
+            blocks.iter().any(|block| {
+                matches!(
+                    block["type"].as_str(),
+                    Some("tool_use" | "tool_result" | "thinking" | "redacted_thinking" | "image")
+                )
+            })
+        })
+    });
+    if anthropic_blocks {
+        ImportFormat::Anthropic
+    } else {
+        ImportFormat::OpenAi
+    }
+}
+
+/// Convert a transcript into goose messages
+pub fn parse_transcript(transcript: &Value, format: ImportFormat) -> Result<Vec<Message>> {
+    let messages = transcript_messages(transcript)?;
+    let converted = match format {
+        ImportFormat::OpenAi => openai::messages_from_openai(&messages)?,
+        ImportFormat::Anthropic => anthropic::messages_from_anthropic(&messages)?,
+    };
+    if converted.is_empty() {
+        return Err(anyhow!("Transcript has no messages to import"));
+    }
+    Ok(converted)
+}
+
+/// Metadata for an imported conversation, described by its first request
+pub fn imported_metadata(messages: &[Message], working_dir: PathBuf) -> SessionMetadata {
+    let mut metadata = SessionMetadata::new(working_dir);
+    metadata.message_count = messages.len();
+    let first_request = messages
+        .iter()
+        .filter(|m| m.role == mcp_core::role::Role::User)
+        .map(|m| m.as_concat_text())
+        .find(|text| !text.trim().is_empty());
+    if let Some(text) = first_request {
+        let line = text.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+        metadata.description = match line.char_indices().nth(MAX_DESCRIPTION_CHARS) {
+            Some((end, _)) => format!("{}...", &line[..end]),
+            None => line.to_string(),
+        };
+    }
+    metadata
+}
+
+/// Import a transcript file as a new session, returning the session file and its message count
+pub fn import_session(
+    transcript_file: &Path,
+    format: Option<ImportFormat>,
+    session_id: &str,
+    working_dir: PathBuf,
+) -> Result<(PathBuf, usize)> {
+    let content = std::fs::read_to_string(transcript_file)?;
+    let transcript: Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow!("{} is not valid JSON: {}", transcript_file.display(), e))?;
+    let format = format.unwrap_or_else(|| detect_format(&transcript));
+    let messages = parse_transcript(&transcript, format)?;
+
+    let session_file = session::get_path(Identifier::Name(session_id.to_string()))?;
+    if session::session_exists(&session_file) {
+        return Err(anyhow!("Session '{}' already exists", session_id));
+    }
+    let metadata = imported_metadata(&messages, working_dir);
+    session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+    Ok((session_file, messages.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_detect_format() {
+        let openai = json!({"model": "gpt-4o", "messages": [
+            {"role": "system", "content": "Be brief"},
+            {"role": "user", "content": "Hi"}
+        ]});
+        assert_eq!(detect_format(&openai), ImportFormat::OpenAi);
+
+        let anthropic = json!([
+            {"role": "user", "content": "List files"},
+            {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "toolu_1", "name": "shell", "input": {}}
+            ]}
+        ]);
+        assert_eq!(detect_format(&anthropic), ImportFormat::Anthropic);
+        assert_eq!(
+            detect_format(&json!({"system": "Be brief", "messages": []})),
+            ImportFormat::Anthropic
+        );
+    }
+
+    #[test]
+    fn test_logged_response_is_imported() -> Result<()> {
+        let log = json!({
+            "messages": [{"role": "user", "content": "Why does the consumer stall?\nIt started today."}],
+            "choices": [{"message": {"role": "assistant", "content": "Check the rebalance timeout."}}]
+        });
+        let messages = parse_transcript(&log, detect_format(&log))?;
+        assert_eq!(messages.len(), 2);
+
+        let metadata = imported_metadata(&messages, std::env::temp_dir());
+        assert_eq!(metadata.description, "Why does the consumer stall?");
+        assert_eq!(metadata.message_count, 2);
+
+        assert!(parse_transcript(&json!({"messages": []}), ImportFormat::OpenAi).is_err());
+        Ok(())
+    }
+}
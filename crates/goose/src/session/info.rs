@@ -19,7 +19,7 @@ pub enum SortOrder {
 }
 
 pub fn get_valid_sorted_sessions(sort_order: SortOrder) -> Result<Vec<SessionInfo>> {
-    let sessions = match session::list_sessions() {
+    let sessions = match session::session_store().list() {
         Ok(sessions) => sessions,
         Err(e) => {
             tracing::error!("Failed to list sessions: {:?}", e);
@@ -28,22 +28,13 @@ pub fn get_valid_sorted_sessions(sort_order: SortOrder) -> Result<Vec<SessionInf
     };
     let mut session_infos: Vec<SessionInfo> = sessions
         .into_iter()
-        .filter_map(|(id, path)| {
-            let modified = path
-                .metadata()
-                .and_then(|m| m.modified())
-                .map(|time| {
-                    chrono::DateTime::<chrono::Utc>::from(time)
-                        .format("%Y-%m-%d %H:%M:%S UTC")
-                        .to_string()
-                })
-                .ok()?;
-
-            let metadata = session::read_metadata(&path).ok()?;
+        .filter_map(|stored| {
+            let modified = stored.modified.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+            let metadata = session::read_metadata(&stored.path).ok()?;
 
             Some(SessionInfo {
-                id,
-                path: path.to_string_lossy().to_string(),
+                id: stored.id,
+                path: stored.path.to_string_lossy().to_string(),
                 modified,
                 metadata,
             })
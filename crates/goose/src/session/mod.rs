@@ -1,12 +1,18 @@
+pub mod branches;
+pub mod import;
 pub mod info;
+pub mod search;
 pub mod storage;
+pub mod store;
 
 // Re-export common session types and functions
 pub use storage::{
-    ensure_session_dir, generate_description, generate_description_with_schedule_id,
-    generate_session_id, get_most_recent_session, get_path, list_sessions, persist_messages,
-    persist_messages_with_schedule_id, read_messages, read_metadata, update_metadata, Identifier,
-    SessionMetadata,
+    can_access, claim_session, delete_session, ensure_session_dir, generate_description,
+    generate_description_with_schedule_id, generate_session_id, get_most_recent_session, get_path,
+    list_sessions, list_sessions_for_schedule, persist_messages, persist_messages_with_schedule_id,
+    read_messages, read_metadata, record_usage, session_exists, update_metadata, Identifier,
+    ModelSwitch, SessionMetadata,
 };
+pub use store::{session_store, SessionStore};
 
 pub use info::{get_valid_sorted_sessions, SessionInfo};
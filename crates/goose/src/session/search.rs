@@ -0,0 +1,485 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use mcp_core::role::Role;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::message::{Message, MessageContent};
+use crate::session::{self, SessionMetadata};
+
+/// Characters of context kept on each side of a match in a snippet
+const SNIPPET_CONTEXT: usize = 60;
+/// Most matches returned for one session
+const MAX_MATCHES_PER_SESSION: usize = 5;
+
+/// Most bytes of session text kept in the index cache
+const MAX_INDEX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// A session index with the modification time of the session it was built from
+struct CachedIndex {
+    modified: DateTime<Utc>,
+    index: Arc<SessionIndex>,
+    /// When the index was last used, in searches of this cache
+    last_used: u64,
+}
+
+/// Indexes of the sessions searched so far, rebuilt when a session changes
+///
+/// The least recently used indexes are dropped once their text passes `max_bytes`.
+struct IndexCache {
+    entries: HashMap<PathBuf, CachedIndex>,
+    bytes: usize,
+    max_bytes: usize,
+    clock: u64,
+}
+
+impl IndexCache {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            bytes: 0,
+            max_bytes,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, path: &Path, modified: DateTime<Utc>) -> Option<Arc<SessionIndex>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(path)?;
+        if entry.modified != modified {
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(Arc::clone(&entry.index))
+    }
+
+    fn insert(&mut self, path: PathBuf, modified: DateTime<Utc>, index: Arc<SessionIndex>) {
+        self.clock += 1;
+        self.bytes += index.size();
+        let entry = CachedIndex {
+            modified,
+            index,
+            last_used: self.clock,
+        };
+        if let Some(old) = self.entries.insert(path, entry) {
+            self.bytes -= old.index.size();
+        }
+        while self.bytes > self.max_bytes && self.entries.len() > 1 {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.bytes -= entry.index.size();
+        }
+    }
+
+    /// Drop the indexes of sessions that are gone
+    fn retain(&mut self, paths: &HashSet<&Path>) {
+        let gone: Vec<PathBuf> = self
+            .entries
+            .keys()
+            .filter(|path| !paths.contains(path.as_path()))
+            .cloned()
+            .collect();
+        for path in gone {
+            self.remove(&path);
+        }
+    }
+}
+
+static INDEX_CACHE: Lazy<Mutex<IndexCache>> =
+    Lazy::new(|| Mutex::new(IndexCache::new(MAX_INDEX_CACHE_BYTES)));
+
+/// What to look for across sessions, every term of `text` has to appear in a session
+#[derive(Debug, Clone, Default)]
+pub struct SearchQuery {
+    pub text: String,
+    /// Only sessions modified at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions modified at or before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only sessions run in this directory or below it
+    pub working_dir: Option<PathBuf>,
+    pub schedule_id: Option<String>,
+    /// Most sessions returned, all of them when None
+    pub limit: Option<usize>,
+}
+
+/// The part of a session a match was found in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Description,
+    WorkingDir,
+    Text,
+    ToolName,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct SearchMatch {
+    pub field: MatchField,
+    /// Index of the matching message, None for matches in the session metadata
+    pub message_index: Option<usize>,
+    pub role: Option<Role>,
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SearchResult {
+    pub id: String,
+    pub path: String,
+    pub modified: String,
+    pub metadata: SessionMetadata,
+    /// The best matches in the session, most terms first
+    pub matches: Vec<SearchMatch>,
+}
+
+struct IndexedField {
+    field: MatchField,
+    message_index: Option<usize>,
+    role: Option<Role>,
+    text: String,
+    /// Lowercase copy of `text` the terms are matched against
+    folded: String,
+}
+
+impl IndexedField {
+    fn new(
+        field: MatchField,
+        message_index: Option<usize>,
+        role: Option<Role>,
+        text: &str,
+    ) -> Self {
+        Self {
+            field,
+            message_index,
+            role,
+            text: text.to_string(),
+            folded: text.to_lowercase(),
+        }
+    }
+}
+
+/// The searchable text of one session
+struct SessionIndex {
+    fields: Vec<IndexedField>,
+}
+
+impl SessionIndex {
+    fn build(metadata: &SessionMetadata, messages: &[Message]) -> Self {
+        let mut fields = vec![
+            IndexedField::new(MatchField::Description, None, None, &metadata.description),
+            IndexedField::new(
+                MatchField::WorkingDir,
+                None,
+                None,
+                &metadata.working_dir.to_string_lossy(),
+            ),
+        ];
+        for (index, message) in messages.iter().enumerate() {
+            let text = message.as_concat_text();
+            if !text.trim().is_empty() {
+                fields.push(IndexedField::new(
+                    MatchField::Text,
+                    Some(index),
+                    Some(message.role.clone()),
+                    &text,
+                ));
+            }
+            for content in &message.content {
+                if let MessageContent::ToolRequest(request) = content {
+                    if let Ok(call) = &request.tool_call {
+                        fields.push(IndexedField::new(
+                            MatchField::ToolName,
+                            Some(index),
+                            Some(message.role.clone()),
+                            &call.name,
+                        ));
+                    }
+                }
+            }
+        }
+        Self { fields }
+    }
+
+    /// Bytes of text held by the index
+    fn size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|f| f.text.len() + f.folded.len())
+            .sum()
+    }
+
+    /// The matching fields if every term appears somewhere in the session
+    fn search(&self, terms: &[String]) -> Option<Vec<SearchMatch>> {
+        let all_found = terms
+            .iter()
+            .all(|term| self.fields.iter().any(|f| f.folded.contains(term)));
+        if !all_found {
+            return None;
+        }
+
+        let mut hits: Vec<(usize, &IndexedField)> = self
+            .fields
+            .iter()
+            .map(|f| (terms.iter().filter(|t| f.folded.contains(*t)).count(), f))
+            .filter(|(found, _)| *found > 0)
+            .collect();
+        // Stable, so fields with as many terms keep the order of the session
+        hits.sort_by_key(|(found, _)| std::cmp::Reverse(*found));
+        Some(
+            hits.into_iter()
+                .take(MAX_MATCHES_PER_SESSION)
+                .map(|(_, f)| SearchMatch {
+                    field: f.field,
+                    message_index: f.message_index,
+                    role: f.role.clone(),
+                    snippet: snippet(&f.text, &f.folded, terms),
+                })
+                .collect(),
+        )
+    }
+}
+
+/// The text around the first term found, on one line
+fn snippet(text: &str, folded: &str, terms: &[String]) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    // Lowercasing can change the length of some characters, then the match is only approximate
+    let position = terms
+        .iter()
+        .filter_map(|term| folded.find(term.as_str()))
+        .min()
+        .map(|byte| folded[..byte].chars().count().min(chars.len()))
+        .unwrap_or(0);
+
+    let start = position.saturating_sub(SNIPPET_CONTEXT);
+    let end = (position + SNIPPET_CONTEXT * 2).min(chars.len());
+    let excerpt: String = chars[start..end].iter().collect();
+    let excerpt = excerpt.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        excerpt,
+        if end < chars.len() { "..." } else { "" }
+    )
+}
+
+/// The query split into lowercase terms
+fn terms(text: &str) -> Vec<String> {
+    text.split_whitespace().map(str::to_lowercase).collect()
+}
+
+fn cached_index(
+    path: &Path,
+    modified: DateTime<Utc>,
+    metadata: &SessionMetadata,
+) -> Result<Arc<SessionIndex>> {
+    if let Some(index) = INDEX_CACHE.lock().unwrap().get(path, modified) {
+        return Ok(index);
+    }
+    let messages = session::read_messages(path)?;
+    let index = Arc::new(SessionIndex::build(metadata, &messages));
+    INDEX_CACHE
+        .lock()
+        .unwrap()
+        .insert(path.to_path_buf(), modified, Arc::clone(&index));
+    Ok(index)
+}
+
+/// Search the text, tool calls and working directories of all sessions, newest first
+pub fn search_sessions(query: &SearchQuery) -> Result<Vec<SearchResult>> {
+    let terms = terms(&query.text);
+    if terms.is_empty() {
+        return Err(anyhow!("Search query is empty"));
+    }
+
+    let mut sessions = session::session_store().list()?;
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.modified));
+    INDEX_CACHE
+        .lock()
+        .unwrap()
+        .retain(&sessions.iter().map(|s| s.path.as_path()).collect());
+
+    let mut results = Vec::new();
+    for stored in sessions {
+        if query.since.is_some_and(|since| stored.modified < since)
+            || query.until.is_some_and(|until| stored.modified > until)
+        {
+            continue;
+        }
+        let Ok(metadata) = session::read_metadata(&stored.path) else {
+            continue;
+        };
+        if query
+            .working_dir
+            .as_ref()
+            .is_some_and(|dir| !metadata.working_dir.starts_with(dir))
+            || query
+                .schedule_id
+                .as_ref()
+                .is_some_and(|id| metadata.schedule_id.as_ref() != Some(id))
+        {
+            continue;
+        }
+
+        let index = match cached_index(&stored.path, stored.modified, &metadata) {
+            Ok(index) => index,
+            Err(e) => {
+                tracing::warn!("Skipping session {} in search: {}", stored.id, e);
+                continue;
+            }
+        };
+        let Some(matches) = index.search(&terms) else {
+            continue;
+        };
+
+        results.push(SearchResult {
+            id: stored.id,
+            path: stored.path.to_string_lossy().to_string(),
+            modified: stored.modified.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+            metadata,
+            matches,
+        });
+        if query.limit.is_some_and(|limit| results.len() >= limit) {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+/// Parse a date filter given as `YYYY-MM-DD` or an RFC 3339 time.
+/// A bare date covers the whole day, so `end_of_day` picks its last second for an upper bound.
+pub fn parse_date_filter(value: &str, end_of_day: bool) -> Result<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date '{}', use YYYY-MM-DD", value))?;
+    let time = if end_of_day {
+        NaiveTime::from_hms_opt(23, 59, 59)
+    } else {
+        NaiveTime::from_hms_opt(0, 0, 0)
+    }
+    .expect("valid time");
+    Ok(date.and_time(time).and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::ToolCall;
+    use serde_json::json;
+
+    fn index() -> SessionIndex {
+        let metadata = SessionMetadata {
+            description: "Consumer lag".to_string(),
+            working_dir: PathBuf::from("/work/streaming"),
+            ..Default::default()
+        };
+        let messages = vec![
+            Message::user().with_text("Why does the Kafka consumer stop after a rebalance?"),
+            Message::assistant()
+                .with_text("Let me look at the consumer config.")
+                .with_tool_request(
+                    "call_1",
+                    Ok(ToolCall::new(
+                        "developer__shell",
+                        json!({"command": "cat consumer.properties"}),
+                    )),
+                ),
+        ];
+        SessionIndex::build(&metadata, &messages)
+    }
+
+    #[test]
+    fn test_index_cache_drops_least_recently_used() {
+        let size = index().size();
+        let mut cache = IndexCache::new(size * 2);
+        let modified = Utc::now();
+        for name in ["a", "b"] {
+            cache.insert(PathBuf::from(name), modified, Arc::new(index()));
+        }
+        assert!(cache.get(Path::new("a"), modified).is_some());
+        assert!(cache.get(Path::new("a"), Utc::now()).is_none());
+
+        cache.insert(PathBuf::from("c"), modified, Arc::new(index()));
+        assert!(cache.get(Path::new("b"), modified).is_none());
+        assert!(cache.get(Path::new("a"), modified).is_some());
+        assert_eq!(cache.bytes, size * 2);
+
+        cache.retain(&[Path::new("c")].into_iter().collect());
+        assert_eq!(cache.entries.len(), 1);
+        assert_eq!(cache.bytes, size);
+    }
+
+    #[test]
+    fn test_every_term_has_to_match() {
+        let index = index();
+        assert!(index.search(&terms("kafka rebalance")).is_some());
+        assert!(index.search(&terms("kafka zookeeper")).is_none());
+
+        // Terms may be found in different parts of the session
+        let matches = index.search(&terms("KAFKA shell streaming")).unwrap();
+        let fields: Vec<_> = matches.iter().map(|m| (m.field, m.message_index)).collect();
+        assert_eq!(
+            fields,
+            vec![
+                (MatchField::WorkingDir, None),
+                (MatchField::Text, Some(0)),
+                (MatchField::ToolName, Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matches_with_more_terms_come_first() {
+        let matches = index().search(&terms("consumer rebalance")).unwrap();
+        assert_eq!(matches[0].message_index, Some(0));
+        assert_eq!(matches[0].role, Some(Role::User));
+        assert_eq!(matches.len(), 3);
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let snippet = snippet(&text, &text, &terms("needle"));
+        assert!(snippet.starts_with("...a a"));
+        assert!(snippet.contains("needle"));
+        assert!(snippet.ends_with("b b..."));
+
+        assert_eq!(
+            super::snippet("short\ntext", "short\ntext", &terms("text")),
+            "short text"
+        );
+    }
+
+    #[test]
+    fn test_parse_date_filter() {
+        assert_eq!(
+            parse_date_filter("2025-03-01", false).unwrap().to_rfc3339(),
+            "2025-03-01T00:00:00+00:00"
+        );
+        assert_eq!(
+            parse_date_filter("2025-03-01", true).unwrap().to_rfc3339(),
+            "2025-03-01T23:59:59+00:00"
+        );
+        assert!(parse_date_filter("2025-03-01T10:00:00Z", true).is_ok());
+        assert!(parse_date_filter("yesterday", false).is_err());
+    }
+}
@@ -5,8 +5,11 @@
 // - Backup creation
 // Additional debug logging can be added if needed for troubleshooting.
 
+use crate::agents::hooks::HookOutcome;
 use crate::message::Message;
-use crate::providers::base::Provider;
+use crate::providers::base::{Provider, ProviderUsage};
+use crate::session::branches;
+use crate::session::store::session_store;
 use anyhow::Result;
 use chrono::Local;
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
@@ -30,6 +33,17 @@ fn get_home_dir() -> PathBuf {
         .to_path_buf()
 }
 
+/// A change of provider or model made during a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ModelSwitch {
+    pub provider: String,
+    pub model: String,
+    /// Number of messages in the session when the switch was made
+    pub message_count: usize,
+    /// Unix timestamp of the switch
+    pub timestamp: i64,
+}
+
 /// Metadata for a session, stored as the first line in the session file
 #[derive(Debug, Clone, Serialize, ToSchema)]
 pub struct SessionMetadata {
@@ -54,6 +68,18 @@ pub struct SessionMetadata {
     pub accumulated_input_tokens: Option<i32>,
     /// The number of output tokens used in the session. Accumulated across all messages.
     pub accumulated_output_tokens: Option<i32>,
+    /// Outcomes of the lifecycle hooks that ran during the session
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hook_outcomes: Vec<HookOutcome>,
+    /// Name of the API token that started the session, sessions without one are shared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Configuration profile that was active when the session started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Provider and model changes made during the session, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub model_switches: Vec<ModelSwitch>,
 }
 
 // Custom deserializer to handle old sessions without working_dir
@@ -74,6 +100,14 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_input_tokens: Option<i32>,
             accumulated_output_tokens: Option<i32>,
             working_dir: Option<PathBuf>,
+            #[serde(default)]
+            hook_outcomes: Vec<HookOutcome>,
+            #[serde(default)]
+            owner: Option<String>,
+            #[serde(default)]
+            profile: Option<String>,
+            #[serde(default)]
+            model_switches: Vec<ModelSwitch>,
         }
 
         let helper = Helper::deserialize(deserializer)?;
@@ -95,6 +129,10 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_input_tokens: helper.accumulated_input_tokens,
             accumulated_output_tokens: helper.accumulated_output_tokens,
             working_dir,
+            hook_outcomes: helper.hook_outcomes,
+            owner: helper.owner,
+            profile: helper.profile,
+            model_switches: helper.model_switches,
         })
     }
 }
@@ -119,6 +157,10 @@ impl SessionMetadata {
             accumulated_total_tokens: None,
             accumulated_input_tokens: None,
             accumulated_output_tokens: None,
+            hook_outcomes: Vec::new(),
+            owner: None,
+            profile: None,
+            model_switches: Vec::new(),
         }
     }
 }
@@ -324,51 +366,51 @@ pub fn ensure_session_dir() -> Result<PathBuf> {
     Ok(data_dir)
 }
 
-/// Get the path to the most recently modified session file
+/// Get the path to the most recently modified session
 pub fn get_most_recent_session() -> Result<PathBuf> {
-    let session_dir = ensure_session_dir()?;
-    let mut entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
-        .collect::<Vec<_>>();
-
-    if entries.is_empty() {
-        return Err(anyhow::anyhow!("No session files found"));
-    }
-
-    // Sort by modification time, most recent first
-    entries.sort_by(|a, b| {
-        b.metadata()
-            .and_then(|m| m.modified())
-            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-            .cmp(
-                &a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
-            )
-    });
-
-    Ok(entries[0].path())
+    session_store()
+        .list()?
+        .into_iter()
+        .max_by_key(|session| session.modified)
+        .map(|session| session.path)
+        .ok_or_else(|| anyhow::anyhow!("No session files found"))
 }
 
-/// List all available session files
+/// List all available sessions by id and path
 pub fn list_sessions() -> Result<Vec<(String, PathBuf)>> {
-    let session_dir = ensure_session_dir()?;
-    let entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-
-            if path.extension().is_some_and(|ext| ext == "jsonl") {
-                let name = path.file_stem()?.to_string_lossy().to_string();
-                Some((name, path))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    Ok(session_store()
+        .list()?
+        .into_iter()
+        .map(|session| (session.id, session.path))
+        .collect())
+}
+
+/// Sessions started by a schedule, with their metadata
+pub fn list_sessions_for_schedule(schedule_id: &str) -> Result<Vec<(String, SessionMetadata)>> {
+    session_store().list_for_schedule(schedule_id)
+}
+
+/// Whether a session has been stored
+pub fn session_exists(session_file: &Path) -> bool {
+    get_path(Identifier::Path(session_file.to_path_buf()))
+        .is_ok_and(|path| session_store().exists(&path))
+}
+
+/// Remove a session from the store
+pub fn delete_session(session_file: &Path) -> Result<()> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    session_store().delete(&secure_path)?;
+    // Inactive branches live next to the session path whatever the store
+    match fs::remove_file(branches::branches_path(&secure_path)) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
 
-    Ok(entries)
+/// Keep the usage of one provider call with the session, for stores that track it per turn
+pub fn record_usage(session_file: &Path, usage: &ProviderUsage) -> Result<()> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    session_store().record_usage(&secure_path, usage)
 }
 
 /// Generate a session ID using timestamp format (yyyymmdd_hhmmss)
@@ -389,8 +431,12 @@ pub fn generate_session_id() -> String {
 pub fn read_messages(session_file: &Path) -> Result<Vec<Message>> {
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    session_store().read_messages(&secure_path)
+}
 
-    let result = read_messages_with_truncation(&secure_path, Some(50000)); // 50KB limit per message content
+/// Read the messages of a JSONL session file, see [`read_messages`]
+pub(crate) fn read_jsonl_messages(secure_path: &Path) -> Result<Vec<Message>> {
+    let result = read_messages_with_truncation(secure_path, Some(50000)); // 50KB limit per message content
     match &result {
         Ok(_messages) => {}
         Err(e) => println!(
@@ -987,19 +1033,23 @@ fn truncate_json_string(json_str: &str, max_content_size: usize) -> String {
 pub fn read_metadata(session_file: &Path) -> Result<SessionMetadata> {
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    session_store().read_metadata(&secure_path)
+}
 
+/// Read the metadata line of a JSONL session file
+pub(crate) fn read_jsonl_metadata(secure_path: &Path) -> Result<SessionMetadata> {
     if !secure_path.exists() {
         return Ok(SessionMetadata::default());
     }
 
     // Security check: file size
-    let file_metadata = fs::metadata(&secure_path)?;
+    let file_metadata = fs::metadata(secure_path)?;
     if file_metadata.len() > MAX_FILE_SIZE {
         tracing::warn!("Session file exceeds size limit during metadata read");
         return Err(anyhow::anyhow!("Session file too large"));
     }
 
-    let file = fs::File::open(&secure_path).map_err(|e| {
+    let file = fs::File::open(secure_path).map_err(|e| {
         tracing::error!("Failed to open session file for metadata read: {}", e);
         anyhow::anyhow!("Failed to access session file")
     })?;
@@ -1063,12 +1113,6 @@ pub async fn persist_messages_with_schedule_id(
     // Validate the session file path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
-    // Security check: message count limit
-    if messages.len() > MAX_MESSAGE_COUNT {
-        tracing::warn!("Message count exceeds limit: {}", messages.len());
-        return Err(anyhow::anyhow!("Too many messages"));
-    }
-
     // Count user messages
     let user_message_count = messages
         .iter()
@@ -1113,10 +1157,18 @@ pub fn save_messages_with_metadata(
     metadata: &SessionMetadata,
     messages: &[Message],
 ) -> Result<()> {
-    use fs2::FileExt;
-
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    session_store().save(&secure_path, metadata, messages)
+}
+
+/// Write a JSONL session file, see [`save_messages_with_metadata`]
+pub(crate) fn write_jsonl_session(
+    secure_path: &Path,
+    metadata: &SessionMetadata,
+    messages: &[Message],
+) -> Result<()> {
+    use fs2::FileExt;
 
     // Security check: message count limit
     if messages.len() > MAX_MESSAGE_COUNT {
@@ -1207,7 +1259,7 @@ pub fn save_messages_with_metadata(
     })?;
 
     // Atomically move the temporary file to the final location
-    fs::rename(&temp_file, &secure_path).map_err(|e| {
+    fs::rename(&temp_file, secure_path).map_err(|e| {
         // Clean up temp file on failure
         tracing::error!("Failed to move temporary file: {}", e);
         let _ = fs::remove_file(&temp_file);
@@ -1321,7 +1373,7 @@ pub async fn generate_description_with_schedule_id(
     save_messages_with_metadata(&secure_path, &metadata, messages)
 }
 
-/// Update only the metadata in a session file, preserving all messages
+/// Update only the metadata of a session, preserving all messages
 ///
 /// Security features:
 /// - Validates file paths to prevent directory traversal
@@ -1330,11 +1382,29 @@ pub async fn update_metadata(session_file: &Path, metadata: &SessionMetadata) ->
     // Validate the path for security
     let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
 
-    // Read all messages from the file
-    let messages = read_messages(&secure_path)?;
+    session_store().update_metadata(&secure_path, metadata)
+}
 
-    // Rewrite the file with the new metadata and existing messages
-    save_messages_with_metadata(&secure_path, metadata, &messages)
+/// Whether `user` may see a session, where None is the local user who sees every session
+pub fn can_access(metadata: &SessionMetadata, user: Option<&str>) -> bool {
+    match user {
+        None => true,
+        Some(user) => metadata.owner.as_deref() == Some(user),
+    }
+}
+
+/// Create an empty session file owned by `owner`, unless the session already exists
+///
+/// Messages persisted later keep the metadata, so the owner stays recorded with the session.
+pub fn claim_session(session_file: &Path, owner: &str, working_dir: PathBuf) -> Result<()> {
+    let secure_path = get_path(Identifier::Path(session_file.to_path_buf()))?;
+    if session_store().exists(&secure_path) {
+        return Ok(());
+    }
+
+    let mut metadata = SessionMetadata::new(working_dir);
+    metadata.owner = Some(owner.to_string());
+    save_messages_with_metadata(&secure_path, &metadata, &[])
 }
 
 #[cfg(test)]
@@ -1799,4 +1869,56 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_claimed_session_keeps_owner() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("test_owned.jsonl");
+
+        claim_session(&file_path, "alice", dir.path().to_path_buf())?;
+        persist_messages(&file_path, &[Message::user().with_text("Hello")], None).await?;
+        // Claiming an existing session leaves its owner alone
+        claim_session(&file_path, "bob", dir.path().to_path_buf())?;
+
+        let metadata = read_metadata(&file_path)?;
+        assert_eq!(metadata.owner.as_deref(), Some("alice"));
+        assert_eq!(read_messages(&file_path)?.len(), 1);
+        assert!(can_access(&metadata, Some("alice")));
+        assert!(!can_access(&metadata, Some("bob")));
+        assert!(can_access(&metadata, None));
+
+        let shared = SessionMetadata::default();
+        assert!(!can_access(&shared, Some("alice")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_model_switches_survive_persisting() -> Result<()> {
+        let dir = tempdir()?;
+        let file_path = dir.path().join("test_switches.jsonl");
+        let messages = vec![Message::user().with_text("Hello")];
+
+        let mut metadata = SessionMetadata::new(dir.path().to_path_buf());
+        metadata.model_switches.push(ModelSwitch {
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            message_count: 1,
+            timestamp: 1_700_000_000,
+        });
+        save_messages_with_metadata(&file_path, &metadata, &messages)?;
+        persist_messages(&file_path, &messages, None).await?;
+        assert_eq!(
+            read_metadata(&file_path)?.model_switches,
+            metadata.model_switches
+        );
+
+        // Sessions written before switches were recorded have none
+        let old: SessionMetadata = serde_json::from_str(
+            r#"{"working_dir": "/tmp", "description": "", "message_count": 0}"#,
+        )?;
+        assert!(old.model_switches.is_empty());
+
+        Ok(())
+    }
 }
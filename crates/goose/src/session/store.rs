@@ -0,0 +1,529 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, SecondsFormat, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::config::{registry, Config};
+use crate::message::Message;
+use crate::providers::base::ProviderUsage;
+use crate::session::storage::{self, SessionMetadata};
+
+/// Name of the SQLite database inside the session directory
+pub const SESSION_DB_FILE: &str = "sessions.db";
+
+/// A session as listed by a store
+#[derive(Debug, Clone)]
+pub struct StoredSession {
+    pub id: String,
+    /// The path the session is addressed by, which only exists on disk for the JSONL store
+    pub path: PathBuf,
+    pub modified: DateTime<Utc>,
+}
+
+/// Where sessions are kept
+///
+/// Sessions are addressed by their path in the session directory, `<id>.jsonl`, whatever the
+/// store, so callers never need to know which backend is configured. Paths are validated by
+/// the functions in [`storage`] before they reach a store.
+pub trait SessionStore: Send + Sync {
+    fn exists(&self, session_file: &Path) -> bool;
+
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>>;
+
+    /// Metadata of a session, the default metadata if it has none yet
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata>;
+
+    /// Replace the messages and metadata of a session, creating it if needed
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()>;
+
+    fn update_metadata(&self, session_file: &Path, metadata: &SessionMetadata) -> Result<()> {
+        let messages = self.read_messages(session_file)?;
+        self.save(session_file, metadata, &messages)
+    }
+
+    /// Keep the usage of one provider call, stores without a usage history ignore it
+    fn record_usage(&self, _session_file: &Path, _usage: &ProviderUsage) -> Result<()> {
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<StoredSession>>;
+
+    /// Sessions started by a schedule, with their metadata
+    fn list_for_schedule(&self, schedule_id: &str) -> Result<Vec<(String, SessionMetadata)>> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter_map(|session| {
+                let metadata = self.read_metadata(&session.path).ok()?;
+                (metadata.schedule_id.as_deref() == Some(schedule_id))
+                    .then_some((session.id, metadata))
+            })
+            .collect())
+    }
+
+    fn delete(&self, session_file: &Path) -> Result<()>;
+}
+
+fn session_id(session_file: &Path) -> Result<String> {
+    session_file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .ok_or_else(|| anyhow!("Invalid session path"))
+}
+
+/// One JSONL file per session, metadata on the first line and a message on each line after
+pub struct JsonlStore;
+
+impl SessionStore for JsonlStore {
+    fn exists(&self, session_file: &Path) -> bool {
+        session_file.exists()
+    }
+
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        storage::read_jsonl_messages(session_file)
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        storage::read_jsonl_metadata(session_file)
+    }
+
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        storage::write_jsonl_session(session_file, metadata, messages)
+    }
+
+    fn list(&self) -> Result<Vec<StoredSession>> {
+        let session_dir = storage::ensure_session_dir()?;
+        let sessions = fs::read_dir(&session_dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+                if path.extension().is_none_or(|ext| ext != "jsonl") {
+                    return None;
+                }
+                let modified = entry.metadata().and_then(|m| m.modified()).ok()?;
+                Some(StoredSession {
+                    id: path.file_stem()?.to_string_lossy().into_owned(),
+                    modified: modified.into(),
+                    path,
+                })
+            })
+            .collect();
+        Ok(sessions)
+    }
+
+    fn delete(&self, session_file: &Path) -> Result<()> {
+        fs::remove_file(session_file)?;
+        Ok(())
+    }
+}
+
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sessions (
+    id TEXT PRIMARY KEY,
+    working_dir TEXT NOT NULL,
+    schedule_id TEXT,
+    description TEXT NOT NULL,
+    metadata TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    idx INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    PRIMARY KEY (session_id, idx)
+);
+CREATE TABLE IF NOT EXISTS usage (
+    session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+    recorded_at TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_tokens INTEGER,
+    output_tokens INTEGER,
+    total_tokens INTEGER
+);
+CREATE INDEX IF NOT EXISTS sessions_working_dir ON sessions(working_dir);
+CREATE INDEX IF NOT EXISTS sessions_schedule_id ON sessions(schedule_id);
+CREATE INDEX IF NOT EXISTS sessions_updated_at ON sessions(updated_at);
+CREATE INDEX IF NOT EXISTS usage_session_id ON usage(session_id);
+";
+
+fn timestamp() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Micros, true)
+}
+
+/// All sessions in one SQLite database, with the usage of every provider call
+///
+/// Saving only rewrites the messages after the first one that differs from the stored
+/// history, so appending writes just the new messages, and there is no cap on the number of
+/// messages.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+    session_dir: PathBuf,
+}
+
+impl SqliteStore {
+    pub fn open(db_path: &Path, session_dir: PathBuf) -> Result<Self> {
+        let conn = Connection::open(db_path)?;
+        conn.pragma_update(None, "foreign_keys", "ON")?;
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.execute_batch(SQLITE_SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            session_dir,
+        })
+    }
+
+    /// The database in the session directory
+    pub fn open_default() -> Result<Self> {
+        let session_dir = storage::ensure_session_dir()?;
+        Self::open(&session_dir.join(SESSION_DB_FILE), session_dir)
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SessionStore for SqliteStore {
+    fn exists(&self, session_file: &Path) -> bool {
+        let Ok(id) = session_id(session_file) else {
+            return false;
+        };
+        self.conn()
+            .query_row("SELECT 1 FROM sessions WHERE id = ?1", [id], |_| Ok(()))
+            .optional()
+            .is_ok_and(|row| row.is_some())
+    }
+
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        let id = session_id(session_file)?;
+        let conn = self.conn();
+        let mut statement =
+            conn.prepare("SELECT idx, message FROM messages WHERE session_id = ?1 ORDER BY idx")?;
+        let rows = statement.query_map([&id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut messages = Vec::new();
+        for row in rows {
+            let (idx, json) = row?;
+            match serde_json::from_str(&json) {
+                Ok(message) => messages.push(message),
+                Err(e) => tracing::warn!("Skipping unreadable message {} of {}: {}", idx, id, e),
+            }
+        }
+        Ok(messages)
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        let id = session_id(session_file)?;
+        let metadata: Option<String> = self
+            .conn()
+            .query_row("SELECT metadata FROM sessions WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match metadata {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(SessionMetadata::default()),
+        }
+    }
+
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        let id = session_id(session_file)?;
+        let now = timestamp();
+        let mut conn = self.conn();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO sessions (id, working_dir, schedule_id, description, metadata, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+             ON CONFLICT(id) DO UPDATE SET
+                working_dir = excluded.working_dir,
+                schedule_id = excluded.schedule_id,
+                description = excluded.description,
+                metadata = excluded.metadata,
+                updated_at = excluded.updated_at",
+            params![
+                id,
+                metadata.working_dir.to_string_lossy(),
+                metadata.schedule_id,
+                metadata.description,
+                serde_json::to_string(metadata)?,
+                now,
+            ],
+        )?;
+
+        // Keep the stored messages up to the first one that changed
+        let serialized = messages
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut keep = 0;
+        {
+            let mut statement = tx.prepare(
+                "SELECT message FROM messages WHERE session_id = ?1 ORDER BY idx LIMIT ?2",
+            )?;
+            let mut rows = statement.query(params![id, serialized.len() as i64])?;
+            while let Some(row) = rows.next()? {
+                if row.get::<_, String>(0)? != serialized[keep] {
+                    break;
+                }
+                keep += 1;
+            }
+        }
+        tx.execute(
+            "DELETE FROM messages WHERE session_id = ?1 AND idx >= ?2",
+            params![id, keep as i64],
+        )?;
+        {
+            let mut insert =
+                tx.prepare("INSERT INTO messages (session_id, idx, message) VALUES (?1, ?2, ?3)")?;
+            for (idx, message) in serialized.iter().enumerate().skip(keep) {
+                insert.execute(params![id, idx as i64, message])?;
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn record_usage(&self, session_file: &Path, usage: &ProviderUsage) -> Result<()> {
+        let id = session_id(session_file)?;
+        self.conn().execute(
+            "INSERT INTO usage (session_id, recorded_at, model, input_tokens, output_tokens, total_tokens)
+             SELECT ?1, ?2, ?3, ?4, ?5, ?6 WHERE EXISTS (SELECT 1 FROM sessions WHERE id = ?1)",
+            params![
+                id,
+                timestamp(),
+                usage.model,
+                usage.usage.input_tokens,
+                usage.usage.output_tokens,
+                usage.usage.total_tokens,
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<StoredSession>> {
+        let conn = self.conn();
+        let mut statement =
+            conn.prepare("SELECT id, updated_at FROM sessions ORDER BY updated_at DESC")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut sessions = Vec::new();
+        for row in rows {
+            let (id, updated_at) = row?;
+            let modified = DateTime::parse_from_rfc3339(&updated_at)
+                .map(|time| time.with_timezone(&Utc))
+                .unwrap_or_default();
+            sessions.push(StoredSession {
+                path: self.session_dir.join(format!("{}.jsonl", id)),
+                id,
+                modified,
+            });
+        }
+        Ok(sessions)
+    }
+
+    fn list_for_schedule(&self, schedule_id: &str) -> Result<Vec<(String, SessionMetadata)>> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(
+            "SELECT id, metadata FROM sessions WHERE schedule_id = ?1 ORDER BY updated_at DESC",
+        )?;
+        let rows = statement.query_map([schedule_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut sessions = Vec::new();
+        for row in rows {
+            let (id, json) = row?;
+            sessions.push((id, serde_json::from_str(&json)?));
+        }
+        Ok(sessions)
+    }
+
+    fn delete(&self, session_file: &Path) -> Result<()> {
+        let id = session_id(session_file)?;
+        let deleted = self
+            .conn()
+            .execute("DELETE FROM sessions WHERE id = ?1", [&id])?;
+        if deleted == 0 {
+            return Err(anyhow!("No session {}", id));
+        }
+        Ok(())
+    }
+}
+
+static SESSION_STORE: Lazy<Box<dyn SessionStore>> = Lazy::new(|| {
+    let kind = Config::global()
+        .get_key(&registry::GOOSE_SESSION_STORE)
+        .unwrap_or_else(|_| "jsonl".to_string());
+    if kind == "sqlite" {
+        match SqliteStore::open_default() {
+            Ok(store) => return Box::new(store),
+            Err(e) => tracing::error!("Failed to open session database, using JSONL files: {}", e),
+        }
+    }
+    Box::new(JsonlStore)
+});
+
+/// The store selected by `GOOSE_SESSION_STORE`, opened on first use
+pub fn session_store() -> &'static dyn SessionStore {
+    SESSION_STORE.as_ref()
+}
+
+/// Outcome of copying JSONL sessions into another store
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: Vec<String>,
+    /// Sessions the target already had, left alone
+    pub skipped: Vec<String>,
+    pub failed: Vec<(String, String)>,
+}
+
+/// Copy the JSONL session files into `target`, replacing its copies only when `overwrite` is set
+pub fn import_jsonl_sessions(target: &dyn SessionStore, overwrite: bool) -> Result<ImportSummary> {
+    let source = JsonlStore;
+    let mut summary = ImportSummary::default();
+    for session in source.list()? {
+        if !overwrite && target.exists(&session.path) {
+            summary.skipped.push(session.id);
+            continue;
+        }
+        let copied = source.read_metadata(&session.path).and_then(|metadata| {
+            let messages = source.read_messages(&session.path)?;
+            target.save(&session.path, &metadata, &messages)
+        });
+        match copied {
+            Ok(()) => summary.imported.push(session.id),
+            Err(e) => summary.failed.push((session.id, e.to_string())),
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn store(dir: &Path) -> SqliteStore {
+        SqliteStore::open(&dir.join(SESSION_DB_FILE), dir.to_path_buf()).unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_store_round_trip() -> Result<()> {
+        let dir = tempdir()?;
+        let store = store(dir.path());
+        let path = dir.path().join("20250101_120000.jsonl");
+        assert!(!store.exists(&path));
+
+        let mut metadata = SessionMetadata::new(dir.path().to_path_buf());
+        metadata.description = "testing".to_string();
+        metadata.schedule_id = Some("nightly".to_string());
+        let mut messages = vec![
+            Message::user().with_text("hello"),
+            Message::assistant().with_text("hi"),
+        ];
+        store.save(&path, &metadata, &messages)?;
+
+        // Appending keeps the stored prefix, rewinding drops what was cut
+        messages.push(Message::user().with_text("again"));
+        store.save(&path, &metadata, &messages)?;
+        assert_eq!(store.read_messages(&path)?.len(), 3);
+        store.save(&path, &metadata, &messages[..1])?;
+        let read = store.read_messages(&path)?;
+        assert_eq!(read.len(), 1);
+        assert_eq!(read[0].as_concat_text(), "hello");
+
+        assert_eq!(store.read_metadata(&path)?.description, "testing");
+        assert_eq!(store.list()?[0].id, "20250101_120000");
+        assert_eq!(store.list_for_schedule("nightly")?.len(), 1);
+        assert!(store.list_for_schedule("other")?.is_empty());
+
+        store.record_usage(
+            &path,
+            &ProviderUsage::new(
+                "model".to_string(),
+                crate::providers::base::Usage::new(Some(1), Some(2), Some(3)),
+            ),
+        )?;
+        store.delete(&path)?;
+        assert!(!store.exists(&path));
+        let usage_rows: i64 = store
+            .conn()
+            .query_row("SELECT COUNT(*) FROM usage", [], |row| row.get(0))?;
+        assert_eq!(usage_rows, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sqlite_store_replaces_edited_history() -> Result<()> {
+        let dir = tempdir()?;
+        let store = store(dir.path());
+        let path = dir.path().join("edited.jsonl");
+        let metadata = SessionMetadata::new(dir.path().to_path_buf());
+
+        store.save(
+            &path,
+            &metadata,
+            &[
+                Message::user().with_text("one"),
+                Message::assistant().with_text("two"),
+            ],
+        )?;
+        store.save(
+            &path,
+            &metadata,
+            &[
+                Message::user().with_text("summary"),
+                Message::assistant().with_text("changed"),
+                Message::user().with_text("three"),
+            ],
+        )?;
+        let texts: Vec<String> = store
+            .read_messages(&path)?
+            .iter()
+            .map(|m| m.as_concat_text())
+            .collect();
+        assert_eq!(texts, vec!["summary", "changed", "three"]);
+
+        // An edit before the last stored message is written even when the tail is unchanged
+        store.save(
+            &path,
+            &metadata,
+            &[
+                Message::user().with_text("edited"),
+                Message::assistant().with_text("changed"),
+                Message::user().with_text("three"),
+                Message::assistant().with_text("four"),
+            ],
+        )?;
+        let texts: Vec<String> = store
+            .read_messages(&path)?
+            .iter()
+            .map(|m| m.as_concat_text())
+            .collect();
+        assert_eq!(texts, vec!["edited", "changed", "three", "four"]);
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::process::Command;
 use std::sync::Arc;
 use std::time::Duration;
@@ -12,6 +13,7 @@ use tracing::{info, warn};
 use crate::scheduler::{normalize_cron_expression, ScheduledJob, SchedulerError};
 use crate::scheduler_trait::SchedulerTrait;
 use crate::session::storage::SessionMetadata;
+use crate::session::store::session_store;
 
 const TEMPORAL_SERVICE_STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
 const TEMPORAL_SERVICE_HEALTH_CHECK_INTERVAL: Duration = Duration::from_millis(500);
@@ -706,32 +708,10 @@ impl TemporalScheduler {
         sched_id: &str,
         limit: usize,
     ) -> Result<Vec<(String, SessionMetadata)>, SchedulerError> {
-        use crate::session::storage;
-
-        // Get all session files
-        let all_session_files = storage::list_sessions().map_err(|e| {
-            SchedulerError::SchedulerInternalError(format!("Failed to list sessions: {}", e))
-        })?;
-
-        let mut schedule_sessions: Vec<(String, SessionMetadata)> = Vec::new();
-
-        for (session_name, session_path) in all_session_files {
-            match storage::read_metadata(&session_path) {
-                Ok(metadata) => {
-                    // Check if this session belongs to the requested schedule
-                    if metadata.schedule_id.as_deref() == Some(sched_id) {
-                        schedule_sessions.push((session_name, metadata));
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!(
-                        "Failed to read metadata for session file {}: {}. Skipping.",
-                        session_path.display(),
-                        e
-                    );
-                }
-            }
-        }
+        let mut schedule_sessions =
+            crate::session::list_sessions_for_schedule(sched_id).map_err(|e| {
+                SchedulerError::SchedulerInternalError(format!("Failed to list sessions: {}", e))
+            })?;
 
         // Sort by session_name (timestamp string) in descending order (newest first)
         schedule_sessions.sort_by(|a, b| b.0.cmp(&a.0));
@@ -856,40 +836,24 @@ impl TemporalScheduler {
 
                 // Secondary check: look for recent session activity (more lenient timing)
                 let recent_sessions = self.sessions(&job.id, 3).await?;
+                let modified_times = session_modified_times();
                 let mut has_active_session = false;
 
                 for (session_name, _) in recent_sessions {
-                    let session_path = match crate::session::storage::get_path(
-                        crate::session::storage::Identifier::Name(session_name.clone()),
-                    ) {
-                        Ok(path) => path,
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to get session path for '{}': {}",
-                                session_name,
-                                e
+                    // Check if the session was modified recently (within last 5 minutes instead of 2)
+                    if let Some(modified_dt) = modified_times.get(&session_name) {
+                        let now = Utc::now();
+                        let time_diff = now.signed_duration_since(*modified_dt);
+
+                        // Increased tolerance to 5 minutes to reduce false positives
+                        if time_diff.num_minutes() < 5 {
+                            has_active_session = true;
+                            tracing::debug!(
+                                "Found active session for job '{}' modified {} minutes ago",
+                                job.id,
+                                time_diff.num_minutes()
                             );
-                            continue;
-                        }
-                    };
-
-                    // Check if session file was modified recently (within last 5 minutes instead of 2)
-                    if let Ok(metadata) = std::fs::metadata(&session_path) {
-                        if let Ok(modified) = metadata.modified() {
-                            let modified_dt: DateTime<Utc> = modified.into();
-                            let now = Utc::now();
-                            let time_diff = now.signed_duration_since(modified_dt);
-
-                            // Increased tolerance to 5 minutes to reduce false positives
-                            if time_diff.num_minutes() < 5 {
-                                has_active_session = true;
-                                tracing::debug!(
-                                    "Found active session for job '{}' modified {} minutes ago",
-                                    job.id,
-                                    time_diff.num_minutes()
-                                );
-                                break;
-                            }
+                            break;
                         }
                     }
                 }
@@ -966,37 +930,16 @@ impl TemporalScheduler {
                         let recent_sessions = self.sessions(sched_id, 1).await?;
 
                         if let Some((session_name, _session_metadata)) = recent_sessions.first() {
-                            // Check if this session is still active by looking at the session file
-                            let session_path = match crate::session::storage::get_path(
-                                crate::session::storage::Identifier::Name(session_name.clone()),
-                            ) {
-                                Ok(path) => path,
-                                Err(e) => {
-                                    tracing::warn!(
-                                        "Failed to get session path for '{}': {}",
-                                        session_name,
-                                        e
-                                    );
-                                    // Fallback: return a temporal session ID with current time
-                                    let session_id =
-                                        format!("temporal-{}-{}", sched_id, Utc::now().timestamp());
-                                    let start_time = Utc::now();
-                                    return Ok(Some((session_id, start_time)));
-                                }
-                            };
-
-                            // If the session file was modified recently (within last 5 minutes),
+                            // If the session was modified recently (within last 5 minutes),
                             // consider it as the current running session
-                            if let Ok(metadata) = std::fs::metadata(&session_path) {
-                                if let Ok(modified) = metadata.modified() {
-                                    let modified_dt: DateTime<Utc> = modified.into();
-                                    let now = Utc::now();
-                                    let time_diff = now.signed_duration_since(modified_dt);
-
-                                    if time_diff.num_minutes() < 5 {
-                                        // This looks like an active session
-                                        return Ok(Some((session_name.clone(), modified_dt)));
-                                    }
+                            if let Some(modified_dt) = session_modified_times().remove(session_name)
+                            {
+                                let now = Utc::now();
+                                let time_diff = now.signed_duration_since(modified_dt);
+
+                                if time_diff.num_minutes() < 5 {
+                                    // This looks like an active session
+                                    return Ok(Some((session_name.clone(), modified_dt)));
                                 }
                             }
                         }
@@ -1054,6 +997,21 @@ impl TemporalScheduler {
     }
 }
 
+/// When each session was last written, from the configured session store so the SQLite
+/// backend is covered as well as JSONL files
+fn session_modified_times() -> HashMap<String, DateTime<Utc>> {
+    match session_store().list() {
+        Ok(sessions) => sessions
+            .into_iter()
+            .map(|session| (session.id, session.modified))
+            .collect(),
+        Err(e) => {
+            warn!("Failed to list sessions: {}", e);
+            HashMap::new()
+        }
+    }
+}
+
 impl Drop for TemporalScheduler {
     fn drop(&mut self) {
         // Services continue running independently - no cleanup needed
@@ -1,5 +1,19 @@
+use mcp_core::{Content, ToolResult};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::config::Config;
+
+/// Config key holding the loop detection settings
+pub const LOOP_DETECTION_CONFIG_KEY: &str = "loop_detection";
+
+/// Longest sequence of calls that is recognised as a repeating cycle (e.g. A, B, C, A, B, C)
+const MAX_CYCLE_LENGTH: usize = 4;
+
+/// Number of recent calls kept for cycle detection
+const MAX_HISTORY: usize = 64;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
@@ -15,6 +29,128 @@ impl ToolCall {
     fn matches(&self, other: &ToolCall) -> bool {
         self.name == other.name && self.parameters == other.parameters
     }
+
+    /// A fingerprint that treats calls differing only in whitespace as the same call
+    fn fingerprint(&self) -> String {
+        format!("{}:{}", self.name, normalize(&self.parameters))
+    }
+}
+
+fn normalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(s) => {
+            serde_json::Value::String(s.split_whitespace().collect::<Vec<_>>().join(" "))
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(normalize).collect())
+        }
+        serde_json::Value::Object(map) => serde_json::Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), normalize(value)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// What the agent does once a loop has been detected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopAction {
+    /// Tell the model it is repeating itself and let it carry on
+    #[default]
+    Nudge,
+    /// End the turn and wait for the user to steer
+    Pause,
+    /// Stop the run with an error
+    Stop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopKind {
+    /// The same (or nearly the same) call made over and over
+    RepeatedCall,
+    /// A sequence of calls repeating, such as alternating between two tools
+    Cycle,
+    /// A tool failing with the same error again and again
+    RepeatedError,
+    /// Turns whose calls all returned results that were already seen
+    NoProgress,
+}
+
+/// A loop found by the monitor, surfaced to frontends as an agent event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopDetection {
+    pub kind: LoopKind,
+    pub action: LoopAction,
+    pub message: String,
+}
+
+impl LoopDetection {
+    /// Text added to the conversation so the model can change course
+    pub fn nudge(&self) -> String {
+        format!(
+            "{} You appear to be stuck in a loop. Stop repeating these calls, \
+            reconsider the approach, and try something different or ask the user for help.",
+            self.message
+        )
+    }
+}
+
+fn default_max_cycle_repeats() -> u32 {
+    3
+}
+
+fn default_max_repeated_errors() -> u32 {
+    3
+}
+
+fn default_max_no_progress_turns() -> u32 {
+    3
+}
+
+/// Settings for detecting loops beyond exact repeats, read from the `loop_detection` config key.
+/// Setting a limit to 0 turns that check off.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopDetectionConfig {
+    /// How many times a call or a cycle of calls may repeat before it counts as a loop
+    #[serde(default = "default_max_cycle_repeats")]
+    pub max_cycle_repeats: u32,
+    /// How many times in a row a tool may fail with the same error
+    #[serde(default = "default_max_repeated_errors")]
+    pub max_repeated_errors: u32,
+    /// How many turns in a row may only return results that were already seen
+    #[serde(default = "default_max_no_progress_turns")]
+    pub max_no_progress_turns: u32,
+    #[serde(default)]
+    pub action: LoopAction,
+}
+
+impl Default for LoopDetectionConfig {
+    fn default() -> Self {
+        Self {
+            max_cycle_repeats: default_max_cycle_repeats(),
+            max_repeated_errors: default_max_repeated_errors(),
+            max_no_progress_turns: default_max_no_progress_turns(),
+            action: LoopAction::default(),
+        }
+    }
+}
+
+impl LoopDetectionConfig {
+    /// Load the settings from config, if loop detection has been configured
+    pub fn from_config() -> Option<Self> {
+        Config::global()
+            .get_param::<Self>(LOOP_DETECTION_CONFIG_KEY)
+            .ok()
+    }
 }
 
 #[derive(Debug)]
@@ -23,6 +159,15 @@ pub struct ToolMonitor {
     last_call: Option<ToolCall>,
     repeat_count: u32,
     call_counts: HashMap<String, u32>,
+    loop_detection: Option<LoopDetectionConfig>,
+    history: Vec<String>,
+    last_error: Option<(String, String)>,
+    error_repeats: u32,
+    seen_results: HashSet<u64>,
+    turn_results: u32,
+    turn_new_results: u32,
+    no_progress_turns: u32,
+    pending: Option<LoopDetection>,
 }
 
 impl ToolMonitor {
@@ -32,13 +177,35 @@ impl ToolMonitor {
             last_call: None,
             repeat_count: 0,
             call_counts: HashMap::new(),
+            loop_detection: None,
+            history: Vec::new(),
+            last_error: None,
+            error_repeats: 0,
+            seen_results: HashSet::new(),
+            turn_results: 0,
+            turn_new_results: 0,
+            no_progress_turns: 0,
+            pending: None,
         }
     }
 
+    /// Also look for cycles, repeated errors and turns without progress
+    pub fn with_loop_detection(mut self, config: LoopDetectionConfig) -> Self {
+        self.loop_detection = Some(config);
+        self
+    }
+
     pub fn check_tool_call(&mut self, tool_call: ToolCall) -> bool {
         let total_calls = self.call_counts.entry(tool_call.name.clone()).or_insert(0);
         *total_calls += 1;
 
+        if self.loop_detection.is_some() {
+            self.history.push(tool_call.fingerprint());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+        }
+
         if self.max_repetitions.is_none() {
             self.last_call = Some(tool_call);
             self.repeat_count = 1;
@@ -62,6 +229,151 @@ impl ToolMonitor {
         true
     }
 
+    /// Record the result of a call, to spot repeated errors and turns that learn nothing new
+    pub fn record_result(&mut self, tool_call: &ToolCall, result: &ToolResult<Vec<Content>>) {
+        let Some(config) = &self.loop_detection else {
+            return;
+        };
+
+        let outcome = match result {
+            Ok(content) => serde_json::to_string(content).unwrap_or_default(),
+            Err(e) => e.to_string(),
+        };
+        self.turn_results += 1;
+        if self
+            .seen_results
+            .insert(hash_of((tool_call.fingerprint(), &outcome)))
+        {
+            self.turn_new_results += 1;
+        }
+
+        match result {
+            Err(e) => {
+                let error = (tool_call.name.clone(), e.to_string());
+                if self.last_error.as_ref() == Some(&error) {
+                    self.error_repeats += 1;
+                } else {
+                    self.last_error = Some(error);
+                    self.error_repeats = 1;
+                }
+
+                if config.max_repeated_errors > 0
+                    && self.error_repeats >= config.max_repeated_errors
+                    && self.pending.is_none()
+                {
+                    self.pending = Some(LoopDetection {
+                        kind: LoopKind::RepeatedError,
+                        action: config.action,
+                        message: format!(
+                            "{} failed with the same error {} times in a row: {}",
+                            tool_call.name, self.error_repeats, e
+                        ),
+                    });
+                    self.last_error = None;
+                    self.error_repeats = 0;
+                }
+            }
+            Ok(_) => {
+                self.last_error = None;
+                self.error_repeats = 0;
+            }
+        }
+    }
+
+    /// Finish a turn of tool calls and report a loop, if one was found
+    pub fn end_turn(&mut self) -> Option<LoopDetection> {
+        let config = self.loop_detection.clone()?;
+
+        let made_calls = self.turn_results > 0;
+        let made_progress = self.turn_new_results > 0;
+        self.turn_results = 0;
+        self.turn_new_results = 0;
+
+        if let Some(detection) = self.pending.take() {
+            self.history.clear();
+            return Some(detection);
+        }
+
+        if let Some(detection) = self.detect_cycle(&config) {
+            self.history.clear();
+            return Some(detection);
+        }
+
+        if made_calls {
+            if made_progress {
+                self.no_progress_turns = 0;
+            } else {
+                self.no_progress_turns += 1;
+            }
+        }
+        if config.max_no_progress_turns > 0
+            && self.no_progress_turns >= config.max_no_progress_turns
+        {
+            let turns = self.no_progress_turns;
+            self.no_progress_turns = 0;
+            return Some(LoopDetection {
+                kind: LoopKind::NoProgress,
+                action: config.action,
+                message: format!(
+                    "The last {} turns only returned results that were already seen.",
+                    turns
+                ),
+            });
+        }
+
+        None
+    }
+
+    fn detect_cycle(&self, config: &LoopDetectionConfig) -> Option<LoopDetection> {
+        let repeats = config.max_cycle_repeats as usize;
+        if repeats < 2 {
+            return None;
+        }
+
+        for length in 1..=MAX_CYCLE_LENGTH {
+            let window = length * repeats;
+            if self.history.len() < window {
+                break;
+            }
+
+            let tail = &self.history[self.history.len() - window..];
+            let cycle = &tail[..length];
+            let is_cycle = tail.chunks(length).all(|chunk| chunk == cycle);
+            // A cycle made of one repeated call is already caught at the shorter length
+            let distinct = cycle.iter().collect::<HashSet<_>>().len();
+            if !is_cycle || (length > 1 && distinct == 1) {
+                continue;
+            }
+
+            let names = cycle
+                .iter()
+                .map(|fingerprint| fingerprint.split(':').next().unwrap_or_default())
+                .collect::<Vec<_>>();
+            return Some(if length == 1 {
+                LoopDetection {
+                    kind: LoopKind::RepeatedCall,
+                    action: config.action,
+                    message: format!(
+                        "{} was called {} times in a row with the same arguments.",
+                        names[0], repeats
+                    ),
+                }
+            } else {
+                LoopDetection {
+                    kind: LoopKind::Cycle,
+                    action: config.action,
+                    message: format!(
+                        "The calls {} repeated {} times in a row.",
+                        names.join(" -> "),
+                        repeats
+                    ),
+                }
+            });
+        }
+
+        None
+    }
+
     pub fn get_stats(&self) -> HashMap<String, u32> {
         self.call_counts.clone()
     }
@@ -70,5 +382,148 @@ impl ToolMonitor {
         self.last_call = None;
         self.repeat_count = 0;
         self.call_counts.clear();
+        self.history.clear();
+        self.last_error = None;
+        self.error_repeats = 0;
+        self.seen_results.clear();
+        self.turn_results = 0;
+        self.turn_new_results = 0;
+        self.no_progress_turns = 0;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::ToolError;
+    use serde_json::json;
+
+    fn monitor() -> ToolMonitor {
+        ToolMonitor::new(None).with_loop_detection(LoopDetectionConfig::default())
+    }
+
+    fn call(name: &str, parameters: serde_json::Value) -> ToolCall {
+        ToolCall::new(name.to_string(), parameters)
+    }
+
+    fn run_turn(
+        monitor: &mut ToolMonitor,
+        calls: &[(ToolCall, ToolResult<Vec<Content>>)],
+    ) -> Option<LoopDetection> {
+        for (tool_call, result) in calls {
+            assert!(monitor.check_tool_call(tool_call.clone()));
+            monitor.record_result(tool_call, result);
+        }
+        monitor.end_turn()
+    }
+
+    #[test]
+    fn test_exact_repetition_limit() {
+        let mut monitor = ToolMonitor::new(Some(2));
+        let tool_call = call("shell", json!({"command": "ls"}));
+        assert!(monitor.check_tool_call(tool_call.clone()));
+        assert!(monitor.check_tool_call(tool_call.clone()));
+        assert!(!monitor.check_tool_call(tool_call));
+    }
+
+    #[test]
+    fn test_near_identical_calls_are_detected() {
+        let mut monitor = monitor();
+        let mut detection = None;
+        for (i, command) in ["ls  -la", "ls -la ", " ls -la"].iter().enumerate() {
+            let tool_call = call("shell", json!({ "command": command }));
+            let output = Ok(vec![Content::text(format!("output {}", i))]);
+            detection = run_turn(&mut monitor, &[(tool_call, output)]);
+        }
+
+        let detection = detection.expect("repeated call should be detected");
+        assert_eq!(detection.kind, LoopKind::RepeatedCall);
+        assert_eq!(detection.action, LoopAction::Nudge);
+    }
+
+    #[test]
+    fn test_alternating_calls_are_a_cycle() {
+        let mut monitor = monitor();
+        let mut detections = Vec::new();
+        for i in 0..3 {
+            for name in ["read", "write"] {
+                let tool_call = call(name, json!({"path": "a.txt"}));
+                let output = Ok(vec![Content::text(format!("{} {}", name, i))]);
+                detections.extend(run_turn(&mut monitor, &[(tool_call, output)]));
+            }
+        }
+
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].kind, LoopKind::Cycle);
+        assert!(detections[0].message.contains("read -> write"));
+    }
+
+    #[test]
+    fn test_repeated_errors_are_detected() {
+        let mut monitor = monitor();
+        let mut detection = None;
+        for i in 0..3 {
+            let tool_call = call("shell", json!({"command": format!("make target{}", i)}));
+            let output = Err(ToolError::ExecutionError("make: not found".to_string()));
+            detection = run_turn(&mut monitor, &[(tool_call, output)]);
+        }
+
+        assert_eq!(detection.unwrap().kind, LoopKind::RepeatedError);
+    }
+
+    #[test]
+    fn test_turns_without_new_results() {
+        let mut monitor = ToolMonitor::new(None).with_loop_detection(LoopDetectionConfig {
+            max_cycle_repeats: 0,
+            action: LoopAction::Pause,
+            ..Default::default()
+        });
+        let turn = [
+            (
+                call("read", json!({"path": "a"})),
+                Ok(vec![Content::text("a")]),
+            ),
+            (
+                call("read", json!({"path": "b"})),
+                Ok(vec![Content::text("b")]),
+            ),
+        ];
+
+        // The first turn learns something, the next three do not
+        assert!(run_turn(&mut monitor, &turn).is_none());
+        assert!(run_turn(&mut monitor, &turn).is_none());
+        assert!(run_turn(&mut monitor, &turn).is_none());
+        let detection = run_turn(&mut monitor, &turn).unwrap();
+        assert_eq!(detection.kind, LoopKind::NoProgress);
+        assert_eq!(detection.action, LoopAction::Pause);
+    }
+
+    #[test]
+    fn test_varied_calls_are_not_loops() {
+        let mut monitor = monitor();
+        for i in 0..10 {
+            let tool_call = call("shell", json!({"command": format!("echo {}", i)}));
+            let output = Ok(vec![Content::text(i.to_string())]);
+            assert!(run_turn(&mut monitor, &[(tool_call, output)]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_without_loop_detection_nothing_is_reported() {
+        let mut monitor = ToolMonitor::new(None);
+        let tool_call = call("shell", json!({"command": "ls"}));
+        for _ in 0..5 {
+            assert!(run_turn(&mut monitor, &[(tool_call.clone(), Ok(vec![]))]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config: LoopDetectionConfig =
+            serde_yaml::from_str("max_repeated_errors: 5\naction: stop\n").unwrap();
+        assert_eq!(config.max_repeated_errors, 5);
+        assert_eq!(config.max_cycle_repeats, 3);
+        assert_eq!(config.action, LoopAction::Stop);
     }
 }
@@ -142,6 +142,15 @@ async fn run_truncate_test(
             Ok(AgentEvent::ModelChange { .. }) => {
                 // Model change events are informational, just continue
             }
+            Ok(AgentEvent::LoopDetected(_)) => {
+                // Loop detections are informational, just continue
+            }
+            Ok(AgentEvent::ToolCallTiming { .. }) => {
+                // Tool timings are informational, just continue
+            }
+            Ok(AgentEvent::Stopped(_)) => {
+                // The reason is already in the last message, just continue
+            }
 
             Err(e) => {
                 println!("Error: {:?}", e);
@@ -709,6 +718,7 @@ mod max_turns_tests {
             schedule_id: None,
             execution_mode: None,
             max_turns: Some(1),
+            profile: None,
         };
         let messages = vec![Message::user().with_text("Hello")];
 
@@ -734,6 +744,9 @@ mod max_turns_tests {
                 }
                 Ok(AgentEvent::McpNotification(_)) => {}
                 Ok(AgentEvent::ModelChange { .. }) => {}
+                Ok(AgentEvent::LoopDetected(_)) => {}
+                Ok(AgentEvent::ToolCallTiming { .. }) => {}
+                Ok(AgentEvent::Stopped(_)) => {}
                 Err(e) => {
                     return Err(e);
                 }
@@ -410,5 +410,9 @@ pub fn create_test_session_metadata(message_count: usize, working_dir: &str) ->
         accumulated_total_tokens: Some(100),
         accumulated_input_tokens: Some(50),
         accumulated_output_tokens: Some(50),
+        hook_outcomes: Vec::new(),
+        owner: None,
+        profile: None,
+        model_switches: Vec::new(),
     }
 }
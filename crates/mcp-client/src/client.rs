@@ -170,8 +170,6 @@ where
     where
         R: for<'de> Deserialize<'de>,
     {
-        let mut service = self.service.lock().await;
-        service.ready().await.map_err(|_| Error::NotReady)?;
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
 
         let mut params = params.clone();
@@ -186,26 +184,34 @@ where
             params: Some(params),
         });
 
-        let response_msg = service
-            .call(request)
-            .await
-            .map_err(|e| Error::McpServerError {
-                server: self
-                    .server_info
-                    .as_ref()
-                    .map(|s| s.name.clone())
-                    .unwrap_or("".to_string()),
-                method: method.to_string(),
-                // we don't need include params because it can be really large
-                source: Box::<Error>::new(e.into()),
-            })?;
+        // Only hold the service lock while the request is handed off, so that several
+        // requests can be in flight at once; responses are matched back up by id
+        let response_future = {
+            let mut service = self.service.lock().await;
+            service.ready().await.map_err(|_| Error::NotReady)?;
+            service.call(request)
+        };
+
+        let response_msg = response_future.await.map_err(|e| Error::McpServerError {
+            server: self
+                .server_info
+                .as_ref()
+                .map(|s| s.name.clone())
+                .unwrap_or("".to_string()),
+            method: method.to_string(),
+            // we don't need include params because it can be really large
+            source: Box::<Error>::new(e.into()),
+        })?;
 
         match response_msg {
             JsonRpcMessage::Response(JsonRpcResponse {
-                id, result, error, ..
+                id: response_id,
+                result,
+                error,
+                ..
             }) => {
                 // Verify id matches
-                if id != Some(self.next_id.load(Ordering::SeqCst) - 1) {
+                if response_id != Some(id) {
                     return Err(Error::UnexpectedResponse(
                         "id mismatch for JsonRpcResponse".to_string(),
                     ));
@@ -221,8 +227,12 @@ where
                     Err(Error::UnexpectedResponse("missing result".to_string()))
                 }
             }
-            JsonRpcMessage::Error(JsonRpcError { id, error, .. }) => {
-                if id != Some(self.next_id.load(Ordering::SeqCst) - 1) {
+            JsonRpcMessage::Error(JsonRpcError {
+                id: response_id,
+                error,
+                ..
+            }) => {
+                if response_id != Some(id) {
                     return Err(Error::UnexpectedResponse(
                         "id mismatch for JsonRpcError".to_string(),
                     ));
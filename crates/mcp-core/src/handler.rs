@@ -18,6 +18,8 @@ pub enum ToolError {
     SchemaError(String),
     #[error("Tool not found: {0}")]
     NotFound(String),
+    #[error("Tool '{tool}' timed out after {seconds} seconds and was cancelled")]
+    Timeout { tool: String, seconds: u64 },
 }
 
 pub type ToolResult<T> = std::result::Result<T, ToolError>;
@@ -8,6 +8,10 @@ export interface Parameter {
   input_type: string;
   default?: string;
   requirement: 'required' | 'optional' | 'user_prompt';
+  options?: string[];
+  pattern?: string;
+  min?: number;
+  max?: number;
 }
 
 export interface Recipe {
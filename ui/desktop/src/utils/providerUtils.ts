@@ -192,6 +192,8 @@ export const initializeSystem = async (
     const recipeConfig = window.appConfig?.get?.('recipeConfig');
     const botPrompt = (recipeConfig as { instructions?: string })?.instructions;
     const responseConfig = (recipeConfig as { response?: { json_schema?: unknown } })?.response;
+    const toolTimeouts = (recipeConfig as { settings?: { tool_timeouts?: Record<string, number> } })
+      ?.settings?.tool_timeouts;
 
     // Extend the system prompt with desktop-specific information
     const response = await fetch(getApiUrl('/agent/prompt'), {
@@ -215,8 +217,8 @@ export const initializeSystem = async (
       }
     }
 
-    // Configure session with response config if present
-    if (responseConfig?.json_schema) {
+    // Configure session with response config and tool timeouts if present
+    if (responseConfig?.json_schema || toolTimeouts) {
       const sessionConfigResponse = await fetch(getApiUrl('/agent/session_config'), {
         method: 'POST',
         headers: {
@@ -224,13 +226,14 @@ export const initializeSystem = async (
           'X-Secret-Key': getSecretKey(),
         },
         body: JSON.stringify({
-          response: responseConfig,
+          response: responseConfig?.json_schema ? responseConfig : undefined,
+          tool_timeouts: toolTimeouts,
         }),
       });
       if (!sessionConfigResponse.ok) {
         console.warn(`Failed to configure session: ${sessionConfigResponse.statusText}`);
       } else {
-        console.log('Configured session with response schema');
+        console.log('Configured session from recipe');
       }
     }
 